@@ -0,0 +1,39 @@
+#!/usr/bin/env sh
+# Checks that every build configuration compiles, so none of them rots unnoticed.
+#
+#   ./check.sh native    the desktop game, with its tests
+#   ./check.sh headless  just the game rules, without a GUI
+#   ./check.sh web       the wasm library the pages in docs/ load
+#   ./check.sh           all of the above
+#
+# The web check needs `rustup target add wasm32-unknown-unknown`.
+set -eu
+cd "$(dirname "$0")"
+
+native() {
+    cargo check --workspace --all-targets
+    cargo test --workspace
+}
+
+headless() {
+    cargo check --workspace --all-targets --no-default-features
+}
+
+web() {
+    cargo check --workspace --lib --target wasm32-unknown-unknown
+}
+
+case "${1:-all}" in
+    native) native ;;
+    headless) headless ;;
+    web) web ;;
+    all)
+        native
+        headless
+        web
+        ;;
+    *)
+        echo "usage: $0 [native|headless|web|all]" >&2
+        exit 2
+        ;;
+esac
@@ -0,0 +1,397 @@
+//! Sound effects and music from embedded files, played through rodio natively and through
+//! audio elements in the browser. Without a working audio device everything is quietly skipped.
+
+use std::collections::HashMap;
+
+/// Plays the sounds registered with it by name.
+pub struct AudioPlayer {
+    sounds: HashMap<&'static str, &'static [u8]>,
+    /// For sound effects, between 0 and 1.
+    volume: f32,
+    music_volume: f32,
+    music_paused: bool,
+    backend: Box<dyn Backend>,
+}
+
+impl Default for AudioPlayer {
+    fn default() -> Self {
+        Self::with_backend(PlatformBackend::default())
+    }
+}
+
+impl AudioPlayer {
+    fn with_backend(backend: impl Backend + 'static) -> Self {
+        Self {
+            sounds: HashMap::new(),
+            volume: 0.0,
+            music_volume: 0.0,
+            music_paused: false,
+            backend: Box::new(backend),
+        }
+    }
+
+    /// Makes the WAV file playable as `name`.
+    pub fn register(&mut self, name: &'static str, bytes: &'static [u8]) {
+        self.sounds.insert(name, bytes);
+    }
+
+    /// Starts playing the sound without waiting for it to end.
+    pub fn play_sfx(&mut self, name: &str) {
+        if self.volume <= 0.0 {
+            return;
+        }
+
+        if let Some((&name, &bytes)) = self.sounds.get_key_value(name) {
+            self.backend.play_sfx(name, bytes, self.volume);
+        }
+    }
+
+    /// Replaces whatever music is playing with the sound, either once or over and over.
+    pub fn play_music(&mut self, name: &str, looped: bool) {
+        self.stop_music();
+        if let Some((&name, &bytes)) = self.sounds.get_key_value(name) {
+            self.backend.play_music(name, bytes, looped);
+            self.update_music();
+        }
+    }
+
+    pub fn stop_music(&mut self) {
+        self.backend.stop_music();
+    }
+
+    /// The volume of sound effects, between 0 and 1.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+    }
+
+    pub fn set_music_volume(&mut self, volume: f32) {
+        self.music_volume = volume;
+        self.update_music();
+    }
+
+    pub fn set_music_paused(&mut self, paused: bool) {
+        self.music_paused = paused;
+        self.update_music();
+    }
+
+    /// The player has just clicked something. Browsers only allow playback to start after
+    /// that, so the music is held back until then.
+    pub fn unlock(&mut self) {
+        self.backend.unlock();
+        self.update_music();
+    }
+
+    /// Gets every registered sound ready to play, rather than on first use, e.g. while the page
+    /// shows a loading bar.
+    pub fn preload(&mut self) {
+        for (&name, &bytes) in &self.sounds {
+            self.backend.preload(name, bytes);
+        }
+    }
+
+    fn update_music(&mut self) {
+        self.backend
+            .update_music(self.music_volume, self.music_paused);
+    }
+}
+
+/// Where the sounds end up, once the player has decided they are to be heard.
+trait Backend {
+    fn play_sfx(&mut self, name: &'static str, bytes: &'static [u8], volume: f32);
+    fn play_music(&mut self, name: &'static str, bytes: &'static [u8], looped: bool);
+    fn stop_music(&mut self);
+    fn update_music(&mut self, volume: f32, paused: bool);
+    fn unlock(&mut self);
+    fn preload(&mut self, name: &'static str, bytes: &'static [u8]);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type PlatformBackend = Rodio;
+
+#[cfg(target_arch = "wasm32")]
+type PlatformBackend = AudioElements;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Rodio {
+    /// Opened on the first sound rather than up front. Holds `None` once opening has failed, so
+    /// a missing device isn't asked for again every frame.
+    output: Option<Option<(rodio::OutputStream, rodio::OutputStreamHandle)>>,
+    /// Dropping it stops the music.
+    music: Option<rodio::Sink>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Rodio {
+    fn handle(&mut self) -> Option<&rodio::OutputStreamHandle> {
+        let output = self
+            .output
+            .get_or_insert_with(|| rodio::OutputStream::try_default().ok());
+
+        output.as_ref().map(|(_, handle)| handle)
+    }
+
+    fn start_music(&mut self, bytes: &'static [u8], looped: bool) -> Option<rodio::Sink> {
+        use rodio::Source;
+
+        let sink = rodio::Sink::try_new(self.handle()?).ok()?;
+        let source = rodio::Decoder::new(std::io::Cursor::new(bytes)).ok()?;
+        if looped {
+            sink.append(source.repeat_infinite());
+        } else {
+            sink.append(source);
+        }
+
+        Some(sink)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Backend for Rodio {
+    fn play_sfx(&mut self, _name: &'static str, bytes: &'static [u8], volume: f32) {
+        if let Some(handle) = self.handle() {
+            if let Ok(sink) = handle.play_once(std::io::Cursor::new(bytes)) {
+                sink.set_volume(volume);
+                sink.detach();
+            }
+        }
+    }
+
+    fn play_music(&mut self, _name: &'static str, bytes: &'static [u8], looped: bool) {
+        self.music = self.start_music(bytes, looped);
+    }
+
+    fn stop_music(&mut self) {
+        self.music = None;
+    }
+
+    fn update_music(&mut self, volume: f32, paused: bool) {
+        if let Some(music) = &self.music {
+            music.set_volume(volume);
+            if paused {
+                music.pause();
+            } else {
+                music.play();
+            }
+        }
+    }
+
+    fn unlock(&mut self) {}
+
+    /// rodio decodes as it plays, so there is nothing to get ready.
+    fn preload(&mut self, _name: &'static str, _bytes: &'static [u8]) {}
+}
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+struct AudioElements {
+    /// Object URLs for the sounds, created when they are preloaded or first played and kept
+    /// for as long as the game runs.
+    urls: HashMap<&'static str, String>,
+    music: Option<web_sys::HtmlAudioElement>,
+    /// Whether the player has clicked anything yet, which browsers require before playback.
+    unlocked: bool,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl AudioElements {
+    fn url(&mut self, name: &'static str, bytes: &'static [u8]) -> Option<String> {
+        if let Some(url) = self.urls.get(name) {
+            return Some(url.clone());
+        }
+
+        let url = object_url(bytes)?;
+        self.urls.insert(name, url.clone());
+        Some(url)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Backend for AudioElements {
+    fn preload(&mut self, name: &'static str, bytes: &'static [u8]) {
+        self.url(name, bytes);
+    }
+
+    fn play_sfx(&mut self, name: &'static str, bytes: &'static [u8], volume: f32) {
+        let url = match self.url(name, bytes) {
+            Some(url) => url,
+            None => return,
+        };
+
+        if let Ok(audio) = web_sys::HtmlAudioElement::new_with_src(&url) {
+            audio.set_volume(volume as f64);
+            // Browsers refuse to play before the first click on the page; those sounds are lost.
+            let _ = audio.play();
+        }
+    }
+
+    fn play_music(&mut self, name: &'static str, bytes: &'static [u8], looped: bool) {
+        self.music = self
+            .url(name, bytes)
+            .and_then(|url| web_sys::HtmlAudioElement::new_with_src(&url).ok())
+            .map(|music| {
+                music.set_loop(looped);
+                music
+            });
+    }
+
+    fn stop_music(&mut self) {
+        if let Some(music) = self.music.take() {
+            music.pause().ok();
+        }
+    }
+
+    fn update_music(&mut self, volume: f32, paused: bool) {
+        let music = match &self.music {
+            Some(music) => music,
+            None => return,
+        };
+
+        music.set_volume(volume as f64);
+        if paused {
+            music.pause().ok();
+        } else if self.unlocked && music.paused() && !music.ended() {
+            let _ = music.play();
+        }
+    }
+
+    fn unlock(&mut self) {
+        self.unlocked = true;
+    }
+}
+
+/// Makes the embedded WAV file loadable by an audio element.
+#[cfg(target_arch = "wasm32")]
+fn object_url(bytes: &[u8]) -> Option<String> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("audio/wav");
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options).ok()?;
+    web_sys::Url::create_object_url_with_blob(&blob).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    /// Notes down what reaches it instead of making a sound.
+    #[derive(Clone, Default)]
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl Recorder {
+        fn note(&self, call: String) {
+            self.0.borrow_mut().push(call);
+        }
+    }
+
+    impl Backend for Recorder {
+        fn play_sfx(&mut self, name: &'static str, _bytes: &'static [u8], volume: f32) {
+            self.note(format!("sfx {} at {}", name, volume));
+        }
+
+        fn play_music(&mut self, name: &'static str, _bytes: &'static [u8], looped: bool) {
+            self.note(format!("music {}, looped: {}", name, looped));
+        }
+
+        fn stop_music(&mut self) {
+            self.note("stop music".to_string());
+        }
+
+        fn update_music(&mut self, volume: f32, paused: bool) {
+            self.note(format!("music at {}, paused: {}", volume, paused));
+        }
+
+        fn unlock(&mut self) {
+            self.note("unlock".to_string());
+        }
+
+        fn preload(&mut self, name: &'static str, _bytes: &'static [u8]) {
+            self.note(format!("preload {}", name));
+        }
+    }
+
+    #[test]
+    fn sounds_are_handed_to_the_backend() {
+        let recorder = Recorder::default();
+        let mut audio = AudioPlayer::with_backend(recorder.clone());
+        audio.register("click", b"click");
+
+        // Sound effects are off until there is a volume, and unknown ones are skipped.
+        audio.play_sfx("click");
+        audio.set_volume(0.5);
+        audio.play_sfx("click");
+        audio.play_sfx("missing");
+        audio.preload();
+        assert_eq!(*recorder.0.borrow(), ["sfx click at 0.5", "preload click"]);
+    }
+
+    #[test]
+    fn music_follows_its_volume_and_pause() {
+        let recorder = Recorder::default();
+        let mut audio = AudioPlayer::with_backend(recorder.clone());
+        audio.register("theme", b"theme");
+
+        audio.set_music_volume(0.25);
+        audio.play_music("theme", true);
+        audio.set_music_paused(true);
+        audio.unlock();
+        audio.play_music("missing", false);
+        assert_eq!(
+            *recorder.0.borrow(),
+            [
+                "music at 0.25, paused: false",
+                "stop music",
+                "music theme, looped: true",
+                "music at 0.25, paused: false",
+                "music at 0.25, paused: true",
+                "unlock",
+                "music at 0.25, paused: true",
+                "stop music",
+            ]
+        );
+    }
+
+    /// A tenth of a second of silence, as 8-bit mono WAV.
+    #[cfg(all(feature = "audio-device", not(target_arch = "wasm32")))]
+    fn silence() -> &'static [u8] {
+        const SAMPLES: u32 = 800;
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + SAMPLES).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // PCM, one channel, 8000 samples a second of one byte each.
+        for &field in &[1u16, 1] {
+            wav.extend_from_slice(&field.to_le_bytes());
+        }
+        for &field in &[8000u32, 8000] {
+            wav.extend_from_slice(&field.to_le_bytes());
+        }
+        for &field in &[1u16, 8] {
+            wav.extend_from_slice(&field.to_le_bytes());
+        }
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&SAMPLES.to_le_bytes());
+        wav.resize(wav.len() + SAMPLES as usize, 128);
+        Box::leak(wav.into_boxed_slice())
+    }
+
+    /// Needs a working audio device, which build machines rarely have, so it only runs with
+    /// `cargo test --features audio-device`.
+    #[cfg(all(feature = "audio-device", not(target_arch = "wasm32")))]
+    #[test]
+    fn music_plays_on_the_audio_device() {
+        assert!(rodio::Decoder::new(std::io::Cursor::new(silence())).is_ok());
+        let mut rodio = Rodio::default();
+        rodio.play_music("silence", silence(), true);
+        assert!(rodio.music.is_some(), "no audio device to play on");
+
+        rodio.play_sfx("silence", silence(), 0.1);
+        rodio.update_music(0.1, true);
+        rodio.stop_music();
+        assert!(rodio.music.is_none());
+    }
+}
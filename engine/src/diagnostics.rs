@@ -0,0 +1,128 @@
+//! How long frames take, for FPS counters and debug overlays.
+
+/// How many of the latest frames the statistics cover.
+pub const WINDOW: usize = 120;
+
+/// Frame times in seconds over the last `WINDOW` frames. Recording a frame never allocates.
+#[derive(Debug, Clone)]
+pub struct FrameDiagnostics {
+    /// A ring buffer; `next` is where the next frame goes.
+    frame_seconds: [f64; WINDOW],
+    next: usize,
+    frame_count: u64,
+}
+
+impl Default for FrameDiagnostics {
+    fn default() -> Self {
+        Self {
+            frame_seconds: [0.0; WINDOW],
+            next: 0,
+            frame_count: 0,
+        }
+    }
+}
+
+impl FrameDiagnostics {
+    pub fn record(&mut self, frame_seconds: f64) {
+        self.frame_seconds[self.next] = frame_seconds;
+        self.next = (self.next + 1) % WINDOW;
+        self.frame_count += 1;
+    }
+
+    /// Every frame recorded so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn last_frame_seconds(&self) -> Option<f64> {
+        if self.frame_count == 0 {
+            return None;
+        }
+
+        Some(self.frame_seconds[(self.next + WINDOW - 1) % WINDOW])
+    }
+
+    pub fn average_frame_seconds(&self) -> Option<f64> {
+        let len = self.window().len();
+        if len == 0 {
+            return None;
+        }
+
+        Some(self.window().sum::<f64>() / len as f64)
+    }
+
+    pub fn min_frame_seconds(&self) -> Option<f64> {
+        self.window().fold(None, |min, seconds| {
+            Some(min.map_or(seconds, |min: f64| min.min(seconds)))
+        })
+    }
+
+    pub fn max_frame_seconds(&self) -> Option<f64> {
+        self.window().fold(None, |max, seconds| {
+            Some(max.map_or(seconds, |max: f64| max.max(seconds)))
+        })
+    }
+
+    /// Frames per second over the window, or 0 before the first frame.
+    pub fn average_fps(&self) -> f64 {
+        match self.average_frame_seconds() {
+            Some(seconds) if seconds > 0.0 => 1.0 / seconds,
+            _ => 0.0,
+        }
+    }
+
+    /// The recorded part of the buffer, in no particular order.
+    fn window(&self) -> impl ExactSizeIterator<Item = f64> + '_ {
+        let len = self.frame_count.min(WINDOW as u64) as usize;
+        self.frame_seconds[..len].iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_is_known_before_the_first_frame() {
+        let diagnostics = FrameDiagnostics::default();
+        assert_eq!(diagnostics.frame_count(), 0);
+        assert_eq!(diagnostics.last_frame_seconds(), None);
+        assert_eq!(diagnostics.average_frame_seconds(), None);
+        assert_eq!(diagnostics.min_frame_seconds(), None);
+        assert_eq!(diagnostics.max_frame_seconds(), None);
+        assert_eq!(diagnostics.average_fps(), 0.0);
+    }
+
+    #[test]
+    fn statistics_cover_the_frames_so_far() {
+        let mut diagnostics = FrameDiagnostics::default();
+        for seconds in [0.02, 0.01, 0.03] {
+            diagnostics.record(seconds);
+        }
+
+        assert_eq!(diagnostics.frame_count(), 3);
+        assert_eq!(diagnostics.last_frame_seconds(), Some(0.03));
+        assert_eq!(diagnostics.min_frame_seconds(), Some(0.01));
+        assert_eq!(diagnostics.max_frame_seconds(), Some(0.03));
+        assert!((diagnostics.average_frame_seconds().unwrap() - 0.02).abs() < 1e-12);
+        assert!((diagnostics.average_fps() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn old_frames_drop_out_of_the_window() {
+        let mut diagnostics = FrameDiagnostics::default();
+        diagnostics.record(1.0);
+        for _ in 0..WINDOW - 1 {
+            diagnostics.record(0.5);
+        }
+        assert_eq!(diagnostics.max_frame_seconds(), Some(1.0));
+
+        diagnostics.record(0.25);
+        assert_eq!(diagnostics.frame_count(), WINDOW as u64 + 1);
+        assert_eq!(diagnostics.last_frame_seconds(), Some(0.25));
+        assert_eq!(diagnostics.max_frame_seconds(), Some(0.5));
+        assert_eq!(diagnostics.min_frame_seconds(), Some(0.25));
+        let average = (0.5 * (WINDOW - 1) as f64 + 0.25) / WINDOW as f64;
+        assert!((diagnostics.average_frame_seconds().unwrap() - average).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,59 @@
+//! Why a game couldn't be run.
+
+use std::{
+    any::Any,
+    fmt::{Display, Formatter},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunError {
+    /// The window would have no area, or an infinite one.
+    InvalidWindowSize { width: f32, height: f32 },
+    /// A frame rate of 0 would never draw anything.
+    InvalidTargetFps,
+    /// There is no display server to open a window on, e.g. over SSH or in a container.
+    NoDisplay,
+    /// Something panicked while setting up or running the game.
+    Panicked(String),
+    /// The browser wouldn't let the game draw on its canvas, e.g. because there is no canvas
+    /// with that id or no WebGL.
+    WebStartFailed(String),
+}
+
+impl RunError {
+    /// Wraps what `catch_unwind` caught.
+    pub fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+
+        Self::Panicked(message)
+    }
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidWindowSize { width, height } => write!(
+                f,
+                "the window size must be positive, but it is {} × {}",
+                width, height
+            ),
+            Self::InvalidTargetFps => write!(f, "the target frame rate must be at least 1"),
+            Self::NoDisplay => write!(
+                f,
+                "no display found; set DISPLAY or WAYLAND_DISPLAY to run with a window"
+            ),
+            Self::Panicked(message) => write!(f, "the game crashed: {}", message),
+            Self::WebStartFailed(message) => {
+                write!(f, "the game couldn't start in the browser: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
@@ -0,0 +1,32 @@
+//! Closing the game from inside it.
+
+/// Asks for the game to close at the end of the frame, through the backend rather than
+/// `std::process::exit`, so it still gets to save and shut down its audio on the way out.
+#[derive(Debug, Default)]
+pub struct ExitRequest {
+    requested: bool,
+}
+
+impl ExitRequest {
+    /// A browser tab can't close itself, so on the web this only leaves a note in the console.
+    pub fn request(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        log::info!("Quitting isn't possible in the browser; close the tab.");
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.requested = true;
+        }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    /// Passes the request on to eframe, which then calls `App::on_exit` and closes the window.
+    #[cfg(feature = "backend-eframe")]
+    pub fn apply(&self, frame: &mut eframe::epi::Frame<'_>) {
+        if self.requested {
+            frame.quit();
+        }
+    }
+}
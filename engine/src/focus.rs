@@ -0,0 +1,38 @@
+//! Whether the player is looking at the game, so it can pause itself when they switch away.
+//! Only browsers tell for now; eframe doesn't pass on the focus of native windows.
+
+/// Whether `is_focused` can ever be false on this platform.
+pub fn is_supported() -> bool {
+    cfg!(target_arch = "wasm32")
+}
+
+/// The game's tab is showing and has the keyboard.
+#[cfg(target_arch = "wasm32")]
+pub fn is_focused() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .map_or(true, |document| {
+            !document.hidden() && document.has_focus().unwrap_or(true)
+        })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn is_focused() -> bool {
+    true
+}
+
+/// Notices the frame the game loses focus, so it is only reacted to once.
+#[derive(Debug, Default)]
+pub struct FocusWatcher {
+    unfocused: bool,
+}
+
+impl FocusWatcher {
+    /// Call once a frame. True on the first frame without focus.
+    pub fn lost_focus(&mut self) -> bool {
+        let focused = is_focused();
+        let lost = !focused && !self.unfocused;
+        self.unfocused = !focused;
+        lost
+    }
+}
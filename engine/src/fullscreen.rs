@@ -0,0 +1,41 @@
+//! Switching between a window and the whole screen. Only browsers can do this for now; eframe's
+//! native windows can't change mode once they are open.
+
+/// Whether `set_fullscreen` does anything on this platform.
+pub fn is_supported() -> bool {
+    cfg!(target_arch = "wasm32")
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn is_fullscreen() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.fullscreen_element())
+        .is_some()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn is_fullscreen() -> bool {
+    false
+}
+
+/// Shows the whole page fullscreen, or goes back to the browser window. Browsers only allow
+/// this in response to a click, which is fine for a checkbox.
+#[cfg(target_arch = "wasm32")]
+pub fn set_fullscreen(fullscreen: bool) {
+    let document = match web_sys::window().and_then(|window| window.document()) {
+        Some(document) => document,
+        None => return,
+    };
+
+    if fullscreen {
+        if let Some(element) = document.document_element() {
+            element.request_fullscreen().ok();
+        }
+    } else {
+        document.exit_fullscreen();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_fullscreen(_fullscreen: bool) {}
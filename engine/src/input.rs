@@ -0,0 +1,479 @@
+//! Keys bound to named actions, so games ask whether "pause" was pressed rather than Space, and
+//! players can rebind them. The names are up to the game.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The keys that can be bound. These are the ones every backend reports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum KeyCode {
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+    Escape,
+    Tab,
+    Backspace,
+    Enter,
+    Space,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Num0,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
+    Num5,
+    Num6,
+    Num7,
+    Num8,
+    Num9,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+}
+
+impl Display for KeyCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ArrowDown => "↓",
+            Self::ArrowLeft => "←",
+            Self::ArrowRight => "→",
+            Self::ArrowUp => "↑",
+            Self::Escape => "Esc",
+            Self::PageUp => "Page Up",
+            Self::PageDown => "Page Down",
+            Self::Num0 => "0",
+            Self::Num1 => "1",
+            Self::Num2 => "2",
+            Self::Num3 => "3",
+            Self::Num4 => "4",
+            Self::Num5 => "5",
+            Self::Num6 => "6",
+            Self::Num7 => "7",
+            Self::Num8 => "8",
+            Self::Num9 => "9",
+            other => return write!(f, "{:?}", other),
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl KeyCode {
+    /// The key a browser names `key` in its keyboard events, if it can be bound.
+    pub fn from_dom_key(key: &str) -> Option<Self> {
+        let code = match key {
+            "ArrowDown" => Self::ArrowDown,
+            "ArrowLeft" => Self::ArrowLeft,
+            "ArrowRight" => Self::ArrowRight,
+            "ArrowUp" => Self::ArrowUp,
+            "Escape" | "Esc" => Self::Escape,
+            "Tab" => Self::Tab,
+            "Backspace" => Self::Backspace,
+            "Enter" => Self::Enter,
+            " " | "Spacebar" => Self::Space,
+            "Insert" => Self::Insert,
+            "Delete" | "Del" => Self::Delete,
+            "Home" => Self::Home,
+            "End" => Self::End,
+            "PageUp" => Self::PageUp,
+            "PageDown" => Self::PageDown,
+            "0" => Self::Num0,
+            "1" => Self::Num1,
+            "2" => Self::Num2,
+            "3" => Self::Num3,
+            "4" => Self::Num4,
+            "5" => Self::Num5,
+            "6" => Self::Num6,
+            "7" => Self::Num7,
+            "8" => Self::Num8,
+            "9" => Self::Num9,
+            letter => return Self::from_letter(letter),
+        };
+
+        Some(code)
+    }
+
+    fn from_letter(letter: &str) -> Option<Self> {
+        let code = match letter.to_ascii_uppercase().as_str() {
+            "A" => Self::A,
+            "B" => Self::B,
+            "C" => Self::C,
+            "D" => Self::D,
+            "E" => Self::E,
+            "F" => Self::F,
+            "G" => Self::G,
+            "H" => Self::H,
+            "I" => Self::I,
+            "J" => Self::J,
+            "K" => Self::K,
+            "L" => Self::L,
+            "M" => Self::M,
+            "N" => Self::N,
+            "O" => Self::O,
+            "P" => Self::P,
+            "Q" => Self::Q,
+            "R" => Self::R,
+            "S" => Self::S,
+            "T" => Self::T,
+            "U" => Self::U,
+            "V" => Self::V,
+            "W" => Self::W,
+            "X" => Self::X,
+            "Y" => Self::Y,
+            "Z" => Self::Z,
+            _ => return None,
+        };
+
+        Some(code)
+    }
+}
+
+/// Which action each key triggers. A key is bound to one action at most.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InputMap {
+    bindings: BTreeMap<String, KeyCode>,
+}
+
+impl InputMap {
+    pub fn new(bindings: &[(&str, KeyCode)]) -> Self {
+        let mut map = Self::default();
+        for &(action, key) in bindings {
+            map.bind(action, key);
+        }
+
+        map
+    }
+
+    /// Binds `key` to `action`, taking it away from whichever action had it before. Returns
+    /// that action, so the player can be told it is unbound now.
+    pub fn bind(&mut self, action: &str, key: KeyCode) -> Option<String> {
+        let previous = self
+            .action(key)
+            .filter(|&previous| previous != action)
+            .map(str::to_string);
+        if let Some(previous) = &previous {
+            self.bindings.remove(previous);
+        }
+        self.bindings.insert(action.to_string(), key);
+
+        previous
+    }
+
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+    }
+
+    /// The key bound to `action`, if any.
+    pub fn key(&self, action: &str) -> Option<KeyCode> {
+        self.bindings.get(action).copied()
+    }
+
+    /// The action `key` triggers, if any.
+    pub fn action(&self, key: KeyCode) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|&(_, &bound)| bound == key)
+            .map(|(action, _)| action.as_str())
+    }
+
+    /// Adds the default binding for every action this map doesn't know yet, e.g. because it was
+    /// saved by an older version, as long as the key is still free.
+    pub fn with_defaults(mut self, defaults: &InputMap) -> Self {
+        for (action, &key) in &defaults.bindings {
+            if !self.bindings.contains_key(action) && self.action(key).is_none() {
+                self.bindings.insert(action.clone(), key);
+            }
+        }
+
+        self
+    }
+
+    /// The actions triggered by the keys pressed this frame, in the order they were pressed.
+    pub fn translate(&self, pressed: impl IntoIterator<Item = KeyCode>) -> ActionState {
+        ActionState {
+            just_pressed: pressed
+                .into_iter()
+                .filter_map(|key| self.action(key))
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// What the player asked for with the keyboard this frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionState {
+    just_pressed: Vec<String>,
+}
+
+impl ActionState {
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.just_pressed.iter().any(|pressed| pressed == action)
+    }
+
+    /// Every action triggered this frame, in the order the keys were pressed.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.just_pressed.iter().map(String::as_str)
+    }
+}
+
+#[cfg(feature = "backend-eframe")]
+mod egui_keys {
+    use eframe::egui::{self, Key};
+
+    use super::{ActionState, InputMap, KeyCode};
+
+    impl KeyCode {
+        fn to_egui(self) -> Key {
+            match self {
+                Self::ArrowDown => Key::ArrowDown,
+                Self::ArrowLeft => Key::ArrowLeft,
+                Self::ArrowRight => Key::ArrowRight,
+                Self::ArrowUp => Key::ArrowUp,
+                Self::Escape => Key::Escape,
+                Self::Tab => Key::Tab,
+                Self::Backspace => Key::Backspace,
+                Self::Enter => Key::Enter,
+                Self::Space => Key::Space,
+                Self::Insert => Key::Insert,
+                Self::Delete => Key::Delete,
+                Self::Home => Key::Home,
+                Self::End => Key::End,
+                Self::PageUp => Key::PageUp,
+                Self::PageDown => Key::PageDown,
+                Self::Num0 => Key::Num0,
+                Self::Num1 => Key::Num1,
+                Self::Num2 => Key::Num2,
+                Self::Num3 => Key::Num3,
+                Self::Num4 => Key::Num4,
+                Self::Num5 => Key::Num5,
+                Self::Num6 => Key::Num6,
+                Self::Num7 => Key::Num7,
+                Self::Num8 => Key::Num8,
+                Self::Num9 => Key::Num9,
+                Self::A => Key::A,
+                Self::B => Key::B,
+                Self::C => Key::C,
+                Self::D => Key::D,
+                Self::E => Key::E,
+                Self::F => Key::F,
+                Self::G => Key::G,
+                Self::H => Key::H,
+                Self::I => Key::I,
+                Self::J => Key::J,
+                Self::K => Key::K,
+                Self::L => Key::L,
+                Self::M => Key::M,
+                Self::N => Key::N,
+                Self::O => Key::O,
+                Self::P => Key::P,
+                Self::Q => Key::Q,
+                Self::R => Key::R,
+                Self::S => Key::S,
+                Self::T => Key::T,
+                Self::U => Key::U,
+                Self::V => Key::V,
+                Self::W => Key::W,
+                Self::X => Key::X,
+                Self::Y => Key::Y,
+                Self::Z => Key::Z,
+            }
+        }
+    }
+
+    /// Every bindable key pressed this frame, in order.
+    pub fn pressed_keys(input: &egui::InputState) -> Vec<KeyCode> {
+        input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Key {
+                    key, pressed: true, ..
+                } => ALL_KEYS.iter().copied().find(|code| code.to_egui() == *key),
+                _ => None,
+            })
+            .collect()
+    }
+
+    impl ActionState {
+        pub fn from_egui(map: &InputMap, input: &egui::InputState) -> Self {
+            map.translate(pressed_keys(input))
+        }
+    }
+
+    const ALL_KEYS: &[KeyCode] = &[
+        KeyCode::ArrowDown,
+        KeyCode::ArrowLeft,
+        KeyCode::ArrowRight,
+        KeyCode::ArrowUp,
+        KeyCode::Escape,
+        KeyCode::Tab,
+        KeyCode::Backspace,
+        KeyCode::Enter,
+        KeyCode::Space,
+        KeyCode::Insert,
+        KeyCode::Delete,
+        KeyCode::Home,
+        KeyCode::End,
+        KeyCode::PageUp,
+        KeyCode::PageDown,
+        KeyCode::Num0,
+        KeyCode::Num1,
+        KeyCode::Num2,
+        KeyCode::Num3,
+        KeyCode::Num4,
+        KeyCode::Num5,
+        KeyCode::Num6,
+        KeyCode::Num7,
+        KeyCode::Num8,
+        KeyCode::Num9,
+        KeyCode::A,
+        KeyCode::B,
+        KeyCode::C,
+        KeyCode::D,
+        KeyCode::E,
+        KeyCode::F,
+        KeyCode::G,
+        KeyCode::H,
+        KeyCode::I,
+        KeyCode::J,
+        KeyCode::K,
+        KeyCode::L,
+        KeyCode::M,
+        KeyCode::N,
+        KeyCode::O,
+        KeyCode::P,
+        KeyCode::Q,
+        KeyCode::R,
+        KeyCode::S,
+        KeyCode::T,
+        KeyCode::U,
+        KeyCode::V,
+        KeyCode::W,
+        KeyCode::X,
+        KeyCode::Y,
+        KeyCode::Z,
+    ];
+}
+
+#[cfg(feature = "backend-eframe")]
+pub use egui_keys::pressed_keys;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> InputMap {
+        InputMap::new(&[("pause", KeyCode::Space), ("speed_up", KeyCode::Num2)])
+    }
+
+    #[test]
+    fn pressed_keys_turn_into_their_actions_in_order() {
+        let actions = map().translate([KeyCode::Num2, KeyCode::Q, KeyCode::Space]);
+
+        assert_eq!(actions.iter().collect::<Vec<_>>(), ["speed_up", "pause"]);
+        assert!(actions.just_pressed("pause"));
+        assert!(!actions.just_pressed("scramble_a"));
+        assert_eq!(map().translate([]), ActionState::default());
+    }
+
+    #[test]
+    fn rebinding_a_key_takes_it_from_the_action_that_had_it() {
+        let mut map = map();
+
+        assert_eq!(
+            map.bind("speed_up", KeyCode::Space),
+            Some("pause".to_string())
+        );
+        assert_eq!(map.key("pause"), None);
+        assert_eq!(map.key("speed_up"), Some(KeyCode::Space));
+        assert_eq!(map.action(KeyCode::Num2), None);
+
+        // Binding an action to the key it already has takes nothing away.
+        assert_eq!(map.bind("speed_up", KeyCode::Space), None);
+        map.unbind("speed_up");
+        assert_eq!(map.action(KeyCode::Space), None);
+    }
+
+    #[test]
+    fn defaults_fill_in_new_actions_on_free_keys_only() {
+        let saved = InputMap::new(&[("pause", KeyCode::P), ("speed_up", KeyCode::Num1)]);
+        let defaults = InputMap::new(&[
+            ("pause", KeyCode::Space),
+            ("slow_down", KeyCode::Num1),
+            ("scramble_a", KeyCode::A),
+        ]);
+
+        let map = saved.with_defaults(&defaults);
+        assert_eq!(map.key("pause"), Some(KeyCode::P));
+        assert_eq!(map.key("speed_up"), Some(KeyCode::Num1));
+        assert_eq!(map.key("slow_down"), None);
+        assert_eq!(map.key("scramble_a"), Some(KeyCode::A));
+    }
+
+    #[test]
+    fn browser_key_names_are_understood() {
+        assert_eq!(KeyCode::from_dom_key(" "), Some(KeyCode::Space));
+        assert_eq!(KeyCode::from_dom_key("Esc"), Some(KeyCode::Escape));
+        assert_eq!(KeyCode::from_dom_key("q"), Some(KeyCode::Q));
+        assert_eq!(KeyCode::from_dom_key("7"), Some(KeyCode::Num7));
+        assert_eq!(KeyCode::from_dom_key("F5"), None);
+        assert_eq!(KeyCode::from_dom_key("Shift"), None);
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    #[test]
+    fn egui_key_presses_are_translated_but_releases_are_not() {
+        use eframe::egui::{Event, InputState, Key, Modifiers};
+
+        let key = |key, pressed| Event::Key {
+            key,
+            pressed,
+            modifiers: Modifiers::default(),
+        };
+        let mut input = InputState::default();
+        input.events = vec![
+            key(Key::Space, false),
+            key(Key::Num2, true),
+            Event::Text("2".to_string()),
+            key(Key::Space, true),
+        ];
+
+        let actions = ActionState::from_egui(&map(), &input);
+        assert_eq!(actions.iter().collect::<Vec<_>>(), ["speed_up", "pause"]);
+    }
+}
@@ -1,19 +1,49 @@
+#[cfg(feature = "backend-eframe")]
 pub extern crate eframe;
 
+pub use error::RunError;
+pub use exit::ExitRequest;
+#[cfg(feature = "backend-eframe")]
+pub use textures::{DecodedTextures, EguiTextures, TextureAsset};
+#[cfg(feature = "backend-eframe")]
+pub use window::{run, RunConfig};
+
+pub mod audio;
+pub mod diagnostics;
+mod error;
+mod exit;
+pub mod focus;
+pub mod fullscreen;
+pub mod input;
+pub mod pacing;
+pub mod screenshot;
+#[cfg(feature = "backend-eframe")]
+pub mod textures;
+pub mod touch;
+pub mod ui;
+#[cfg(feature = "backend-eframe")]
+mod window;
+
 // pub struct Engine {
 //     time: MyTime,
 // }
 
-// pub fn run<G: Plugin>(game: G, title: &str) {
-//     App::build()
-//         .insert_resource(WindowDescriptor {
-//             title: title.to_string(),
-//             ..Default::default()
-//         })
-//         // .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
-//         // .insert_resource(Msaa { samples: 4 })
-//         .add_plugins(DefaultPlugins)
-//         // .add_plugin(EguiPlugin)
-//         .add_plugin(game)
-//         .run();
-// }
+/// The rules of a game, separate from its GUI, so they can run without a window.
+pub trait Headless {
+    /// Plays one frame that took `frame_time` of real time.
+    fn step(&mut self, frame_time: std::time::Duration);
+}
+
+/// Plays `frames` frames at 60 per second as fast as possible, without opening a window, and
+/// hands the game back so its state can be inspected.
+pub fn run_headless<G: Headless>(mut game: G, frames: u32) -> Result<G, RunError> {
+    let frame_time = std::time::Duration::from_secs_f64(1.0 / 60.0);
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+        for _ in 0..frames {
+            game.step(frame_time);
+        }
+
+        game
+    }))
+    .map_err(RunError::from_panic)
+}
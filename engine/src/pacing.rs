@@ -0,0 +1,47 @@
+//! Keeping the frame rate down to what a game needs, so a mostly static GUI doesn't keep a core
+//! busy redrawing as fast as it can.
+
+use std::time::Duration;
+
+/// No single wait is longer than this, so input is never held back noticeably, however low the
+/// target.
+const MAX_WAIT: Duration = Duration::from_millis(50);
+
+/// Waits out the rest of each frame natively. Browsers already pace frames to the display, so
+/// there it does nothing.
+#[derive(Debug, Clone, Default)]
+pub struct FrameLimiter {
+    /// `None` for as fast as possible.
+    frame_time: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_frame: Option<std::time::Instant>,
+}
+
+impl FrameLimiter {
+    pub fn new(target_fps: Option<u32>) -> Self {
+        Self {
+            frame_time: target_fps
+                .filter(|&fps| fps > 0)
+                .map(|fps| Duration::from_secs_f64(1.0 / fps as f64)),
+            ..Self::default()
+        }
+    }
+
+    /// Call at the start of every frame. Sleeps until a frame time has passed since the last
+    /// call.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn wait(&mut self) {
+        use std::time::Instant;
+
+        if let (Some(frame_time), Some(last_frame)) = (self.frame_time, self.last_frame) {
+            let remaining = frame_time.checked_sub(last_frame.elapsed());
+            if let Some(remaining) = remaining {
+                std::thread::sleep(remaining.min(MAX_WAIT));
+            }
+        }
+        self.last_frame = Some(Instant::now());
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn wait(&mut self) {}
+}
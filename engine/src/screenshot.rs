@@ -0,0 +1,71 @@
+//! Saving what the game currently shows as a PNG. Only the browser can do this for now, where
+//! the canvas is turned into a download; eframe doesn't let native apps read the frame back.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScreenshotError {
+    /// This platform can't read the frame back at all.
+    Unsupported,
+    /// The browser refused, e.g. because the canvas is gone.
+    Failed(String),
+}
+
+impl Display for ScreenshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "screenshots aren't supported here"),
+            Self::Failed(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ScreenshotError {}
+
+/// Whether `save` can work on this platform.
+pub fn is_supported() -> bool {
+    cfg!(target_arch = "wasm32")
+}
+
+/// Offers the current contents of the canvas with `canvas_id`, the one the game was started on,
+/// as a PNG download, named `name_prefix` followed by the date and time. Returns the file name.
+#[cfg(target_arch = "wasm32")]
+pub fn save(canvas_id: &str, name_prefix: &str) -> Result<String, ScreenshotError> {
+    use wasm_bindgen::JsCast;
+
+    let failed = |what: &str| ScreenshotError::Failed(what.to_string());
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| failed("no document"))?;
+    let canvas = document
+        .get_element_by_id(canvas_id)
+        .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+        .ok_or_else(|| failed("the game's canvas is missing"))?;
+    let url = canvas
+        .to_data_url_with_type("image/png")
+        .map_err(|error| ScreenshotError::Failed(format!("{:?}", error)))?;
+
+    // ISO time without the characters file systems dislike, e.g. 2021-06-05T14-03-59.
+    let timestamp: String = String::from(js_sys::Date::new_0().to_iso_string())
+        .chars()
+        .take(19)
+        .map(|c| if c == ':' { '-' } else { c })
+        .collect();
+    let file_name = format!("{}_{}.png", name_prefix, timestamp);
+
+    let link = document
+        .create_element("a")
+        .ok()
+        .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+        .ok_or_else(|| failed("couldn't create a download link"))?;
+    link.set_href(&url);
+    link.set_download(&file_name);
+    link.click();
+
+    Ok(file_name)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(_canvas_id: &str, _name_prefix: &str) -> Result<String, ScreenshotError> {
+    Err(ScreenshotError::Unsupported)
+}
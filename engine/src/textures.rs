@@ -0,0 +1,109 @@
+//! Images for egui, loaded once at startup and looked up by name afterwards.
+
+use std::collections::HashMap;
+
+use eframe::{
+    egui::{Color32, TextureId},
+    epi,
+};
+
+/// An embedded PNG and the name it is looked up by.
+pub type TextureAsset = (&'static str, &'static [u8]);
+
+/// Decoded pixels and their size.
+type Image = ((usize, usize), Vec<Color32>);
+
+/// Every texture the game asked for, by name.
+#[derive(Debug, Default)]
+pub struct EguiTextures {
+    textures: HashMap<&'static str, TextureId>,
+}
+
+/// Assets decoded ahead of registering them, e.g. one by one while the page shows a loading
+/// bar. Only registering needs the backend.
+#[derive(Debug, Default)]
+pub struct DecodedTextures {
+    /// `None` for assets that couldn't be decoded.
+    images: Vec<(&'static str, Option<Image>)>,
+}
+
+impl DecodedTextures {
+    pub fn decode(&mut self, &(name, png): &TextureAsset) {
+        let image = decode(png)
+            .map_err(|error| log::warn!("Could not load texture {:?}: {}", name, error))
+            .ok();
+        self.images.push((name, image));
+    }
+}
+
+impl EguiTextures {
+    /// Decodes and registers every asset. One that can't be decoded gets a checkerboard
+    /// placeholder instead, so the mistake is visible without taking the game down.
+    pub fn load(tex_allocator: &mut dyn epi::TextureAllocator, assets: &[TextureAsset]) -> Self {
+        let mut decoded = DecodedTextures::default();
+        for asset in assets {
+            decoded.decode(asset);
+        }
+
+        Self::register(tex_allocator, decoded)
+    }
+
+    /// Registers assets decoded before, with the same placeholder as `load` for the ones that
+    /// couldn't be.
+    pub fn register(
+        tex_allocator: &mut dyn epi::TextureAllocator,
+        decoded: DecodedTextures,
+    ) -> Self {
+        let mut placeholder = None;
+        let textures = decoded
+            .images
+            .into_iter()
+            .map(|(name, image)| {
+                let texture_id = match image {
+                    Some((size, pixels)) => tex_allocator.alloc_srgba_premultiplied(size, &pixels),
+                    None => *placeholder.get_or_insert_with(|| {
+                        let (size, pixels) = checkerboard();
+                        tex_allocator.alloc_srgba_premultiplied(size, &pixels)
+                    }),
+                };
+                (name, texture_id)
+            })
+            .collect();
+
+        Self { textures }
+    }
+
+    /// The texture registered under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<TextureId> {
+        self.textures.get(name).copied()
+    }
+}
+
+fn decode(png: &[u8]) -> Result<Image, image::ImageError> {
+    let image = image::load_from_memory(png)?.to_rgba8();
+    let (width, height) = image.dimensions();
+    let pixels = image
+        .into_raw()
+        .chunks(4)
+        .map(|pixel| Color32::from_rgba_unmultiplied(pixel[0], pixel[1], pixel[2], pixel[3]))
+        .collect();
+
+    Ok(((width as usize, height as usize), pixels))
+}
+
+/// Magenta and black, like every engine's missing texture.
+fn checkerboard() -> Image {
+    const SIZE: usize = 8;
+
+    let pixels = (0..SIZE * SIZE)
+        .map(|index| {
+            if (index % SIZE + index / SIZE).is_multiple_of(2) {
+                Color32::from_rgb(255, 0, 255)
+            } else {
+                Color32::BLACK
+            }
+        })
+        .collect();
+
+    ((SIZE, SIZE), pixels)
+}
@@ -0,0 +1,12 @@
+//! Whether the player plays with their fingers, so the GUI can make room for them.
+
+/// The device has a touch screen. Only browsers tell; native builds assume a mouse.
+#[cfg(target_arch = "wasm32")]
+pub fn has_touch_screen() -> bool {
+    web_sys::window().map_or(false, |window| window.navigator().max_touch_points() > 0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn has_touch_screen() -> bool {
+    false
+}
@@ -0,0 +1,17 @@
+//! Settings that apply to the whole of egui.
+
+use std::ops::RangeInclusive;
+
+/// How far the UI can be scaled. Beyond this it either doesn't fit a laptop screen or can't be
+/// read any more.
+pub const UI_SCALES: RangeInclusive<f32> = 0.5..=3.0;
+
+/// Scales the whole UI by `scale`, kept within `UI_SCALES`. The context is only touched when
+/// the scale actually changes, since egui lays everything out afresh then.
+#[cfg(feature = "backend-eframe")]
+pub fn set_ui_scale(ctx: &eframe::egui::CtxRef, scale: f32) {
+    let scale = scale.max(*UI_SCALES.start()).min(*UI_SCALES.end());
+    if ctx.pixels_per_point() != scale {
+        ctx.set_pixels_per_point(scale);
+    }
+}
@@ -0,0 +1,331 @@
+//! Running an app in a window of its own natively, or on a canvas in the browser.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, rc::Rc};
+
+use eframe::{egui, epi};
+
+use crate::{pacing::FrameLimiter, RunError};
+
+/// How the window looks and behaves.
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    pub title: String,
+    /// The size the window opens at, in points.
+    pub width: f32,
+    pub height: f32,
+    pub resizable: bool,
+    /// What shows wherever the app doesn't draw anything.
+    pub clear_color: egui::Color32,
+    /// The id of the canvas element the app draws on in the browser. The page decides its size,
+    /// so `width` and `height` only matter natively.
+    pub canvas_id: String,
+    /// The canvas follows the size of the browser window, up to this many points. Huge WebGL
+    /// canvases are slow in some browsers, but eframe's default of 1024 points across leaves
+    /// wide screens with empty space at the sides.
+    pub max_canvas_size: egui::Vec2,
+    /// Sets up the app's look once, before its first frame. Whatever changes later, like the
+    /// player picking another theme, is up to the app itself.
+    pub style: Option<fn(&mut egui::Style)>,
+    /// How many frames per second to draw at most natively, or `None` for as many as possible.
+    /// In the browser the display sets the pace.
+    pub target_fps: Option<u32>,
+    /// A PNG for the window's title bar and the task bar. In the browser the page's favicon is
+    /// used instead.
+    pub icon: Option<&'static [u8]>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            title: "Game".to_string(),
+            width: 1280.0,
+            height: 720.0,
+            resizable: true,
+            clear_color: egui::Color32::from_rgb(12, 12, 12),
+            canvas_id: "the_canvas_id".to_string(),
+            max_canvas_size: egui::Vec2::new(3840.0, 2160.0),
+            style: None,
+            target_fps: Some(60),
+            icon: None,
+        }
+    }
+}
+
+impl RunConfig {
+    /// Catches mistakes in the configuration before eframe gets to trip over them.
+    pub fn validate(&self) -> Result<(), RunError> {
+        let valid = |size: f32| size.is_finite() && size > 0.0;
+        if !valid(self.width) || !valid(self.height) {
+            return Err(RunError::InvalidWindowSize {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if self.target_fps == Some(0) {
+            return Err(RunError::InvalidTargetFps);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn native_options(&self) -> epi::NativeOptions {
+        epi::NativeOptions {
+            initial_window_size: Some(egui::Vec2::new(self.width, self.height)),
+            resizable: self.resizable,
+            icon_data: self.icon.and_then(icon_data),
+            ..Default::default()
+        }
+    }
+}
+
+/// Opens a window as configured and runs the app in it until the window is closed, which ends
+/// the process. Only returns if the game can't be run.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run<A: epi::App + 'static>(app: A, config: RunConfig) -> Result<(), RunError> {
+    config.validate()?;
+    if !has_display() {
+        return Err(RunError::NoDisplay);
+    }
+
+    let options = config.native_options();
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        eframe::run_native(Box::new(Configured::new(app, config)), options)
+    }))
+    .map_err(RunError::from_panic)
+}
+
+/// Starts the app on the configured canvas and returns straight away; the browser calls back
+/// into it every frame from then on. A page has no window to close, so the app's `on_exit` runs
+/// when the page is about to be unloaded instead.
+#[cfg(target_arch = "wasm32")]
+pub fn run<A: epi::App + 'static>(app: A, config: RunConfig) -> Result<(), RunError> {
+    config.validate()?;
+    let canvas_id = config.canvas_id.clone();
+    let app = Shared(Rc::new(RefCell::new(app)));
+    exit_on_unload(Rc::clone(&app.0));
+    eframe::start_web(&canvas_id, Box::new(Configured::new(app, config)))
+        .map_err(|error| RunError::WebStartFailed(format!("{:?}", error)))
+}
+
+/// Calls the app's `on_exit` when the page is about to go away, e.g. on reload or when the tab
+/// is closed. Anything that goes wrong is logged; it never keeps the page from closing.
+#[cfg(target_arch = "wasm32")]
+fn exit_on_unload<A: epi::App + 'static>(app: Rc<RefCell<A>>) {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => {
+            log::warn!("No window to tell when the page closes; the app won't see it exit");
+            return;
+        }
+    };
+    let listener = Closure::wrap(Box::new(move || match app.try_borrow_mut() {
+        Ok(mut app) => app.on_exit(),
+        Err(_) => log::warn!("The app was busy when the page closed and didn't see it exit"),
+    }) as Box<dyn FnMut()>);
+    if let Err(error) =
+        window.add_event_listener_with_callback("beforeunload", listener.as_ref().unchecked_ref())
+    {
+        log::warn!("Couldn't listen for the page closing: {:?}", error);
+    }
+    // The listener is needed for as long as the page is open.
+    listener.forget();
+}
+
+/// The app, shared between eframe and the listener for the page closing.
+#[cfg(target_arch = "wasm32")]
+struct Shared<A>(Rc<RefCell<A>>);
+
+#[cfg(target_arch = "wasm32")]
+impl<A: epi::App> epi::App for Shared<A> {
+    fn update(&mut self, ctx: &egui::CtxRef, frame: &mut epi::Frame<'_>) {
+        self.0.borrow_mut().update(ctx, frame);
+    }
+
+    fn setup(
+        &mut self,
+        ctx: &egui::CtxRef,
+        frame: &mut epi::Frame<'_>,
+        storage: Option<&dyn epi::Storage>,
+    ) {
+        self.0.borrow_mut().setup(ctx, frame, storage);
+    }
+
+    fn warm_up_enabled(&self) -> bool {
+        self.0.borrow().warm_up_enabled()
+    }
+
+    fn save(&mut self, storage: &mut dyn epi::Storage) {
+        self.0.borrow_mut().save(storage);
+    }
+
+    fn on_exit(&mut self) {
+        self.0.borrow_mut().on_exit();
+    }
+
+    /// Never asked for; `Configured` answers with the configured title.
+    fn name(&self) -> &str {
+        ""
+    }
+
+    fn auto_save_interval(&self) -> std::time::Duration {
+        self.0.borrow().auto_save_interval()
+    }
+}
+
+/// Decodes the window icon. One that can't be decoded is left out rather than keeping the game
+/// from starting.
+#[cfg(not(target_arch = "wasm32"))]
+fn icon_data(png: &[u8]) -> Option<epi::IconData> {
+    match image::load_from_memory(png) {
+        Ok(image) => {
+            let image = image.to_rgba8();
+            let (width, height) = image.dimensions();
+            Some(epi::IconData {
+                rgba: image.into_raw(),
+                width,
+                height,
+            })
+        }
+        Err(error) => {
+            log::warn!("Could not load the window icon: {}", error);
+            None
+        }
+    }
+}
+
+/// Whether a window could be opened at all. Only X11 and Wayland say so up front; everywhere
+/// else there is always a display.
+#[cfg(not(target_arch = "wasm32"))]
+fn has_display() -> bool {
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|variable| std::env::var_os(variable).is_some_and(|value| !value.is_empty()))
+    } else {
+        true
+    }
+}
+
+/// eframe asks the app itself for the window title, clear colour and canvas size, so this
+/// answers with the configured ones, installs the configured style, keeps the app animating at
+/// the configured frame rate and otherwise leaves everything to the app.
+struct Configured<A> {
+    app: A,
+    config: RunConfig,
+    limiter: FrameLimiter,
+}
+
+impl<A> Configured<A> {
+    fn new(app: A, config: RunConfig) -> Self {
+        Self {
+            app,
+            limiter: FrameLimiter::new(config.target_fps),
+            config,
+        }
+    }
+}
+
+impl<A: epi::App> epi::App for Configured<A> {
+    fn update(&mut self, ctx: &egui::CtxRef, frame: &mut epi::Frame<'_>) {
+        self.limiter.wait();
+        self.app.update(ctx, frame);
+        // Games move on their own, so there is always another frame to draw.
+        ctx.request_repaint();
+    }
+
+    fn setup(&mut self, ctx: &egui::CtxRef) {
+        if let Some(install_style) = self.config.style {
+            let mut style = (*ctx.style()).clone();
+            install_style(&mut style);
+            ctx.set_style(style);
+        }
+        self.app.setup(ctx);
+    }
+
+    fn load(&mut self, storage: &dyn epi::Storage) {
+        self.app.load(storage);
+    }
+
+    fn warm_up_enabled(&self) -> bool {
+        self.app.warm_up_enabled()
+    }
+
+    fn save(&mut self, storage: &mut dyn epi::Storage) {
+        self.app.save(storage);
+    }
+
+    fn on_exit(&mut self) {
+        self.app.on_exit();
+    }
+
+    fn name(&self) -> &str {
+        &self.config.title
+    }
+
+    fn auto_save_interval(&self) -> std::time::Duration {
+        self.app.auto_save_interval()
+    }
+
+    fn max_size_points(&self) -> egui::Vec2 {
+        self.config.max_canvas_size
+    }
+
+    fn clear_color(&self) -> egui::Rgba {
+        self.config.clear_color.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_default_config_is_valid() {
+        assert_eq!(RunConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn a_window_without_a_proper_size_is_refused() {
+        for (width, height) in [(0.0, 720.0), (1280.0, -1.0), (f32::INFINITY, 720.0)] {
+            let config = RunConfig {
+                width,
+                height,
+                ..RunConfig::default()
+            };
+            assert_eq!(
+                config.validate(),
+                Err(RunError::InvalidWindowSize { width, height })
+            );
+        }
+
+        let config = RunConfig {
+            width: f32::NAN,
+            ..RunConfig::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(RunError::InvalidWindowSize { .. })
+        ));
+    }
+
+    #[test]
+    fn a_frame_rate_of_zero_is_refused_but_no_limit_is_fine() {
+        let config = RunConfig {
+            target_fps: Some(0),
+            ..RunConfig::default()
+        };
+        assert_eq!(config.validate(), Err(RunError::InvalidTargetFps));
+
+        let config = RunConfig {
+            target_fps: None,
+            ..RunConfig::default()
+        };
+        assert_eq!(config.validate(), Ok(()));
+    }
+}
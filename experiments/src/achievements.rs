@@ -0,0 +1,320 @@
+//! Goals that carry over between runs. Each is checked against what the current run has done
+//! so far whenever something that could matter to it happens, and once more when the run ends.
+//! An achievement stays unlocked for good once it is.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::{
+    events::GameEvent,
+    game::{UnitAction, UnitId},
+    game_mode::{GameMode, Medal},
+    stats::GameStats,
+    storage::{self, Storage},
+};
+
+/// Names the achievements in storage, so their order and wording can change.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum AchievementId {
+    FirstInterception,
+    TenInOneRun,
+    BossHunter,
+    CleanThreeMinutes,
+    TenMinutes,
+    LeanContract,
+    GoldContract,
+}
+
+/// What the current run has done so far, beyond its stats.
+#[derive(Debug, Default, Clone)]
+pub struct RunFacts {
+    pub survived_seconds: f64,
+    pub mode: GameMode,
+    /// Whether the run is followed from its start, rather than from where a save picked it up.
+    /// Only then is it known which units ever left storage.
+    pub from_start: bool,
+    /// Units that came out of storage at any point.
+    pub units_unstored: BTreeSet<UnitId>,
+    pub bosses_intercepted: usize,
+    /// The medal for the contract, once it is fulfilled.
+    pub medal: Option<Medal>,
+}
+
+pub struct Achievement {
+    pub id: AchievementId,
+    /// Whether the run so far earns the achievement.
+    pub check: fn(&GameStats, &RunFacts) -> bool,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: AchievementId::FirstInterception,
+        check: |stats, _| stats.total_enemies_destroyed() >= 1,
+    },
+    Achievement {
+        id: AchievementId::TenInOneRun,
+        check: |stats, _| stats.total_enemies_destroyed() >= 10,
+    },
+    Achievement {
+        id: AchievementId::BossHunter,
+        check: |_, run| run.bosses_intercepted >= 1,
+    },
+    Achievement {
+        id: AchievementId::CleanThreeMinutes,
+        check: |stats, run| run.survived_seconds >= 3.0 * 60.0 && stats.units_lost == 0,
+    },
+    Achievement {
+        id: AchievementId::TenMinutes,
+        check: |_, run| run.survived_seconds >= 10.0 * 60.0,
+    },
+    Achievement {
+        id: AchievementId::LeanContract,
+        check: |_, run| run.medal.is_some() && run.from_start && run.units_unstored.len() <= 4,
+    },
+    Achievement {
+        id: AchievementId::GoldContract,
+        check: |_, run| run.medal == Some(Medal::Gold),
+    },
+];
+
+/// The achievements unlocked so far, and what the current run has done towards the others.
+#[derive(Default)]
+pub struct Achievements {
+    unlocked: BTreeSet<AchievementId>,
+    run: RunFacts,
+}
+
+impl Achievements {
+    const STORAGE_KEY: &'static str = "achievements";
+
+    pub fn load(storage: &dyn Storage) -> Self {
+        Self {
+            unlocked: storage::load(storage, Self::STORAGE_KEY),
+            run: RunFacts::default(),
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn is_unlocked(&self, id: AchievementId) -> bool {
+        self.unlocked.contains(&id)
+    }
+
+    /// Forgets what the last run did, for a run in `mode` followed from now on.
+    pub fn start_run(&mut self, mode: GameMode, from_start: bool) {
+        self.run = RunFacts {
+            mode,
+            from_start,
+            ..RunFacts::default()
+        };
+    }
+
+    /// Follows the run `survived_seconds` into it, with `stats` already counting `event`.
+    /// Returns the achievements this unlocked, which are saved straight away.
+    pub fn record(
+        &mut self,
+        event: &GameEvent,
+        stats: &GameStats,
+        survived_seconds: f64,
+        storage: &mut dyn Storage,
+    ) -> Vec<AchievementId> {
+        self.run.survived_seconds = survived_seconds;
+        match event {
+            GameEvent::UnitOrdered {
+                unit,
+                action: UnitAction::UnStore,
+            } => {
+                self.run.units_unstored.insert(*unit);
+            }
+            GameEvent::EnemyIntercepted { is_boss: true, .. } => {
+                self.run.bosses_intercepted += 1;
+            }
+            GameEvent::Victory { .. } => {
+                self.run.medal = self
+                    .run
+                    .mode
+                    .contract()
+                    .map(|contract| contract.medal(stats.units_lost, stats.bosses_leaked));
+            }
+            // Too frequent, and never enough to earn anything on their own.
+            GameEvent::EnemyBreaching { .. } | GameEvent::ParkingChanged { .. } => {
+                return Vec::new()
+            }
+            _ => {}
+        }
+
+        let newly_unlocked: Vec<_> = ACHIEVEMENTS
+            .iter()
+            .filter(|achievement| !self.unlocked.contains(&achievement.id))
+            .filter(|achievement| (achievement.check)(stats, &self.run))
+            .map(|achievement| achievement.id)
+            .collect();
+        if !newly_unlocked.is_empty() {
+            self.unlocked.extend(newly_unlocked.iter().copied());
+            storage::save(storage, Self::STORAGE_KEY, &self.unlocked);
+        }
+
+        newly_unlocked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::CombatType, storage::MemoryStorage};
+
+    /// A run fed with synthetic events, the way the game passes them on.
+    struct Run {
+        stats: GameStats,
+        achievements: Achievements,
+        storage: MemoryStorage,
+    }
+
+    impl Run {
+        fn new(mode: GameMode) -> Self {
+            Self::resume(MemoryStorage::default(), mode)
+        }
+
+        fn resume(storage: MemoryStorage, mode: GameMode) -> Self {
+            let mut achievements = Achievements::load(&storage);
+            achievements.start_run(mode, true);
+            Self {
+                stats: GameStats::default(),
+                achievements,
+                storage,
+            }
+        }
+
+        fn record(&mut self, event: GameEvent, survived_seconds: f64) -> Vec<AchievementId> {
+            self.stats.record(&event);
+            self.achievements
+                .record(&event, &self.stats, survived_seconds, &mut self.storage)
+        }
+
+        fn intercept(&mut self, is_boss: bool, survived_seconds: f64) -> Vec<AchievementId> {
+            self.record(
+                GameEvent::EnemyIntercepted {
+                    unit: UnitId(1),
+                    combat_type: CombatType::A,
+                    is_boss,
+                },
+                survived_seconds,
+            )
+        }
+
+        fn un_store(&mut self, unit: u32) {
+            self.record(
+                GameEvent::UnitOrdered {
+                    unit: UnitId(unit),
+                    action: UnitAction::UnStore,
+                },
+                0.0,
+            );
+        }
+    }
+
+    #[test]
+    fn interceptions_unlock_once_each() {
+        let mut run = Run::new(GameMode::Endless);
+        assert_eq!(
+            run.intercept(false, 5.0),
+            [AchievementId::FirstInterception]
+        );
+        for _ in 2..10 {
+            assert!(run.intercept(false, 5.0).is_empty());
+        }
+        assert_eq!(run.intercept(false, 5.0), [AchievementId::TenInOneRun]);
+        assert!(run.intercept(false, 5.0).is_empty());
+    }
+
+    #[test]
+    fn only_a_boss_counts_for_the_boss_hunter() {
+        let mut run = Run::new(GameMode::Endless);
+        run.intercept(false, 5.0);
+        assert_eq!(run.achievements.run.bosses_intercepted, 0);
+        assert_eq!(run.intercept(true, 6.0), [AchievementId::BossHunter]);
+    }
+
+    #[test]
+    fn three_clean_minutes_need_every_unit_to_come_back() {
+        let mut run = Run::new(GameMode::Endless);
+        let repaired = GameEvent::UnitRepaired { unit: UnitId(1) };
+        assert!(run.record(repaired.clone(), 179.0).is_empty());
+        assert_eq!(
+            run.record(repaired.clone(), 180.0),
+            [AchievementId::CleanThreeMinutes]
+        );
+
+        let mut run = Run::new(GameMode::Endless);
+        run.record(GameEvent::UnitDestroyed { unit: UnitId(2) }, 10.0);
+        assert!(run.record(repaired.clone(), 180.0).is_empty());
+        assert_eq!(run.record(repaired, 600.0), [AchievementId::TenMinutes]);
+    }
+
+    #[test]
+    fn a_clean_lean_contract_earns_both_contract_achievements() {
+        let mut run = Run::new(GameMode::DefenseContract);
+        for unit in [1, 2, 3, 4, 1] {
+            run.un_store(unit);
+        }
+
+        let unlocked = run.record(
+            GameEvent::Victory {
+                survived_seconds: 300.0,
+            },
+            300.0,
+        );
+        assert!(unlocked.contains(&AchievementId::LeanContract));
+        assert!(unlocked.contains(&AchievementId::GoldContract));
+    }
+
+    #[test]
+    fn a_contract_with_losses_or_many_units_earns_less() {
+        let mut run = Run::new(GameMode::DefenseContract);
+        for unit in 1..=5 {
+            run.un_store(unit);
+        }
+        run.record(GameEvent::UnitDestroyed { unit: UnitId(5) }, 10.0);
+
+        let unlocked = run.record(
+            GameEvent::Victory {
+                survived_seconds: 300.0,
+            },
+            300.0,
+        );
+        assert!(!unlocked.contains(&AchievementId::LeanContract));
+        assert!(!unlocked.contains(&AchievementId::GoldContract));
+        assert_eq!(run.achievements.run.medal, Some(Medal::Silver));
+    }
+
+    #[test]
+    fn a_contract_picked_up_from_a_save_is_never_lean() {
+        let mut run = Run::new(GameMode::DefenseContract);
+        run.achievements.start_run(GameMode::DefenseContract, false);
+
+        let unlocked = run.record(
+            GameEvent::Victory {
+                survived_seconds: 300.0,
+            },
+            300.0,
+        );
+        assert!(unlocked.contains(&AchievementId::GoldContract));
+        assert!(!unlocked.contains(&AchievementId::LeanContract));
+    }
+
+    #[test]
+    fn unlocks_are_kept_and_not_unlocked_again() {
+        let mut run = Run::new(GameMode::Endless);
+        run.intercept(false, 5.0);
+
+        let mut next = Run::resume(run.storage, GameMode::Endless);
+        assert!(next.intercept(false, 5.0).is_empty());
+        assert!(next
+            .achievements
+            .unlocked
+            .contains(&AchievementId::FirstInterception));
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::game::{CombatType, Enemy, EnemyId, Unit, UnitBundle};
+
+/// How far down its lane an unopposed enemy has to get before the player is warned, in order.
+const THRESHOLDS: [f32; 2] = [0.75, 0.9];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ThreatAlert {
+    pub(crate) enemy: EnemyId,
+    pub(crate) combat_type: CombatType,
+    /// Which of the thresholds the enemy passed, as a fraction of its lane.
+    pub(crate) threshold: f32,
+    pub(crate) eta_seconds: f32,
+}
+
+/// Every enemy that has passed at least one threshold while no unit patrols its lane, with the
+/// furthest threshold it passed.
+pub fn unopposed_threats(units: &[UnitBundle], enemies: &[Enemy]) -> Vec<ThreatAlert> {
+    enemies
+        .iter()
+        .filter(|enemy| {
+            !units.iter().any(|bundle| {
+                matches!(bundle.unit,
+                    Unit::Patrolling(_, combat_type) if combat_type == enemy.combat_type
+                )
+            })
+        })
+        .filter_map(|enemy| {
+            let progress = enemy.progress.percent();
+            let threshold = THRESHOLDS
+                .iter()
+                .rev()
+                .find(|&&threshold| progress >= threshold)?;
+
+            Some(ThreatAlert {
+                enemy: enemy.id,
+                combat_type: enemy.combat_type,
+                threshold: *threshold,
+                eta_seconds: enemy.progress.remaining_seconds(),
+            })
+        })
+        .collect()
+}
+
+/// Remembers which alerts already went off, so each enemy triggers each threshold only once,
+/// and which ones are still on screen.
+#[derive(Debug, Default)]
+pub struct ThreatAlerts {
+    fired: HashMap<EnemyId, f32>,
+    active: Vec<ThreatAlert>,
+}
+
+impl ThreatAlerts {
+    /// Looks at this frame's threats and returns the alerts that went off for the first time.
+    /// Alerts for enemies that were intercepted or got through are cleared.
+    pub fn update(&mut self, units: &[UnitBundle], enemies: &[Enemy]) -> Vec<ThreatAlert> {
+        let alive = |id: &EnemyId| enemies.iter().any(|enemy| enemy.id == *id);
+        self.fired.retain(|id, _| alive(id));
+        self.active.retain(|alert| alive(&alert.enemy));
+        for alert in self.active.iter_mut() {
+            if let Some(enemy) = enemies.iter().find(|enemy| enemy.id == alert.enemy) {
+                alert.eta_seconds = enemy.progress.remaining_seconds();
+            }
+        }
+
+        let mut new_alerts = Vec::new();
+        for alert in unopposed_threats(units, enemies) {
+            let already_fired = self
+                .fired
+                .get(&alert.enemy)
+                .is_some_and(|&fired| fired >= alert.threshold);
+            if already_fired {
+                continue;
+            }
+
+            self.fired.insert(alert.enemy, alert.threshold);
+            self.active.retain(|active| active.enemy != alert.enemy);
+            self.active.push(alert);
+            new_alerts.push(alert);
+        }
+
+        new_alerts
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn active(&self) -> &[ThreatAlert] {
+        &self.active
+    }
+
+    /// Hides an alert without forgetting that it went off.
+    #[cfg(feature = "backend-eframe")]
+    pub fn dismiss(&mut self, enemy: EnemyId) {
+        self.active.retain(|alert| alert.enemy != enemy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::fixtures::{enemy, unit},
+        helpers::{Duration, Timer},
+    };
+
+    fn advance(enemies: &mut [Enemy], seconds: f64) {
+        for enemy in enemies {
+            enemy.progress.tick(Duration::from_secs_f64(seconds));
+        }
+    }
+
+    #[test]
+    fn each_threshold_fires_exactly_once() {
+        let units = [unit(1, Unit::InStorage)];
+        let mut enemies = vec![enemy(1, CombatType::A, 10.0, 7.0)];
+        let mut alerts = ThreatAlerts::default();
+        assert!(alerts.update(&units, &enemies).is_empty());
+
+        advance(&mut enemies, 0.5);
+        let fired = alerts.update(&units, &enemies);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].enemy, EnemyId(1));
+        assert_eq!(fired[0].threshold, 0.75);
+        assert_eq!(fired[0].eta_seconds, 2.5);
+
+        advance(&mut enemies, 0.5);
+        assert!(alerts.update(&units, &enemies).is_empty());
+        assert_eq!(alerts.active[0].eta_seconds, 2.0);
+
+        advance(&mut enemies, 1.0);
+        let fired = alerts.update(&units, &enemies);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].threshold, 0.9);
+        assert_eq!(alerts.active.len(), 1);
+
+        advance(&mut enemies, 0.5);
+        assert!(alerts.update(&units, &enemies).is_empty());
+    }
+
+    #[test]
+    fn a_patrol_in_the_lane_keeps_it_quiet() {
+        let units = [unit(
+            1,
+            Unit::Patrolling(Timer::from_seconds(10.0, false), CombatType::A),
+        )];
+        let enemies = [
+            enemy(1, CombatType::A, 10.0, 9.5),
+            enemy(2, CombatType::B, 10.0, 8.0),
+        ];
+
+        let fired = ThreatAlerts::default().update(&units, &enemies);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].combat_type, CombatType::B);
+    }
+
+    #[test]
+    fn an_interception_clears_the_alert() {
+        let units = [unit(1, Unit::InStorage)];
+        let mut enemies = vec![
+            enemy(1, CombatType::A, 10.0, 8.0),
+            enemy(2, CombatType::C, 10.0, 1.0),
+        ];
+        let mut alerts = ThreatAlerts::default();
+        assert_eq!(alerts.update(&units, &enemies).len(), 1);
+
+        enemies.remove(0);
+        assert!(alerts.update(&units, &enemies).is_empty());
+        assert!(alerts.active.is_empty());
+        assert!(alerts.fired.is_empty());
+    }
+}
@@ -0,0 +1,41 @@
+//! Counts heap allocations so the per-frame churn can be watched next to the FPS. Only built
+//! with the `count-allocations` feature, as it puts itself in front of every allocation.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LAST_FRAME: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Starts counting the next frame and remembers how many allocations the last one made.
+pub fn end_frame() {
+    LAST_FRAME.store(ALLOCATIONS.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+}
+
+/// How many allocations the last complete frame made.
+pub fn last_frame() -> usize {
+    LAST_FRAME.load(Ordering::Relaxed)
+}
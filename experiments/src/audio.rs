@@ -0,0 +1,292 @@
+//! Sound effects and music. Game events are turned into sounds, queued up during the frame and
+//! handed to the engine's player at the end of it. The music follows the game state instead.
+//! Without a working audio device the game simply stays quiet.
+
+use engine::audio::AudioPlayer;
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
+
+use crate::events::GameEvent;
+#[cfg(feature = "backend-eframe")]
+use crate::{game::GameState, settings::Settings};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, IntoStaticStr)]
+pub enum SoundEvent {
+    EnemySpawned,
+    Interception,
+    UnitDestroyed,
+    EnemyLeaked,
+    ButtonClick,
+    GameOver,
+}
+
+impl SoundEvent {
+    /// The sound a game event makes, if any. Orders count as button clicks, whether they came
+    /// from a button or a key.
+    pub fn for_event(event: &GameEvent) -> Option<Self> {
+        let sound = match event {
+            GameEvent::EnemySpawned { .. } => Self::EnemySpawned,
+            GameEvent::EnemyIntercepted { .. } => Self::Interception,
+            GameEvent::UnitDestroyed { .. } => Self::UnitDestroyed,
+            GameEvent::BossLeaked { .. } => Self::EnemyLeaked,
+            GameEvent::UnitOrdered { .. } => Self::ButtonClick,
+            GameEvent::GameOver { .. } => Self::GameOver,
+            _ => return None,
+        };
+
+        Some(sound)
+    }
+
+    /// The embedded WAV file.
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Self::EnemySpawned => include_bytes!("../assets/sounds/enemy_spawned.wav"),
+            Self::Interception => include_bytes!("../assets/sounds/interception.wav"),
+            Self::UnitDestroyed => include_bytes!("../assets/sounds/unit_destroyed.wav"),
+            Self::EnemyLeaked => include_bytes!("../assets/sounds/enemy_leaked.wav"),
+            Self::ButtonClick => include_bytes!("../assets/sounds/button_click.wav"),
+            Self::GameOver => include_bytes!("../assets/sounds/game_over.wav"),
+        }
+    }
+}
+
+/// A piece of background music.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, IntoStaticStr)]
+pub enum Track {
+    /// Loops for as long as the run lasts.
+    Theme,
+    /// Plays once when the base is hit.
+    GameOverSting,
+}
+
+impl Track {
+    #[cfg(feature = "backend-eframe")]
+    fn loops(self) -> bool {
+        self == Self::Theme
+    }
+
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Self::Theme => include_bytes!("../assets/music/theme.wav"),
+            Self::GameOverSting => include_bytes!("../assets/music/game_over_sting.wav"),
+        }
+    }
+}
+
+/// What the music should be doing right now.
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MusicState {
+    pub track: Option<Track>,
+    pub paused: bool,
+    pub volume: f32,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl MusicState {
+    pub fn new(game_state: GameState, settings: &Settings) -> Self {
+        let track = match game_state {
+            GameState::MainMenu => None,
+            GameState::Running | GameState::Paused => Some(Track::Theme),
+            GameState::GameOver => Some(Track::GameOverSting),
+            // There is no music for winning yet, and the game-over sting would be wrong.
+            GameState::Victory => None,
+        };
+
+        Self {
+            track,
+            paused: game_state == GameState::Paused,
+            volume: if settings.music_muted {
+                0.0
+            } else {
+                settings.music_volume
+            },
+        }
+    }
+}
+
+/// Something that can play the queued sounds; the engine's player in the game.
+#[cfg(feature = "backend-eframe")]
+pub trait PlaysSounds {
+    /// Starts playing the sound at `volume`, between 0 and 1, without waiting for it to end.
+    fn play(&mut self, sound: SoundEvent, volume: f32);
+}
+
+/// Plays the game's sounds and music through the engine.
+pub struct SoundPlayer {
+    audio: AudioPlayer,
+    #[cfg(feature = "backend-eframe")]
+    track: Option<Track>,
+}
+
+impl Default for SoundPlayer {
+    fn default() -> Self {
+        let mut audio = AudioPlayer::default();
+        for sound in SoundEvent::iter() {
+            audio.register(sound.into(), sound.bytes());
+        }
+        for track in Track::iter() {
+            audio.register(track.into(), track.bytes());
+        }
+
+        Self {
+            audio,
+            #[cfg(feature = "backend-eframe")]
+            track: None,
+        }
+    }
+}
+
+#[cfg(feature = "backend-eframe")]
+impl PlaysSounds for SoundPlayer {
+    fn play(&mut self, sound: SoundEvent, volume: f32) {
+        self.audio.set_volume(volume);
+        self.audio.play_sfx(sound.into());
+    }
+}
+
+impl SoundPlayer {
+    /// Brings the music in line with `state`. Called every frame, so it does next to nothing
+    /// when the state hasn't changed.
+    #[cfg(feature = "backend-eframe")]
+    pub fn update_music(&mut self, state: &MusicState) {
+        if self.track != state.track {
+            self.track = state.track;
+            match state.track {
+                Some(track) => self.audio.play_music(track.into(), track.loops()),
+                None => self.audio.stop_music(),
+            }
+        }
+
+        self.audio.set_music_volume(state.volume);
+        self.audio.set_music_paused(state.paused);
+    }
+
+    /// The player has just clicked something. Browsers only allow playback to start after
+    /// that, so the music is held back until then.
+    #[cfg(feature = "backend-eframe")]
+    pub fn unlock(&mut self) {
+        self.audio.unlock();
+    }
+
+    /// Gets every sound and track ready to play, rather than on first use.
+    pub fn preload(&mut self) {
+        self.audio.preload();
+    }
+}
+
+/// The sounds requested this frame.
+#[derive(Debug, Default)]
+pub struct SoundQueue {
+    pending: Vec<SoundEvent>,
+}
+
+impl SoundQueue {
+    pub fn push(&mut self, sound: SoundEvent) {
+        // The same sound several times in one frame would only be louder, not clearer.
+        if !self.pending.contains(&sound) {
+            self.pending.push(sound);
+        }
+    }
+
+    /// What is waiting to be played, in order.
+    #[cfg(all(test, feature = "backend-eframe"))]
+    pub(crate) fn queued(&self) -> &[SoundEvent] {
+        &self.pending
+    }
+
+    /// Plays and forgets everything queued, or just forgets it while the sound is off.
+    #[cfg(feature = "backend-eframe")]
+    pub fn play_all(&mut self, player: &mut dyn PlaysSounds, settings: &Settings) {
+        for sound in self.pending.drain(..) {
+            if !settings.muted && settings.sound_volume > 0.0 {
+                player.play(sound, settings.sound_volume);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+    use crate::game::CombatType;
+
+    /// Remembers what it was asked to play.
+    #[derive(Default)]
+    struct RecordingPlayer(Vec<(SoundEvent, f32)>);
+
+    impl PlaysSounds for RecordingPlayer {
+        fn play(&mut self, sound: SoundEvent, volume: f32) {
+            self.0.push((sound, volume));
+        }
+    }
+
+    fn queue(sounds: &[SoundEvent]) -> SoundQueue {
+        let mut queue = SoundQueue::default();
+        for &sound in sounds {
+            queue.push(sound);
+        }
+        queue
+    }
+
+    #[test]
+    fn queued_sounds_are_played_once_each_and_then_forgotten() {
+        let settings = Settings {
+            sound_volume: 0.5,
+            ..Settings::default()
+        };
+        let mut queue = queue(&[
+            SoundEvent::Interception,
+            SoundEvent::EnemySpawned,
+            SoundEvent::Interception,
+        ]);
+        let mut player = RecordingPlayer::default();
+
+        queue.play_all(&mut player, &settings);
+        queue.play_all(&mut player, &settings);
+
+        assert_eq!(
+            player.0,
+            [
+                (SoundEvent::Interception, 0.5),
+                (SoundEvent::EnemySpawned, 0.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn nothing_is_played_while_the_sound_is_off() {
+        let muted = Settings {
+            muted: true,
+            ..Settings::default()
+        };
+        let silent = Settings {
+            sound_volume: 0.0,
+            ..Settings::default()
+        };
+        let mut player = RecordingPlayer::default();
+
+        for settings in [muted, silent] {
+            let mut queue = queue(&[SoundEvent::GameOver]);
+            queue.play_all(&mut player, &settings);
+            // Turning the sound back on doesn't bring back what was skipped.
+            queue.play_all(&mut player, &Settings::default());
+        }
+
+        assert!(player.0.is_empty());
+    }
+
+    #[test]
+    fn only_some_events_make_a_sound() {
+        let spawned = GameEvent::EnemySpawned {
+            combat_type: CombatType::A,
+            is_boss: false,
+        };
+        let parked = GameEvent::ParkingChanged { used: 1 };
+
+        assert_eq!(
+            SoundEvent::for_event(&spawned),
+            Some(SoundEvent::EnemySpawned)
+        );
+        assert_eq!(SoundEvent::for_event(&parked), None);
+    }
+}
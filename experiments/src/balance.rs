@@ -0,0 +1,334 @@
+//! The numbers the game is balanced with, all in one place. They come from `balance.ron`, which
+//! is built into the game; the native build can read another file instead, given with
+//! `--balance <path>`, to try numbers out without building the game again.
+
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::game::{Difficulty, UnitTimings};
+
+/// The balance the game ships with.
+const BUILT_IN: &str = include_str!("../assets/balance.ron");
+
+/// Every field has to be given, and any the game doesn't know is an error, so a typo can't go
+/// unnoticed while balancing.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GameConfig {
+    pub units: UnitConfig,
+    pub enemies: EnemyConfig,
+    pub easy: DifficultyConfig,
+    pub normal: DifficultyConfig,
+    pub hard: DifficultyConfig,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnitConfig {
+    pub starting_units: u32,
+    pub parking_spaces: usize,
+    /// Out of a unit's health, which goes from 0 to 1.
+    pub damage_per_hit: f64,
+    pub un_store_seconds: f64,
+    pub store_seconds: f64,
+    pub park_seconds: f64,
+    pub patrol_seconds: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyConfig {
+    /// Every so many enemies is a boss.
+    pub enemies_per_boss: usize,
+    /// The standard deviation of the wait between two enemies around its current average.
+    pub spread_seconds: f64,
+    /// The shortest the spawner ever waits between two enemies.
+    pub min_seconds_between_enemies: f64,
+    /// The longest the spawner ever waits between two enemies.
+    pub max_seconds_between_enemies: f64,
+    /// What the average wait between two enemies is multiplied by for every New Game+ level.
+    pub new_game_plus_pace: f64,
+}
+
+/// What sets the difficulties apart.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DifficultyConfig {
+    /// The average wait between two enemies at the start of an endless run.
+    pub initial_seconds_between_enemies: f64,
+    /// How much shorter the average wait gets with every spawn.
+    pub spawn_acceleration: f64,
+    /// How long an enemy takes to get from the spawn point to the base.
+    pub enemy_run_seconds: f64,
+    /// How long a unit in storage takes to be repaired from no health left to full.
+    pub repair_seconds: f64,
+    /// How long a parked unit takes to get ready for a combat type.
+    pub prepare_seconds: f64,
+}
+
+/// The same numbers as `balance.ron`, for when that can't be read.
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            units: UnitConfig {
+                starting_units: 8,
+                parking_spaces: 3,
+                damage_per_hit: 0.25,
+                un_store_seconds: 10.0,
+                store_seconds: 10.0,
+                park_seconds: 5.0,
+                patrol_seconds: 30.0,
+            },
+            enemies: EnemyConfig {
+                enemies_per_boss: 10,
+                spread_seconds: 5.0,
+                min_seconds_between_enemies: 1.0,
+                max_seconds_between_enemies: 10.0,
+                new_game_plus_pace: 0.8,
+            },
+            easy: DifficultyConfig {
+                initial_seconds_between_enemies: 40.0,
+                spawn_acceleration: 0.98,
+                enemy_run_seconds: 40.0,
+                repair_seconds: 10.0,
+                prepare_seconds: 3.0,
+            },
+            normal: DifficultyConfig {
+                initial_seconds_between_enemies: 30.0,
+                spawn_acceleration: 0.97,
+                enemy_run_seconds: 30.0,
+                repair_seconds: 15.0,
+                prepare_seconds: 5.0,
+            },
+            hard: DifficultyConfig {
+                initial_seconds_between_enemies: 20.0,
+                spawn_acceleration: 0.96,
+                enemy_run_seconds: 24.0,
+                repair_seconds: 20.0,
+                prepare_seconds: 7.0,
+            },
+        }
+    }
+}
+
+/// Why a balance couldn't be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    Read {
+        path: PathBuf,
+        message: String,
+    },
+    /// Not RON, or fields are missing, unknown or of the wrong type.
+    Parse {
+        source: String,
+        message: String,
+    },
+    /// Read fine, but a number makes no sense, like a negative time.
+    Invalid {
+        source: String,
+        message: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read { path, message } => {
+                write!(f, "can't read {}: {}", path.display(), message)
+            }
+            Self::Parse { source, message } => write!(f, "{} isn't valid: {}", source, message),
+            Self::Invalid { source, message } => write!(f, "{} doesn't work: {}", source, message),
+        }
+    }
+}
+
+impl GameConfig {
+    /// The balance built into the game. Should that be broken, which is a bug, the error is
+    /// logged and the defaults are used.
+    pub fn built_in() -> Self {
+        Self::from_ron(BUILT_IN, "the built-in balance").unwrap_or_else(|error| {
+            log::error!("{}; using the defaults", error);
+            Self::default()
+        })
+    }
+
+    /// Reads a balance from a file, e.g. one being tried out.
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|error| ConfigError::Read {
+            path: path.to_owned(),
+            message: error.to_string(),
+        })?;
+        Self::from_ron(&text, &path.display().to_string())
+    }
+
+    /// Reads a balance from the text of a RON file. `source` names it in errors.
+    pub fn from_ron(text: &str, source: &str) -> Result<Self, ConfigError> {
+        let config: Self = ron::de::from_str(text).map_err(|error| ConfigError::Parse {
+            source: source.to_string(),
+            message: error.to_string(),
+        })?;
+        config.validate().map_err(|message| ConfigError::Invalid {
+            source: source.to_string(),
+            message,
+        })?;
+        Ok(config)
+    }
+
+    /// What the numbers must be for the game to work at all. Whether they make a good game is
+    /// another matter.
+    fn validate(&self) -> Result<(), String> {
+        let positive = |name: &str, value: f64| {
+            if value.is_finite() && value > 0.0 {
+                Ok(())
+            } else {
+                Err(format!("{} must be more than 0, not {}", name, value))
+            }
+        };
+
+        let units = &self.units;
+        positive("units.un_store_seconds", units.un_store_seconds)?;
+        positive("units.store_seconds", units.store_seconds)?;
+        positive("units.park_seconds", units.park_seconds)?;
+        positive("units.patrol_seconds", units.patrol_seconds)?;
+        let enemies = &self.enemies;
+        positive("enemies.spread_seconds", enemies.spread_seconds)?;
+        positive(
+            "enemies.min_seconds_between_enemies",
+            enemies.min_seconds_between_enemies,
+        )?;
+        positive("enemies.new_game_plus_pace", enemies.new_game_plus_pace)?;
+        for (name, difficulty) in &[
+            ("easy", &self.easy),
+            ("normal", &self.normal),
+            ("hard", &self.hard),
+        ] {
+            let field = |field: &str| format!("{}.{}", name, field);
+            positive(
+                &field("initial_seconds_between_enemies"),
+                difficulty.initial_seconds_between_enemies,
+            )?;
+            positive(&field("spawn_acceleration"), difficulty.spawn_acceleration)?;
+            positive(&field("enemy_run_seconds"), difficulty.enemy_run_seconds)?;
+            positive(&field("repair_seconds"), difficulty.repair_seconds)?;
+            positive(&field("prepare_seconds"), difficulty.prepare_seconds)?;
+        }
+
+        if !(units.damage_per_hit > 0.0 && units.damage_per_hit <= 1.0) {
+            return Err(format!(
+                "units.damage_per_hit must be more than 0 and at most 1, not {}",
+                units.damage_per_hit
+            ));
+        }
+        if enemies.enemies_per_boss == 0 {
+            return Err("enemies.enemies_per_boss must be at least 1".to_string());
+        }
+        if enemies.max_seconds_between_enemies <= enemies.min_seconds_between_enemies {
+            return Err(
+                "enemies.max_seconds_between_enemies must be more than the minimum".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    pub fn difficulty(&self, difficulty: Difficulty) -> &DifficultyConfig {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+        }
+    }
+
+    /// What the average wait between two enemies is multiplied by at New Game+ `level`.
+    pub fn new_game_plus_pace(&self, level: u32) -> f64 {
+        self.enemies.new_game_plus_pace.powi(level as i32)
+    }
+
+    /// How long everything the units do takes on `difficulty`.
+    pub fn unit_timings(&self, difficulty: Difficulty) -> UnitTimings {
+        let difficulty = self.difficulty(difficulty);
+        UnitTimings {
+            seconds_to_fully_repair: difficulty.repair_seconds,
+            prepare_seconds: difficulty.prepare_seconds,
+            un_store_seconds: self.units.un_store_seconds,
+            store_seconds: self.units.store_seconds,
+            park_seconds: self.units.park_seconds,
+            patrol_seconds: self.units.patrol_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_built_in_balance_is_the_default() {
+        assert_eq!(
+            GameConfig::from_ron(BUILT_IN, "the built-in balance"),
+            Ok(GameConfig::default())
+        );
+    }
+
+    #[test]
+    fn a_changed_number_is_taken_over() {
+        let text = BUILT_IN.replace("patrol_seconds: 30.0", "patrol_seconds: 45.0");
+        let config = GameConfig::from_ron(&text, "test.ron").unwrap();
+        assert_eq!(config.units.patrol_seconds, 45.0);
+        assert_eq!(config.unit_timings(Difficulty::Hard).patrol_seconds, 45.0);
+        assert_eq!(config.enemies, GameConfig::default().enemies);
+    }
+
+    #[test]
+    fn unknown_and_missing_fields_are_named() {
+        let unknown = BUILT_IN.replace("patrol_seconds", "patroll_seconds");
+        let error = GameConfig::from_ron(&unknown, "test.ron").unwrap_err();
+        assert!(matches!(&error, ConfigError::Parse { .. }), "{:?}", error);
+        assert!(error.to_string().contains("patroll_seconds"), "{}", error);
+
+        let missing = BUILT_IN.replace("patrol_seconds: 30.0,", "");
+        let error = GameConfig::from_ron(&missing, "test.ron").unwrap_err();
+        assert!(matches!(&error, ConfigError::Parse { .. }), "{:?}", error);
+        assert!(error.to_string().contains("patrol_seconds"), "{}", error);
+    }
+
+    #[test]
+    fn text_that_isnt_ron_is_an_error() {
+        let error = GameConfig::from_ron("GameConfig(units: [", "test.ron").unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { .. }), "{:?}", error);
+        assert!(
+            error.to_string().starts_with("test.ron isn't valid"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn numbers_that_dont_work_are_rejected() {
+        let negative = BUILT_IN.replace("park_seconds: 5.0", "park_seconds: -5.0");
+        assert!(matches!(
+            GameConfig::from_ron(&negative, "test.ron"),
+            Err(ConfigError::Invalid { message, .. }) if message.contains("units.park_seconds")
+        ));
+
+        let no_bosses = BUILT_IN.replace("enemies_per_boss: 10", "enemies_per_boss: 0");
+        assert!(matches!(
+            GameConfig::from_ron(&no_bosses, "test.ron"),
+            Err(ConfigError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn a_file_that_cant_be_read_says_where() {
+        let error = GameConfig::from_file(Path::new("/no/such/balance.ron")).unwrap_err();
+        assert!(matches!(error, ConfigError::Read { .. }), "{:?}", error);
+        assert!(
+            error.to_string().contains("/no/such/balance.ron"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+
+use crate::{events::GameEvent, game::UnitAction};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// Play time in seconds.
+    pub(crate) at: f64,
+    pub(crate) text: String,
+    pub(crate) severity: Severity,
+}
+
+impl LogEntry {
+    /// The entry for the event, unless it isn't worth one.
+    fn from_event(at: f64, event: &GameEvent) -> Option<Self> {
+        let (severity, text) = match event {
+            GameEvent::EnemySpawned {
+                combat_type,
+                is_boss: false,
+            } => (
+                Severity::Info,
+                format!("A type {} enemy appeared.", combat_type),
+            ),
+            GameEvent::EnemySpawned {
+                combat_type,
+                is_boss: true,
+            } => (
+                Severity::Warning,
+                format!("A type {} BOSS appeared!", combat_type),
+            ),
+            GameEvent::EnemyIntercepted {
+                unit, combat_type, ..
+            } => (
+                Severity::Info,
+                format!("Unit {} intercepted a type {} enemy.", unit, combat_type),
+            ),
+            GameEvent::UnitDamaged {
+                unit,
+                health,
+                critical,
+            } => (
+                if *critical {
+                    Severity::Warning
+                } else {
+                    Severity::Info
+                },
+                format!("Unit {} was damaged and is down to {}.", unit, health),
+            ),
+            GameEvent::UnitDestroyed { unit } => {
+                (Severity::Critical, format!("Unit {} was destroyed!", unit))
+            }
+            GameEvent::BossLeaked {
+                combat_type,
+                parking_spaces_left,
+            } => (
+                Severity::Critical,
+                format!(
+                    "A type {} boss broke through and wrecked a parking space! {} left.",
+                    combat_type, parking_spaces_left
+                ),
+            ),
+            GameEvent::EnemyBreaching {
+                combat_type,
+                threshold,
+                eta_seconds,
+            } => (
+                if *threshold >= 0.9 {
+                    Severity::Critical
+                } else {
+                    Severity::Warning
+                },
+                format!("Type {} breaching in {:.0}s!", combat_type, eta_seconds),
+            ),
+            GameEvent::BaseHit { report } => (
+                Severity::Critical,
+                format!("A type {} enemy hit the base!", report.lane),
+            ),
+            GameEvent::SortieCompleted { unit } => (
+                Severity::Info,
+                format!("Unit {} is back and waiting to park.", unit),
+            ),
+            GameEvent::UnitParked { unit } => (Severity::Info, format!("Unit {} is parked.", unit)),
+            GameEvent::UnitOrdered { unit, action } => (
+                Severity::Info,
+                match action {
+                    UnitAction::UnStore => format!("Unit {} is coming out of storage.", unit),
+                    UnitAction::Prepare(combat_type) => {
+                        format!("Unit {} is being prepared for lane {}.", unit, combat_type)
+                    }
+                    UnitAction::TakeOff => format!("Unit {} took off.", unit),
+                    UnitAction::Store => format!("Unit {} is moving into storage.", unit),
+                    UnitAction::Park => format!("Unit {} is parking.", unit),
+                    UnitAction::Recall => format!("Unit {} was recalled.", unit),
+                    UnitAction::CancelPreparation => {
+                        format!("Unit {} stopped preparing.", unit)
+                    }
+                },
+            ),
+            GameEvent::UnitReady { unit, combat_type } => (
+                Severity::Info,
+                format!("Unit {} is ready for lane {}.", unit, combat_type),
+            ),
+            GameEvent::UnitRepaired { unit } => {
+                (Severity::Info, format!("Unit {} is fully repaired.", unit))
+            }
+            GameEvent::GameOver { survived_seconds } => (
+                Severity::Critical,
+                format!("The run is over after {:.0}s.", survived_seconds),
+            ),
+            GameEvent::Victory { survived_seconds } => (
+                Severity::Info,
+                format!("Contract fulfilled after {:.0}s!", survived_seconds),
+            ),
+            // Every unit order already says so.
+            GameEvent::ParkingChanged { .. } => return None,
+        };
+
+        Some(Self { at, text, severity })
+    }
+}
+
+/// The most recent events of the run, oldest first.
+#[derive(Debug, Default)]
+pub struct BattleLog {
+    entries: VecDeque<LogEntry>,
+    recorded: usize,
+}
+
+impl BattleLog {
+    const MAX_ENTRIES: usize = 200;
+
+    pub fn record(&mut self, at: f64, event: &GameEvent) {
+        let entry = match LogEntry::from_event(at, event) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if self.entries.len() == Self::MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        self.recorded += 1;
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+
+    /// How many entries were ever recorded, including the ones trimmed since.
+    #[cfg(feature = "backend-eframe")]
+    pub fn recorded(&self) -> usize {
+        self.recorded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CombatType, UnitId};
+
+    fn parked(unit: u32) -> GameEvent {
+        GameEvent::UnitParked { unit: UnitId(unit) }
+    }
+
+    #[test]
+    fn events_are_logged_at_their_time() {
+        let mut log = BattleLog::default();
+        log.record(1.5, &parked(1));
+        log.record(
+            4.0,
+            &GameEvent::EnemySpawned {
+                combat_type: CombatType::C,
+                is_boss: true,
+            },
+        );
+        log.record(4.0, &GameEvent::ParkingChanged { used: 1 });
+
+        let entries: Vec<_> = log.entries.iter().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].at, 1.5);
+        assert_eq!(entries[0].text, "Unit #1 is parked.");
+        assert_eq!(entries[0].severity, Severity::Info);
+        assert_eq!(entries[1].at, 4.0);
+        assert_eq!(entries[1].severity, Severity::Warning);
+        assert_eq!(log.recorded, 2);
+    }
+
+    #[test]
+    fn the_oldest_entries_make_way_for_new_ones() {
+        let mut log = BattleLog::default();
+        for unit in 0..BattleLog::MAX_ENTRIES as u32 + 5 {
+            log.record(unit as f64, &parked(unit));
+        }
+
+        assert_eq!(log.entries.len(), BattleLog::MAX_ENTRIES);
+        assert_eq!(log.entries.front().unwrap().at, 5.0);
+        assert_eq!(
+            log.entries.back().unwrap().at,
+            (BattleLog::MAX_ENTRIES + 4) as f64
+        );
+        assert_eq!(log.recorded, BattleLog::MAX_ENTRIES + 5);
+    }
+}
@@ -0,0 +1,52 @@
+//! Plays runs without a window as fast as it can, for balancing, and writes a line of CSV for
+//! each to stdout, with the totals on stderr at the end. For example, from `experiments`:
+//!
+//! ```text
+//! cargo run --release --no-default-features --bin simulate -- --seeds 1-500 > runs.csv
+//! ```
+//!
+//! With `--check` every run is checked for states the rules should never get into, best with
+//! `--policy random`. A run that breaks is named with its seed, to play it again with
+//! `--seed`, and the binary then exits with 1.
+//!
+//! See `--help` for the rest.
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", fruitopian_defender::SimulationConfig::USAGE);
+        return;
+    }
+    let config = match fruitopian_defender::SimulationConfig::from_args(args) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!(
+                "{}\n{}",
+                error,
+                fruitopian_defender::SimulationConfig::USAGE
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let mut totals = fruitopian_defender::Totals::default();
+    println!("{}", fruitopian_defender::SimulatedRun::CSV_HEADER);
+    for &seed in &config.seeds {
+        let run = fruitopian_defender::MyGame::simulate_run(&config, seed);
+        println!("{}", run);
+        if let Some(violation) = &run.violation {
+            eprintln!("Seed {} broke: {}", seed, violation);
+        }
+        totals.add(&run);
+    }
+    eprintln!("{}", totals);
+    if totals.broken() > 0 {
+        std::process::exit(1);
+    }
+}
+
+// There is nothing to simulate with in the browser.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,80 @@
+//! Cheats for balancing and testing, offered in the sandbox only. They are given as commands like
+//! any other order, so a replay plays them back, but a run that used one doesn't count for the
+//! high score, the leaderboard or achievements.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::GameEvent,
+    game::{CombatType, Enemy, EnemySpawner, Health, UnitBundle, UnitId},
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cheat {
+    /// Send an enemy down the lane, with the given percentage of the lane still ahead of it.
+    SpawnEnemy {
+        combat_type: CombatType,
+        percent_left: u8,
+    },
+    HealAll,
+    /// Run out whatever the unit is waiting for, e.g. its preparation.
+    FinishNow(UnitId),
+    /// Make enemies come this many seconds apart on average from now on.
+    SetSpawnInterval {
+        seconds: u32,
+    },
+    SetInvincible(bool),
+}
+
+/// What cheats did to the current run. Saved with it, so picking the run up again doesn't
+/// clear it of them.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheatState {
+    /// Any cheat was used.
+    pub used: bool,
+    /// Enemies reaching the base don't end the run.
+    pub invincible: bool,
+}
+
+/// Carries out the cheat and marks the run as having used one.
+pub fn apply_cheat(
+    cheat: Cheat,
+    state: &mut CheatState,
+    units: &mut [UnitBundle],
+    enemies: &mut Vec<Enemy>,
+    enemy_spawner: &mut EnemySpawner,
+    events: &mut Vec<GameEvent>,
+) {
+    state.used = true;
+    match cheat {
+        Cheat::SpawnEnemy {
+            combat_type,
+            percent_left,
+        } => {
+            enemy_spawner.spawn_extra(
+                combat_type,
+                f32::from(percent_left) / 100.0,
+                enemies,
+                events,
+            );
+        }
+        Cheat::HealAll => {
+            for bundle in units
+                .iter_mut()
+                .filter(|bundle| bundle.health.fraction() < 1.0)
+            {
+                bundle.health = Health::default();
+                events.push(GameEvent::UnitRepaired { unit: bundle.id });
+            }
+        }
+        Cheat::FinishNow(unit) => {
+            if let Some(bundle) = units.iter_mut().find(|bundle| bundle.id == unit) {
+                bundle.unit.finish_now();
+            }
+        }
+        Cheat::SetSpawnInterval { seconds } => {
+            enemy_spawner.set_mean_seconds_between_enemies(f64::from(seconds.max(1)));
+        }
+        Cheat::SetInvincible(invincible) => state.invincible = invincible,
+    }
+}
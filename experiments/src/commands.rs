@@ -0,0 +1,188 @@
+//! Orders for the units, from the player or from anything that plays in their place. The GUI only
+//! reads the game and hands these back; the game carries them out after the GUI pass, checking
+//! that each one is still possible by then.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cheats::Cheat,
+    events::GameEvent,
+    game::{
+        scramble, CombatType, ParkingSpace, TokenPool, UnitAction, UnitBundle, UnitId, UnitTimings,
+    },
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameCommand {
+    /// Have the unit carry out the action right away, dropping the orders it had queued.
+    Perform { unit: UnitId, action: UnitAction },
+    /// Like `Perform`, but un-store or park the unit in the given parking space rather than the
+    /// first free one.
+    PerformInSlot {
+        unit: UnitId,
+        action: UnitAction,
+        slot: usize,
+    },
+    /// Send the best unit for the lane, queueing whatever it takes to get it airborne.
+    Scramble(CombatType),
+    /// Only in the sandbox. Carried out by the game rather than here, as cheats reach beyond
+    /// the units.
+    Cheat(Cheat),
+}
+
+/// Carries out the commands in order. Commands for units that are gone, or for actions a unit
+/// can't carry out right now, do nothing.
+pub fn apply_commands(
+    commands: impl IntoIterator<Item = GameCommand>,
+    units: &mut [UnitBundle],
+    timings: &UnitTimings,
+    parking_spaces: &mut TokenPool<ParkingSpace>,
+    events: &mut Vec<GameEvent>,
+) {
+    for command in commands {
+        match command {
+            GameCommand::Perform { unit, action } => {
+                if let Some(bundle) = units.iter_mut().find(|bundle| bundle.id == unit) {
+                    bundle.perform(action, timings, parking_spaces, events);
+                }
+            }
+            GameCommand::PerformInSlot { unit, action, slot } => {
+                if let Some(bundle) = units.iter_mut().find(|bundle| bundle.id == unit) {
+                    bundle.perform_in_slot(action, slot, timings, parking_spaces, events);
+                }
+            }
+            GameCommand::Scramble(combat_type) => {
+                scramble(units, combat_type, timings, parking_spaces, events)
+            }
+            GameCommand::Cheat(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        fixtures::{play, unit, TIMINGS},
+        Unit,
+    };
+
+    fn perform(unit: u32, action: UnitAction) -> GameCommand {
+        GameCommand::Perform {
+            unit: UnitId(unit),
+            action,
+        }
+    }
+
+    struct Game {
+        units: Vec<UnitBundle>,
+        parking_spaces: TokenPool<ParkingSpace>,
+        events: Vec<GameEvent>,
+    }
+
+    impl Game {
+        fn new(units: Vec<UnitBundle>, parking_spaces: usize) -> Self {
+            Self {
+                units,
+                parking_spaces: TokenPool::new(parking_spaces),
+                events: Vec::new(),
+            }
+        }
+
+        fn apply(&mut self, commands: impl IntoIterator<Item = GameCommand>) {
+            apply_commands(
+                commands,
+                &mut self.units,
+                &TIMINGS,
+                &mut self.parking_spaces,
+                &mut self.events,
+            );
+        }
+
+        fn play(&mut self, seconds: f64) {
+            play(
+                seconds,
+                &mut self.units,
+                &mut self.parking_spaces,
+                &mut self.events,
+            );
+        }
+    }
+
+    #[test]
+    fn a_unit_goes_through_its_whole_life_on_commands_alone() {
+        let mut game = Game::new(vec![unit(1, Unit::InStorage)], 1);
+
+        game.apply([perform(1, UnitAction::UnStore)]);
+        game.play(2.1);
+        assert!(matches!(game.units[0].unit, Unit::ParkedUnready(_)));
+
+        game.apply([perform(1, UnitAction::Prepare(CombatType::D))]);
+        game.play(3.1);
+        assert!(matches!(
+            game.units[0].unit,
+            Unit::ParkedReady(_, CombatType::D)
+        ));
+
+        game.apply([perform(1, UnitAction::TakeOff)]);
+        assert!(matches!(game.units[0].unit, Unit::Patrolling(_, _)));
+        assert_eq!(game.parking_spaces.slots_used(), 0);
+
+        game.apply([perform(1, UnitAction::Recall)]);
+        game.play(30.1);
+        assert!(matches!(game.units[0].unit, Unit::WaitingToPark));
+
+        game.apply([perform(1, UnitAction::Park)]);
+        game.play(1.1);
+        game.apply([perform(1, UnitAction::Store)]);
+        game.play(2.1);
+        assert!(matches!(game.units[0].unit, Unit::InStorage));
+        assert_eq!(game.parking_spaces.slots_used(), 0);
+    }
+
+    #[test]
+    fn impossible_commands_do_nothing() {
+        let mut game = Game::new(vec![unit(1, Unit::InStorage), unit(2, Unit::InStorage)], 2);
+
+        game.apply([
+            perform(1, UnitAction::TakeOff),
+            perform(1, UnitAction::Recall),
+            perform(3, UnitAction::UnStore),
+            GameCommand::PerformInSlot {
+                unit: UnitId(1),
+                action: UnitAction::UnStore,
+                slot: 5,
+            },
+        ]);
+        assert!(game.events.is_empty());
+        assert!(matches!(game.units[0].unit, Unit::InStorage));
+
+        // A command is checked when its turn comes, after the ones before it.
+        game.apply([
+            GameCommand::PerformInSlot {
+                unit: UnitId(1),
+                action: UnitAction::UnStore,
+                slot: 1,
+            },
+            GameCommand::PerformInSlot {
+                unit: UnitId(2),
+                action: UnitAction::UnStore,
+                slot: 1,
+            },
+        ]);
+        assert_eq!(game.events.len(), 1);
+        assert!(matches!(game.units[1].unit, Unit::InStorage));
+    }
+
+    #[test]
+    fn a_direct_order_drops_the_queued_ones() {
+        let mut game = Game::new(vec![unit(1, Unit::InStorage)], 1);
+        game.apply([GameCommand::Scramble(CombatType::A)]);
+        assert_eq!(game.units[0].orders.len(), 2);
+
+        game.play(2.1);
+        game.apply([perform(1, UnitAction::CancelPreparation)]);
+        assert!(matches!(game.units[0].unit, Unit::ParkedUnready(_)));
+        assert!(game.units[0].orders.is_empty());
+    }
+}
@@ -0,0 +1,359 @@
+//! The commands the developer console understands, like `spawn B 0.5` or `speed 2`. Orders for
+//! the run come out as the same commands the GUI gives, cheats included; the rest is for the
+//! console itself to answer.
+
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    cheats::Cheat,
+    commands::GameCommand,
+    game::{CombatType, UnitId},
+    helpers::GameSpeed,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleCommand {
+    /// An order for the run, carried out like one from the GUI.
+    Game(GameCommand),
+    Speed(GameSpeed),
+    ShowSeed,
+    ShowStats,
+    Help,
+}
+
+/// A command the console offers, for `help` and tab-completion.
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+pub const COMMANDS: &[CommandInfo] = &[
+    CommandInfo {
+        name: "spawn",
+        usage: "spawn <A-D> [fraction of the lane left, 0-1]",
+        description: "Send an enemy down a lane, from its start unless told otherwise.",
+    },
+    CommandInfo {
+        name: "heal",
+        usage: "heal all",
+        description: "Repair every unit fully.",
+    },
+    CommandInfo {
+        name: "finish",
+        usage: "finish <unit>",
+        description: "Run out whatever the unit is waiting for.",
+    },
+    CommandInfo {
+        name: "interval",
+        usage: "interval <seconds>",
+        description: "Make enemies come this many seconds apart on average.",
+    },
+    CommandInfo {
+        name: "invincible",
+        usage: "invincible <on|off>",
+        description: "Keep the run going when an enemy reaches the base.",
+    },
+    CommandInfo {
+        name: "speed",
+        usage: "speed <0.5|1|2|4>",
+        description: "Set the game speed.",
+    },
+    CommandInfo {
+        name: "seed",
+        usage: "seed",
+        description: "Show the seed of the run.",
+    },
+    CommandInfo {
+        name: "stats",
+        usage: "stats",
+        description: "Show the counters of the run.",
+    },
+    CommandInfo {
+        name: "help",
+        usage: "help",
+        description: "List the commands.",
+    },
+];
+
+/// Why a line isn't a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    UnknownCommand(String),
+    MissingArgument {
+        usage: &'static str,
+    },
+    InvalidArgument {
+        argument: String,
+        usage: &'static str,
+    },
+    TooManyArguments {
+        usage: &'static str,
+    },
+    /// Something the console knows about but the game doesn't have.
+    Unsupported {
+        reason: &'static str,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "type a command, or \"help\" for a list"),
+            Self::UnknownCommand(name) => {
+                write!(f, "unknown command {:?}; \"help\" lists them all", name)
+            }
+            Self::MissingArgument { usage } => write!(f, "missing argument; usage: {}", usage),
+            Self::InvalidArgument { argument, usage } => {
+                write!(f, "can't make sense of {:?}; usage: {}", argument, usage)
+            }
+            Self::TooManyArguments { usage } => {
+                write!(f, "too many arguments; usage: {}", usage)
+            }
+            Self::Unsupported { reason } => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// Reads one line typed into the console. Command names and arguments are case-insensitive.
+pub fn parse(line: &str) -> Result<ConsoleCommand, ParseError> {
+    let mut words = line.split_whitespace();
+    let name = words.next().ok_or(ParseError::Empty)?.to_ascii_lowercase();
+    let info = match COMMANDS.iter().find(|info| info.name == name) {
+        Some(info) => info,
+        None if name == "give" => {
+            return Err(ParseError::Unsupported {
+                reason: "there are no credits or anything else to give in this game",
+            })
+        }
+        None => return Err(ParseError::UnknownCommand(name)),
+    };
+    let mut args = Arguments {
+        words,
+        usage: info.usage,
+    };
+
+    let command = match info.name {
+        "spawn" => {
+            let combat_type = args.required(parse_combat_type)?;
+            let fraction_left = args
+                .optional(|word| word.parse::<f32>().ok().filter(|f| (0.0..=1.0).contains(f)))?
+                .unwrap_or(1.0);
+            cheat(Cheat::SpawnEnemy {
+                combat_type,
+                percent_left: (fraction_left * 100.0).round() as u8,
+            })
+        }
+        "heal" => {
+            args.required(|word| word.eq_ignore_ascii_case("all").then_some(()))?;
+            cheat(Cheat::HealAll)
+        }
+        "finish" => {
+            // Units are shown as "#3", but "3" will do.
+            let unit = args.required(|word| word.trim_start_matches('#').parse().ok())?;
+            cheat(Cheat::FinishNow(UnitId(unit)))
+        }
+        "interval" => {
+            let seconds = args.required(|word| word.parse::<u32>().ok().filter(|&s| s > 0))?;
+            cheat(Cheat::SetSpawnInterval { seconds })
+        }
+        "invincible" => cheat(Cheat::SetInvincible(args.required(parse_switch)?)),
+        "speed" => ConsoleCommand::Speed(
+            args.required(|word| word.parse().ok().and_then(GameSpeed::from_scale))?,
+        ),
+        "seed" => ConsoleCommand::ShowSeed,
+        "stats" => ConsoleCommand::ShowStats,
+        "help" => ConsoleCommand::Help,
+        _ => return Err(ParseError::UnknownCommand(name)),
+    };
+    args.finish()?;
+    Ok(command)
+}
+
+/// The command names starting with what was typed so far.
+pub fn completions(prefix: &str) -> Vec<&'static str> {
+    let prefix = prefix.trim_start().to_ascii_lowercase();
+    COMMANDS
+        .iter()
+        .map(|info| info.name)
+        .filter(|name| name.starts_with(&prefix))
+        .collect()
+}
+
+fn cheat(cheat: Cheat) -> ConsoleCommand {
+    ConsoleCommand::Game(GameCommand::Cheat(cheat))
+}
+
+fn parse_combat_type(word: &str) -> Option<CombatType> {
+    match word.to_ascii_uppercase().as_str() {
+        "A" => Some(CombatType::A),
+        "B" => Some(CombatType::B),
+        "C" => Some(CombatType::C),
+        "D" => Some(CombatType::D),
+        _ => None,
+    }
+}
+
+fn parse_switch(word: &str) -> Option<bool> {
+    match word.to_ascii_lowercase().as_str() {
+        "on" | "1" | "true" => Some(true),
+        "off" | "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// The words after the command name, taken one at a time.
+struct Arguments<I> {
+    words: I,
+    usage: &'static str,
+}
+
+impl<'a, I: Iterator<Item = &'a str>> Arguments<I> {
+    fn required<T>(&mut self, parse: impl Fn(&str) -> Option<T>) -> Result<T, ParseError> {
+        self.optional(parse)?
+            .ok_or(ParseError::MissingArgument { usage: self.usage })
+    }
+
+    fn optional<T>(&mut self, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, ParseError> {
+        match self.words.next() {
+            Some(word) => match parse(word) {
+                Some(value) => Ok(Some(value)),
+                None => Err(ParseError::InvalidArgument {
+                    argument: word.to_string(),
+                    usage: self.usage,
+                }),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn finish(mut self) -> Result<(), ParseError> {
+        match self.words.next() {
+            Some(_) => Err(ParseError::TooManyArguments { usage: self.usage }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_command_parses() {
+        assert_eq!(
+            parse("spawn B 0.5"),
+            Ok(cheat(Cheat::SpawnEnemy {
+                combat_type: CombatType::B,
+                percent_left: 50,
+            }))
+        );
+        assert_eq!(
+            parse("spawn d"),
+            Ok(cheat(Cheat::SpawnEnemy {
+                combat_type: CombatType::D,
+                percent_left: 100,
+            }))
+        );
+        assert_eq!(parse("heal all"), Ok(cheat(Cheat::HealAll)));
+        assert_eq!(parse("finish #3"), Ok(cheat(Cheat::FinishNow(UnitId(3)))));
+        assert_eq!(parse("finish 3"), Ok(cheat(Cheat::FinishNow(UnitId(3)))));
+        assert_eq!(
+            parse("interval 4"),
+            Ok(cheat(Cheat::SetSpawnInterval { seconds: 4 }))
+        );
+        assert_eq!(
+            parse("invincible on"),
+            Ok(cheat(Cheat::SetInvincible(true)))
+        );
+        assert_eq!(
+            parse("invincible off"),
+            Ok(cheat(Cheat::SetInvincible(false)))
+        );
+        assert_eq!(
+            parse("speed 2"),
+            Ok(ConsoleCommand::Speed(GameSpeed::Double))
+        );
+        assert_eq!(
+            parse("speed 0.5"),
+            Ok(ConsoleCommand::Speed(GameSpeed::Half))
+        );
+        assert_eq!(parse("seed"), Ok(ConsoleCommand::ShowSeed));
+        assert_eq!(parse("stats"), Ok(ConsoleCommand::ShowStats));
+        assert_eq!(parse("help"), Ok(ConsoleCommand::Help));
+    }
+
+    #[test]
+    fn case_and_spacing_dont_matter() {
+        assert_eq!(parse("  HEAL   All "), Ok(cheat(Cheat::HealAll)));
+        assert_eq!(
+            parse("Speed 4"),
+            Ok(ConsoleCommand::Speed(GameSpeed::Quadruple))
+        );
+    }
+
+    #[test]
+    fn malformed_lines_say_what_is_wrong() {
+        assert_eq!(parse("   "), Err(ParseError::Empty));
+        assert_eq!(
+            parse("launch A"),
+            Err(ParseError::UnknownCommand("launch".to_string()))
+        );
+        assert_eq!(
+            parse("spawn"),
+            Err(ParseError::MissingArgument {
+                usage: COMMANDS[0].usage
+            })
+        );
+        assert_eq!(
+            parse("spawn E"),
+            Err(ParseError::InvalidArgument {
+                argument: "E".to_string(),
+                usage: COMMANDS[0].usage,
+            })
+        );
+        assert!(matches!(
+            parse("spawn A 1.5"),
+            Err(ParseError::InvalidArgument { argument, .. }) if argument == "1.5"
+        ));
+        assert!(matches!(
+            parse("heal some"),
+            Err(ParseError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            parse("interval 0"),
+            Err(ParseError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            parse("speed 3"),
+            Err(ParseError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            parse("invincible maybe"),
+            Err(ParseError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            parse("seed 42"),
+            Err(ParseError::TooManyArguments { .. })
+        ));
+        assert!(matches!(
+            parse("give credits 100"),
+            Err(ParseError::Unsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn errors_show_the_usage() {
+        let error = parse("finish").unwrap_err().to_string();
+        assert!(error.contains("finish <unit>"), "{}", error);
+    }
+
+    #[test]
+    fn completion_offers_the_commands_starting_with_the_prefix() {
+        assert_eq!(completions("s"), vec!["spawn", "speed", "seed", "stats"]);
+        assert_eq!(completions("SE"), vec!["seed"]);
+        assert_eq!(completions("").len(), COMMANDS.len());
+        assert!(completions("x").is_empty());
+    }
+}
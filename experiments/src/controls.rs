@@ -0,0 +1,150 @@
+//! Keyboard shortcuts. The keys themselves are in the player's settings; this only knows the
+//! actions they can be bound to and what each one does.
+
+#[cfg(feature = "backend-eframe")]
+use engine::input::ActionState;
+use engine::input::{InputMap, KeyCode};
+
+#[cfg(feature = "backend-eframe")]
+use crate::game::{CombatType, UnitAction, UnitId};
+
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyCommand {
+    Select(UnitId),
+    Perform(UnitAction),
+    Scramble(CombatType),
+}
+
+/// Pauses and resumes. Handled with the game speed rather than as a `KeyCommand`, since it also
+/// works while paused.
+pub const PAUSE: &str = "pause";
+
+/// The name of every action that can be bound, with its description for the controls lists.
+#[cfg(feature = "backend-eframe")]
+pub const ACTIONS: &[(&str, &str)] = &[
+    (PAUSE, "Pause and resume"),
+    ("select_unit_1", "Select unit 1"),
+    ("select_unit_2", "Select unit 2"),
+    ("select_unit_3", "Select unit 3"),
+    ("select_unit_4", "Select unit 4"),
+    ("select_unit_5", "Select unit 5"),
+    ("select_unit_6", "Select unit 6"),
+    ("select_unit_7", "Select unit 7"),
+    ("select_unit_8", "Select unit 8"),
+    ("un_store", "Bring the selected unit out of storage"),
+    ("prepare_a", "Prepare the selected unit for type A"),
+    ("prepare_b", "Prepare the selected unit for type B"),
+    ("prepare_c", "Prepare the selected unit for type C"),
+    ("prepare_d", "Prepare the selected unit for type D"),
+    ("take_off", "Take off"),
+    ("store", "Move into storage"),
+    ("park", "Park"),
+    ("scramble_a", "Scramble the quickest unit against type A"),
+    ("scramble_b", "Scramble the quickest unit against type B"),
+    ("scramble_c", "Scramble the quickest unit against type C"),
+    ("scramble_d", "Scramble the quickest unit against type D"),
+];
+
+/// The keys the game comes with. Scrambling has none, since every letter that would fit is
+/// taken; players who want it can bind it themselves.
+pub fn default_bindings() -> InputMap {
+    InputMap::new(&[
+        (PAUSE, KeyCode::Space),
+        ("select_unit_1", KeyCode::Num1),
+        ("select_unit_2", KeyCode::Num2),
+        ("select_unit_3", KeyCode::Num3),
+        ("select_unit_4", KeyCode::Num4),
+        ("select_unit_5", KeyCode::Num5),
+        ("select_unit_6", KeyCode::Num6),
+        ("select_unit_7", KeyCode::Num7),
+        ("select_unit_8", KeyCode::Num8),
+        ("un_store", KeyCode::U),
+        ("prepare_a", KeyCode::A),
+        ("prepare_b", KeyCode::B),
+        ("prepare_c", KeyCode::C),
+        ("prepare_d", KeyCode::D),
+        ("take_off", KeyCode::T),
+        ("store", KeyCode::S),
+        ("park", KeyCode::P),
+    ])
+}
+
+#[cfg(feature = "backend-eframe")]
+pub fn key_command(action: &str) -> Option<KeyCommand> {
+    let command = match action {
+        "select_unit_1" => KeyCommand::Select(UnitId(1)),
+        "select_unit_2" => KeyCommand::Select(UnitId(2)),
+        "select_unit_3" => KeyCommand::Select(UnitId(3)),
+        "select_unit_4" => KeyCommand::Select(UnitId(4)),
+        "select_unit_5" => KeyCommand::Select(UnitId(5)),
+        "select_unit_6" => KeyCommand::Select(UnitId(6)),
+        "select_unit_7" => KeyCommand::Select(UnitId(7)),
+        "select_unit_8" => KeyCommand::Select(UnitId(8)),
+        "un_store" => KeyCommand::Perform(UnitAction::UnStore),
+        "prepare_a" => KeyCommand::Perform(UnitAction::Prepare(CombatType::A)),
+        "prepare_b" => KeyCommand::Perform(UnitAction::Prepare(CombatType::B)),
+        "prepare_c" => KeyCommand::Perform(UnitAction::Prepare(CombatType::C)),
+        "prepare_d" => KeyCommand::Perform(UnitAction::Prepare(CombatType::D)),
+        "take_off" => KeyCommand::Perform(UnitAction::TakeOff),
+        "store" => KeyCommand::Perform(UnitAction::Store),
+        "park" => KeyCommand::Perform(UnitAction::Park),
+        "scramble_a" => KeyCommand::Scramble(CombatType::A),
+        "scramble_b" => KeyCommand::Scramble(CombatType::B),
+        "scramble_c" => KeyCommand::Scramble(CombatType::C),
+        "scramble_d" => KeyCommand::Scramble(CombatType::D),
+        _ => return None,
+    };
+
+    Some(command)
+}
+
+/// The commands for this frame's actions, in the order their keys were pressed.
+#[cfg(feature = "backend-eframe")]
+pub fn key_commands(actions: &ActionState) -> impl Iterator<Item = KeyCommand> + '_ {
+    actions.iter().filter_map(key_command)
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_action_but_pause_has_a_command() {
+        for &(action, _) in ACTIONS {
+            assert_eq!(key_command(action).is_some(), action != PAUSE, "{}", action);
+        }
+        assert_eq!(key_command("self_destruct"), None);
+    }
+
+    #[test]
+    fn the_defaults_only_bind_listed_actions() {
+        let defaults = default_bindings();
+        for &(action, _) in ACTIONS {
+            if let Some(key) = defaults.key(action) {
+                assert_eq!(defaults.action(key), Some(action));
+            }
+        }
+        assert_eq!(defaults.action(KeyCode::Z), None);
+    }
+
+    #[test]
+    fn keys_become_commands_in_the_order_they_were_pressed() {
+        let actions = default_bindings().translate(vec![
+            KeyCode::Num2,
+            KeyCode::Z,
+            KeyCode::A,
+            KeyCode::Space,
+            KeyCode::T,
+        ]);
+        let commands: Vec<_> = key_commands(&actions).collect();
+        assert_eq!(
+            commands,
+            [
+                KeyCommand::Select(UnitId(2)),
+                KeyCommand::Perform(UnitAction::Prepare(CombatType::A)),
+                KeyCommand::Perform(UnitAction::TakeOff),
+            ]
+        );
+    }
+}
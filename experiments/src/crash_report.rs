@@ -0,0 +1,157 @@
+//! What a player gets to see and pass on when the game crashes. In the browser a panic would
+//! otherwise only reach the developer console while the canvas just freezes.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    sync::Once,
+};
+
+thread_local! {
+    /// The seed of the run each game on the page is playing, so a report says which enemies
+    /// came.
+    static SEEDS: RefCell<BTreeMap<usize, u64>> = const { RefCell::new(BTreeMap::new()) };
+    static NEXT_GAME: Cell<usize> = const { Cell::new(0) };
+    static PANICKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A game's entry in crash reports. Every game has its own, and it leaves the reports when
+/// the game is dropped.
+#[derive(Debug)]
+pub struct CrashContext {
+    game: usize,
+}
+
+impl Default for CrashContext {
+    fn default() -> Self {
+        Self {
+            game: NEXT_GAME.with(|next| next.replace(next.get() + 1)),
+        }
+    }
+}
+
+impl CrashContext {
+    pub fn set_seed(&self, seed: u64) {
+        SEEDS.with(|seeds| seeds.borrow_mut().insert(self.game, seed));
+    }
+}
+
+impl Drop for CrashContext {
+    fn drop(&mut self) {
+        // The seeds may already be gone when the thread shuts down.
+        let _ = SEEDS.try_with(|seeds| seeds.borrow_mut().remove(&self.game));
+    }
+}
+
+/// Whether the game has crashed. Nothing in it can be trusted to work after that.
+#[cfg(target_arch = "wasm32")]
+pub fn has_panicked() -> bool {
+    PANICKED.with(Cell::get)
+}
+
+/// The version and seeds, needed to reproduce a crash.
+fn summary() -> String {
+    // The panic may have struck while the seeds were being changed.
+    let seeds: Vec<String> = SEEDS
+        .try_with(|seeds| {
+            seeds
+                .try_borrow()
+                .map(|seeds| seeds.values().map(u64::to_string).collect())
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+    let seeds = match seeds.len() {
+        0 => "seed none".to_string(),
+        1 => format!("seed {}", seeds[0]),
+        _ => format!("seeds {}", seeds.join(", ")),
+    };
+    format!(
+        "Fruitopian Defender {} ({}), {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("GIT_HASH"),
+        seeds
+    )
+}
+
+/// Adds the version and seed to what a panic prints. Only the first call does anything.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            PANICKED.with(|panicked| panicked.set(true));
+            default_hook(info);
+            eprintln!("{}", summary());
+        }));
+    });
+}
+
+/// Logs panics to the console as before and also shows them on the page. Only the first call
+/// does anything, however many games the page starts.
+#[cfg(target_arch = "wasm32")]
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        std::panic::set_hook(Box::new(|info| {
+            PANICKED.with(|panicked| panicked.set(true));
+            console_error_panic_hook::hook(info);
+            // Nothing more can be done if the page won't show it either.
+            let _ = show_overlay(&format!("{}\n{}", summary(), info));
+        }));
+    });
+}
+
+/// Covers the frozen canvas with an apology, the details and a button to copy them. Only uses
+/// calls that report failure rather than panicking, since a panic in here would abort.
+#[cfg(target_arch = "wasm32")]
+fn show_overlay(details: &str) -> Option<()> {
+    const OVERLAY_ID: &str = "crash_report";
+    const DETAILS_ID: &str = "crash_report_details";
+
+    let document = web_sys::window()?.document()?;
+    if document.get_element_by_id(OVERLAY_ID).is_some() {
+        return Some(());
+    }
+
+    let overlay = document.create_element("div").ok()?;
+    overlay.set_id(OVERLAY_ID);
+    overlay
+        .set_attribute(
+            "style",
+            "position: fixed; inset: 0; z-index: 1000; overflow: auto; padding: 2em; \
+            background: rgba(20, 10, 20, 0.92); color: #eee; font-family: sans-serif;",
+        )
+        .ok()?;
+
+    let apology = document.create_element("h2").ok()?;
+    apology.set_text_content(Some(
+        "Sorry, the game crashed. Reload the page to play again. If you report this, please \
+        include the details below.",
+    ));
+
+    let text = document.create_element("pre").ok()?;
+    text.set_id(DETAILS_ID);
+    text.set_attribute("style", "white-space: pre-wrap;").ok()?;
+    text.set_text_content(Some(details));
+
+    // An inline handler needs no Rust closure kept alive, and the clipboard API is still
+    // unstable in web-sys.
+    let copy = document.create_element("button").ok()?;
+    copy.set_text_content(Some("Copy details"));
+    copy.set_attribute(
+        "onclick",
+        &format!(
+            "navigator.clipboard.writeText(document.getElementById('{}').textContent)",
+            DETAILS_ID
+        ),
+    )
+    .ok()?;
+
+    overlay.append_child(&apology).ok()?;
+    overlay.append_child(&text).ok()?;
+    overlay.append_child(&copy).ok()?;
+    document.body()?.append_child(&overlay).ok()?;
+
+    Some(())
+}
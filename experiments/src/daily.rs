@@ -0,0 +1,204 @@
+//! The daily challenge: one run a day with the same enemies for everyone, so friends playing the
+//! same date can compare. The seed comes from the date in UTC, the run is endless on Normal, and
+//! cheats and speeds above 1× are off. The first attempt of a day is the one that counts; it is
+//! claimed as soon as it starts, so giving up on a bad start doesn't earn another go. Attempts
+//! after that are practice and go on no record.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "backend-eframe")]
+use crate::helpers::{fnv1a, Instant};
+use crate::{
+    game::Difficulty,
+    game_mode::GameMode,
+    helpers::GameSpeed,
+    storage::{self, Storage},
+};
+
+pub const DIFFICULTY: Difficulty = Difficulty::Normal;
+pub const MODE: GameMode = GameMode::Endless;
+/// The fastest a daily challenge may run, so nobody gets through more of it in the same time.
+pub const MAX_SPEED: GameSpeed = GameSpeed::Normal;
+
+/// Today's date in UTC, like `2024-06-01`.
+#[cfg(feature = "backend-eframe")]
+pub fn today() -> String {
+    Instant::now().utc_date()
+}
+
+/// The seed of the challenge on `date`, the same on every device and in every version.
+#[cfg(feature = "backend-eframe")]
+pub fn seed(date: &str) -> u64 {
+    fnv1a(date.as_bytes())
+}
+
+/// The daily challenge a run is, if it is one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyRun {
+    /// The day the challenge is of, even if the run goes on past midnight.
+    pub date: String,
+    /// Whether this is the attempt of the day that counts, rather than practice.
+    pub scored: bool,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl DailyRun {
+    pub fn seed(&self) -> u64 {
+        seed(&self.date)
+    }
+}
+
+/// How the scored attempt of a day went.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub date: String,
+    pub seed: u64,
+    /// `None` while the attempt is going, and for good if it was given up.
+    pub outcome: Option<DailyOutcome>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyOutcome {
+    pub survived_seconds: f64,
+    pub points: u64,
+}
+
+/// The scored attempts of the last days, newest first. Kept apart from the leaderboard, as only
+/// runs of the same date compare.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyResults {
+    days: Vec<DailyResult>,
+}
+
+impl DailyResults {
+    const STORAGE_KEY: &'static str = "daily_results";
+    #[cfg(feature = "backend-eframe")]
+    pub const MAX_DAYS: usize = 30;
+
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage::load(storage, Self::STORAGE_KEY)
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn days(&self) -> &[DailyResult] {
+        &self.days
+    }
+
+    /// The scored attempt of `date`, if it was started.
+    #[cfg(feature = "backend-eframe")]
+    pub fn of(&self, date: &str) -> Option<&DailyResult> {
+        self.days.iter().find(|day| day.date == date)
+    }
+
+    /// Starts an attempt at the challenge of `date`. It is the scored one if none was started
+    /// that day yet, in which case the day is claimed and persisted straight away.
+    #[cfg(feature = "backend-eframe")]
+    pub fn begin(&mut self, date: &str, storage: &mut dyn Storage) -> DailyRun {
+        let scored = self.of(date).is_none();
+        if scored {
+            self.days.insert(
+                0,
+                DailyResult {
+                    date: date.to_string(),
+                    seed: seed(date),
+                    outcome: None,
+                },
+            );
+            self.days.truncate(Self::MAX_DAYS);
+            storage::save(storage, Self::STORAGE_KEY, self);
+        }
+        DailyRun {
+            date: date.to_string(),
+            scored,
+        }
+    }
+
+    /// Puts down how the scored attempt of `date` went. Refused, returning false, for a day that
+    /// wasn't claimed or already has its result, so practice can't take its place.
+    pub fn record(&mut self, date: &str, outcome: DailyOutcome, storage: &mut dyn Storage) -> bool {
+        let day = match self.days.iter_mut().find(|day| day.date == date) {
+            Some(day) if day.outcome.is_none() => day,
+            _ => return false,
+        };
+        day.outcome = Some(outcome);
+        storage::save(storage, Self::STORAGE_KEY, self);
+        true
+    }
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    const OUTCOME: DailyOutcome = DailyOutcome {
+        survived_seconds: 214.0,
+        points: 3150,
+    };
+
+    #[test]
+    fn the_date_decides_the_seed() {
+        assert_eq!(seed("2024-06-01"), seed("2024-06-01"));
+        assert_ne!(seed("2024-06-01"), seed("2024-06-02"));
+        // Everybody's game has to agree on it, whatever version they play.
+        assert_eq!(seed("2024-06-01"), 14_422_106_417_849_429_718);
+    }
+
+    #[test]
+    fn only_the_first_attempt_of_a_day_is_scored() {
+        let mut storage = MemoryStorage::default();
+        let mut results = DailyResults::load(&storage);
+
+        let first = results.begin("2024-06-01", &mut storage);
+        assert!(first.scored);
+        assert_eq!(first.seed(), seed("2024-06-01"));
+        let practice = results.begin("2024-06-01", &mut storage);
+        assert!(!practice.scored);
+        assert_eq!(practice.seed(), first.seed());
+
+        assert!(results.begin("2024-06-02", &mut storage).scored);
+    }
+
+    #[test]
+    fn a_claimed_day_stays_claimed_across_restarts() {
+        let mut storage = MemoryStorage::default();
+        DailyResults::load(&storage).begin("2024-06-01", &mut storage);
+
+        let mut results = DailyResults::load(&storage);
+        assert!(!results.begin("2024-06-01", &mut storage).scored);
+    }
+
+    #[test]
+    fn a_day_records_one_result() {
+        let mut storage = MemoryStorage::default();
+        let mut results = DailyResults::load(&storage);
+        assert!(!results.record("2024-06-01", OUTCOME, &mut storage));
+
+        results.begin("2024-06-01", &mut storage);
+        assert!(results.record("2024-06-01", OUTCOME, &mut storage));
+        let practice = DailyOutcome {
+            points: 9000,
+            ..OUTCOME
+        };
+        assert!(!results.record("2024-06-01", practice, &mut storage));
+
+        let kept = DailyResults::load(&storage);
+        assert_eq!(kept.of("2024-06-01").unwrap().outcome, Some(OUTCOME));
+    }
+
+    #[test]
+    fn only_the_latest_days_are_kept() {
+        let mut storage = MemoryStorage::default();
+        let mut results = DailyResults::default();
+        for day in 0..DailyResults::MAX_DAYS + 5 {
+            results.begin(&format!("day {}", day), &mut storage);
+        }
+
+        let days = results.days();
+        assert_eq!(days.len(), DailyResults::MAX_DAYS);
+        assert_eq!(days[0].date, format!("day {}", DailyResults::MAX_DAYS + 4));
+        assert!(results.of("day 4").is_none());
+        assert!(results.of("day 5").is_some());
+    }
+}
@@ -0,0 +1,225 @@
+//! What ended a lost run: the enemy that hit the base, and how far the unit closest to stopping
+//! it was from being ready. Taken the moment the enemy gets through, for the run-over window.
+
+use crate::game::{CombatType, Enemy, Unit, UnitBundle, UnitId, UnitTimings};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefeatReport {
+    pub lane: CombatType,
+    pub is_boss: bool,
+    pub at_seconds: f64,
+    /// `None` if there were no units left.
+    pub nearest: Option<NearestDefender>,
+}
+
+impl DefeatReport {
+    pub fn new(
+        enemy: &Enemy,
+        at_seconds: f64,
+        units: &[UnitBundle],
+        timings: &UnitTimings,
+    ) -> Self {
+        Self {
+            lane: enemy.combat_type,
+            is_boss: enemy.is_boss,
+            at_seconds,
+            nearest: nearest_defender(units, enemy.combat_type, timings),
+        }
+    }
+}
+
+/// The unit that was the closest to being ready for the lane.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NearestDefender {
+    pub unit: UnitId,
+    pub state: DefenderState,
+    /// How long it would still have taken to be ready for the lane, with every order given
+    /// straight away. 0 for a unit that was ready or up in the lane already.
+    pub seconds_to_ready: f64,
+}
+
+/// What the nearest unit was doing, as far as it matters for the lane.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DefenderState {
+    /// Up in the lane already, but too late to meet the enemy.
+    Patrolling,
+    /// Parked and ready for the lane, but never sent up.
+    Ready,
+    /// Being prepared for the lane.
+    Preparing,
+    /// Parked, but not prepared for the lane.
+    Parked,
+    /// Coming out of storage or back from a patrol into a parking space.
+    Parking,
+    /// Up over another lane, or flying back.
+    Airborne,
+    /// Back from a patrol, waiting for a parking space.
+    WaitingToPark,
+    /// In storage or on its way there.
+    InStorage,
+}
+
+/// Picks the unit that was the quickest to get ready for `lane`. Between equally quick units the
+/// one listed first counts.
+pub fn nearest_defender(
+    units: &[UnitBundle],
+    lane: CombatType,
+    timings: &UnitTimings,
+) -> Option<NearestDefender> {
+    // From a parking space without preparation to ready for the lane.
+    let prepare = timings.prepare_seconds;
+    units
+        .iter()
+        .map(|bundle| {
+            let (state, seconds_to_ready) = match &bundle.unit {
+                Unit::Patrolling(_, combat_type) if *combat_type == lane => {
+                    (DefenderState::Patrolling, 0.0)
+                }
+                Unit::ParkedReady(_, combat_type) if *combat_type == lane => {
+                    (DefenderState::Ready, 0.0)
+                }
+                Unit::ParkedPreparing(timer, _, combat_type) if *combat_type == lane => (
+                    DefenderState::Preparing,
+                    f64::from(timer.remaining_seconds()),
+                ),
+                // Not prepared, or being prepared for another lane, which is called off first.
+                Unit::ParkedUnready(_) | Unit::ParkedPreparing(_, _, _) => {
+                    (DefenderState::Parked, prepare)
+                }
+                // Ready for another lane, which only a sortie there undoes.
+                Unit::ParkedReady(_, _) => (
+                    DefenderState::Parked,
+                    timings.patrol_seconds + timings.park_seconds + prepare,
+                ),
+                Unit::UnStoring(timer, _) | Unit::Parking(timer, _) => (
+                    DefenderState::Parking,
+                    f64::from(timer.remaining_seconds()) + prepare,
+                ),
+                Unit::Patrolling(timer, _) | Unit::Returning(timer, _) => (
+                    DefenderState::Airborne,
+                    f64::from(timer.remaining_seconds()) + timings.park_seconds + prepare,
+                ),
+                Unit::WaitingToPark => {
+                    (DefenderState::WaitingToPark, timings.park_seconds + prepare)
+                }
+                Unit::InStorage => (DefenderState::InStorage, timings.un_store_seconds + prepare),
+                Unit::Storing(timer) => (
+                    DefenderState::InStorage,
+                    f64::from(timer.remaining_seconds()) + timings.un_store_seconds + prepare,
+                ),
+            };
+            NearestDefender {
+                unit: bundle.id,
+                state,
+                seconds_to_ready: seconds_to_ready.max(0.0),
+            }
+        })
+        .fold(
+            None,
+            |nearest: Option<NearestDefender>, candidate| match nearest {
+                Some(nearest) if nearest.seconds_to_ready <= candidate.seconds_to_ready => {
+                    Some(nearest)
+                }
+                _ => Some(candidate),
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::{
+            fixtures::{enemy, unit, TIMINGS},
+            TokenPool,
+        },
+        helpers::{Duration, Timer},
+    };
+
+    /// A timer of `seconds` with `left` of them still to go.
+    fn timer(seconds: f64, left: f64) -> Timer {
+        let mut timer = Timer::from_seconds(seconds, false);
+        timer.set_elapsed(Duration::from_secs_f64(seconds - left));
+        timer
+    }
+
+    #[test]
+    fn a_near_miss_names_the_unit_that_was_almost_ready() {
+        let mut parking_spaces = TokenPool::new(2);
+        let units = [
+            unit(1, Unit::InStorage),
+            unit(
+                2,
+                Unit::ParkedPreparing(
+                    timer(3.0, 2.1),
+                    parking_spaces.try_take().unwrap(),
+                    CombatType::C,
+                ),
+            ),
+            unit(3, Unit::ParkedUnready(parking_spaces.try_take().unwrap())),
+        ];
+        let report = DefeatReport::new(
+            &enemy(1, CombatType::C, 30.0, 30.0),
+            214.0,
+            &units,
+            &TIMINGS,
+        );
+
+        assert_eq!(report.lane, CombatType::C);
+        assert!(!report.is_boss);
+        assert_eq!(report.at_seconds, 214.0);
+        let nearest = report.nearest.unwrap();
+        assert_eq!(nearest.unit, UnitId(2));
+        assert_eq!(nearest.state, DefenderState::Preparing);
+        assert!((nearest.seconds_to_ready - 2.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn units_for_other_lanes_count_what_it_takes_to_switch() {
+        let mut parking_spaces = TokenPool::new(1);
+        let ready_elsewhere = [unit(
+            1,
+            Unit::ParkedReady(parking_spaces.try_take().unwrap(), CombatType::A),
+        )];
+        let nearest = nearest_defender(&ready_elsewhere, CombatType::B, &TIMINGS).unwrap();
+        assert_eq!(nearest.state, DefenderState::Parked);
+        assert_eq!(
+            nearest.seconds_to_ready,
+            TIMINGS.patrol_seconds + TIMINGS.park_seconds + TIMINGS.prepare_seconds
+        );
+
+        let airborne = [unit(1, Unit::Patrolling(timer(30.0, 4.0), CombatType::A))];
+        let nearest = nearest_defender(&airborne, CombatType::B, &TIMINGS).unwrap();
+        assert_eq!(nearest.state, DefenderState::Airborne);
+        assert!((nearest.seconds_to_ready - 8.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn a_unit_up_in_the_lane_was_just_too_late() {
+        let units = [
+            unit(1, Unit::WaitingToPark),
+            unit(2, Unit::Patrolling(timer(30.0, 12.0), CombatType::D)),
+        ];
+        let nearest = nearest_defender(&units, CombatType::D, &TIMINGS).unwrap();
+        assert_eq!(nearest.unit, UnitId(2));
+        assert_eq!(nearest.state, DefenderState::Patrolling);
+        assert_eq!(nearest.seconds_to_ready, 0.0);
+    }
+
+    #[test]
+    fn the_first_of_equally_quick_units_counts() {
+        let units = [unit(4, Unit::InStorage), unit(5, Unit::InStorage)];
+        let nearest = nearest_defender(&units, CombatType::A, &TIMINGS).unwrap();
+        assert_eq!(nearest.unit, UnitId(4));
+        assert_eq!(nearest.state, DefenderState::InStorage);
+        assert_eq!(
+            nearest.seconds_to_ready,
+            TIMINGS.un_store_seconds + TIMINGS.prepare_seconds
+        );
+    }
+
+    #[test]
+    fn without_units_nobody_is_nearest() {
+        assert_eq!(nearest_defender(&[], CombatType::A, &TIMINGS), None);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    defeat::DefeatReport,
+    game::{CombatType, Health, UnitAction, UnitId},
+};
+
+/// Something noteworthy that happened during a tick. The game logic only reports these; the
+/// stats and the battle log decide what to make of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    EnemySpawned {
+        combat_type: CombatType,
+        is_boss: bool,
+    },
+    EnemyIntercepted {
+        unit: UnitId,
+        combat_type: CombatType,
+        is_boss: bool,
+    },
+    UnitDamaged {
+        unit: UnitId,
+        health: Health,
+        /// One more hit and the unit is gone.
+        critical: bool,
+    },
+    UnitDestroyed {
+        unit: UnitId,
+    },
+    BossLeaked {
+        combat_type: CombatType,
+        parking_spaces_left: usize,
+    },
+    /// An enemy got far down its lane without a unit there to stop it.
+    EnemyBreaching {
+        combat_type: CombatType,
+        /// How far down the lane it is, as a fraction.
+        threshold: f32,
+        eta_seconds: f32,
+    },
+    /// An enemy got through to the base, which ends the run unless the sandbox keeps it going.
+    BaseHit {
+        report: DefeatReport,
+    },
+    /// A unit got back from the battlezone and is waiting to park.
+    SortieCompleted {
+        unit: UnitId,
+    },
+    UnitParked {
+        unit: UnitId,
+    },
+    /// The player, or auto-park, gave a unit an order it could carry out.
+    UnitOrdered {
+        unit: UnitId,
+        action: UnitAction,
+    },
+    /// A unit finished preparing and can take off.
+    UnitReady {
+        unit: UnitId,
+        combat_type: CombatType,
+    },
+    UnitRepaired {
+        unit: UnitId,
+    },
+    /// Units came into or left the parking spaces. Sent once a frame at most, with how many
+    /// spaces are in use now.
+    ParkingChanged {
+        used: usize,
+    },
+    /// The run is over. Sent once, after everything else that happened in its last frame.
+    GameOver {
+        survived_seconds: f64,
+    },
+    /// The contract was fulfilled, which ends the run like `GameOver` does.
+    Victory {
+        survived_seconds: f64,
+    },
+}
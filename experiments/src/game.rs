@@ -1,43 +1,87 @@
 use std::{
+    collections::VecDeque,
     fmt::{Display, Formatter},
     marker::PhantomData,
     sync::Arc,
 };
 
-use eframe::{
-    egui,
-    egui::{Align, Align2, Color32, CtxRef, Pos2, TextStyle, Vec2, Visuals},
-};
-use rand::prelude::Distribution;
-use rand_derive2::RandGen;
+use rand::{distributions::Standard, prelude::Distribution, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 use rand_distr::Normal;
-use retain_mut::RetainMut;
-use strum::{Display, EnumIter, IntoEnumIterator};
-
-use crate::helpers::{Duration, Time, Timer};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoStaticStr};
+
+use crate::{
+    balance::{EnemyConfig, GameConfig},
+    defeat::DefeatReport,
+    events::GameEvent,
+    game_mode::{ContractConfig, GameMode},
+    helpers::{Duration, Time, Timer},
+};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Default)]
 pub enum GameState {
+    #[default]
+    MainMenu,
     Running,
+    /// Only the player or the page hosting the game pause a run.
+    Paused,
     GameOver,
+    /// The contract was fulfilled.
+    Victory,
 }
 
-impl Default for GameState {
-    fn default() -> Self {
-        Self::Running
+impl GameState {
+    /// Whether the run plays out and takes orders. Only while running: a paused run is frozen,
+    /// and one that is over stays over until the next one starts.
+    pub fn is_interactive(self) -> bool {
+        self == Self::Running
+    }
+
+    /// Whether the run has ended, lost or won.
+    pub fn is_over(self) -> bool {
+        matches!(self, Self::GameOver | Self::Victory)
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// How long everything the units do takes. The ground crew is quicker or slower with them
+/// depending on the difficulty; the rest is the same on all of them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UnitTimings {
+    /// How long a unit in storage takes to be repaired from no health left to full.
+    pub seconds_to_fully_repair: f64,
+    /// How long a parked unit takes to get ready for a combat type.
+    pub prepare_seconds: f64,
+    pub un_store_seconds: f64,
+    pub store_seconds: f64,
+    pub park_seconds: f64,
+    pub patrol_seconds: f64,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct PlayTime(Duration);
 
 impl PlayTime {
     fn tick(&mut self, time: &Time) {
         self.0 += time.delta();
     }
+
+    pub(crate) fn seconds(&self) -> f64 {
+        self.0.as_secs_f64()
+    }
 }
 
-#[derive(RandGen, EnumIter, Display, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(
+    EnumIter, Display, IntoStaticStr, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
 pub enum CombatType {
     A,
     B,
@@ -45,6 +89,32 @@ pub enum CombatType {
     D,
 }
 
+/// Draws from the generator it is given, so the lanes enemies come in follow the seed.
+impl Distribution<CombatType> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> CombatType {
+        match rng.gen_range(0..4) {
+            0 => CombatType::A,
+            1 => CombatType::B,
+            2 => CombatType::C,
+            _ => CombatType::D,
+        }
+    }
+}
+
+#[cfg(feature = "backend-eframe")]
+impl CombatType {
+    /// Each type has its own marker shape so it can be told apart without relying on colour.
+    pub(crate) fn glyph(self) -> &'static str {
+        match self {
+            Self::A => "◀",
+            Self::B => "◆",
+            Self::C => "▲",
+            Self::D => "●",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Health(f64);
 
 impl Default for Health {
@@ -54,13 +124,31 @@ impl Default for Health {
 }
 
 impl Health {
-    fn repair_tick(&mut self, time: &Time) {
-        const SECONDS_TO_FULLY_REPAIR: f64 = 15.0;
-        self.0 = (self.0 + time.delta_seconds_f64() / SECONDS_TO_FULLY_REPAIR).min(1.0);
+    /// Health from 0, about to be lost, to 1, fully repaired.
+    pub(crate) fn new(fraction: f64) -> Self {
+        Self(fraction)
     }
 
-    fn take_hit(&mut self) -> bool {
-        self.0 -= 0.25;
+    pub(crate) fn fraction(&self) -> f32 {
+        self.0.clamp(0.0, 1.0) as f32
+    }
+
+    /// Whether it is within 0 and 1, which it has to be while the unit is still around.
+    pub(crate) fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.0)
+    }
+
+    fn repair_tick(&mut self, time: &Time, timings: &UnitTimings) {
+        self.0 = (self.0 + time.delta_seconds_f64() / timings.seconds_to_fully_repair).min(1.0);
+    }
+
+    /// One more hit and the unit is gone.
+    pub(crate) fn is_critical(&self, damage_per_hit: f64) -> bool {
+        self.0 < damage_per_hit
+    }
+
+    fn take_hit(&mut self, damage_per_hit: f64) -> bool {
+        self.0 -= damage_per_hit;
 
         self.0 >= 0.0
     }
@@ -88,7 +176,7 @@ pub enum Unit {
 
 impl Unit {
     fn tick(&mut self, time: &Time) {
-        // web_sys::console::log_1(&format!("Tick - time: {:#?}", time).into());
+        log::trace!("Tick - time: {:?}", time);
         match self {
             Self::ParkedPreparing(timer, parking_space, combat_type) => {
                 timer.tick(time.delta());
@@ -139,6 +227,49 @@ impl Unit {
         }
     }
 
+    /// The parking space the unit holds on to, if any.
+    pub(crate) fn parking_space(&self) -> Option<&Token<ParkingSpace>> {
+        match self {
+            Self::UnStoring(_, parking_space)
+            | Self::ParkedUnready(parking_space)
+            | Self::ParkedPreparing(_, parking_space, _)
+            | Self::ParkedReady(parking_space, _)
+            | Self::Parking(_, parking_space) => Some(parking_space),
+            Self::InStorage
+            | Self::Patrolling(_, _)
+            | Self::Returning(_, _)
+            | Self::WaitingToPark
+            | Self::Storing(_) => None,
+        }
+    }
+
+    /// What the unit is waiting on to move on by itself, if anything.
+    pub(crate) fn timer(&self) -> Option<&Timer> {
+        match self {
+            Self::UnStoring(timer, _)
+            | Self::ParkedPreparing(timer, _, _)
+            | Self::Patrolling(timer, _)
+            | Self::Returning(timer, _)
+            | Self::Storing(timer)
+            | Self::Parking(timer, _) => Some(timer),
+            Self::InStorage
+            | Self::ParkedUnready(_)
+            | Self::ParkedReady(_, _)
+            | Self::WaitingToPark => None,
+        }
+    }
+
+    /// Whether the unit is sitting still, waiting for its next order.
+    fn is_idle(&self) -> bool {
+        matches!(
+            self,
+            Self::InStorage
+                | Self::ParkedUnready(_)
+                | Self::ParkedReady(_, _)
+                | Self::WaitingToPark
+        )
+    }
+
     fn progress_percent(&self) -> f32 {
         match self {
             Self::Patrolling(timer, _) => timer.percent(),
@@ -146,7 +277,7 @@ impl Unit {
         }
     }
 
-    fn return_to_base(&mut self) {
+    pub(crate) fn return_to_base(&mut self) {
         if let Self::Patrolling(timer, combat_type) = self {
             *self = Self::Returning(timer.clone(), *combat_type);
         } else {
@@ -154,20 +285,21 @@ impl Unit {
         }
     }
 
-    fn un_store(&mut self, parking_spaces: &mut TokenPool<ParkingSpace>) {
+    pub(crate) fn un_store(&mut self, parking_space: Token<ParkingSpace>, timings: &UnitTimings) {
         if let Self::InStorage = self {
-            let parking_space = parking_spaces.try_take().unwrap();
-
-            *self = Self::UnStoring(Timer::from_seconds(10.0, false), parking_space);
+            *self = Self::UnStoring(
+                Timer::from_seconds(timings.un_store_seconds, false),
+                parking_space,
+            );
         } else {
             panic!("Invalid state for unmothballing.")
         }
     }
 
-    fn prepare(&mut self, combat_type: CombatType) {
+    pub(crate) fn prepare(&mut self, combat_type: CombatType, timings: &UnitTimings) {
         if let Self::ParkedUnready(parking_space) = self {
             *self = Self::ParkedPreparing(
-                Timer::from_seconds(5.0, false),
+                Timer::from_seconds(timings.prepare_seconds, false),
                 parking_space.clone(),
                 combat_type,
             )
@@ -176,15 +308,43 @@ impl Unit {
         }
     }
 
-    fn take_off(&mut self) {
+    /// Runs out whatever the unit is waiting for, e.g. its preparation or its patrol, so it
+    /// moves on with the next tick. Only the sandbox's cheats do this.
+    pub(crate) fn finish_now(&mut self) {
+        match self {
+            Self::UnStoring(timer, _)
+            | Self::ParkedPreparing(timer, _, _)
+            | Self::Patrolling(timer, _)
+            | Self::Returning(timer, _)
+            | Self::Storing(timer)
+            | Self::Parking(timer, _) => timer.finish_now(),
+            Self::InStorage
+            | Self::ParkedUnready(_)
+            | Self::ParkedReady(_, _)
+            | Self::WaitingToPark => {}
+        }
+    }
+
+    pub(crate) fn cancel_preparation(&mut self) {
+        if let Self::ParkedPreparing(_, parking_space, _) = self {
+            *self = Self::ParkedUnready(parking_space.clone());
+        } else {
+            panic!("Invalid state for cancelling the preparation")
+        }
+    }
+
+    pub(crate) fn take_off(&mut self, timings: &UnitTimings) {
         if let Self::ParkedReady(_, combat_type) = self {
-            *self = Self::Patrolling(Timer::from_seconds(30.0, false), *combat_type);
+            *self = Self::Patrolling(
+                Timer::from_seconds(timings.patrol_seconds, false),
+                *combat_type,
+            );
         } else {
             panic!("Invalid state for taking off")
         }
     }
 
-    fn move_into_storage(&mut self) {
+    pub(crate) fn move_into_storage(&mut self, timings: &UnitTimings) {
         match self {
             Unit::ParkedUnready(_) => {}
             Unit::ParkedPreparing(_, _, _) => {}
@@ -195,32 +355,288 @@ impl Unit {
             }
         }
 
-        *self = Self::Storing(Timer::from_seconds(10.0, false));
+        *self = Self::Storing(Timer::from_seconds(timings.store_seconds, false));
     }
 
-    fn park_after_returning(&mut self, parking_spaces: &mut TokenPool<ParkingSpace>) {
+    pub(crate) fn park_after_returning(
+        &mut self,
+        parking_space: Token<ParkingSpace>,
+        timings: &UnitTimings,
+    ) {
         if let Self::WaitingToPark = self {
-            let parking_space = parking_spaces.try_take().unwrap();
-
-            *self = Self::Parking(Timer::from_seconds(5.0, false), parking_space);
+            *self = Self::Parking(
+                Timer::from_seconds(timings.park_seconds, false),
+                parking_space,
+            );
         } else {
             panic!("Invalid state for parking.")
         }
     }
 }
 
-pub struct UnitBundle(Unit, Health);
+/// Player-facing unit number, starting at 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct UnitId(pub u32);
 
-pub struct Enemy {
-    progress: Timer,
+impl Display for UnitId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Everything the player can order a unit to do.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitAction {
+    UnStore,
+    Prepare(CombatType),
+    TakeOff,
+    Store,
+    Park,
+    /// Break off the patrol and head back to base.
+    Recall,
+    /// Stop preparing and stay parked, ready to be prepared for another lane.
+    CancelPreparation,
+}
+
+impl UnitAction {
+    /// Every action, with one `Prepare` per combat type.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::UnStore,
+            Self::Prepare(CombatType::A),
+            Self::Prepare(CombatType::B),
+            Self::Prepare(CombatType::C),
+            Self::Prepare(CombatType::D),
+            Self::TakeOff,
+            Self::Store,
+            Self::Park,
+            Self::Recall,
+            Self::CancelPreparation,
+        ]
+    }
+}
+
+pub struct UnitBundle {
+    pub(crate) id: UnitId,
+    pub(crate) unit: Unit,
+    pub(crate) health: Health,
+    /// Orders to carry out one after the other, each as soon as the unit is idle again.
+    pub(crate) orders: VecDeque<UnitAction>,
+}
+
+impl UnitBundle {
+    /// Whether the action is currently available, under the same conditions as its button.
+    pub(crate) fn can_perform(
+        &self,
+        action: UnitAction,
+        parking_spaces: &TokenPool<ParkingSpace>,
+    ) -> bool {
+        match (action, &self.unit) {
+            (UnitAction::UnStore, Unit::InStorage) => parking_spaces.can_take(),
+            (UnitAction::Prepare(_), Unit::ParkedUnready(_)) => true,
+            (UnitAction::TakeOff, Unit::ParkedReady(_, _)) => true,
+            (UnitAction::Store, Unit::ParkedUnready(_)) => true,
+            (UnitAction::Store, Unit::WaitingToPark) => true,
+            (UnitAction::Park, Unit::WaitingToPark) => parking_spaces.can_take(),
+            (UnitAction::Recall, Unit::Patrolling(_, _)) => true,
+            (UnitAction::CancelPreparation, Unit::ParkedPreparing(_, _, _)) => true,
+            _ => false,
+        }
+    }
+
+    /// Carries out the action if it is available and does nothing otherwise.
+    pub(crate) fn perform(
+        &mut self,
+        action: UnitAction,
+        timings: &UnitTimings,
+        parking_spaces: &mut TokenPool<ParkingSpace>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        if self.perform_in(action, None, timings, parking_spaces, events) {
+            self.orders.clear();
+        }
+    }
+
+    /// Like `perform`, but un-stores or parks the unit in the given parking space rather than
+    /// the first free one.
+    pub(crate) fn perform_in_slot(
+        &mut self,
+        action: UnitAction,
+        slot: usize,
+        timings: &UnitTimings,
+        parking_spaces: &mut TokenPool<ParkingSpace>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        if self.perform_in(action, Some(slot), timings, parking_spaces, events) {
+            self.orders.clear();
+        }
+    }
+
+    /// Carries out the next queued order once the unit is idle. An order that can't be carried
+    /// out, e.g. for lack of a parking space, drops the whole queue.
+    fn follow_orders(
+        &mut self,
+        timings: &UnitTimings,
+        parking_spaces: &mut TokenPool<ParkingSpace>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        if !self.unit.is_idle() {
+            return;
+        }
+
+        if let Some(action) = self.orders.pop_front() {
+            if !self.perform_in(action, None, timings, parking_spaces, events) {
+                self.orders.clear();
+            }
+        }
+    }
+
+    /// Returns whether the action was carried out.
+    fn perform_in(
+        &mut self,
+        action: UnitAction,
+        slot: Option<usize>,
+        timings: &UnitTimings,
+        parking_spaces: &mut TokenPool<ParkingSpace>,
+        events: &mut Vec<GameEvent>,
+    ) -> bool {
+        if !self.can_perform(action, parking_spaces) {
+            return false;
+        }
+
+        match action {
+            UnitAction::UnStore | UnitAction::Park => {
+                let parking_space = match slot {
+                    Some(slot) => parking_spaces.try_take_slot(slot),
+                    None => parking_spaces.try_take(),
+                };
+                let parking_space = match parking_space {
+                    Some(parking_space) => parking_space,
+                    None => return false,
+                };
+
+                if action == UnitAction::UnStore {
+                    self.unit.un_store(parking_space, timings);
+                } else {
+                    self.unit.park_after_returning(parking_space, timings);
+                }
+            }
+            UnitAction::Prepare(combat_type) => self.unit.prepare(combat_type, timings),
+            UnitAction::TakeOff => self.unit.take_off(timings),
+            UnitAction::Store => self.unit.move_into_storage(timings),
+            UnitAction::Recall => self.unit.return_to_base(),
+            UnitAction::CancelPreparation => self.unit.cancel_preparation(),
+        }
+
+        events.push(GameEvent::UnitOrdered {
+            unit: self.id,
+            action,
+        });
+        true
+    }
+}
+
+/// The unit a scramble for `combat_type` would send, and roughly how long it would take to get
+/// airborne.
+pub struct ScrambleCandidate {
+    pub index: usize,
+    pub id: UnitId,
+    pub seconds_to_airborne: f64,
+}
+
+/// Picks the unit that gets airborne against `combat_type` the quickest: one that's already
+/// prepared for it, then a parked one, then one from storage if there is a free parking space.
+/// Between equally quick units the healthiest one goes. Units that are already following
+/// orders are left alone.
+pub fn scramble_candidate(
+    units: &[UnitBundle],
     combat_type: CombatType,
+    timings: &UnitTimings,
+    parking_spaces: &TokenPool<ParkingSpace>,
+) -> Option<ScrambleCandidate> {
+    units
+        .iter()
+        .enumerate()
+        .filter(|(_, bundle)| bundle.orders.is_empty())
+        .filter_map(|(index, bundle)| {
+            let seconds_to_airborne = match &bundle.unit {
+                Unit::ParkedReady(_, ready_for) if *ready_for == combat_type => 0.0,
+                Unit::ParkedUnready(_) => timings.prepare_seconds,
+                Unit::InStorage if parking_spaces.can_take() => {
+                    timings.un_store_seconds + timings.prepare_seconds
+                }
+                _ => return None,
+            };
+
+            Some((index, bundle, seconds_to_airborne))
+        })
+        .min_by(|(_, a, a_seconds), (_, b, b_seconds)| {
+            a_seconds
+                .partial_cmp(b_seconds)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| {
+                    b.health
+                        .fraction()
+                        .partial_cmp(&a.health.fraction())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+        })
+        .map(|(index, bundle, seconds_to_airborne)| ScrambleCandidate {
+            index,
+            id: bundle.id,
+            seconds_to_airborne,
+        })
+}
+
+/// Sends the best available unit against `combat_type`, queueing every step up to take-off so
+/// it goes on by itself as each one finishes. Does nothing if no unit is available.
+pub fn scramble(
+    units: &mut [UnitBundle],
+    combat_type: CombatType,
+    timings: &UnitTimings,
+    parking_spaces: &mut TokenPool<ParkingSpace>,
+    events: &mut Vec<GameEvent>,
+) {
+    let candidate = match scramble_candidate(units, combat_type, timings, parking_spaces) {
+        Some(candidate) => candidate,
+        None => return,
+    };
+    log::debug!(
+        "Scrambling unit {} against {:?}, airborne in {:.1}s",
+        candidate.id,
+        combat_type,
+        candidate.seconds_to_airborne
+    );
+
+    let bundle = &mut units[candidate.index];
+    if let Unit::InStorage = bundle.unit {
+        bundle.orders.push_back(UnitAction::UnStore);
+    }
+    if !matches!(bundle.unit, Unit::ParkedReady(_, _)) {
+        bundle.orders.push_back(UnitAction::Prepare(combat_type));
+    }
+    bundle.orders.push_back(UnitAction::TakeOff);
+    bundle.follow_orders(timings, parking_spaces, events);
+}
+
+/// Tells enemies apart for as long as they live, counting up from 1 in spawn order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnemyId(pub u32);
+
+pub struct Enemy {
+    pub(crate) id: EnemyId,
+    pub(crate) progress: Timer,
+    pub(crate) combat_type: CombatType,
+    pub(crate) is_boss: bool,
 }
 
 impl Enemy {
-    fn new(run_time: Duration, combat_type: CombatType) -> Self {
+    fn new(id: EnemyId, run_time: Duration, combat_type: CombatType, is_boss: bool) -> Self {
         Self {
+            id,
             progress: Timer::new(run_time, false),
             combat_type,
+            is_boss,
         }
     }
 
@@ -233,38 +649,217 @@ impl Enemy {
     }
 }
 
-pub fn repair_tick(time: &Time, units: &mut [UnitBundle]) {
-    for UnitBundle(unit, health) in units.iter_mut() {
+pub fn repair_tick(
+    time: &Time,
+    timings: &UnitTimings,
+    units: &mut [UnitBundle],
+    events: &mut Vec<GameEvent>,
+) {
+    for UnitBundle {
+        id, unit, health, ..
+    } in units.iter_mut()
+    {
         if matches!(unit, Unit::InStorage) {
-            health.repair_tick(&time);
+            let was_damaged = health.fraction() < 1.0;
+            health.repair_tick(time, timings);
+
+            if was_damaged && health.fraction() >= 1.0 {
+                events.push(GameEvent::UnitRepaired { unit: *id });
+            }
         }
     }
 }
 
-pub fn init_stuff(units: &mut Vec<UnitBundle>) {
-    for _ in 0..8 {
-        units.push(UnitBundle(Unit::InStorage, Health::default()));
+/// What is going on in one lane, for the threat overview.
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaneSummary {
+    pub(crate) combat_type: CombatType,
+    pub(crate) inbound: usize,
+    /// Seconds until the closest enemy reaches the base, if there is one.
+    pub(crate) soonest_eta: Option<f32>,
+    /// How far along the lane the closest enemy is, from 0 at the spawn to 1 at the base.
+    pub(crate) furthest_progress: Option<f32>,
+    pub(crate) airborne: usize,
+    /// Units parked for this lane, whether still preparing or ready to take off.
+    pub(crate) prepared: usize,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl LaneSummary {
+    fn new(combat_type: CombatType) -> Self {
+        Self {
+            combat_type,
+            inbound: 0,
+            soonest_eta: None,
+            furthest_progress: None,
+            airborne: 0,
+            prepared: 0,
+        }
+    }
+
+    /// Enemies further along the lane than this are about to hit the base.
+    const BREACHING_PROGRESS: f32 = 0.8;
+
+    /// More enemies are coming than there are units to meet them.
+    pub(crate) fn is_uncovered(&self) -> bool {
+        self.inbound > self.airborne + self.prepared
+    }
+
+    /// Only units already out in the lane count as cover here, since prepared ones may not
+    /// take off in time.
+    pub(crate) fn danger_level(&self) -> DangerLevel {
+        if self
+            .furthest_progress
+            .is_some_and(|progress| progress > Self::BREACHING_PROGRESS)
+        {
+            DangerLevel::Breaching
+        } else if self.inbound > self.airborne {
+            DangerLevel::Outnumbered
+        } else if self.inbound > 0 {
+            DangerLevel::Covered
+        } else {
+            DangerLevel::Clear
+        }
     }
 }
 
-pub fn units_meet_enemies(units: &mut Vec<UnitBundle>, enemies: &mut Vec<Enemy>) {
-    enemies.retain(|enemy| {
-        let mut hit = false;
-        units.retain_mut(|UnitBundle(unit, health)| {
-            if !matches!(*unit,
-                Unit::Patrolling(_, combat_type) if combat_type == enemy.combat_type
-            ) {
-                return true;
-            }
+/// How worried the player should be about a lane, from least to most.
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DangerLevel {
+    /// No enemies in the lane.
+    Clear,
+    /// There are enemies, but at least as many units patrolling the lane.
+    Covered,
+    /// More enemies than units patrolling the lane.
+    Outnumbered,
+    /// An enemy is close to hitting the base.
+    Breaching,
+}
+
+/// How far the enemy closest to the base is down its lane, from 0 with no enemies around to 1
+/// when it is about to hit.
+#[cfg(feature = "backend-eframe")]
+pub fn nearest_threat(enemies: &[Enemy]) -> f32 {
+    enemies
+        .iter()
+        .map(|enemy| enemy.progress.percent())
+        .fold(0.0, f32::max)
+}
+
+#[cfg(feature = "backend-eframe")]
+pub fn lane_summary(units: &[UnitBundle], enemies: &[Enemy]) -> [LaneSummary; 4] {
+    let mut summaries = [
+        LaneSummary::new(CombatType::A),
+        LaneSummary::new(CombatType::B),
+        LaneSummary::new(CombatType::C),
+        LaneSummary::new(CombatType::D),
+    ];
+
+    for summary in summaries.iter_mut() {
+        let combat_type = summary.combat_type;
+
+        for enemy in enemies
+            .iter()
+            .filter(|enemy| enemy.combat_type == combat_type)
+        {
+            let eta = enemy.progress.remaining_seconds();
+            let progress = enemy.progress.percent();
+            summary.inbound += 1;
+            summary.soonest_eta = Some(summary.soonest_eta.map_or(eta, |soonest| soonest.min(eta)));
+            summary.furthest_progress = Some(
+                summary
+                    .furthest_progress
+                    .map_or(progress, |furthest| furthest.max(progress)),
+            );
+        }
 
-            if unit.progress_percent() >= enemy.remaining_percent() {
-                unit.return_to_base();
-                hit = true;
-                return health.take_hit();
+        for UnitBundle { unit, .. } in units {
+            match unit {
+                Unit::Patrolling(_, unit_combat_type) if *unit_combat_type == combat_type => {
+                    summary.airborne += 1;
+                }
+                Unit::ParkedPreparing(_, _, unit_combat_type)
+                | Unit::ParkedReady(_, unit_combat_type)
+                    if *unit_combat_type == combat_type =>
+                {
+                    summary.prepared += 1;
+                }
+                _ => {}
             }
+        }
+    }
+
+    summaries
+}
+
+/// Parks every unit that is waiting to, for as long as there are free parking spaces.
+pub fn auto_park(
+    units: &mut [UnitBundle],
+    timings: &UnitTimings,
+    parking_spaces: &mut TokenPool<ParkingSpace>,
+    events: &mut Vec<GameEvent>,
+) {
+    for unit in units.iter_mut() {
+        unit.perform(UnitAction::Park, timings, parking_spaces, events);
+    }
+}
 
-            true
+pub fn init_stuff(units: &mut Vec<UnitBundle>, config: &GameConfig) {
+    for id in 1..=config.units.starting_units {
+        units.push(UnitBundle {
+            id: UnitId(id),
+            unit: Unit::InStorage,
+            health: Health::default(),
+            orders: VecDeque::new(),
         });
+    }
+}
+
+pub fn units_meet_enemies(
+    units: &mut Vec<UnitBundle>,
+    enemies: &mut Vec<Enemy>,
+    damage_per_hit: f64,
+    events: &mut Vec<GameEvent>,
+) {
+    enemies.retain(|enemy| {
+        let mut hit = false;
+        units.retain_mut(
+            |UnitBundle {
+                 id, unit, health, ..
+             }| {
+                if !matches!(*unit,
+                    Unit::Patrolling(_, combat_type) if combat_type == enemy.combat_type
+                ) {
+                    return true;
+                }
+
+                if unit.progress_percent() >= enemy.remaining_percent() {
+                    unit.return_to_base();
+                    hit = true;
+                    events.push(GameEvent::EnemyIntercepted {
+                        unit: *id,
+                        combat_type: enemy.combat_type,
+                        is_boss: enemy.is_boss,
+                    });
+
+                    let survived = health.take_hit(damage_per_hit);
+                    events.push(if survived {
+                        GameEvent::UnitDamaged {
+                            unit: *id,
+                            health: *health,
+                            critical: health.is_critical(damage_per_hit),
+                        }
+                    } else {
+                        GameEvent::UnitDestroyed { unit: *id }
+                    });
+                    return survived;
+                }
+
+                true
+            },
+        );
 
         !hit
     });
@@ -273,58 +868,405 @@ pub fn units_meet_enemies(units: &mut Vec<UnitBundle>, enemies: &mut Vec<Enemy>)
 pub struct EnemySpawner {
     time_to_next_spawn: Timer,
     mean_time_between_enemies: Duration,
+    enemies_spawned: usize,
+    difficulty: Difficulty,
+    mode: GameMode,
+    /// The New Game+ level of the run, 0 for an ordinary one.
+    new_game_plus: u32,
+    /// The balance the run started with.
+    config: GameConfig,
+    /// What the random numbers for this run were seeded with, so a run can be told apart from
+    /// another in bug reports.
+    seed: u64,
+    /// The same generator `StdRng` is, but this one can tell how far it has got, so a saved
+    /// run carries on with the same enemies.
+    rng: ChaCha12Rng,
 }
 
 impl Default for EnemySpawner {
     fn default() -> Self {
-        let initial_mean_time_between_enemies = Duration::from_secs_f64(30.0);
+        Self::new(
+            Difficulty::default(),
+            GameMode::default(),
+            0,
+            &GameConfig::default(),
+        )
+    }
+}
 
-        let time_to_first_enemy = Self::new_time_to_next_spawn(initial_mean_time_between_enemies);
+impl EnemySpawner {
+    pub fn new(
+        difficulty: Difficulty,
+        mode: GameMode,
+        new_game_plus: u32,
+        config: &GameConfig,
+    ) -> Self {
+        Self::with_seed(difficulty, mode, new_game_plus, rand::random(), config)
+    }
+
+    /// A spawner that sends exactly the same enemies as any other with the same seed, mode, New
+    /// Game+ level and balance.
+    pub fn with_seed(
+        difficulty: Difficulty,
+        mode: GameMode,
+        new_game_plus: u32,
+        seed: u64,
+        config: &GameConfig,
+    ) -> Self {
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let initial_mean_time_between_enemies = match mode.contract() {
+            Some(contract) => {
+                contract_mean_time_between_enemies(config, difficulty, new_game_plus, contract, 0.0)
+            }
+            None => Duration::from_secs_f64(
+                config
+                    .difficulty(difficulty)
+                    .initial_seconds_between_enemies
+                    * config.new_game_plus_pace(new_game_plus),
+            ),
+        };
+
+        let time_to_first_enemy = Self::new_time_to_next_spawn(
+            &config.enemies,
+            &mut rng,
+            initial_mean_time_between_enemies,
+        );
 
         Self {
             time_to_next_spawn: Timer::new(time_to_first_enemy, false),
             mean_time_between_enemies: initial_mean_time_between_enemies,
+            enemies_spawned: 0,
+            difficulty,
+            mode,
+            new_game_plus,
+            config: config.clone(),
+            seed,
+            rng,
         }
     }
-}
 
-impl EnemySpawner {
-    fn new_time_to_next_spawn(mean_time_between_enemies: Duration) -> Duration {
-        const SPREAD: f64 = 5.0;
-        let normal = Normal::new(mean_time_between_enemies.as_secs_f64(), SPREAD).unwrap();
-
-        let seconds_to_next_spawn = normal.sample(&mut rand::thread_rng()).clamp(1.0, 10.0);
+    fn new_time_to_next_spawn(
+        config: &EnemyConfig,
+        rng: &mut ChaCha12Rng,
+        mean_time_between_enemies: Duration,
+    ) -> Duration {
+        let normal = Normal::new(
+            mean_time_between_enemies.as_secs_f64(),
+            config.spread_seconds,
+        )
+        .unwrap();
+
+        let seconds_to_next_spawn = normal.sample(rng).clamp(
+            config.min_seconds_between_enemies,
+            config.max_seconds_between_enemies,
+        );
         Duration::from_secs_f64(seconds_to_next_spawn)
     }
 
-    fn tick(&mut self, time: &Time, enemies: &mut Vec<Enemy>) {
+    /// Seconds until the next enemy shows up.
+    #[cfg(feature = "backend-eframe")]
+    pub fn next_spawn_eta(&self) -> f32 {
+        self.time_to_next_spawn.remaining_seconds()
+    }
+
+    /// The average wait between two enemies at the moment, before the random spread.
+    #[cfg(feature = "backend-eframe")]
+    pub fn mean_seconds_between_enemies(&self) -> f64 {
+        self.mean_time_between_enemies.as_secs_f64()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn new_game_plus(&self) -> u32 {
+        self.new_game_plus
+    }
+
+    pub fn snapshot(&self) -> SpawnerSnapshot {
+        SpawnerSnapshot {
+            time_to_next_spawn: self.time_to_next_spawn.clone(),
+            mean_time_between_enemies: self.mean_time_between_enemies,
+            enemies_spawned: self.enemies_spawned,
+            difficulty: self.difficulty,
+            mode: self.mode,
+            new_game_plus: self.new_game_plus,
+            seed: self.seed,
+            rng_word_pos: self.rng.get_word_pos(),
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn from_snapshot(snapshot: SpawnerSnapshot, config: &GameConfig) -> Self {
+        let mut rng = ChaCha12Rng::seed_from_u64(snapshot.seed);
+        rng.set_word_pos(snapshot.rng_word_pos);
+
+        Self {
+            time_to_next_spawn: snapshot.time_to_next_spawn,
+            mean_time_between_enemies: snapshot.mean_time_between_enemies,
+            enemies_spawned: snapshot.enemies_spawned,
+            difficulty: snapshot.difficulty,
+            mode: snapshot.mode,
+            new_game_plus: snapshot.new_game_plus,
+            config: config.clone(),
+            seed: snapshot.seed,
+            rng,
+        }
+    }
+
+    /// Makes enemies come every `seconds` on average from now on, drawing the wait for the next
+    /// one again. An endless run keeps speeding up from there; a contract goes back to its
+    /// schedule with the next enemy. Only the sandbox's cheats do this.
+    pub(crate) fn set_mean_seconds_between_enemies(&mut self, seconds: f64) {
+        self.mean_time_between_enemies = Duration::from_secs_f64(seconds);
+        let time_to_next_spawn = Self::new_time_to_next_spawn(
+            &self.config.enemies,
+            &mut self.rng,
+            self.mean_time_between_enemies,
+        );
+        self.time_to_next_spawn.set_duration(time_to_next_spawn);
+        self.time_to_next_spawn.reset();
+    }
+
+    /// Sends an enemy of `combat_type` on top of the spawner's own, with `percent_left` of its
+    /// lane still ahead of it. It counts like any other, so it is a boss if it is a boss's turn.
+    /// Only the sandbox's cheats do this.
+    pub(crate) fn spawn_extra(
+        &mut self,
+        combat_type: CombatType,
+        percent_left: f32,
+        enemies: &mut Vec<Enemy>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        self.send(combat_type, enemies, events);
+        if let Some(enemy) = enemies.last_mut() {
+            let run_time = enemy.progress.duration();
+            let percent_left = f64::from(percent_left.clamp(0.0, 1.0));
+            enemy
+                .progress
+                .set_elapsed(run_time.mul_f64(1.0 - percent_left));
+        }
+    }
+
+    /// Sends the next enemy down its lane, a boss every so often.
+    fn send(
+        &mut self,
+        combat_type: CombatType,
+        enemies: &mut Vec<Enemy>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        let is_boss =
+            (self.enemies_spawned + 1).is_multiple_of(self.config.enemies.enemies_per_boss);
+        self.send_scripted(combat_type, is_boss, enemies, events);
+    }
+
+    /// Sends an enemy of `combat_type` down its lane, a boss only if `is_boss`, as a scenario's
+    /// script says. It counts like any other.
+    pub(crate) fn send_scripted(
+        &mut self,
+        combat_type: CombatType,
+        is_boss: bool,
+        enemies: &mut Vec<Enemy>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        self.enemies_spawned += 1;
+        enemies.push(Enemy::new(
+            EnemyId(self.enemies_spawned as u32),
+            Duration::from_secs_f64(self.config.difficulty(self.difficulty).enemy_run_seconds),
+            combat_type,
+            is_boss,
+        ));
+        events.push(GameEvent::EnemySpawned {
+            combat_type,
+            is_boss,
+        });
+    }
+
+    /// How hard the spawner is pushing, from 0 while enemies come as slowly as they ever do to 1
+    /// once they come as fast as they ever will.
+    #[cfg(feature = "backend-eframe")]
+    pub fn current_pressure(&self) -> f32 {
+        let min = self.config.enemies.min_seconds_between_enemies;
+        let max = self.config.enemies.max_seconds_between_enemies;
+        let mean = self.mean_time_between_enemies.as_secs_f64().clamp(min, max);
+        ((max - mean) / (max - min)) as f32
+    }
+
+    fn tick(
+        &mut self,
+        time: &Time,
+        play_time: &PlayTime,
+        enemies: &mut Vec<Enemy>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        let contract = self.mode.contract();
+        // Nothing comes once the contract is fulfilled.
+        if contract.is_some_and(|contract| play_time.seconds() >= contract.duration_seconds) {
+            return;
+        }
+
         self.time_to_next_spawn.tick(time.delta());
 
         if self.time_to_next_spawn.finished() {
-            enemies.push(Enemy::new(
-                Duration::from_secs_f64(30.0),
-                CombatType::generate_random(),
-            ));
-
-            self.mean_time_between_enemies = self.mean_time_between_enemies.mul_f64(0.97);
-            let time_to_next_spawn = Self::new_time_to_next_spawn(self.mean_time_between_enemies);
+            let combat_type: CombatType = self.rng.gen();
+            self.send(combat_type, enemies, events);
+
+            self.mean_time_between_enemies = match contract {
+                Some(contract) => contract_mean_time_between_enemies(
+                    &self.config,
+                    self.difficulty,
+                    self.new_game_plus,
+                    contract,
+                    play_time.seconds(),
+                ),
+                None => self
+                    .mean_time_between_enemies
+                    .mul_f64(self.config.difficulty(self.difficulty).spawn_acceleration),
+            };
+            let time_to_next_spawn = Self::new_time_to_next_spawn(
+                &self.config.enemies,
+                &mut self.rng,
+                self.mean_time_between_enemies,
+            );
             self.time_to_next_spawn.set_duration(time_to_next_spawn);
             self.time_to_next_spawn.reset();
         }
     }
 }
 
-pub fn spawn_enemies(enemy_spawner: &mut EnemySpawner, time: &Time, enemies: &mut Vec<Enemy>) {
-    enemy_spawner.tick(&time, enemies);
+/// The average wait between two enemies `seconds` into a contract. The contract's schedule is for
+/// Normal, and stretched or squeezed like the start of an endless run for the other difficulties,
+/// then squeezed some more for every New Game+ level.
+fn contract_mean_time_between_enemies(
+    config: &GameConfig,
+    difficulty: Difficulty,
+    new_game_plus: u32,
+    contract: &ContractConfig,
+    seconds: f64,
+) -> Duration {
+    let pace = config
+        .difficulty(difficulty)
+        .initial_seconds_between_enemies
+        / config.normal.initial_seconds_between_enemies
+        * config.new_game_plus_pace(new_game_plus);
+    Duration::from_secs_f64(contract.mean_seconds_between_enemies(seconds) * pace)
+}
+
+/// Everything about a spawner that a saved run needs to carry on where it left off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpawnerSnapshot {
+    time_to_next_spawn: Timer,
+    mean_time_between_enemies: Duration,
+    enemies_spawned: usize,
+    difficulty: Difficulty,
+    /// Saves from before there were modes are endless runs.
+    #[serde(default)]
+    mode: GameMode,
+    /// Saves from before New Game+ are of ordinary runs.
+    #[serde(default)]
+    new_game_plus: u32,
+    seed: u64,
+    /// How many words the random number generator had handed out.
+    rng_word_pos: u128,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl SpawnerSnapshot {
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+}
+
+pub fn spawn_enemies(
+    enemy_spawner: &mut EnemySpawner,
+    time: &Time,
+    play_time: &PlayTime,
+    enemies: &mut Vec<Enemy>,
+    events: &mut Vec<GameEvent>,
+) {
+    enemy_spawner.tick(time, play_time, enemies, events);
+}
+
+/// How many units are in each kind of state and how many enemies are inbound.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Census {
+    pub stored: usize,
+    /// On their way into or out of storage, or into a parking space.
+    pub moving: usize,
+    pub parked: usize,
+    pub airborne: usize,
+    pub waiting: usize,
+    pub enemies: usize,
+    pub bosses: usize,
+}
+
+pub fn census(units: &[UnitBundle], enemies: &[Enemy]) -> Census {
+    let mut census = Census::default();
+
+    for UnitBundle { unit, .. } in units {
+        let count = match unit {
+            Unit::InStorage => &mut census.stored,
+            Unit::Storing(_) | Unit::UnStoring(_, _) | Unit::Parking(_, _) => &mut census.moving,
+            Unit::ParkedUnready(_) | Unit::ParkedPreparing(_, _, _) | Unit::ParkedReady(_, _) => {
+                &mut census.parked
+            }
+            Unit::Patrolling(_, _) | Unit::Returning(_, _) => &mut census.airborne,
+            Unit::WaitingToPark => &mut census.waiting,
+        };
+        *count += 1;
+    }
+
+    census.enemies = enemies.len();
+    census.bosses = enemies.iter().filter(|enemy| enemy.is_boss).count();
+
+    census
 }
 
 #[derive(Debug, Clone)]
 pub struct ParkingSpace {}
 
-type Token<T> = Arc<PhantomData<T>>;
+/// A claim on one slot of a [`TokenPool`]. The slot is free again once the token and all its
+/// clones are dropped.
+pub struct Token<T> {
+    slot: usize,
+    holder: Arc<PhantomData<T>>,
+}
+
+impl<T> Token<T> {
+    /// Which of the pool's slots this is, counting from 0.
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+}
+
+// Derived impls would needlessly require `T: Clone + Debug`.
+impl<T> Clone for Token<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot,
+            holder: self.holder.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Token<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Token").field("slot", &self.slot).finish()
+    }
+}
 
 pub struct TokenPool<T> {
-    token_holder: Arc<PhantomData<T>>,
+    /// One handle per slot; a slot is taken while any token shares its handle. Slots beyond
+    /// `max_count` are only kept for the tokens still holding them.
+    slots: Vec<Arc<PhantomData<T>>>,
     max_count: usize,
 }
 
@@ -337,303 +1279,516 @@ impl<T> Default for TokenPool<T> {
 impl<T> TokenPool<T> {
     pub fn new(initial_count: usize) -> Self {
         Self {
-            token_holder: Arc::new(PhantomData),
+            slots: (0..initial_count).map(|_| Arc::new(PhantomData)).collect(),
             max_count: initial_count,
         }
     }
 
+    /// Takes the first free slot.
     pub fn try_take(&mut self) -> Option<Token<T>> {
-        if !self.can_take() {
+        let slot = (0..self.max_count).find(|&slot| self.is_free(slot))?;
+        self.try_take_slot(slot)
+    }
+
+    pub fn try_take_slot(&mut self, slot: usize) -> Option<Token<T>> {
+        if !self.is_free(slot) {
             return None;
         }
 
-        Some(self.token_holder.clone())
+        Some(Token {
+            slot,
+            holder: self.slots[slot].clone(),
+        })
     }
 
     pub fn can_take(&self) -> bool {
-        Arc::strong_count(&self.token_holder) < self.max_count + 1
+        (0..self.max_count).any(|slot| self.is_free(slot))
+    }
+
+    /// Whether a token for the slot is out, even if the slot is no longer within capacity.
+    pub fn is_held(&self, slot: usize) -> bool {
+        // The pool's own handle doesn't count.
+        self.slots
+            .get(slot)
+            .is_some_and(|holder| Arc::strong_count(holder) > 1)
+    }
+
+    pub fn is_free(&self, slot: usize) -> bool {
+        slot < self.max_count && !self.is_held(slot)
     }
 
     pub fn slots_used(&self) -> usize {
-        Arc::strong_count(&self.token_holder) - 1
+        (0..self.slots.len())
+            .filter(|&slot| self.is_held(slot))
+            .count()
     }
-}
 
-pub fn ticker(
-    time: &Time,
-    units: &mut [UnitBundle],
-    enemies: &mut [Enemy],
-    game_state: &mut GameState,
-    play_time: &mut PlayTime,
-) {
-    for UnitBundle(unit, _) in units.iter_mut() {
-        unit.tick(&time);
+    /// How many slots there are to show: all within capacity, plus any beyond it that are
+    /// still held.
+    pub fn slot_count(&self) -> usize {
+        let last_held = (0..self.slots.len()).rev().find(|&slot| self.is_held(slot));
+        last_held.map_or(0, |slot| slot + 1).max(self.max_count)
     }
 
-    for mut enemy in enemies.iter_mut() {
-        enemy.tick(&time);
-        if enemy.progress.finished() {
-            *game_state = GameState::GameOver;
+    /// A token for the slot, even one beyond capacity, for restoring the tokens that were out
+    /// when a run was saved. Fails if the slot is held already, since no two units share one.
+    #[cfg(feature = "backend-eframe")]
+    pub fn restore_slot(&mut self, slot: usize) -> Option<Token<T>> {
+        if self.is_held(slot) {
+            return None;
+        }
+
+        while self.slots.len() <= slot {
+            self.slots.push(Arc::new(PhantomData));
         }
+        Some(Token {
+            slot,
+            holder: self.slots[slot].clone(),
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.max_count
     }
 
-    play_time.tick(&time);
+    /// Changes the number of slots. Tokens already handed out stay valid, so the pool may
+    /// temporarily be over capacity until enough of them are returned.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        while self.slots.len() < capacity {
+            self.slots.push(Arc::new(PhantomData));
+        }
+        self.max_count = capacity;
+    }
 }
 
-pub fn gui(
-    egui_ctx: &CtxRef,
+/// What a step of the run goes by without changing it.
+pub struct StepContext<'a> {
+    pub time: &'a Time,
+    pub timings: &'a UnitTimings,
+    pub mode: GameMode,
+    /// Enemies reaching the base don't end the run, as the sandbox can ask for.
+    pub invincible: bool,
+}
+
+pub fn ticker(
+    context: &StepContext<'_>,
     units: &mut [UnitBundle],
-    enemies: &mut [Enemy],
+    enemies: &mut Vec<Enemy>,
     parking_spaces: &mut TokenPool<ParkingSpace>,
-    game_state: &GameState,
-    play_time: &PlayTime,
+    game_state: &mut GameState,
+    play_time: &mut PlayTime,
+    events: &mut Vec<GameEvent>,
 ) {
-    // web_sys::console::log_1(&"Gui!".into());
-    let dark_purple = Color32::from_rgb(77, 53, 77).linear_multiply(0.25);
-
-    let mut visuals = Visuals::dark();
+    let StepContext {
+        time,
+        timings,
+        mode,
+        invincible,
+    } = *context;
+    for bundle in units.iter_mut() {
+        let UnitBundle { id, unit, .. } = bundle;
+        let was_airborne = matches!(unit, Unit::Patrolling(_, _) | Unit::Returning(_, _));
+        let was_parking = matches!(unit, Unit::UnStoring(_, _) | Unit::Parking(_, _));
+        let was_preparing = matches!(unit, Unit::ParkedPreparing(_, _, _));
+        unit.tick(time);
+
+        if was_airborne && matches!(unit, Unit::WaitingToPark) {
+            events.push(GameEvent::SortieCompleted { unit: *id });
+        }
+        if was_parking && matches!(unit, Unit::ParkedUnready(_)) {
+            events.push(GameEvent::UnitParked { unit: *id });
+        }
+        if let (true, Unit::ParkedReady(_, combat_type)) = (was_preparing, &unit) {
+            events.push(GameEvent::UnitReady {
+                unit: *id,
+                combat_type: *combat_type,
+            });
+        }
 
-    visuals.extreme_bg_color = dark_purple;
-    visuals.widgets.noninteractive.bg_fill = dark_purple;
+        bundle.follow_orders(timings, parking_spaces, events);
+    }
 
-    egui_ctx.set_visuals(visuals);
+    enemies.retain_mut(|enemy| {
+        enemy.tick(time);
+        if !enemy.progress.finished() {
+            return true;
+        }
 
-    egui::TopPanel::top("top_panel").show(egui_ctx, |ui| {
-        // The top panel is often a good place for a menu bar:
-        egui::menu::bar(ui, |ui| {
-            egui::menu::menu(ui, "File", |ui| {
-                if ui.button("Quit").clicked() {
-                    std::process::exit(0);
-                }
+        if enemy.is_boss && parking_spaces.capacity() > 0 {
+            boss_leaked(parking_spaces);
+            events.push(GameEvent::BossLeaked {
+                combat_type: enemy.combat_type,
+                parking_spaces_left: parking_spaces.capacity(),
             });
+        }
+
+        events.push(GameEvent::BaseHit {
+            report: DefeatReport::new(enemy, play_time.seconds(), units, timings),
         });
+        // The sandbox can keep the run going, for as long as it takes to try something out.
+        if invincible {
+            return false;
+        }
+        *game_state = GameState::GameOver;
+        true
     });
 
-    egui::CentralPanel::default().show(egui_ctx, |ui| {
-        if *game_state == GameState::GameOver {
-            ui.set_enabled(false);
+    play_time.tick(time);
+
+    if let Some(contract) = mode.contract() {
+        if *game_state == GameState::Running && play_time.seconds() >= contract.duration_seconds {
+            *game_state = GameState::Victory;
         }
+    }
+}
 
-        ui.heading(format!(
-            "You have survived for {:.0} seconds so far!",
-            play_time.0.as_secs_f64()
-        ));
-        ui.label("Ze evil people from ze Meatropolis wiz zeir Queen on zat island in ze sea are \
-            invading our great country of Fruitopia! \
-            
-            Zey vant to cut down our precious orchards to make ze trees into zeir wretched sawdust sausages!\n\
-            Ze Kaiser has ordered YOU to run ze main defense operation agenst ze Meatropolitans. \
-            Zey are stronk and REALLY vant zose orchards. Zey vill come faster and faster.\n\
-            
-            Hold zem off for as long as you can and ve vill propose you for ze Eiserne Pflaume medal!");
-        egui::warn_if_debug_build(ui);
-
-        ui.separator();
-        ui.separator();
-
-        ui.heading("Your Base");
-        ui.separator();
-        ui.heading("Stored Units");
-        ui.label("Repair damaged units here.");
-
-        for UnitBundle(unit, health) in units.iter_mut() {
-            match unit {
-                Unit::InStorage => {
-                    ui.horizontal(|ui| {
-                        ui.label(format!("Health: {}. Unit", health));
-                        if !parking_spaces.can_take() {
-                            ui.set_enabled(false);
-                        }
+/// A boss that reaches the base wrecks one of the parking spaces as it ends the game.
+fn boss_leaked(parking_spaces: &mut TokenPool<ParkingSpace>) {
+    let capacity = parking_spaces.capacity();
+    parking_spaces.set_capacity(capacity.saturating_sub(1));
+}
 
-                        if ui.button("Bring out of storage").clicked() {
-                            unit.un_store(parking_spaces);
-                        }
-                    });
-                }
-                Unit::Storing(timer) => {
-                    ui.label(format!(
-                        "Health: {}. Moving into Storage. {:.0}% / {:.1} seconds to go.",
-                        health,
-                        timer.percent() * 100.0,
-                        (timer.duration() - timer.elapsed()).as_secs_f64()
-                    ));
-                }
-                _ => {}
-            }
+/// Units and enemies built by hand, for tests.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+
+    /// A unit at full health with no orders.
+    pub(crate) fn unit(id: u32, unit: Unit) -> UnitBundle {
+        UnitBundle {
+            id: UnitId(id),
+            unit,
+            health: Health::default(),
+            orders: VecDeque::new(),
         }
-        ui.separator();
-        ui.heading(format!(
-            "Parking Area ({}/{} spaces used)",
-            parking_spaces.slots_used(),
-            parking_spaces.max_count
-        ));
-        ui.label("Prepare your units for battle in one of the lanes and send them off to fight here!");
-        for UnitBundle(unit, health) in units.iter_mut() {
-            match &unit {
-                Unit::UnStoring(timer, _) => {
-                    ui.label(format!(
-                        "Health: {}. Coming out of storage. {:.0}% / {:.1} seconds to go.",
-                        health,
-                        timer.percent() * 100.0,
-                        (timer.duration() - timer.elapsed()).as_secs_f64()
-                    ));
-                }
-                Unit::Parking(timer, _) => {
-                    ui.label(format!(
-                        "Health: {}. Parking. {:.0}% / {:.1} seconds to go.",
-                        health,
-                        timer.percent() * 100.0,
-                        (timer.duration() - timer.elapsed()).as_secs_f64()
-                    ));
-                }
-                Unit::ParkedUnready(_) => {
-                    let mut selected_combat_type = None;
-                    let mut storage_requested = false;
-                    ui.horizontal(|ui| {
-                        ui.label(format!("Health: {}. Unit not ready. Prepare for... ", health));
-                        for combat_type in CombatType::iter() {
-                            if ui.button(format!("... {}", combat_type.to_string())).clicked() {
-                                selected_combat_type = Some(combat_type);
-                            }
-                        }
-                        storage_requested = ui.button("Move into storage").clicked();
-                    });
+    }
 
-                    if let Some(combat_type) = selected_combat_type {
-                        unit.prepare(combat_type);
-                    } else if storage_requested {
-                        unit.move_into_storage();
-                    }
-                }
-                Unit::ParkedPreparing(timer, _, combat_type) => {
-                    ui.label(format!(
-                        "Health: {}. Preparing combat type {}. {:.0}% / {:.1} seconds to go.",
-                        health,
-                        combat_type,
-                        timer.percent() * 100.0,
-                        (timer.duration() - timer.elapsed()).as_secs_f64()
-                    ));
-                }
-                Unit::ParkedReady(_, combat_type) => {
-                    let take_off_clicked = ui.horizontal(|ui| {
-                        ui.label(format!(
-                            "Health: {}. Ready for combat type {}.",
-                            health, combat_type
-                        ));
-                        ui.button("Take off!").clicked()
-                    });
+    /// An enemy `elapsed_seconds` into a lane it crosses in `run_seconds`.
+    pub(crate) fn enemy(
+        id: u32,
+        combat_type: CombatType,
+        run_seconds: f64,
+        elapsed_seconds: f64,
+    ) -> Enemy {
+        let mut enemy = Enemy::new(
+            EnemyId(id),
+            Duration::from_secs_f64(run_seconds),
+            combat_type,
+            false,
+        );
+        enemy
+            .progress
+            .set_elapsed(Duration::from_secs_f64(elapsed_seconds));
+        enemy
+    }
 
-                    if take_off_clicked.inner {
-                        unit.take_off();
-                    }
-                }
-                _ => {}
-            }
+    /// Quick, round timings for units in tests.
+    pub(crate) const TIMINGS: UnitTimings = UnitTimings {
+        seconds_to_fully_repair: 10.0,
+        prepare_seconds: 3.0,
+        un_store_seconds: 2.0,
+        store_seconds: 2.0,
+        park_seconds: 1.0,
+        patrol_seconds: 30.0,
+    };
+
+    /// Plays `seconds` of a run with no enemies, in frames of a tenth of a second.
+    pub(crate) fn play(
+        seconds: f64,
+        units: &mut [UnitBundle],
+        parking_spaces: &mut TokenPool<ParkingSpace>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        let mut time = Time::new();
+        let mut game_state = GameState::Running;
+        let mut play_time = PlayTime::default();
+        for _ in 0..(seconds * 10.0).round() as usize {
+            time.advance(Duration::from_secs_f64(0.1));
+            let context = StepContext {
+                time: &time,
+                timings: &TIMINGS,
+                mode: GameMode::default(),
+                invincible: false,
+            };
+            ticker(
+                &context,
+                units,
+                &mut Vec::new(),
+                parking_spaces,
+                &mut game_state,
+                &mut play_time,
+                events,
+            );
         }
-        ui.separator();
-        ui.heading("Waiting to Return");
-        ui.label("Units here are just standing around when they could be fighting or getting repaired! Move them on as quickly as you can!");
-        for UnitBundle(unit, health) in units.iter_mut() {
-            match &unit {
-                Unit::WaitingToPark => {
-                    ui.horizontal(|ui| {
-                        ui.label(format!("Health: {}. Unit", health));
-
-                        if ui.button("Move into storage").clicked() {
-                            unit.move_into_storage();
-                        }
+    }
+}
 
-                        if !parking_spaces.can_take() {
-                            ui.set_enabled(false);
-                        }
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    #[cfg(feature = "backend-eframe")]
+    use crate::game::fixtures::{enemy, unit};
+    use crate::game::fixtures::{play, TIMINGS};
+
+    #[test]
+    fn a_pool_without_capacity_hands_out_nothing() {
+        let mut pool = TokenPool::<ParkingSpace>::new(0);
+        assert!(!pool.can_take());
+        assert!(pool.try_take().is_none());
+        assert!(pool.try_take_slot(0).is_none());
+        assert_eq!(pool.slots_used(), 0);
+        assert_eq!(pool.slot_count(), 0);
+    }
 
-                        if ui.button("Park").clicked() {
-                            unit.park_after_returning(parking_spaces);
-                        }
-                    });
-                }
-                _ => {}
+    #[test]
+    fn a_boss_leak_wrecks_a_held_space_only_once_it_is_left() {
+        let mut pool = TokenPool::<ParkingSpace>::new(2);
+        let first = pool.try_take().unwrap();
+        let second = pool.try_take().unwrap();
+        assert_eq!(second.slot(), 1);
+
+        boss_leaked(&mut pool);
+        assert_eq!(pool.capacity(), 1);
+        assert_eq!(pool.slots_used(), 2);
+        assert_eq!(pool.slot_count(), 2);
+        assert!(!pool.can_take());
+
+        drop(second);
+        assert_eq!(pool.slots_used(), 1);
+        assert_eq!(pool.slot_count(), 1);
+        assert!(pool.try_take_slot(1).is_none());
+
+        drop(first);
+        assert_eq!(pool.try_take().unwrap().slot(), 0);
+    }
+
+    #[test]
+    fn boss_leaks_stop_at_no_capacity() {
+        let mut pool = TokenPool::<ParkingSpace>::new(1);
+        boss_leaked(&mut pool);
+        boss_leaked(&mut pool);
+        assert_eq!(pool.capacity(), 0);
+        assert!(pool.try_take().is_none());
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    #[test]
+    fn a_leaking_boss_wrecks_a_space_and_ends_the_run() {
+        let mut boss = enemy(1, CombatType::B, 10.0, 9.95);
+        boss.is_boss = true;
+        let mut enemies = vec![boss];
+        let mut parking_spaces = TokenPool::<ParkingSpace>::new(3);
+        let mut game_state = GameState::Running;
+        let mut events = Vec::new();
+        let mut time = Time::new();
+        time.advance(Duration::from_secs_f64(0.1));
+        let context = StepContext {
+            time: &time,
+            timings: &TIMINGS,
+            mode: GameMode::default(),
+            invincible: false,
+        };
+
+        ticker(
+            &context,
+            &mut [],
+            &mut enemies,
+            &mut parking_spaces,
+            &mut game_state,
+            &mut PlayTime::default(),
+            &mut events,
+        );
+
+        assert_eq!(parking_spaces.capacity(), 2);
+        assert_eq!(game_state, GameState::GameOver);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::BossLeaked {
+                parking_spaces_left: 2,
+                ..
             }
-        }
-        ui.separator();
-        ui.separator();
+        )));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::BaseHit { .. })));
+    }
 
-        ui.heading("The Battlezone");
-        ui.label("Enemies (red) approach from the right on different lanes. Prepare your units for the \
-        right type of lane and send them off to fight. Each unit (green) can fend off a single enemy before it returns to base (amber). \
-        Your units will wear out with use. Remember to repair them!");
-        ui.separator();
+    #[test]
+    fn health_is_critical_when_one_more_hit_takes_it() {
+        assert!(!Health::new(0.5).is_critical(0.25));
+        assert!(!Health::new(0.25).is_critical(0.25));
+        assert!(Health::new(0.2).is_critical(0.25));
 
-        for combat_type in CombatType::iter() {
-            let enemies = enemies
-                .iter_mut()
-                .filter(|enemy| enemy.combat_type == combat_type);
-
-            ui.horizontal(|ui| {
-                ui.heading(combat_type.to_string());
-                ui.separator();
-                let (response, painter) = ui
-                    .allocate_painter(ui.available_size_before_wrap_finite(), egui::Sense::hover());
-                let rect = response.rect;
-                let y = 0.5 * rect.height() + rect.top();
-
-                for enemy in enemies {
-                    let x = rect.left() + rect.width() * enemy.progress.percent_left();
-                    painter.text(
-                        Pos2 { x, y },
-                        Align2([Align::Min, Align::Center]),
-                        format!("◀ t-{:.1}s", enemy.progress.remaining_seconds()),
-                        TextStyle::Heading,
-                        Color32::RED,
-                    );
-                }
+        let mut health = Health::new(0.2);
+        assert!(!health.take_hit(0.25));
+    }
 
-                for UnitBundle(unit, health) in units.iter_mut() {
-                    match &*unit {
-                        Unit::Patrolling(progress, unit_combat_type)
-                            if *unit_combat_type == combat_type =>
-                        {
-                            let x = rect.left() + rect.width() * progress.percent();
-                            painter.text(
-                                Pos2 { x, y },
-                                Align2([Align::Max, Align::Center]),
-                                format!("{} ▶", health),
-                                TextStyle::Heading,
-                                Color32::GREEN,
-                            );
-                        }
-                        Unit::Returning(progress, unit_combat_type)
-                            if *unit_combat_type == combat_type =>
-                        {
-                            let x = rect.left() + rect.width() * progress.percent();
-                            painter.text(
-                                Pos2 { x, y },
-                                Align2([Align::Max, Align::Center]),
-                                format!("{} ▶", health),
-                                TextStyle::Heading,
-                                Color32::GOLD,
-                            );
-                        }
-                        _ => {}
-                    }
-                }
-            });
-            ui.separator();
-        }
-    });
+    #[cfg(feature = "backend-eframe")]
+    #[test]
+    fn two_enemies_against_one_patrol_leave_the_lane_uncovered() {
+        let units = [
+            unit(
+                1,
+                Unit::Patrolling(Timer::from_seconds(10.0, false), CombatType::A),
+            ),
+            unit(2, Unit::InStorage),
+        ];
+        let enemies = [
+            enemy(1, CombatType::A, 10.0, 6.0),
+            enemy(2, CombatType::A, 10.0, 2.0),
+        ];
+
+        let [a, b, ..] = lane_summary(&units, &enemies);
+        assert_eq!(a.combat_type, CombatType::A);
+        assert_eq!(a.inbound, 2);
+        assert_eq!(a.airborne, 1);
+        assert_eq!(a.prepared, 0);
+        assert_eq!(a.soonest_eta, Some(4.0));
+        assert_eq!(a.furthest_progress, Some(0.6));
+        assert!(a.is_uncovered());
+
+        assert_eq!(b.combat_type, CombatType::B);
+        assert_eq!(b.inbound, 0);
+        assert_eq!(b.airborne, 0);
+        assert_eq!(b.soonest_eta, None);
+        assert!(!b.is_uncovered());
+    }
 
-    if *game_state == GameState::GameOver {
-        egui::Window::new("Hit!")
-            .anchor(Align2::CENTER_CENTER, Vec2::new(0.0,0.0))
-            .show(egui_ctx, |ui| {
-                ui.heading("Your base was hit! You are dead !!!!");
-                ui.label(format!(
-                    "You survived for {:.0} seconds though, which is great! Now take a screenshot and brag to your friends about your m4d sk1llz :-D",
-                    play_time.0.as_secs_f64()
-                ));
-                if ui.button("Thanks man! This was totally fun!! Let me try this again...").clicked() {
-                    std::process::exit(0);
-                };
-            });
+    #[cfg(feature = "backend-eframe")]
+    #[test]
+    fn lanes_are_classified_by_cover_and_by_the_closest_enemy() {
+        let patrol = |id, combat_type| {
+            unit(
+                id,
+                Unit::Patrolling(Timer::from_seconds(10.0, false), combat_type),
+            )
+        };
+        let units = [
+            patrol(1, CombatType::B),
+            patrol(2, CombatType::C),
+            patrol(3, CombatType::D),
+        ];
+        let enemies = [
+            enemy(1, CombatType::B, 10.0, 5.0),
+            enemy(2, CombatType::C, 10.0, 1.0),
+            enemy(3, CombatType::C, 10.0, 2.0),
+            enemy(4, CombatType::D, 10.0, 8.5),
+        ];
+
+        let levels: Vec<_> = lane_summary(&units, &enemies)
+            .iter()
+            .map(LaneSummary::danger_level)
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                DangerLevel::Clear,
+                DangerLevel::Covered,
+                DangerLevel::Outnumbered,
+                DangerLevel::Breaching,
+            ]
+        );
+    }
+
+    #[test]
+    fn a_scramble_from_storage_goes_on_by_itself_until_the_unit_is_airborne() {
+        let mut units = [fixtures::unit(1, Unit::InStorage)];
+        let mut parking_spaces = TokenPool::new(1);
+        let mut events = Vec::new();
+
+        let candidate =
+            scramble_candidate(&units, CombatType::C, &TIMINGS, &parking_spaces).unwrap();
+        assert_eq!(candidate.seconds_to_airborne, 5.0);
+        scramble(
+            &mut units,
+            CombatType::C,
+            &TIMINGS,
+            &mut parking_spaces,
+            &mut events,
+        );
+        assert!(matches!(units[0].unit, Unit::UnStoring(_, _)));
+        // Busy units aren't sent twice.
+        assert!(scramble_candidate(&units, CombatType::C, &TIMINGS, &parking_spaces).is_none());
+
+        play(2.1, &mut units, &mut parking_spaces, &mut events);
+        assert!(matches!(
+            units[0].unit,
+            Unit::ParkedPreparing(_, _, CombatType::C)
+        ));
+        play(3.1, &mut units, &mut parking_spaces, &mut events);
+        assert!(matches!(units[0].unit, Unit::Patrolling(_, CombatType::C)));
+        assert!(units[0].orders.is_empty());
+        assert_eq!(parking_spaces.slots_used(), 0);
+
+        let ordered: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::UnitOrdered { action, .. } => Some(*action),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            ordered,
+            [
+                UnitAction::UnStore,
+                UnitAction::Prepare(CombatType::C),
+                UnitAction::TakeOff
+            ]
+        );
+    }
+
+    #[test]
+    fn a_scramble_without_a_parking_space_drops_its_orders() {
+        let mut parking_spaces = TokenPool::new(1);
+        let mut units = [fixtures::unit(1, Unit::InStorage)];
+        assert!(scramble_candidate(&units, CombatType::A, &TIMINGS, &parking_spaces).is_some());
+        units[0].orders.extend([
+            UnitAction::UnStore,
+            UnitAction::Prepare(CombatType::A),
+            UnitAction::TakeOff,
+        ]);
+        // Somebody else takes the last space before the unit gets to it.
+        let _taken = parking_spaces.try_take().unwrap();
+        let mut events = Vec::new();
+
+        play(0.1, &mut units, &mut parking_spaces, &mut events);
+        assert!(matches!(units[0].unit, Unit::InStorage));
+        assert!(units[0].orders.is_empty());
+        assert!(events.is_empty());
+
+        scramble(
+            &mut units,
+            CombatType::A,
+            &TIMINGS,
+            &mut parking_spaces,
+            &mut events,
+        );
+        assert!(units[0].orders.is_empty());
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn the_seed_decides_the_lanes() {
+        let lanes = |seed| {
+            let mut rng = ChaCha12Rng::seed_from_u64(seed);
+            (0..32).map(|_| rng.gen::<CombatType>()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(lanes(7), lanes(7));
+        assert_ne!(lanes(7), lanes(8));
+        assert!(CombatType::iter().all(|combat_type| lanes(7).contains(&combat_type)));
+    }
+
+    #[test]
+    fn every_action_is_listed_once() {
+        let actions = UnitAction::all();
+        for combat_type in CombatType::iter() {
+            assert!(actions.contains(&UnitAction::Prepare(combat_type)));
+        }
+        for (index, action) in actions.iter().enumerate() {
+            assert!(!actions[..index].contains(action), "{:?} twice", action);
+        }
     }
 }
@@ -0,0 +1,142 @@
+//! The kinds of run to choose from on the main menu. An endless run lasts until the base is hit;
+//! a defense contract is won by holding out for a set time against enemies that come on a
+//! fixed schedule, with a medal for how cleanly it went.
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, Serialize, Deserialize, Default)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    DefenseContract,
+}
+
+impl GameMode {
+    /// What it takes to win a run in this mode, for modes that can be won.
+    pub fn contract(self) -> Option<&'static ContractConfig> {
+        match self {
+            Self::Endless => None,
+            Self::DefenseContract => Some(&DEFENSE_CONTRACT),
+        }
+    }
+}
+
+/// How good a won contract was, worst first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+/// The most a medal allows to go wrong over the contract.
+#[derive(Debug)]
+pub struct MedalThreshold {
+    pub medal: Medal,
+    pub max_units_lost: usize,
+    /// Bosses that broke through and wrecked a parking space.
+    pub max_leaks: usize,
+}
+
+#[derive(Debug)]
+pub struct ContractConfig {
+    /// How long the base has to hold out.
+    pub duration_seconds: f64,
+    /// Seconds into the contract, and the average wait between two enemies on Normal from
+    /// then on. The wait eases from one to the next in between.
+    pub ramp: &'static [(f64, f64)],
+    /// The medals above bronze, best first. Any won contract earns bronze.
+    pub medals: &'static [MedalThreshold],
+}
+
+const DEFENSE_CONTRACT: ContractConfig = ContractConfig {
+    duration_seconds: 5.0 * 60.0,
+    ramp: &[
+        (0.0, 20.0),
+        (60.0, 12.0),
+        (150.0, 7.0),
+        (240.0, 4.0),
+        (300.0, 3.0),
+    ],
+    medals: &[
+        MedalThreshold {
+            medal: Medal::Gold,
+            max_units_lost: 0,
+            max_leaks: 0,
+        },
+        MedalThreshold {
+            medal: Medal::Silver,
+            max_units_lost: 2,
+            max_leaks: 1,
+        },
+    ],
+};
+
+impl ContractConfig {
+    /// The average wait between two enemies on Normal, `seconds` into the contract.
+    pub fn mean_seconds_between_enemies(&self, seconds: f64) -> f64 {
+        let next = self
+            .ramp
+            .iter()
+            .position(|&(from, _)| from > seconds)
+            .unwrap_or(self.ramp.len());
+        let previous = next.checked_sub(1).and_then(|index| self.ramp.get(index));
+        match (previous, self.ramp.get(next)) {
+            (Some(&(from, start)), Some(&(to, end))) => {
+                start + (end - start) * (seconds - from) / (to - from)
+            }
+            (Some(&(_, mean)), None) | (None, Some(&(_, mean))) => mean,
+            (None, None) => 0.0,
+        }
+    }
+
+    /// The medal for a contract won with `units_lost` units lost and `leaks` bosses broken
+    /// through.
+    pub fn medal(&self, units_lost: usize, leaks: usize) -> Medal {
+        self.medals
+            .iter()
+            .find(|threshold| {
+                units_lost <= threshold.max_units_lost && leaks <= threshold.max_leaks
+            })
+            .map_or(Medal::Bronze, |threshold| threshold.medal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        balance::GameConfig,
+        game::{Difficulty, EnemySpawner},
+    };
+
+    #[test]
+    fn every_preset_repairs_and_prepares_as_documented_in_every_mode() {
+        // Seconds to repair from nothing to full, and to get ready for a lane.
+        let presets = [
+            (Difficulty::Easy, 10.0, 3.0),
+            (Difficulty::Normal, 15.0, 5.0),
+            (Difficulty::Hard, 20.0, 7.0),
+        ];
+        assert_eq!(presets.len(), Difficulty::iter().count());
+
+        let config = GameConfig::built_in();
+        for mode in GameMode::iter() {
+            for &(difficulty, repair_seconds, prepare_seconds) in &presets {
+                // A run asks its spawner which preset it is on.
+                let spawner = EnemySpawner::with_seed(difficulty, mode, 0, 1, &config);
+                let timings = config.unit_timings(spawner.difficulty());
+                assert_eq!(
+                    (timings.seconds_to_fully_repair, timings.prepare_seconds),
+                    (repair_seconds, prepare_seconds),
+                    "{:?} in {:?}",
+                    difficulty,
+                    mode
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,3334 @@
+use std::borrow::Cow;
+
+use eframe::{
+    egui,
+    egui::{Align, Align2, Color32, CtxRef, Pos2, Rect, Stroke, TextStyle, TextureId, Vec2},
+    epi,
+};
+use engine::{
+    input::{pressed_keys, ActionState, KeyCode},
+    DecodedTextures,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    achievements::{AchievementId, Achievements, ACHIEVEMENTS},
+    alerts::ThreatAlerts,
+    balance::GameConfig,
+    battle_log::{BattleLog, Severity},
+    cheats::CheatState,
+    commands::GameCommand,
+    controls::{default_bindings, key_commands, KeyCommand, ACTIONS, PAUSE},
+    daily::{self, DailyResults, DailyRun},
+    defeat::{DefeatReport, DefenderState},
+    game::{
+        lane_summary, nearest_threat, scramble_candidate, CombatType, DangerLevel, Difficulty,
+        Enemy, EnemySpawner, GameState, Health, ParkingSpace, PlayTime, TokenPool, Unit,
+        UnitAction, UnitBundle, UnitId, UnitTimings,
+    },
+    game_mode::{GameMode, Medal},
+    gui::{
+        confirm::{ConfirmDialog, ConfirmResponse},
+        sprites::Sprites,
+        strings::{Strings, Text},
+        theme::{lane_name, Theme, PATROLLING_GLYPH, RETURNING_GLYPH},
+    },
+    helpers::{GameSpeed, Time, Timer},
+    high_score::{HighScore, Leaderboard, LeaderboardEntry},
+    interpolation::LanePositions,
+    new_game_plus::CarryOver,
+    online_leaderboard::SubmissionStatus,
+    save_state::{SaveSlots, NAMED_SLOTS},
+    scenario::{Goal, Scenario, ScenarioRun},
+    score::Score,
+    settings::{ColorScheme, Language, Settings},
+    stats::GameStats,
+    timeline::{MarkKind, Timeline, TimelineSample},
+    toasts::{ToastMessage, Toasts},
+    tutorial::Tutorial,
+};
+
+mod cheat_panel;
+mod confirm;
+mod console;
+mod debug_overlay;
+mod sprites;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use sprites::ASSETS as SPRITE_ASSETS;
+mod strings;
+mod theme;
+
+pub use theme::install_style;
+
+/// Requests from the GUI that have to be handled outside of it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GuiAction {
+    None,
+    /// Leave the main menu and start a run.
+    Start,
+    /// Like `Start`, but with the tutorial even if it was done before.
+    StartTutorial,
+    /// Start today's daily challenge, from the main menu or once a run is over.
+    StartDaily,
+    /// Play the numbered scenario, from the main menu or once a run is over.
+    StartScenario(usize),
+    /// Leave the main menu and pick up the run saved last time.
+    ContinueRun,
+    SkipTutorial,
+    Pause,
+    Resume,
+    SetSpeed(GameSpeed),
+    /// Step up to the next speed, with the + key.
+    SpeedUp,
+    /// Step down to the next speed, with the - key.
+    SlowDown,
+    /// Throw the run away and go back to the main menu.
+    Restart,
+    /// Start a new run straight from the game-over screen.
+    PlayAgain,
+    /// Like `PlayAgain`, but with the enemies of the run that just ended.
+    RetrySameSeed,
+    /// Take on the next contract at one New Game+ level higher, after winning one.
+    NewGamePlus,
+    /// Keep the run in the numbered slot, under the name typed in.
+    SaveGame(usize),
+    /// Swap the current run, if any, for the one the player saved in the numbered slot.
+    LoadGame(usize),
+    /// Throw away the run saved in the numbered slot.
+    DeleteSave(usize),
+    /// Keep the run that just ended to be watched again.
+    SaveReplay,
+    /// Swap the current run, if any, for the saved replay.
+    WatchReplay,
+    /// Put the run that just ended on the leaderboard, under the initials typed in.
+    EnterInitials,
+    /// Save what the game shows right now as a picture.
+    SaveScreenshot,
+    Quit,
+}
+
+/// State that only matters to the GUI, like which windows are open.
+#[derive(Default)]
+pub struct GuiState {
+    settings_open: bool,
+    high_scores_open: bool,
+    daily_results_open: bool,
+    scenarios_open: bool,
+    achievements_open: bool,
+    saves_open: bool,
+    /// What the player typed in to call the next save.
+    save_name: String,
+    /// What the player typed in for the leaderboard.
+    initials: String,
+    selected_unit: Option<UnitId>,
+    roster_layout: RosterLayout,
+    sprites: Sprites,
+    sprites_loaded: bool,
+    pending_confirmation: Option<PendingConfirmation>,
+    /// Stops the battle log from jumping to new entries while reading older ones.
+    hold_log_scroll: bool,
+    /// How many log entries had been recorded when the log was last drawn.
+    log_entries_seen: usize,
+    /// Orders given through the GUI since the game last collected them.
+    commands: Vec<GameCommand>,
+    cheat_panel: cheat_panel::CheatPanelState,
+    console: console::ConsoleState,
+    /// Indices into the units for each of the base panel's sections. Kept between frames so the
+    /// vectors are only allocated once.
+    roster_sections: [Vec<usize>; 3],
+    /// The colour scheme and background the egui visuals were last set up for.
+    applied_visuals: Option<(ColorScheme, Color32)>,
+    /// The text scale the fonts were last set up for.
+    applied_text_scale: Option<f32>,
+    /// Whether the spacing was last set up for the touch layout.
+    applied_touch_layout: Option<bool>,
+    /// The action waiting for the player to press its new key.
+    rebinding: Option<&'static str>,
+    /// The sprites, if they were decoded before the GUI started.
+    decoded_sprites: Option<DecodedTextures>,
+    /// The score multiplier shown last frame.
+    shown_multiplier: Option<u32>,
+    /// Real seconds since the game started when the score multiplier last changed.
+    multiplier_changed_at: Option<f64>,
+}
+
+impl GuiState {
+    pub fn take_commands(&mut self) -> Vec<GameCommand> {
+        std::mem::take(&mut self.commands)
+    }
+
+    /// Registers the sprites, decoding them first unless that was done already. Does nothing once
+    /// they are registered.
+    pub fn load_sprites(&mut self, tex_allocator: &mut dyn epi::TextureAllocator) {
+        if self.sprites_loaded {
+            return;
+        }
+        self.sprites_loaded = true;
+        self.sprites = match self.decoded_sprites.take() {
+            Some(decoded) => Sprites::register(tex_allocator, decoded),
+            None => Sprites::load(tex_allocator),
+        };
+    }
+
+    pub fn set_decoded_sprites(&mut self, decoded: DecodedTextures) {
+        self.decoded_sprites = Some(decoded);
+    }
+
+    /// The initials typed in for the leaderboard, leaving the field empty for the next run.
+    pub fn take_initials(&mut self) -> String {
+        std::mem::take(&mut self.initials)
+    }
+
+    /// The name typed in for a save, leaving the field empty for the next one.
+    pub fn take_save_name(&mut self) -> String {
+        std::mem::take(&mut self.save_name)
+    }
+}
+
+/// An action that throws away the current run or a saved one, waiting for the player to confirm
+/// it.
+#[derive(Debug, Copy, Clone)]
+struct PendingConfirmation {
+    action: GuiAction,
+    /// The game was running when the question came up and was paused for it.
+    resume_on_cancel: bool,
+}
+
+impl PendingConfirmation {
+    fn cancel(self) -> GuiAction {
+        if self.resume_on_cancel {
+            GuiAction::Resume
+        } else {
+            GuiAction::None
+        }
+    }
+}
+
+/// Asks the player before an action that throws away the current run. Returns what to do
+/// straight away, which is pausing the game while the question is open.
+fn confirm_first(
+    action: GuiAction,
+    game_state: &GameState,
+    pending_confirmation: &mut Option<PendingConfirmation>,
+) -> GuiAction {
+    match game_state {
+        GameState::MainMenu | GameState::GameOver | GameState::Victory => action,
+        GameState::Running => {
+            *pending_confirmation = Some(PendingConfirmation {
+                action,
+                resume_on_cancel: true,
+            });
+            GuiAction::Pause
+        }
+        GameState::Paused => {
+            *pending_confirmation = Some(PendingConfirmation {
+                action,
+                resume_on_cancel: false,
+            });
+            GuiAction::None
+        }
+    }
+}
+
+/// How the base panel lists the units.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+enum RosterLayout {
+    /// One section each for the storage, the parking area and the units waiting to return.
+    #[default]
+    ByState,
+    /// Every unit exactly once, with the buttons for whatever state it is in.
+    SingleList,
+}
+
+/// The sections of the base panel when units are listed by state.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BaseSection {
+    Storage,
+    ParkingArea,
+    WaitingToReturn,
+}
+
+impl BaseSection {
+    const ALL: [Self; 3] = [Self::Storage, Self::ParkingArea, Self::WaitingToReturn];
+
+    /// The section a unit is listed in, or `None` while it is out in the battlezone.
+    fn of(unit: &Unit) -> Option<Self> {
+        match unit {
+            Unit::InStorage | Unit::Storing(_) => Some(Self::Storage),
+            Unit::UnStoring(_, _)
+            | Unit::Parking(_, _)
+            | Unit::ParkedUnready(_)
+            | Unit::ParkedPreparing(_, _, _)
+            | Unit::ParkedReady(_, _) => Some(Self::ParkingArea),
+            Unit::WaitingToPark => Some(Self::WaitingToReturn),
+            Unit::Patrolling(_, _) | Unit::Returning(_, _) => None,
+        }
+    }
+
+    fn title(self, tr: Strings, count: usize) -> String {
+        match self {
+            Self::Storage => tr.format(Text::StoredUnits, &[&count]),
+            Self::ParkingArea => tr.format(Text::ParkingArea, &[&count]),
+            Self::WaitingToReturn => tr.format(Text::WaitingToReturn, &[&count]),
+        }
+    }
+
+    fn id_source(self) -> &'static str {
+        match self {
+            Self::Storage => "stored_units",
+            Self::ParkingArea => "parking_area",
+            Self::WaitingToReturn => "waiting_to_return",
+        }
+    }
+
+    fn description(self) -> Text {
+        match self {
+            Self::Storage => Text::StoredUnitsDescription,
+            Self::ParkingArea => Text::ParkingAreaDescription,
+            Self::WaitingToReturn => Text::WaitingToReturnDescription,
+        }
+    }
+}
+
+/// Everything of the game the GUI shows, borrowed for a frame.
+pub struct GameView<'a> {
+    pub units: &'a [UnitBundle],
+    pub enemies: &'a [Enemy],
+    pub lane_positions: &'a LanePositions,
+    pub enemy_spawner: &'a EnemySpawner,
+    pub config: &'a GameConfig,
+    pub parking_spaces: &'a TokenPool<ParkingSpace>,
+    pub game_state: &'a GameState,
+    pub play_time: &'a PlayTime,
+    pub time: &'a Time,
+    pub high_score: &'a HighScore,
+    pub leaderboard: &'a Leaderboard,
+    pub online_status: Option<&'a SubmissionStatus>,
+    pub defeat: Option<&'a DefeatReport>,
+    pub achievements: &'a Achievements,
+    pub awaiting_initials: bool,
+    pub saved_run_seconds: Option<f64>,
+    pub save_slots: &'a SaveSlots,
+    pub watching_replay: bool,
+    pub can_save_replay: bool,
+    pub saved_replay_seconds: Option<f64>,
+    pub challenge_seed: Option<u64>,
+    pub daily: Option<&'a DailyRun>,
+    pub daily_results: &'a DailyResults,
+    pub scenarios: &'a [Scenario],
+    pub scenario: Option<&'a ScenarioRun>,
+    pub sandbox: bool,
+    pub cheats: &'a CheatState,
+    pub stats: &'a GameStats,
+    pub score: &'a Score,
+    pub timeline: &'a Timeline,
+    pub battle_log: &'a BattleLog,
+    pub toasts: &'a Toasts,
+    pub tutorial: Option<&'a Tutorial>,
+}
+
+pub fn gui(
+    egui_ctx: &CtxRef,
+    view: &GameView<'_>,
+    threat_alerts: &mut ThreatAlerts,
+    settings: &mut Settings,
+    gui_state: &mut GuiState,
+) -> GuiAction {
+    log::trace!("Gui!");
+    let GameView {
+        units,
+        enemies,
+        lane_positions,
+        enemy_spawner,
+        config,
+        parking_spaces,
+        game_state,
+        play_time,
+        time,
+        high_score,
+        leaderboard,
+        achievements,
+        save_slots,
+        watching_replay,
+        daily,
+        daily_results,
+        scenarios,
+        scenario,
+        sandbox,
+        cheats,
+        stats,
+        score,
+        battle_log,
+        toasts,
+        tutorial,
+        ..
+    } = *view;
+    let mut action = GuiAction::None;
+    // The daily challenge is the same for everyone, cheats and all.
+    let sandbox = sandbox && daily.is_none();
+
+    // While a key is being rebound, the next key press is meant for that and nothing else.
+    let capturing = gui_state.rebinding.is_some();
+    if !capturing && (sandbox || cfg!(debug_assertions)) {
+        gui_state.console.toggle_on_key(egui_ctx);
+    }
+    // Neither are keys typed into the console.
+    let typing = gui_state.console.is_open();
+    let actions = if capturing {
+        capture_binding(egui_ctx, settings, gui_state);
+        ActionState::default()
+    } else if typing {
+        ActionState::default()
+    } else {
+        ActionState::from_egui(&settings.key_bindings, egui_ctx.input())
+    };
+
+    if !capturing && egui_ctx.input().key_pressed(egui::Key::Escape) {
+        if typing {
+            gui_state.console.close();
+        } else if let Some(pending) = gui_state.pending_confirmation.take() {
+            action = pending.cancel();
+        } else {
+            match game_state {
+                GameState::Running => action = GuiAction::Pause,
+                GameState::Paused => action = GuiAction::Resume,
+                GameState::MainMenu | GameState::GameOver | GameState::Victory => {}
+            }
+        }
+    }
+
+    let in_run = *game_state == GameState::Running || *game_state == GameState::Paused;
+    if !capturing && !typing && in_run {
+        if let Some(speed_action) = speed_keys(egui_ctx, &actions, game_state) {
+            if gui_state.pending_confirmation.is_none() {
+                action = speed_action;
+            }
+        }
+    }
+
+    // A replay gives the orders itself.
+    if game_state.is_interactive() && !watching_replay {
+        handle_key_commands(&actions, gui_state);
+    }
+
+    let theme = Theme::new(settings.color_scheme, settings.colorblind_mode);
+    let tr = Strings::new(settings.language);
+    let timings = config.unit_timings(enemy_spawner.difficulty());
+    // Tutorial runs, scenarios and replays are never saved, like with the autosave.
+    let can_save = matches!(game_state, GameState::Running | GameState::Paused)
+        && tutorial.is_none()
+        && scenario.is_none()
+        && !watching_replay
+        && daily.is_none();
+    let background =
+        theme.ambient_background(nearest_threat(enemies), *game_state == GameState::GameOver);
+    if gui_state.applied_visuals != Some((settings.color_scheme, background)) {
+        set_visuals(egui_ctx, theme.visuals(background));
+        gui_state.applied_visuals = Some((settings.color_scheme, background));
+    }
+    let text_scale = settings.text_scale();
+    if gui_state.applied_text_scale != Some(text_scale) {
+        egui_ctx.set_fonts(scaled_fonts(text_scale));
+        gui_state.applied_text_scale = Some(text_scale);
+    }
+    let touch_layout = settings.uses_touch_layout();
+    if gui_state.applied_touch_layout != Some(touch_layout) {
+        let mut style = (*egui_ctx.style()).clone();
+        style.spacing = spacing(touch_layout);
+        egui_ctx.set_style(style);
+        gui_state.applied_touch_layout = Some(touch_layout);
+    }
+
+    if *game_state == GameState::MainMenu {
+        action = main_menu(egui_ctx, view, settings, gui_state);
+        settings_window(egui_ctx, settings, gui_state);
+        high_scores_window(egui_ctx, tr, &theme, leaderboard, gui_state);
+        daily_results_window(egui_ctx, tr, daily_results, gui_state);
+        if let Some(chosen) = scenarios_window(egui_ctx, tr, scenarios, gui_state) {
+            action = chosen;
+        }
+        achievements_window(egui_ctx, tr, achievements, gui_state);
+        if let Some(saves_action) =
+            saves_window(egui_ctx, tr, game_state, save_slots, false, gui_state)
+        {
+            action = saves_action;
+        }
+        if let Some(confirmed) = confirmation_dialog(egui_ctx, tr, play_time, save_slots, gui_state)
+        {
+            action = confirmed;
+        }
+        return action;
+    }
+
+    egui::TopPanel::top("top_panel").show(egui_ctx, |ui| {
+        // The top panel is often a good place for a menu bar:
+        egui::menu::bar(ui, |ui| {
+            egui::menu::menu(ui, tr.get(Text::File), |ui| {
+                if ui
+                    .button(tr.get(Text::Restart))
+                    .on_hover_text(tr.get(Text::RestartTooltip))
+                    .clicked()
+                {
+                    action = confirm_first(
+                        GuiAction::Restart,
+                        game_state,
+                        &mut gui_state.pending_confirmation,
+                    );
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .button(tr.get(Text::Quit))
+                    .on_hover_text(tr.get(Text::QuitTooltip))
+                    .clicked()
+                {
+                    action = confirm_first(
+                        GuiAction::Quit,
+                        game_state,
+                        &mut gui_state.pending_confirmation,
+                    );
+                }
+            });
+
+            if *game_state == GameState::Running
+                && ui
+                    .button(tr.get(Text::Menu))
+                    .on_hover_text(tr.get(Text::MenuTooltip))
+                    .clicked()
+            {
+                action = GuiAction::Pause;
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.set_enabled(!game_state.is_over());
+                let pause = egui::Button::new("⏸").enabled(*game_state == GameState::Running);
+                if ui
+                    .add(pause)
+                    .on_hover_text(tr.get(Text::PauseTooltip))
+                    .clicked()
+                {
+                    action = GuiAction::Pause;
+                }
+
+                let current = GameSpeed::from_scale(time.scale());
+                for &speed in GameSpeed::ALL.iter() {
+                    let too_fast = daily.is_some() && speed.scale() > daily::MAX_SPEED.scale();
+                    let tooltip = if too_fast {
+                        Text::DailySpeedTooltip
+                    } else {
+                        Text::GameSpeedTooltip
+                    };
+                    ui.horizontal(|ui| {
+                        ui.set_enabled(!too_fast);
+                        if ui
+                            .selectable_label(current == Some(speed), speed.label())
+                            .on_hover_text(tr.get(tooltip))
+                            .clicked()
+                        {
+                            action = GuiAction::SetSpeed(speed);
+                        }
+                    });
+                }
+            });
+            ui.separator();
+
+            if ui
+                .button(tr.get(Text::Settings))
+                .on_hover_text(tr.get(Text::SettingsTooltip))
+                .clicked()
+            {
+                gui_state.settings_open = true;
+            }
+
+            ui.label(tr.format(
+                Text::Best,
+                &[&format!("{:.0}", high_score.best_survival_seconds())],
+            ));
+
+            if settings.show_fps {
+                ui.label(tr.format(
+                    Text::Fps,
+                    &[&format!("{:.0}", 1.0 / egui_ctx.input().unstable_dt)],
+                ));
+                #[cfg(feature = "count-allocations")]
+                ui.label(tr.format(
+                    Text::AllocationsPerFrame,
+                    &[&crate::alloc_count::last_frame()],
+                ));
+            }
+        });
+
+        // Nothing is on its way while the tutorial holds enemies back or the game is paused, and
+        // a scenario's enemies come when its script says rather than the spawner.
+        let show_eta =
+            *game_state == GameState::Running && tutorial.is_none() && scenario.is_none();
+        let multiplier = score.multiplier();
+        if gui_state
+            .shown_multiplier
+            .is_some_and(|shown| shown != multiplier)
+        {
+            gui_state.multiplier_changed_at = Some(time.real_seconds());
+        }
+        gui_state.shown_multiplier = Some(multiplier);
+        let flash_multiplier = gui_state
+            .multiplier_changed_at
+            .is_some_and(|changed_at| time.real_seconds() - changed_at < MULTIPLIER_FLASH_SECONDS);
+        status_header(ui, tr, &theme, view, flash_multiplier, show_eta);
+
+        let mut dismissed = None;
+        for alert in threat_alerts.active() {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    pulse(egui_ctx, theme.critical),
+                    tr.format(
+                        Text::Breaching,
+                        &[
+                            &alert.combat_type.glyph(),
+                            &alert.combat_type,
+                            &format!("{:.0}", alert.eta_seconds),
+                        ],
+                    ),
+                );
+                if ui
+                    .small_button("✖")
+                    .on_hover_text(tr.get(Text::DismissAlertTooltip))
+                    .clicked()
+                {
+                    dismissed = Some(alert.enemy);
+                }
+            });
+        }
+        if let Some(enemy) = dismissed {
+            threat_alerts.dismiss(enemy);
+        }
+    });
+
+    // On narrow screens the base panel gives up some room so the lanes stay usable.
+    let base_panel_width =
+        BASE_PANEL_WIDTH.min(egui_ctx.available_rect().width() * BASE_PANEL_SHARE);
+    egui::SidePanel::left("base_panel", base_panel_width).show(egui_ctx, |ui| {
+        ui.set_enabled(game_state.is_interactive() && !watching_replay);
+
+        egui::ScrollArea::auto_sized().show(ui, |ui| {
+            ui.heading(tr.get(Text::YourBase));
+            ui.collapsing(tr.get(Text::Controls), |ui| {
+                for &(action, description) in ACTIONS {
+                    if let Some(key) = settings.key_bindings.key(action) {
+                        ui.label(format!("{}: {}", key, description));
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr.get(Text::ListUnits));
+                ui.radio_value(
+                    &mut gui_state.roster_layout,
+                    RosterLayout::ByState,
+                    tr.get(Text::ByState),
+                )
+                .on_hover_text(tr.get(Text::ByStateTooltip));
+                ui.radio_value(
+                    &mut gui_state.roster_layout,
+                    RosterLayout::SingleList,
+                    tr.get(Text::SingleList),
+                )
+                .on_hover_text(tr.get(Text::SingleListTooltip));
+            });
+
+            parking_grid(
+                ui,
+                tr,
+                &theme,
+                units,
+                parking_spaces,
+                gui_state.selected_unit,
+                &mut gui_state.commands,
+            );
+
+            let rows = RowContext {
+                tr,
+                theme: &theme,
+                timings: &timings,
+                damage_per_hit: config.units.damage_per_hit,
+                parking_spaces,
+                text_scale,
+                touch_layout,
+                icon: gui_state.sprites.unit(),
+            };
+            match gui_state.roster_layout {
+                RosterLayout::ByState => {
+                    // Taken out for the loop, as the rows need the rest of the GUI state.
+                    let mut sections = std::mem::take(&mut gui_state.roster_sections);
+                    for indices in sections.iter_mut() {
+                        indices.clear();
+                    }
+                    for (index, bundle) in units.iter().enumerate() {
+                        if let Some(section) = BaseSection::of(&bundle.unit) {
+                            sections[section as usize].push(index);
+                        }
+                    }
+
+                    for (&section, indices) in BaseSection::ALL.iter().zip(sections.iter()) {
+                        egui::CollapsingHeader::new(section.title(tr, indices.len()))
+                            .id_source(section.id_source())
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.label(tr.get(section.description()));
+                                for &index in indices {
+                                    unit_row(
+                                        ui,
+                                        &units[index],
+                                        &rows,
+                                        &mut gui_state.selected_unit,
+                                        &mut gui_state.commands,
+                                    );
+                                }
+                            });
+                    }
+                    gui_state.roster_sections = sections;
+                }
+                RosterLayout::SingleList => {
+                    for bundle in units.iter() {
+                        unit_row(
+                            ui,
+                            bundle,
+                            &rows,
+                            &mut gui_state.selected_unit,
+                            &mut gui_state.commands,
+                        );
+                    }
+                }
+            }
+        });
+    });
+
+    egui::CentralPanel::default().show(egui_ctx, |ui| {
+        ui.set_enabled(game_state.is_interactive());
+
+        egui::ScrollArea::auto_sized().show(ui, |ui| {
+            ui.heading(tr.format(
+                Text::SurvivedSoFar,
+                &[&format!("{:.0}", play_time.seconds())],
+            ));
+            egui::CollapsingHeader::new(tr.get(Text::Briefing)).show(ui, |ui| {
+                ui.label(tr.get(Text::BriefingBody));
+            });
+            egui::warn_if_debug_build(ui);
+
+            ui.separator();
+            ui.heading(tr.get(Text::Battlezone));
+            ui.label(tr.get(Text::BattlezoneHelp));
+            ui.separator();
+
+            let summaries = lane_summary(units, enemies);
+            let mut scramble_lane = None;
+            egui::CollapsingHeader::new(tr.get(Text::Threats))
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::Grid::new("threat_summary")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(tr.get(Text::Lane));
+                            ui.label(tr.get(Text::Inbound));
+                            ui.label(tr.get(Text::NextHitIn));
+                            ui.label(tr.get(Text::Airborne));
+                            ui.label(tr.get(Text::Prepared));
+                            ui.label(tr.get(Text::Scramble));
+                            ui.end_row();
+
+                            for summary in &summaries {
+                                let lane = lane_name(summary.combat_type);
+                                if summary.is_uncovered() {
+                                    ui.colored_label(theme.enemy, lane)
+                                        .on_hover_text(tr.get(Text::UncoveredLaneTooltip));
+                                } else {
+                                    ui.label(lane);
+                                }
+                                ui.label(summary.inbound.to_string());
+                                ui.label(match summary.soonest_eta {
+                                    Some(eta) => format!("{:.1}s", eta),
+                                    None => "-".to_string(),
+                                });
+                                ui.label(summary.airborne.to_string());
+                                ui.label(summary.prepared.to_string());
+                                match scramble_candidate(
+                                    units,
+                                    summary.combat_type,
+                                    &timings,
+                                    parking_spaces,
+                                ) {
+                                    Some(candidate) => {
+                                        let label = tr.format(
+                                            Text::ScrambleButton,
+                                            &[
+                                                &candidate.id.to_string(),
+                                                &format!("{:.0}", candidate.seconds_to_airborne),
+                                            ],
+                                        );
+                                        if ui
+                                            .button(label)
+                                            .on_hover_text(tr.get(Text::ScrambleTooltip))
+                                            .clicked()
+                                        {
+                                            scramble_lane = Some(summary.combat_type);
+                                        }
+                                    }
+                                    None => {
+                                        ui.add(
+                                            egui::Button::new(tr.get(Text::Scramble))
+                                                .enabled(false),
+                                        )
+                                        .on_hover_text(tr.get(Text::NothingToScrambleTooltip));
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            if let Some(combat_type) = scramble_lane {
+                gui_state.commands.push(GameCommand::Scramble(combat_type));
+            }
+            egui::CollapsingHeader::new(tr.get(Text::Stats)).show(ui, |ui| {
+                stats_grid(ui, tr, "live_stats", stats);
+            });
+            ui.separator();
+
+            // Worked out once so every lane comes out the same size.
+            let lane_height = if touch_layout {
+                LANE_HEIGHT * text_scale * TOUCH_LANE_SCALE
+            } else {
+                LANE_HEIGHT * text_scale
+            };
+            let heading_size = Vec2::new(LANE_HEADING_WIDTH * text_scale, lane_height);
+            for (combat_type, summary) in CombatType::iter().zip(&summaries) {
+                let danger_level = summary.danger_level();
+                let alerted = threat_alerts
+                    .active()
+                    .iter()
+                    .any(|alert| alert.combat_type == combat_type);
+
+                ui.horizontal(|ui| {
+                    let (heading_rect, heading) =
+                        ui.allocate_exact_size(heading_size, egui::Sense::hover());
+                    let glyph = ui.painter().text(
+                        heading_rect.left_center(),
+                        Align2::LEFT_CENTER,
+                        combat_type.glyph(),
+                        TextStyle::Heading,
+                        theme.combat_type(combat_type),
+                    );
+                    let name: &'static str = combat_type.into();
+                    ui.painter().text(
+                        Pos2::new(glyph.right() + 4.0, heading_rect.center().y),
+                        Align2::LEFT_CENTER,
+                        name,
+                        TextStyle::Heading,
+                        danger_color(&theme, danger_level, alerted, time),
+                    );
+                    heading.on_hover_text(tr.get(Text::LaneTooltip));
+                    ui.separator();
+                    let (response, painter) = ui.allocate_painter(
+                        Vec2::new(ui.available_width(), lane_height),
+                        egui::Sense::hover(),
+                    );
+                    let track = paint_lane(
+                        &painter,
+                        response.rect,
+                        &theme,
+                        tr,
+                        summary.is_uncovered(),
+                        danger_level == DangerLevel::Breaching,
+                    );
+                    let y = track.center().y;
+
+                    let icon_size = track.height() * MARKER_ICON_FRACTION;
+                    // Markers stop short of the lane's ends so they aren't cut off there.
+                    let travel = track.shrink2(Vec2::new(icon_size, 0.0));
+
+                    for enemy in enemies
+                        .iter()
+                        .filter(|enemy| enemy.combat_type == combat_type)
+                    {
+                        let mut x = travel.left()
+                            + travel.width() * lane_positions.enemy_percent_left(enemy);
+                        if let Some(texture_id) = gui_state.sprites.enemy(enemy.combat_type) {
+                            let icon = Rect::from_min_size(
+                                Pos2::new(x, y - 0.5 * icon_size),
+                                Vec2::splat(icon_size),
+                            );
+                            sprites::paint(&painter, texture_id, icon);
+                            x += icon_size;
+                        }
+
+                        let glyph = enemy.combat_type.glyph();
+                        let glyph = if enemy.is_boss {
+                            format!("{}{}", glyph, glyph)
+                        } else {
+                            glyph.to_string()
+                        };
+                        let glyph = painter.text(
+                            Pos2 { x, y },
+                            Align2([Align::Min, Align::Center]),
+                            glyph,
+                            TextStyle::Heading,
+                            theme.combat_type(enemy.combat_type),
+                        );
+                        let label = if enemy.is_boss {
+                            format!(
+                                " {} t-{:.1}s",
+                                tr.get(Text::Boss),
+                                enemy.progress.remaining_seconds()
+                            )
+                        } else {
+                            format!(" t-{:.1}s", enemy.progress.remaining_seconds())
+                        };
+                        painter.text(
+                            Pos2 {
+                                x: glyph.right(),
+                                y,
+                            },
+                            Align2([Align::Min, Align::Center]),
+                            label,
+                            TextStyle::Heading,
+                            theme.enemy,
+                        );
+                    }
+
+                    for bundle in units.iter() {
+                        let UnitBundle { unit, health, .. } = bundle;
+                        let (progress, glyph, role_color) = match unit {
+                            Unit::Patrolling(progress, unit_combat_type)
+                                if *unit_combat_type == combat_type =>
+                            {
+                                (progress, PATROLLING_GLYPH, theme.patrolling)
+                            }
+                            Unit::Returning(progress, unit_combat_type)
+                                if *unit_combat_type == combat_type =>
+                            {
+                                (progress, RETURNING_GLYPH, theme.returning)
+                            }
+                            _ => continue,
+                        };
+
+                        let percent = lane_positions
+                            .unit_percent(bundle)
+                            .unwrap_or_else(|| progress.percent());
+                        let mut x = travel.left() + travel.width() * percent;
+                        if let Some(texture_id) = gui_state.sprites.unit() {
+                            let icon = Rect::from_min_size(
+                                Pos2::new(x - icon_size, y - 0.5 * icon_size),
+                                Vec2::splat(icon_size),
+                            );
+                            sprites::paint(&painter, texture_id, icon);
+                            x -= icon_size;
+                        }
+
+                        let marker = painter.text(
+                            Pos2 { x, y },
+                            Align2([Align::Max, Align::Center]),
+                            format!("{} {}", health, glyph),
+                            TextStyle::Heading,
+                            blend(
+                                role_color,
+                                health_color(egui_ctx, health, config.units.damage_per_hit),
+                            ),
+                        );
+                        painter.text(
+                            Pos2 {
+                                x: marker.left(),
+                                y,
+                            },
+                            Align2([Align::Max, Align::Center]),
+                            format!("{} ", combat_type.glyph()),
+                            TextStyle::Heading,
+                            theme.combat_type(combat_type),
+                        );
+                    }
+                });
+                ui.separator();
+            }
+
+            egui::CollapsingHeader::new(tr.get(Text::BattleLog))
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.checkbox(&mut gui_state.hold_log_scroll, tr.get(Text::HoldLogScroll))
+                        .on_hover_text(tr.get(Text::HoldLogScrollTooltip));
+
+                    let has_new_entries = battle_log.recorded() != gui_state.log_entries_seen;
+                    gui_state.log_entries_seen = battle_log.recorded();
+
+                    egui::ScrollArea::from_max_height(BATTLE_LOG_HEIGHT).show(ui, |ui| {
+                        for entry in battle_log.entries() {
+                            let color = match entry.severity {
+                                Severity::Info => ui.visuals().weak_text_color(),
+                                Severity::Warning => theme.warning,
+                                Severity::Critical => theme.critical,
+                            };
+                            ui.colored_label(color, format!("[{:.1}s] {}", entry.at, entry.text));
+                        }
+
+                        if has_new_entries && !gui_state.hold_log_scroll {
+                            ui.scroll_to_cursor(Align::BOTTOM);
+                        }
+                    });
+                });
+        });
+    });
+
+    if *game_state == GameState::Paused && gui_state.pending_confirmation.is_none() {
+        egui::Window::new(tr.get(Text::Paused))
+            .anchor(Align2::CENTER_CENTER, Vec2::new(0.0, 0.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                if ui
+                    .button(tr.get(Text::Resume))
+                    .on_hover_text(tr.get(Text::ResumeTooltip))
+                    .clicked()
+                {
+                    action = GuiAction::Resume;
+                }
+                if (can_save || !save_slots.is_empty())
+                    && ui
+                        .button(tr.get(Text::SavedGames))
+                        .on_hover_text(tr.format(Text::SavedGamesTooltip, &[&NAMED_SLOTS]))
+                        .clicked()
+                {
+                    gui_state.saves_open = true;
+                }
+                if ui
+                    .button(tr.get(Text::Restart))
+                    .on_hover_text(tr.get(Text::RestartTooltip))
+                    .clicked()
+                {
+                    action = confirm_first(
+                        GuiAction::Restart,
+                        game_state,
+                        &mut gui_state.pending_confirmation,
+                    );
+                }
+                if ui
+                    .button(tr.get(Text::Settings))
+                    .on_hover_text(tr.get(Text::SettingsTooltip))
+                    .clicked()
+                {
+                    gui_state.settings_open = true;
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .button(tr.get(Text::Quit))
+                    .on_hover_text(tr.get(Text::QuitTooltip))
+                    .clicked()
+                {
+                    action = confirm_first(
+                        GuiAction::Quit,
+                        game_state,
+                        &mut gui_state.pending_confirmation,
+                    );
+                }
+            });
+    }
+
+    if *game_state == GameState::Paused {
+        if let Some(saves_action) =
+            saves_window(egui_ctx, tr, game_state, save_slots, can_save, gui_state)
+        {
+            action = saves_action;
+        }
+    }
+    if let Some(confirmed) = confirmation_dialog(egui_ctx, tr, play_time, save_slots, gui_state) {
+        action = confirmed;
+    }
+
+    settings_window(egui_ctx, settings, gui_state);
+    toast_stack(egui_ctx, tr, &theme, toasts, time);
+
+    if settings.show_debug_overlay {
+        debug_overlay::show(egui_ctx, tr, view, &timings);
+    }
+
+    // A replay plays back the cheats it recorded by itself.
+    if sandbox && *game_state == GameState::Running && !watching_replay {
+        let requested = cheat_panel::show(
+            egui_ctx,
+            tr,
+            cheats,
+            gui_state.selected_unit,
+            &mut gui_state.cheat_panel,
+        );
+        gui_state
+            .commands
+            .extend(requested.into_iter().map(GameCommand::Cheat));
+    }
+
+    let run = console::RunInfo {
+        sandbox,
+        game_state: *game_state,
+        enemy_spawner,
+        stats,
+    };
+    // Orders from the console are the player's, which a replay doesn't take.
+    let mut console_commands = Vec::new();
+    if let Some(console_action) = console::show(
+        egui_ctx,
+        &mut gui_state.console,
+        &run,
+        &mut console_commands,
+    ) {
+        action = console_action;
+    }
+    if !watching_replay {
+        gui_state.commands.extend(console_commands);
+    }
+
+    if let Some((tutorial, step)) =
+        tutorial.and_then(|tutorial| Some((tutorial, tutorial.current_step()?)))
+    {
+        egui::Window::new(tr.get(Text::Tutorial))
+            .anchor(Align2::RIGHT_TOP, Vec2::new(-16.0, 48.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                ui.label(tr.format(
+                    Text::TutorialStep,
+                    &[&tutorial.step_number(), &tutorial.step_count()],
+                ));
+                ui.label(step.instruction);
+                if ui
+                    .button(tr.get(Text::SkipTutorial))
+                    .on_hover_text(tr.get(Text::SkipTutorialTooltip))
+                    .clicked()
+                {
+                    action = GuiAction::SkipTutorial;
+                }
+            });
+    }
+
+    if let (Some(scenario), false) = (scenario, game_state.is_over()) {
+        egui::Window::new(&scenario.scenario().name)
+            .anchor(Align2::RIGHT_TOP, Vec2::new(-16.0, 48.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                match scenario.scenario().goal {
+                    Goal::SurviveSeconds(seconds) => ui.label(tr.format(
+                        Text::ScenarioTimeLeft,
+                        &[&format!("{:.0}", (seconds - play_time.seconds()).max(0.0))],
+                    )),
+                    Goal::InterceptAll => ui.label(tr.format(
+                        Text::ScenarioEnemiesLeft,
+                        &[&scenario.enemies_left(enemies)],
+                    )),
+                };
+                if let Some(message) = scenario.message(play_time) {
+                    ui.label(message);
+                }
+            });
+    }
+
+    if game_state.is_over() {
+        if let Some(chosen) = run_over_window(egui_ctx, tr, &theme, view, gui_state) {
+            action = chosen;
+        }
+    }
+
+    action
+}
+
+/// The window for a run that ended, lost or won: how it went and what to do next. Returns what
+/// the player chose, if anything.
+fn run_over_window(
+    egui_ctx: &CtxRef,
+    tr: Strings,
+    theme: &Theme,
+    view: &GameView<'_>,
+    gui_state: &mut GuiState,
+) -> Option<GuiAction> {
+    let GameView {
+        game_state,
+        enemy_spawner,
+        play_time,
+        high_score,
+        awaiting_initials,
+        online_status,
+        defeat,
+        can_save_replay,
+        challenge_seed,
+        cheats,
+        watching_replay,
+        daily,
+        scenario,
+        stats,
+        score,
+        timeline,
+        ..
+    } = *view;
+    let cheated = cheats.used;
+    let new_game_plus = CarryOver::extract(*game_state, enemy_spawner, cheats, watching_replay);
+    let mut action = None;
+    let contract = enemy_spawner.mode().contract();
+    let won = *game_state == GameState::Victory;
+    let title = match (scenario, won) {
+        (Some(_), true) => Text::ScenarioWon,
+        (Some(_), false) => Text::ScenarioFailed,
+        (None, true) => Text::ContractFulfilled,
+        (None, false) => Text::Hit,
+    };
+    egui::Window::new(tr.get(title))
+        .anchor(Align2::CENTER_CENTER, Vec2::new(0.0, 0.0))
+        .show(egui_ctx, |ui| {
+            match (scenario, contract) {
+                (Some(scenario), _) => {
+                    ui.heading(&scenario.scenario().name);
+                    ui.label(tr.format(
+                        Text::SurvivedFinal,
+                        &[&format!("{:.0}", play_time.seconds())],
+                    ));
+                }
+                (None, Some(contract)) if won => {
+                    ui.heading(tr.get(Text::ContractFulfilledHeading));
+                    let medal = contract.medal(stats.units_lost, stats.bosses_leaked);
+                    ui.colored_label(
+                        theme.highlight,
+                        tr.format(Text::MedalEarned, &[&tr.get(medal_text(medal))]),
+                    );
+                }
+                _ => {
+                    ui.heading(tr.get(Text::BaseHit));
+                    ui.label(tr.format(
+                        Text::SurvivedFinal,
+                        &[&format!("{:.0}", play_time.seconds())],
+                    ));
+                }
+            }
+            if let Some(defeat) = defeat {
+                defeat_report(ui, tr, theme, defeat);
+            }
+            if high_score.is_new_record() {
+                ui.colored_label(theme.highlight, tr.get(Text::NewPersonalBest));
+            }
+            ui.label(tr.format(
+                Text::FinalScore,
+                &[&tr.number(score.points(play_time.seconds()))],
+            ));
+            if high_score.is_new_best_points() {
+                ui.colored_label(theme.highlight, tr.get(Text::NewBestScore));
+            }
+            if cheated {
+                ui.label(tr.get(Text::CheatsUsed));
+            }
+            match daily {
+                Some(daily) if daily.scored => {
+                    ui.colored_label(
+                        theme.highlight,
+                        tr.format(Text::DailyRecorded, &[&daily.date]),
+                    );
+                }
+                Some(_) => {
+                    ui.label(tr.get(Text::DailyPractice));
+                }
+                None => {}
+            }
+            match online_status {
+                Some(SubmissionStatus::Submitting) => {
+                    ui.label(tr.get(Text::OnlineSubmitting));
+                }
+                Some(SubmissionStatus::Submitted) => {
+                    ui.colored_label(theme.highlight, tr.get(Text::OnlineSubmitted));
+                }
+                Some(SubmissionStatus::Failed(reason)) => {
+                    ui.label(tr.format(Text::OnlineFailed, &[reason]));
+                }
+                None => {}
+            }
+            if awaiting_initials {
+                ui.separator();
+                ui.label(tr.get(Text::MadeLeaderboard));
+                ui.horizontal(|ui| {
+                    let field = ui.add(
+                        egui::TextEdit::singleline(&mut gui_state.initials)
+                            .desired_width(INITIALS_FIELD_WIDTH),
+                    );
+                    gui_state.initials = LeaderboardEntry::clean_initials(&gui_state.initials);
+                    let typed_in = !gui_state.initials.is_empty();
+                    let pressed_enter =
+                        field.lost_focus() && egui_ctx.input().key_pressed(egui::Key::Enter);
+                    ui.set_enabled(typed_in);
+                    if ui
+                        .button(tr.get(Text::EnterInitials))
+                        .on_hover_text(tr.get(Text::EnterInitialsTooltip))
+                        .clicked()
+                        || (typed_in && pressed_enter)
+                    {
+                        action = Some(GuiAction::EnterInitials);
+                    }
+                });
+            }
+            ui.separator();
+            stats_grid(ui, tr, "final_stats", stats);
+            egui::CollapsingHeader::new(tr.get(Text::Timeline))
+                .default_open(true)
+                .show(ui, |ui| {
+                    timeline_chart(ui, tr, theme, timeline, play_time.seconds());
+                });
+            ui.separator();
+            let (play_again, play_again_tooltip) = match scenario {
+                Some(scenario) => (
+                    GuiAction::StartScenario(scenario.index()),
+                    Text::ScenarioPlayAgainTooltip,
+                ),
+                None => (GuiAction::PlayAgain, Text::PlayAgainTooltip),
+            };
+            if ui
+                .button(tr.get(Text::PlayAgain))
+                .on_hover_text(tr.get(play_again_tooltip))
+                .clicked()
+            {
+                action = Some(play_again);
+            };
+            // Another go at the daily challenge is practice on the same enemies.
+            if let Some(daily) = daily {
+                if ui
+                    .button(tr.get(Text::DailyChallenge))
+                    .on_hover_text(tr.format(Text::DailyPracticeTooltip, &[&daily.date]))
+                    .clicked()
+                {
+                    action = Some(GuiAction::StartDaily);
+                }
+            }
+            // Challenge runs always replay their seed, so there is nothing to choose, and a
+            // scenario's enemies don't come from it.
+            if challenge_seed.is_none()
+                && daily.is_none()
+                && scenario.is_none()
+                && ui
+                    .button(tr.format(Text::RetrySameSeed, &[&enemy_spawner.seed()]))
+                    .on_hover_text(tr.get(Text::RetrySameSeedTooltip))
+                    .clicked()
+            {
+                action = Some(GuiAction::RetrySameSeed);
+            }
+            if let Some(carry_over) = new_game_plus {
+                if ui
+                    .button(tr.format(Text::NewGamePlus, &[&carry_over.level]))
+                    .on_hover_text(tr.get(Text::NewGamePlusTooltip))
+                    .clicked()
+                {
+                    action = Some(GuiAction::NewGamePlus);
+                }
+            }
+            ui.horizontal(|ui| {
+                let supported = engine::screenshot::is_supported();
+                ui.set_enabled(supported);
+                let tooltip = if supported {
+                    Text::SaveScreenshotTooltip
+                } else {
+                    Text::ScreenshotUnsupportedTooltip
+                };
+                if ui
+                    .button(tr.get(Text::SaveScreenshot))
+                    .on_hover_text(tr.get(tooltip))
+                    .clicked()
+                {
+                    action = Some(GuiAction::SaveScreenshot);
+                }
+            });
+            if can_save_replay
+                && ui
+                    .button(tr.get(Text::SaveReplay))
+                    .on_hover_text(tr.get(Text::SaveReplayTooltip))
+                    .clicked()
+            {
+                action = Some(GuiAction::SaveReplay);
+            }
+            ui.separator();
+            build_info(ui, tr, Some(enemy_spawner.seed()));
+        });
+
+    action
+}
+
+/// What got through to the base, and how close the nearest unit was to stopping it.
+fn defeat_report(ui: &mut egui::Ui, tr: Strings, theme: &Theme, defeat: &DefeatReport) {
+    let breached = if defeat.is_boss {
+        Text::DefeatBossBreached
+    } else {
+        Text::DefeatBreached
+    };
+    ui.colored_label(
+        theme.critical,
+        tr.format(
+            breached,
+            &[&defeat.lane, &format!("{:.0}", defeat.at_seconds)],
+        ),
+    );
+
+    let nearest = match defeat.nearest {
+        Some(nearest) => nearest,
+        None => {
+            ui.label(tr.get(Text::DefeatNoUnits));
+            return;
+        }
+    };
+    let text = match nearest.state {
+        DefenderState::Patrolling => Text::DefeatPatrolling,
+        DefenderState::Ready => Text::DefeatReady,
+        DefenderState::Preparing => Text::DefeatPreparing,
+        DefenderState::Parked => Text::DefeatParked,
+        DefenderState::Parking => Text::DefeatParking,
+        DefenderState::Airborne => Text::DefeatAirborne,
+        DefenderState::WaitingToPark => Text::DefeatWaitingToPark,
+        DefenderState::InStorage => Text::DefeatInStorage,
+    };
+    ui.label(tr.format(
+        text,
+        &[
+            &defeat.lane,
+            &nearest.unit,
+            &format!("{:.1}", nearest.seconds_to_ready),
+        ],
+    ));
+}
+
+/// The title screen shown before a run starts. `saved_run_seconds` is how long the run that can
+/// be continued had lasted, if there is one, and `saved_replay_seconds` the same for the saved
+/// replay. `challenge_seed` is the seed every run uses, if the game was started with one, e.g.
+/// from a shared link. `daily_results` tells whether today's daily challenge still counts.
+/// Scenarios are only offered if there are any.
+fn main_menu(
+    egui_ctx: &CtxRef,
+    view: &GameView<'_>,
+    settings: &mut Settings,
+    gui_state: &mut GuiState,
+) -> GuiAction {
+    let GameView {
+        high_score,
+        saved_run_seconds,
+        save_slots,
+        saved_replay_seconds,
+        challenge_seed,
+        daily_results,
+        scenarios,
+        ..
+    } = *view;
+    let has_scenarios = !scenarios.is_empty();
+    let mut action = GuiAction::None;
+    let tr = Strings::new(settings.language);
+
+    egui::CentralPanel::default().show(egui_ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.heading("Fruitopian Defender");
+            ui.separator();
+            ui.label(tr.get(Text::BriefingBody));
+            ui.separator();
+
+            if let Some(seed) = challenge_seed {
+                ui.label(tr.format(
+                    Text::ChallengeSeed,
+                    &[&seed, &tr.get(difficulty_text(settings.difficulty))],
+                ));
+                ui.separator();
+            }
+
+            if let Some(seconds) = saved_run_seconds {
+                if ui
+                    .button(tr.format(Text::ContinueRun, &[&format!("{:.0}", seconds)]))
+                    .on_hover_text(tr.get(Text::ContinueRunTooltip))
+                    .clicked()
+                {
+                    action = GuiAction::ContinueRun;
+                }
+            }
+            if !save_slots.is_empty()
+                && ui
+                    .button(tr.get(Text::SavedGames))
+                    .on_hover_text(tr.format(Text::SavedGamesTooltip, &[&NAMED_SLOTS]))
+                    .clicked()
+            {
+                gui_state.saves_open = true;
+            }
+            if let Some(seconds) = saved_replay_seconds {
+                if ui
+                    .button(tr.format(Text::WatchReplay, &[&format!("{:.0}", seconds)]))
+                    .on_hover_text(tr.get(Text::WatchReplayTooltip))
+                    .clicked()
+                {
+                    action = GuiAction::WatchReplay;
+                }
+            }
+            if saved_run_seconds.is_some()
+                || !save_slots.is_empty()
+                || saved_replay_seconds.is_some()
+            {
+                ui.separator();
+            }
+
+            ui.label(tr.get(Text::Mode));
+            ui.horizontal(|ui| {
+                for mode in GameMode::iter() {
+                    let tooltip = match mode.contract() {
+                        Some(contract) => tr.format(
+                            Text::DefenseContractTooltip,
+                            &[&format!("{:.0}", contract.duration_seconds / 60.0)],
+                        ),
+                        None => tr.get(Text::EndlessTooltip).to_string(),
+                    };
+                    ui.radio_value(&mut settings.game_mode, mode, tr.get(mode_text(mode)))
+                        .on_hover_text(tooltip);
+                }
+            });
+
+            ui.label(tr.get(Text::Difficulty));
+            ui.horizontal(|ui| {
+                for difficulty in Difficulty::iter() {
+                    let name = difficulty_text(difficulty);
+                    ui.radio_value(&mut settings.difficulty, difficulty, tr.get(name))
+                        .on_hover_text(tr.get(Text::DifficultyTooltip));
+                }
+            });
+
+            if ui
+                .button(tr.get(Text::Start))
+                .on_hover_text(tr.get(Text::StartTooltip))
+                .clicked()
+            {
+                action = GuiAction::Start;
+            }
+            if ui
+                .button(tr.get(Text::Tutorial))
+                .on_hover_text(tr.get(Text::TutorialTooltip))
+                .clicked()
+            {
+                action = GuiAction::StartTutorial;
+            }
+            let today = daily::today();
+            let daily_tooltip = if daily_results.of(&today).is_some() {
+                Text::DailyPracticeTooltip
+            } else {
+                Text::DailyScoredTooltip
+            };
+            if ui
+                .button(tr.get(Text::DailyChallenge))
+                .on_hover_text(tr.format(daily_tooltip, &[&today]))
+                .clicked()
+            {
+                action = GuiAction::StartDaily;
+            }
+            if has_scenarios
+                && ui
+                    .button(tr.get(Text::Scenarios))
+                    .on_hover_text(tr.get(Text::ScenariosTooltip))
+                    .clicked()
+            {
+                gui_state.scenarios_open = true;
+            }
+            if ui
+                .button(tr.get(Text::HighScores))
+                .on_hover_text(tr.get(Text::HighScoresTooltip))
+                .clicked()
+            {
+                gui_state.high_scores_open = true;
+            }
+            if ui
+                .button(tr.get(Text::DailyResults))
+                .on_hover_text(tr.get(Text::DailyResultsTooltip))
+                .clicked()
+            {
+                gui_state.daily_results_open = true;
+            }
+            if ui
+                .button(tr.get(Text::Achievements))
+                .on_hover_text(tr.get(Text::AchievementsTooltip))
+                .clicked()
+            {
+                gui_state.achievements_open = true;
+            }
+            if ui
+                .button(tr.get(Text::Settings))
+                .on_hover_text(tr.get(Text::SettingsTooltip))
+                .clicked()
+            {
+                gui_state.settings_open = true;
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .button(tr.get(Text::Quit))
+                .on_hover_text(tr.get(Text::QuitTooltip))
+                .clicked()
+            {
+                action = GuiAction::Quit;
+            }
+
+            ui.separator();
+            ui.label(tr.format(
+                Text::Best,
+                &[&format!("{:.0}", high_score.best_survival_seconds())],
+            ));
+            ui.label(tr.format(Text::BestScore, &[&tr.number(high_score.best_points())]));
+            ui.separator();
+            build_info(ui, tr, None);
+        });
+    });
+
+    action
+}
+
+fn mode_text(mode: GameMode) -> Text {
+    match mode {
+        GameMode::Endless => Text::Endless,
+        GameMode::DefenseContract => Text::DefenseContract,
+    }
+}
+
+fn medal_text(medal: Medal) -> Text {
+    match medal {
+        Medal::Bronze => Text::Bronze,
+        Medal::Silver => Text::Silver,
+        Medal::Gold => Text::Gold,
+    }
+}
+
+/// The name and description of an achievement. The description doubles as the hint while it
+/// is locked.
+fn achievement_text(achievement: AchievementId) -> (Text, Text) {
+    match achievement {
+        AchievementId::FirstInterception => {
+            (Text::FirstInterception, Text::FirstInterceptionDescription)
+        }
+        AchievementId::TenInOneRun => (Text::TenInOneRun, Text::TenInOneRunDescription),
+        AchievementId::BossHunter => (Text::BossHunter, Text::BossHunterDescription),
+        AchievementId::CleanThreeMinutes => {
+            (Text::CleanThreeMinutes, Text::CleanThreeMinutesDescription)
+        }
+        AchievementId::TenMinutes => (Text::TenMinutes, Text::TenMinutesDescription),
+        AchievementId::LeanContract => (Text::LeanContract, Text::LeanContractDescription),
+        AchievementId::GoldContract => (Text::GoldContract, Text::GoldContractDescription),
+    }
+}
+
+fn difficulty_text(difficulty: Difficulty) -> Text {
+    match difficulty {
+        Difficulty::Easy => Text::Easy,
+        Difficulty::Normal => Text::Normal,
+        Difficulty::Hard => Text::Hard,
+    }
+}
+
+/// The version and commit the game was built from, and the run's seed once there is one, so
+/// bug reports and screenshots say exactly what was played.
+fn build_info(ui: &mut egui::Ui, tr: Strings, seed: Option<u64>) {
+    ui.horizontal(|ui| {
+        ui.label(tr.format(
+            Text::Version,
+            &[&env!("CARGO_PKG_VERSION"), &env!("GIT_HASH")],
+        ));
+        if let Some(seed) = seed {
+            ui.label(tr.format(Text::RunSeed, &[&seed]));
+            if ui
+                .small_button(tr.get(Text::CopySeed))
+                .on_hover_text(tr.get(Text::CopySeedTooltip))
+                .clicked()
+            {
+                ui.output().copied_text = seed.to_string();
+            }
+        }
+    });
+}
+
+/// Wide enough for three capital letters.
+const INITIALS_FIELD_WIDTH: f32 = 48.0;
+
+/// The leaderboard, best first, with the run played last highlighted.
+fn high_scores_window(
+    egui_ctx: &CtxRef,
+    tr: Strings,
+    theme: &Theme,
+    leaderboard: &Leaderboard,
+    gui_state: &mut GuiState,
+) {
+    let mut high_scores_open = gui_state.high_scores_open;
+    egui::Window::new(tr.get(Text::HighScores))
+        .open(&mut high_scores_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx, |ui| {
+            if leaderboard.entries().is_empty() {
+                ui.label(tr.get(Text::NoHighScores));
+                return;
+            }
+
+            let most_recent = leaderboard.most_recent();
+            egui::Grid::new("high_scores").striped(true).show(ui, |ui| {
+                for heading in &[
+                    Text::RankColumn,
+                    Text::InitialsColumn,
+                    Text::PointsColumn,
+                    Text::TimeColumn,
+                    Text::Difficulty,
+                    Text::SeedColumn,
+                    Text::DateColumn,
+                ] {
+                    ui.add(egui::Label::new(tr.get(*heading)).strong());
+                }
+                ui.end_row();
+
+                for (index, entry) in leaderboard.entries().iter().enumerate() {
+                    let cells = [
+                        (index + 1).to_string(),
+                        // Runs whose player left without typing their initials in.
+                        if entry.initials.is_empty() {
+                            "---".to_string()
+                        } else {
+                            entry.initials.clone()
+                        },
+                        tr.number(entry.points),
+                        tr.format(
+                            Text::SecondsValue,
+                            &[&format!("{:.0}", entry.survived_seconds)],
+                        ),
+                        if entry.new_game_plus > 0 {
+                            format!(
+                                "{} {}",
+                                tr.get(difficulty_text(entry.difficulty)),
+                                tr.format(Text::NewGamePlusLevel, &[&entry.new_game_plus])
+                            )
+                        } else {
+                            tr.get(difficulty_text(entry.difficulty)).to_string()
+                        },
+                        entry.seed.to_string(),
+                        entry.date(),
+                    ];
+                    for cell in cells.iter() {
+                        if most_recent == Some(index) {
+                            ui.colored_label(theme.highlight, cell);
+                        } else {
+                            ui.label(cell.as_str());
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    gui_state.high_scores_open = high_scores_open;
+}
+
+/// The scored attempts at the daily challenges of the last days, newest first.
+fn daily_results_window(
+    egui_ctx: &CtxRef,
+    tr: Strings,
+    daily_results: &DailyResults,
+    gui_state: &mut GuiState,
+) {
+    let mut daily_results_open = gui_state.daily_results_open;
+    egui::Window::new(tr.get(Text::DailyResults))
+        .open(&mut daily_results_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx, |ui| {
+            if daily_results.days().is_empty() {
+                ui.label(tr.get(Text::NoDailyResults));
+                return;
+            }
+
+            egui::Grid::new("daily_results")
+                .striped(true)
+                .show(ui, |ui| {
+                    for heading in &[
+                        Text::DateColumn,
+                        Text::PointsColumn,
+                        Text::TimeColumn,
+                        Text::SeedColumn,
+                    ] {
+                        ui.add(egui::Label::new(tr.get(*heading)).strong());
+                    }
+                    ui.end_row();
+
+                    for day in daily_results.days() {
+                        ui.label(day.date.as_str());
+                        match day.outcome {
+                            Some(outcome) => {
+                                ui.label(tr.number(outcome.points));
+                                ui.label(tr.format(
+                                    Text::SecondsValue,
+                                    &[&format!("{:.0}", outcome.survived_seconds)],
+                                ));
+                            }
+                            // Claimed when it started, but given up or still going.
+                            None => {
+                                ui.label(tr.get(Text::DailyUnfinished));
+                                ui.label("");
+                            }
+                        }
+                        ui.label(day.seed.to_string());
+                        ui.end_row();
+                    }
+                });
+        });
+    gui_state.daily_results_open = daily_results_open;
+}
+
+/// The scenarios on offer, each with what it is about and a button to play it. Returns the one
+/// the player chose, if any.
+fn scenarios_window(
+    egui_ctx: &CtxRef,
+    tr: Strings,
+    scenarios: &[Scenario],
+    gui_state: &mut GuiState,
+) -> Option<GuiAction> {
+    let mut action = None;
+    let mut scenarios_open = gui_state.scenarios_open;
+    egui::Window::new(tr.get(Text::Scenarios))
+        .open(&mut scenarios_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx, |ui| {
+            for (index, scenario) in scenarios.iter().enumerate() {
+                if index > 0 {
+                    ui.separator();
+                }
+                ui.heading(&scenario.name);
+                ui.label(&scenario.description);
+                ui.label(match scenario.goal {
+                    Goal::SurviveSeconds(seconds) => {
+                        tr.format(Text::ScenarioSurviveGoal, &[&format!("{:.0}", seconds)])
+                    }
+                    Goal::InterceptAll => tr.get(Text::ScenarioInterceptAllGoal).to_string(),
+                });
+                ui.label(tr.get(difficulty_text(scenario.difficulty)));
+                if ui
+                    .button(tr.get(Text::PlayScenario))
+                    .on_hover_text(tr.get(Text::PlayScenarioTooltip))
+                    .clicked()
+                {
+                    action = Some(GuiAction::StartScenario(index));
+                }
+            }
+        });
+    gui_state.scenarios_open = scenarios_open && action.is_none();
+    action
+}
+
+/// The slots the player saves runs into, each with buttons to save the current run there, load
+/// the run in it or delete that. Saving over a run and deleting one are confirmed first, and so
+/// is loading while a run is going. Saving is only offered if `can_save`.
+fn saves_window(
+    egui_ctx: &CtxRef,
+    tr: Strings,
+    game_state: &GameState,
+    save_slots: &SaveSlots,
+    can_save: bool,
+    gui_state: &mut GuiState,
+) -> Option<GuiAction> {
+    if gui_state.pending_confirmation.is_some() {
+        return None;
+    }
+
+    let mut action = None;
+    let mut saves_open = gui_state.saves_open;
+    egui::Window::new(tr.get(Text::SavedGames))
+        .open(&mut saves_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx, |ui| {
+            if can_save {
+                ui.horizontal(|ui| {
+                    ui.label(tr.get(Text::SaveName));
+                    ui.text_edit_singleline(&mut gui_state.save_name);
+                });
+                ui.separator();
+            }
+
+            egui::Grid::new("save_slots").striped(true).show(ui, |ui| {
+                for heading in &[
+                    Text::RankColumn,
+                    Text::NameColumn,
+                    Text::DateColumn,
+                    Text::TimeColumn,
+                    Text::Difficulty,
+                ] {
+                    ui.add(egui::Label::new(tr.get(*heading)).strong());
+                }
+                ui.end_row();
+
+                for (index, summary) in save_slots.slots().iter().enumerate() {
+                    ui.label((index + 1).to_string());
+                    match summary {
+                        Some(summary) => {
+                            ui.label(summary.name.as_str());
+                            ui.label(summary.saved_at());
+                            ui.label(tr.format(
+                                Text::SecondsValue,
+                                &[&format!("{:.0}", summary.play_seconds)],
+                            ));
+                            ui.label(tr.get(difficulty_text(summary.difficulty)));
+                        }
+                        None => {
+                            ui.colored_label(
+                                ui.visuals().weak_text_color(),
+                                tr.get(Text::EmptySlot),
+                            );
+                            ui.label("");
+                            ui.label("");
+                            ui.label("");
+                        }
+                    }
+
+                    ui.horizontal(|ui| {
+                        if can_save
+                            && ui
+                                .button(tr.get(Text::SaveGame))
+                                .on_hover_text(tr.get(Text::SaveGameTooltip))
+                                .clicked()
+                        {
+                            if summary.is_some() {
+                                gui_state.pending_confirmation = Some(PendingConfirmation {
+                                    action: GuiAction::SaveGame(index),
+                                    resume_on_cancel: false,
+                                });
+                            } else {
+                                action = Some(GuiAction::SaveGame(index));
+                            }
+                        }
+                        if summary.is_some()
+                            && ui
+                                .button(tr.get(Text::Load))
+                                .on_hover_text(tr.get(Text::LoadGameTooltip))
+                                .clicked()
+                        {
+                            action = Some(confirm_first(
+                                GuiAction::LoadGame(index),
+                                game_state,
+                                &mut gui_state.pending_confirmation,
+                            ));
+                        }
+                        if summary.is_some()
+                            && ui
+                                .button(tr.get(Text::DeleteSave))
+                                .on_hover_text(tr.get(Text::DeleteSaveTooltip))
+                                .clicked()
+                        {
+                            gui_state.pending_confirmation = Some(PendingConfirmation {
+                                action: GuiAction::DeleteSave(index),
+                                resume_on_cancel: false,
+                            });
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+    gui_state.saves_open = saves_open;
+
+    if let Some(GuiAction::LoadGame(_)) = action {
+        gui_state.saves_open = false;
+    }
+    action
+}
+
+/// Asks about the action waiting for the player to confirm it, if there is one. Returns what
+/// to do once they answered.
+fn confirmation_dialog(
+    egui_ctx: &CtxRef,
+    tr: Strings,
+    play_time: &PlayTime,
+    save_slots: &SaveSlots,
+    gui_state: &mut GuiState,
+) -> Option<GuiAction> {
+    let pending = gui_state.pending_confirmation?;
+    let run_will_be_lost = || {
+        tr.format(
+            Text::RunWillBeLost,
+            &[&format!("{:.0}", play_time.seconds())],
+        )
+    };
+    let slot_message = |text: Text, slot: usize| match save_slots.get(slot) {
+        Some(summary) => tr.format(text, &[&summary.name, &summary.saved_at()]),
+        None => String::new(),
+    };
+    let dialog = match pending.action {
+        GuiAction::Quit => ConfirmDialog::new(tr.get(Text::QuitQuestion), run_will_be_lost())
+            .confirm_label(tr.get(Text::Quit)),
+        GuiAction::LoadGame(_) => {
+            ConfirmDialog::new(tr.get(Text::LoadQuestion), run_will_be_lost())
+                .confirm_label(tr.get(Text::Load))
+        }
+        GuiAction::SaveGame(slot) => ConfirmDialog::new(
+            tr.get(Text::OverwriteQuestion),
+            slot_message(Text::OverwriteMessage, slot),
+        )
+        .confirm_label(tr.get(Text::SaveGame)),
+        GuiAction::DeleteSave(slot) => ConfirmDialog::new(
+            tr.get(Text::DeleteQuestion),
+            slot_message(Text::DeleteMessage, slot),
+        )
+        .confirm_label(tr.get(Text::DeleteSave)),
+        _ => ConfirmDialog::new(tr.get(Text::RestartQuestion), run_will_be_lost())
+            .confirm_label(tr.get(Text::Restart)),
+    };
+
+    match dialog.show(egui_ctx, tr) {
+        ConfirmResponse::Pending => None,
+        ConfirmResponse::Confirmed => {
+            gui_state.pending_confirmation = None;
+            if let GuiAction::LoadGame(_) = pending.action {
+                gui_state.saves_open = false;
+            }
+            Some(pending.action)
+        }
+        ConfirmResponse::Cancelled => {
+            gui_state.pending_confirmation = None;
+            Some(pending.cancel())
+        }
+    }
+}
+
+/// Every achievement, the locked ones greyed out.
+fn achievements_window(
+    egui_ctx: &CtxRef,
+    tr: Strings,
+    achievements: &Achievements,
+    gui_state: &mut GuiState,
+) {
+    let mut achievements_open = gui_state.achievements_open;
+    egui::Window::new(tr.get(Text::Achievements))
+        .open(&mut achievements_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx, |ui| {
+            let unlocked = ACHIEVEMENTS
+                .iter()
+                .filter(|achievement| achievements.is_unlocked(achievement.id))
+                .count();
+            ui.label(tr.format(
+                Text::AchievementsUnlocked,
+                &[&unlocked, &ACHIEVEMENTS.len()],
+            ));
+            ui.separator();
+
+            egui::Grid::new("achievements")
+                .striped(true)
+                .show(ui, |ui| {
+                    let locked_color = ui.visuals().weak_text_color();
+                    for achievement in ACHIEVEMENTS {
+                        let (name, description) = achievement_text(achievement.id);
+                        if achievements.is_unlocked(achievement.id) {
+                            ui.add(egui::Label::new(tr.get(name)).strong());
+                            ui.label(tr.get(description));
+                        } else {
+                            ui.colored_label(locked_color, tr.get(name));
+                            ui.colored_label(locked_color, tr.get(description));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    gui_state.achievements_open = achievements_open;
+}
+
+fn settings_window(egui_ctx: &CtxRef, settings: &mut Settings, gui_state: &mut GuiState) {
+    let tr = Strings::new(settings.language);
+    let mut settings_open = gui_state.settings_open;
+    egui::Window::new(tr.get(Text::Settings))
+        .open(&mut settings_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr.get(Text::Language));
+                for language in Language::iter() {
+                    ui.radio_value(&mut settings.language, language, language.to_string());
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr.get(Text::ColorScheme))
+                    .on_hover_text(tr.get(Text::ColorSchemeTooltip));
+                for color_scheme in ColorScheme::iter() {
+                    let name = match color_scheme {
+                        ColorScheme::DarkPurple => Text::DarkPurple,
+                        ColorScheme::Light => Text::Light,
+                        ColorScheme::HighContrast => Text::HighContrast,
+                    };
+                    ui.radio_value(&mut settings.color_scheme, color_scheme, tr.get(name));
+                }
+            });
+
+            let mut ui_scale = settings
+                .ui_scale
+                .unwrap_or_else(|| egui_ctx.pixels_per_point());
+            if ui
+                .add(
+                    egui::Slider::new(&mut ui_scale, Settings::UI_SCALES)
+                        .text(tr.get(Text::UiScale)),
+                )
+                .on_hover_text(tr.get(Text::UiScaleTooltip))
+                .changed()
+            {
+                settings.ui_scale = Some(ui_scale);
+            }
+
+            ui.horizontal(|ui| {
+                ui.set_enabled(engine::fullscreen::is_supported());
+                let mut fullscreen = engine::fullscreen::is_fullscreen();
+                if ui
+                    .checkbox(&mut fullscreen, tr.get(Text::Fullscreen))
+                    .on_hover_text(tr.get(Text::FullscreenTooltip))
+                    .changed()
+                {
+                    engine::fullscreen::set_fullscreen(fullscreen);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.set_enabled(engine::focus::is_supported());
+                ui.checkbox(
+                    &mut settings.pause_on_focus_loss,
+                    tr.get(Text::PauseOnFocusLoss),
+                )
+                .on_hover_text(tr.get(Text::PauseOnFocusLossTooltip));
+            });
+
+            ui.add(
+                egui::Slider::new(&mut settings.sound_volume, Settings::VOLUMES)
+                    .text(tr.get(Text::SoundVolume)),
+            )
+            .on_hover_text(tr.get(Text::SoundVolumeTooltip));
+            ui.checkbox(&mut settings.muted, tr.get(Text::Mute))
+                .on_hover_text(tr.get(Text::MuteTooltip));
+            ui.add(
+                egui::Slider::new(&mut settings.music_volume, Settings::VOLUMES)
+                    .text(tr.get(Text::MusicVolume)),
+            )
+            .on_hover_text(tr.get(Text::MusicVolumeTooltip));
+            ui.checkbox(&mut settings.music_muted, tr.get(Text::MuteMusic))
+                .on_hover_text(tr.get(Text::MuteMusicTooltip));
+
+            ui.checkbox(&mut settings.large_ui, tr.get(Text::LargeUi))
+                .on_hover_text(tr.get(Text::LargeUiTooltip));
+            let mut touch_layout = settings.uses_touch_layout();
+            if ui
+                .checkbox(&mut touch_layout, tr.get(Text::TouchLayout))
+                .on_hover_text(tr.get(Text::TouchLayoutTooltip))
+                .changed()
+            {
+                settings.touch_layout = Some(touch_layout);
+            }
+            ui.checkbox(&mut settings.colorblind_mode, tr.get(Text::ColorblindMode))
+                .on_hover_text(tr.get(Text::ColorblindModeTooltip));
+            ui.checkbox(&mut settings.auto_park, tr.get(Text::AutoPark))
+                .on_hover_text(tr.get(Text::AutoParkTooltip));
+            ui.checkbox(&mut settings.show_fps, tr.get(Text::ShowFps))
+                .on_hover_text(tr.get(Text::ShowFpsTooltip));
+            ui.checkbox(&mut settings.show_debug_overlay, tr.get(Text::DebugOverlay))
+                .on_hover_text(tr.get(Text::DebugOverlayTooltip));
+            if cfg!(feature = "online-leaderboard") {
+                online_leaderboard_settings(ui, tr, settings);
+            }
+
+            ui.collapsing(tr.get(Text::Controls), |ui| {
+                key_bindings(ui, tr, settings, gui_state);
+            });
+        });
+    gui_state.settings_open = settings_open;
+}
+
+/// Whether runs go to the online leaderboard, and under which initials.
+fn online_leaderboard_settings(ui: &mut egui::Ui, tr: Strings, settings: &mut Settings) {
+    ui.checkbox(
+        &mut settings.online_leaderboard,
+        tr.get(Text::OnlineLeaderboard),
+    )
+    .on_hover_text(tr.get(Text::OnlineLeaderboardTooltip));
+    ui.horizontal(|ui| {
+        ui.set_enabled(settings.online_leaderboard);
+        ui.label(tr.get(Text::OnlineInitials));
+        ui.add(
+            egui::TextEdit::singleline(&mut settings.online_initials)
+                .desired_width(INITIALS_FIELD_WIDTH),
+        )
+        .on_hover_text(tr.get(Text::OnlineInitialsTooltip));
+        settings.online_initials = LeaderboardEntry::clean_initials(&settings.online_initials);
+    });
+}
+
+/// Every action with the key bound to it. Clicking a key waits for the new one.
+fn key_bindings(ui: &mut egui::Ui, tr: Strings, settings: &mut Settings, gui_state: &mut GuiState) {
+    egui::Grid::new("key_bindings")
+        .striped(true)
+        .show(ui, |ui| {
+            for &(action, description) in ACTIONS {
+                ui.label(description);
+                let key = if gui_state.rebinding == Some(action) {
+                    tr.get(Text::PressAKey).to_string()
+                } else {
+                    settings
+                        .key_bindings
+                        .key(action)
+                        .map_or_else(|| "–".to_string(), |key| key.to_string())
+                };
+                if ui
+                    .button(key)
+                    .on_hover_text(tr.get(Text::RebindTooltip))
+                    .clicked()
+                {
+                    gui_state.rebinding = Some(action);
+                }
+                ui.end_row();
+            }
+        });
+
+    if ui
+        .button(tr.get(Text::ResetControls))
+        .on_hover_text(tr.get(Text::ResetControlsTooltip))
+        .clicked()
+    {
+        settings.key_bindings = default_bindings();
+        gui_state.rebinding = None;
+    }
+}
+
+/// The toasts, stacked below each other in the top right corner and fading out as they expire.
+fn toast_stack(egui_ctx: &CtxRef, tr: Strings, theme: &Theme, toasts: &Toasts, time: &Time) {
+    if toasts.visible().is_empty() {
+        return;
+    }
+
+    let now = time.real_seconds();
+    egui::Area::new("toasts")
+        .anchor(Align2::RIGHT_TOP, Vec2::new(-16.0, 48.0))
+        .interactable(false)
+        .show(egui_ctx, |ui| {
+            for toast in toasts.visible() {
+                let opacity = toast.opacity(now);
+                let color = match toast.severity {
+                    Severity::Info => ui.visuals().text_color(),
+                    Severity::Warning => theme.warning,
+                    Severity::Critical => theme.critical,
+                };
+                let text = match &toast.message {
+                    ToastMessage::UnitDestroyed { unit } => {
+                        tr.format(Text::ToastUnitDestroyed, &[unit])
+                    }
+                    ToastMessage::BossLeaked {
+                        combat_type,
+                        parking_spaces_left,
+                    } => tr.format(Text::ToastBossLeaked, &[combat_type, parking_spaces_left]),
+                    ToastMessage::ScreenshotSaved { file_name } => {
+                        tr.format(Text::ToastScreenshotSaved, &[file_name])
+                    }
+                    ToastMessage::ScreenshotFailed { reason } => {
+                        tr.format(Text::ToastScreenshotFailed, &[reason])
+                    }
+                    ToastMessage::GameSaved { seconds } => {
+                        tr.format(Text::ToastGameSaved, &[&format!("{:.0}", seconds)])
+                    }
+                    ToastMessage::LoadFailed { reason } => {
+                        tr.format(Text::ToastLoadFailed, &[reason])
+                    }
+                    ToastMessage::ReplaySaved => tr.get(Text::ToastReplaySaved).to_string(),
+                    ToastMessage::ReplayDiverged { seconds } => {
+                        tr.format(Text::ToastReplayDiverged, &[&format!("{:.0}", seconds)])
+                    }
+                    ToastMessage::AchievementUnlocked { achievement } => {
+                        let (name, _) = achievement_text(*achievement);
+                        tr.format(Text::ToastAchievementUnlocked, &[&tr.get(name)])
+                    }
+                };
+
+                let mut frame = egui::Frame::popup(ui.style());
+                frame.fill = frame.fill.linear_multiply(opacity);
+                frame.stroke.color = color.linear_multiply(opacity);
+                frame.shadow.color = frame.shadow.color.linear_multiply(opacity);
+                frame.show(ui, |ui| {
+                    ui.colored_label(color.linear_multiply(opacity), text);
+                });
+            }
+        });
+}
+
+/// The line below the menu bar: whether it shows a replay or a daily challenge, how long the run
+/// has lasted and how long a contract has left, when the next enemy is due and how hard they are
+/// coming.
+fn status_header(
+    ui: &mut egui::Ui,
+    tr: Strings,
+    theme: &Theme,
+    view: &GameView<'_>,
+    flash_multiplier: bool,
+    show_eta: bool,
+) {
+    let GameView {
+        play_time,
+        score,
+        enemy_spawner,
+        daily,
+        watching_replay,
+        ..
+    } = *view;
+    ui.horizontal(|ui| {
+        if watching_replay {
+            ui.colored_label(theme.highlight, tr.get(Text::WatchingReplay));
+            ui.label("—");
+        }
+        let new_game_plus = enemy_spawner.new_game_plus();
+        if new_game_plus > 0 {
+            ui.colored_label(
+                theme.highlight,
+                tr.format(Text::NewGamePlusLevel, &[&new_game_plus]),
+            )
+            .on_hover_text(tr.get(Text::NewGamePlusTooltip));
+            ui.label("—");
+        }
+        if let Some(daily) = daily {
+            let (label, tooltip) = if daily.scored {
+                (Text::DailyLabel, Text::DailyScoredTooltip)
+            } else {
+                (Text::DailyPracticeLabel, Text::DailyPracticeTooltip)
+            };
+            ui.colored_label(theme.highlight, tr.format(label, &[&daily.date]))
+                .on_hover_text(tr.format(tooltip, &[&daily.date]));
+            ui.label("—");
+        }
+        ui.label(tr.format(Text::Survived, &[&format!("{:.0}", play_time.seconds())]));
+        if let Some(contract) = enemy_spawner.mode().contract() {
+            let seconds_left = (contract.duration_seconds - play_time.seconds()).max(0.0);
+            ui.label(tr.format(
+                Text::ContractTimeLeft,
+                &[&format!("{:.0}", seconds_left.ceil())],
+            ));
+        }
+        ui.label("—");
+        let score_text = tr.format(
+            Text::Score,
+            &[
+                &tr.number(score.points(play_time.seconds())),
+                &score.multiplier(),
+            ],
+        );
+        if flash_multiplier {
+            ui.colored_label(theme.highlight, score_text);
+        } else {
+            ui.label(score_text);
+        }
+        if show_eta {
+            ui.label("—");
+            ui.label(tr.format(
+                Text::NextContact,
+                &[&format!("{:.0}", enemy_spawner.next_spawn_eta())],
+            ));
+        }
+        ui.label("—");
+        ui.label(tr.get(Text::Pressure));
+        pressure_bar(ui, theme, enemy_spawner.current_pressure())
+            .on_hover_text(tr.get(Text::PressureTooltip));
+    });
+}
+
+/// How long the score lights up when its multiplier changes, in real seconds.
+const MULTIPLIER_FLASH_SECONDS: f64 = 0.6;
+
+const PRESSURE_SEGMENTS: usize = 5;
+const PRESSURE_SEGMENT_SIZE: Vec2 = Vec2 { x: 10.0, y: 12.0 };
+
+/// How many segments of the pressure bar are lit. Any pressure at all lights the first one.
+fn pressure_segments(pressure: f32) -> usize {
+    (pressure.clamp(0.0, 1.0) * PRESSURE_SEGMENTS as f32).ceil() as usize
+}
+
+/// The spawn pressure as a row of segments, the last two of which are in the critical colour.
+fn pressure_bar(ui: &mut egui::Ui, theme: &Theme, pressure: f32) -> egui::Response {
+    let size = Vec2::new(
+        PRESSURE_SEGMENT_SIZE.x * PRESSURE_SEGMENTS as f32,
+        PRESSURE_SEGMENT_SIZE.y,
+    );
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let lit = pressure_segments(pressure);
+
+    let painter = ui.painter();
+    for segment in 0..PRESSURE_SEGMENTS {
+        let min = rect.min + Vec2::new(PRESSURE_SEGMENT_SIZE.x * segment as f32, 0.0);
+        let segment_rect =
+            Rect::from_min_size(min, PRESSURE_SEGMENT_SIZE).shrink2(Vec2::new(1.0, 0.0));
+        let color = if segment >= lit {
+            theme.lane
+        } else if segment + 2 >= PRESSURE_SEGMENTS {
+            theme.critical
+        } else {
+            theme.warning
+        };
+        painter.rect_filled(segment_rect, 1.0, color);
+    }
+
+    response
+}
+
+const BASE_PANEL_WIDTH: f32 = 480.0;
+/// The most of the window's width the base panel may take up.
+const BASE_PANEL_SHARE: f32 = 0.45;
+const LANE_HEIGHT: f32 = 32.0;
+/// How much taller lanes are in the touch layout, so the markers can be made out on a phone.
+const TOUCH_LANE_SCALE: f32 = 1.5;
+/// Room for the glyph and letter in front of each lane.
+const LANE_HEADING_WIDTH: f32 = 48.0;
+const BATTLE_LOG_HEIGHT: f32 = 160.0;
+/// Room at either end of a lane for the base and spawn labels.
+const LANE_END_WIDTH: f32 = 48.0;
+const LANE_TICK_HEIGHT: f32 = 6.0;
+const BLINKS_PER_SECOND: f64 = 2.0;
+
+/// The colour of a lane's heading. Lanes about to be breached, or with an open alert, blink.
+fn danger_color(theme: &Theme, level: DangerLevel, alerted: bool, time: &Time) -> Color32 {
+    if alerted {
+        return blink(time, theme.critical);
+    }
+
+    match level {
+        DangerLevel::Clear => theme.lane_furniture,
+        DangerLevel::Covered => theme.warning,
+        DangerLevel::Outnumbered => theme.critical,
+        DangerLevel::Breaching => blink(time, theme.critical),
+    }
+}
+
+/// Switches the colour on and off. Goes by real time, so it blinks just as fast in slow motion.
+fn blink(time: &Time, color: Color32) -> Color32 {
+    let phase = (time.real_seconds() * BLINKS_PER_SECOND * 2.0) as u64;
+    if phase.is_multiple_of(2) {
+        color
+    } else {
+        color.linear_multiply(0.3)
+    }
+}
+
+/// Draws a lane's background, track, tick marks and end labels, and returns the part of `rect`
+/// that units and enemies travel along.
+fn paint_lane(
+    painter: &egui::Painter,
+    rect: Rect,
+    theme: &Theme,
+    tr: Strings,
+    uncovered: bool,
+    in_danger: bool,
+) -> Rect {
+    let background = if in_danger {
+        theme.enemy.linear_multiply(0.35)
+    } else if uncovered {
+        theme.enemy.linear_multiply(0.15)
+    } else {
+        theme.lane
+    };
+    painter.rect_filled(rect, 2.0, background);
+
+    let track = rect.shrink2(Vec2::new(LANE_END_WIDTH, 0.0));
+    let y = track.center().y;
+    let stroke = Stroke::new(1.0, theme.lane_furniture);
+
+    painter.line_segment(
+        [Pos2::new(track.left(), y), Pos2::new(track.right(), y)],
+        stroke,
+    );
+    for fraction in &[0.25, 0.5, 0.75] {
+        let x = track.left() + track.width() * fraction;
+        painter.line_segment(
+            [
+                Pos2::new(x, y - LANE_TICK_HEIGHT),
+                Pos2::new(x, y + LANE_TICK_HEIGHT),
+            ],
+            stroke,
+        );
+    }
+
+    painter.text(
+        Pos2::new(rect.left() + 4.0, y),
+        Align2::LEFT_CENTER,
+        tr.get(Text::LaneBase),
+        TextStyle::Small,
+        theme.lane_furniture,
+    );
+    painter.text(
+        Pos2::new(rect.right() - 4.0, y),
+        Align2::RIGHT_CENTER,
+        tr.get(Text::LaneSpawn),
+        TextStyle::Small,
+        theme.lane_furniture,
+    );
+
+    track
+}
+
+const TIMELINE_SIZE: Vec2 = Vec2 { x: 360.0, y: 120.0 };
+const TIMELINE_TICK_HEIGHT: f32 = 4.0;
+
+/// One line of the timeline chart: its color, its name and what it counts in each sample.
+type TimelineSeries = (Color32, Text, fn(&TimelineSample) -> u32);
+
+/// The run over time: the score as a shaded area on a scale of its own, and as lines on a
+/// shared scale the enemies on the field, the units in the air and the parking spaces in use.
+/// Leaks and lost units are marked, and each minute is ticked off. `end_seconds` is how long
+/// the run lasted.
+fn timeline_chart(
+    ui: &mut egui::Ui,
+    tr: Strings,
+    theme: &Theme,
+    timeline: &Timeline,
+    end_seconds: f64,
+) {
+    let samples = timeline.samples();
+    let last = match samples.last() {
+        Some(last) => last,
+        None => return,
+    };
+
+    let score_color = theme.highlight.linear_multiply(0.3);
+    let series: [TimelineSeries; 3] = [
+        (theme.enemy, Text::TimelineEnemies, |sample| sample.enemies),
+        (theme.patrolling, Text::TimelineAirborne, |sample| {
+            sample.airborne
+        }),
+        (theme.lane_furniture, Text::TimelineParking, |sample| {
+            sample.parking_used
+        }),
+    ];
+    ui.horizontal_wrapped(|ui| {
+        ui.colored_label(score_color, format!("■ {}", tr.get(Text::TimelineScore)));
+        for &(color, text, _) in series.iter() {
+            ui.colored_label(color, format!("■ {}", tr.get(text)));
+        }
+        ui.colored_label(theme.critical, format!("| {}", tr.get(Text::TimelineLeak)));
+        ui.colored_label(
+            theme.warning,
+            format!("| {}", tr.get(Text::TimelineUnitLost)),
+        );
+    });
+
+    let size = Vec2::new(ui.available_width().min(TIMELINE_SIZE.x), TIMELINE_SIZE.y);
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 1.0, ui.visuals().extreme_bg_color);
+
+    let end_seconds = end_seconds.max(last.at_seconds).max(1.0);
+    let x = |seconds: f64| rect.left() + (seconds / end_seconds) as f32 * rect.width();
+    let y = |fraction: f32| rect.bottom() - fraction * rect.height();
+    let max_points = samples
+        .iter()
+        .map(|sample| sample.points)
+        .max()
+        .unwrap_or(0);
+    let max_count = samples
+        .iter()
+        .map(|sample| {
+            sample
+                .enemies
+                .max(sample.airborne)
+                .max(sample.parking_capacity)
+        })
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    for (index, sample) in samples.iter().enumerate() {
+        let until = samples
+            .get(index + 1)
+            .map_or(end_seconds, |next| next.at_seconds);
+        let top = y(sample.points as f32 / max_points.max(1) as f32);
+        painter.rect_filled(
+            Rect::from_min_max(
+                Pos2::new(x(sample.at_seconds), top),
+                Pos2::new(x(until), rect.bottom()),
+            ),
+            0.0,
+            score_color,
+        );
+    }
+
+    for &(color, _, value) in series.iter() {
+        let point = |sample: &TimelineSample| {
+            Pos2::new(
+                x(sample.at_seconds),
+                y(value(sample) as f32 / max_count as f32),
+            )
+        };
+        for pair in samples.windows(2) {
+            painter.line_segment([point(&pair[0]), point(&pair[1])], Stroke::new(2.0, color));
+        }
+    }
+
+    for mark in timeline.marks() {
+        let color = match mark.kind {
+            MarkKind::Leak => theme.critical,
+            MarkKind::UnitLost => theme.warning,
+        };
+        let at = x(mark.at_seconds);
+        painter.line_segment(
+            [Pos2::new(at, rect.top()), Pos2::new(at, rect.bottom())],
+            Stroke::new(1.0, color),
+        );
+    }
+
+    let minutes = (end_seconds / 60.0) as u32;
+    for minute in 1..=minutes {
+        let at = x(f64::from(minute) * 60.0);
+        painter.line_segment(
+            [
+                Pos2::new(at, rect.bottom() - TIMELINE_TICK_HEIGHT),
+                Pos2::new(at, rect.bottom()),
+            ],
+            Stroke::new(1.0, theme.lane_furniture),
+        );
+        painter.text(
+            Pos2::new(at, rect.bottom() - TIMELINE_TICK_HEIGHT),
+            Align2::CENTER_BOTTOM,
+            tr.format(Text::TimelineMinute, &[&minute]),
+            TextStyle::Small,
+            theme.lane_furniture,
+        );
+    }
+
+    let pointer = ui.input().pointer.hover_pos();
+    if let (true, Some(pointer)) = (response.hovered(), pointer) {
+        let seconds = f64::from((pointer.x - rect.left()) / rect.width()) * end_seconds;
+        let sample = samples
+            .iter()
+            .rev()
+            .find(|sample| sample.at_seconds <= seconds)
+            .unwrap_or(&samples[0]);
+        response.on_hover_text(tr.format(
+            Text::TimelineHover,
+            &[
+                &format!("{:.0}", sample.at_seconds),
+                &tr.number(sample.points),
+                &sample.enemies,
+                &sample.airborne,
+                &sample.parking_used,
+                &sample.parking_capacity,
+            ],
+        ));
+    }
+}
+
+fn stats_grid(ui: &mut egui::Ui, tr: Strings, id_source: &str, stats: &GameStats) {
+    egui::Grid::new(id_source).striped(true).show(ui, |ui| {
+        ui.label(tr.get(Text::SortiesFlown));
+        ui.label(stats.sorties.to_string());
+        ui.end_row();
+
+        ui.label(tr.get(Text::EnemiesDestroyed));
+        ui.label(stats.total_enemies_destroyed().to_string());
+        ui.end_row();
+
+        for combat_type in CombatType::iter() {
+            ui.label(tr.format(Text::InLane, &[&combat_type.glyph(), &combat_type]));
+            ui.label(stats.enemies_destroyed(combat_type).to_string());
+            ui.end_row();
+        }
+
+        ui.label(tr.get(Text::UnitsLost));
+        ui.label(stats.units_lost.to_string());
+        ui.end_row();
+
+        ui.label(tr.get(Text::RepairsCompleted));
+        ui.label(stats.repairs_completed.to_string());
+        ui.end_row();
+
+        ui.label(tr.get(Text::ParkingUtilisation));
+        ui.label(format!("{:.0}%", stats.parking_utilization()));
+        ui.end_row();
+
+        ui.label(tr.get(Text::MostParked));
+        ui.label(stats.most_parked.to_string());
+        ui.end_row();
+
+        ui.label(tr.get(Text::BossesLeaked));
+        ui.label(stats.bosses_leaked.to_string());
+        ui.end_row();
+    });
+}
+
+/// Explains a parking-dependent button, or why it is greyed out.
+fn parking_hint(
+    tr: Strings,
+    parking_spaces: &TokenPool<ParkingSpace>,
+    hint: Text,
+    seconds: f64,
+) -> Cow<'static, str> {
+    if parking_spaces.can_take() {
+        Cow::Owned(timed_hint(tr, hint, seconds))
+    } else {
+        Cow::Borrowed(tr.get(Text::NoFreeParkingTooltip))
+    }
+}
+
+/// Explains a button whose action takes `seconds`. Only put together while it is shown.
+fn timed_hint(tr: Strings, hint: Text, seconds: f64) -> String {
+    tr.format(hint, &[&format!("{:.0}", seconds)])
+}
+
+const ROW_ICON_SIZE: f32 = 16.0;
+const PARKING_SLOT_WIDTH: f32 = 120.0;
+/// How much of a lane's height the unit and enemy icons take up.
+const MARKER_ICON_FRACTION: f32 = 0.8;
+const HEALTH_BAR_SIZE: Vec2 = Vec2 { x: 60.0, y: 6.0 };
+const TIMER_BAR_WIDTH: f32 = 150.0;
+/// Unit rows narrower than this put their buttons on a second line.
+const COMPACT_ROW_WIDTH: f32 = 420.0;
+const COMPACT_ROW_INDENT: f32 = 24.0;
+
+/// Room between and inside widgets. The touch layout makes everything at least as big as a
+/// fingertip.
+fn spacing(touch_layout: bool) -> egui::style::Spacing {
+    let mut spacing = egui::style::Spacing::default();
+    if touch_layout {
+        spacing.interact_size = Vec2::splat(TOUCH_TARGET_SIZE);
+        spacing.button_padding = Vec2::new(12.0, 8.0);
+        spacing.item_spacing = Vec2::splat(8.0);
+    }
+
+    spacing
+}
+
+/// The smallest a button can be for a finger to hit it reliably.
+const TOUCH_TARGET_SIZE: f32 = 44.0;
+
+/// The default fonts with every text style made `scale` times as big.
+fn scaled_fonts(scale: f32) -> egui::FontDefinitions {
+    let mut fonts = egui::FontDefinitions::default();
+    for (_, size) in fonts.family_and_size.values_mut() {
+        *size *= scale;
+    }
+    fonts
+}
+
+/// Traffic-light colour for the unit's health, so damaged units stand out at a glance.
+/// Critical units pulse so they can't be missed.
+fn health_color(ctx: &CtxRef, health: &Health, damage_per_hit: f64) -> Color32 {
+    let color = traffic_light(health.fraction());
+    if health.is_critical(damage_per_hit) {
+        pulse(ctx, color)
+    } else {
+        color
+    }
+}
+
+/// Green down to three quarters, then yellow, orange and, below a quarter, red.
+fn traffic_light(fraction: f32) -> Color32 {
+    if fraction >= 0.75 {
+        Color32::GREEN
+    } else if fraction >= 0.5 {
+        Color32::YELLOW
+    } else if fraction >= 0.25 {
+        Color32::from_rgb(255, 140, 0)
+    } else {
+        Color32::RED
+    }
+}
+
+/// Fades the colour in and out a few times a second to draw attention to it.
+fn pulse(ctx: &CtxRef, color: Color32) -> Color32 {
+    let pulse = 0.5 + 0.5 * (ctx.input().time * 6.0).sin() as f32;
+    color.linear_multiply(0.4 + 0.6 * pulse)
+}
+
+/// Mixes two colours half and half, e.g. a marker's role colour with its health colour.
+fn blend(a: Color32, b: Color32) -> Color32 {
+    let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    Color32::from_rgb(mix(a.r(), b.r()), mix(a.g(), b.g()), mix(a.b(), b.b()))
+}
+
+/// A thin bar in the health's traffic-light colour, followed by the exact value.
+fn health_bar(ui: &mut egui::Ui, tr: Strings, health: &Health, damage_per_hit: f64) {
+    let fraction = health.fraction();
+    let (rect, response) = ui.allocate_exact_size(HEALTH_BAR_SIZE, egui::Sense::hover());
+    let color = health_color(ui.ctx(), health, damage_per_hit);
+
+    let painter = ui.painter();
+    painter.rect_filled(rect, 1.0, ui.visuals().extreme_bg_color);
+    let mut filled = rect;
+    filled.set_width(rect.width() * fraction);
+    painter.rect_filled(filled, 1.0, color);
+
+    response.on_hover_text(tr.format(Text::Health, &[health]));
+
+    let label = egui::Label::new(health.to_string()).text_color(color);
+    if health.is_critical(damage_per_hit) {
+        ui.add(label.strong());
+    } else {
+        ui.add(label);
+    }
+}
+
+/// What every unit row of a frame goes by.
+struct RowContext<'a> {
+    tr: Strings,
+    theme: &'a Theme,
+    timings: &'a UnitTimings,
+    damage_per_hit: f64,
+    parking_spaces: &'a TokenPool<ParkingSpace>,
+    text_scale: f32,
+    touch_layout: bool,
+    icon: Option<TextureId>,
+}
+
+/// The part every unit row starts with: its number, health and what it is currently doing.
+/// Clicking the number selects the unit for keyboard control.
+fn unit_status(
+    ui: &mut egui::Ui,
+    rows: &RowContext<'_>,
+    bundle: &UnitBundle,
+    selected_unit: &mut Option<UnitId>,
+    status: &str,
+    status_color: Option<Color32>,
+) {
+    let RowContext {
+        tr,
+        damage_per_hit,
+        icon,
+        ..
+    } = *rows;
+    let UnitBundle { id, health, .. } = bundle;
+    let id = *id;
+    if let Some(texture_id) = icon {
+        ui.image(texture_id, Vec2::splat(ROW_ICON_SIZE));
+    }
+    if ui
+        .selectable_label(*selected_unit == Some(id), id.to_string())
+        .on_hover_text(tr.get(Text::SelectUnitTooltip))
+        .clicked()
+    {
+        *selected_unit = Some(id);
+    }
+    health_bar(ui, tr, health, damage_per_hit);
+    match status_color {
+        Some(color) => ui.colored_label(color, status),
+        None => ui.label(status),
+    };
+}
+
+/// What the unit is currently doing, in a short sentence. Only the sentences naming a lane
+/// have to be put together.
+fn unit_status_text(tr: Strings, unit: &Unit) -> Cow<'static, str> {
+    match unit {
+        Unit::InStorage => tr.get(Text::InStorage).into(),
+        Unit::Storing(_) => tr.get(Text::MovingIntoStorage).into(),
+        Unit::UnStoring(_, _) => tr.get(Text::ComingOutOfStorage).into(),
+        Unit::Parking(_, _) => tr.get(Text::Parking).into(),
+        Unit::ParkedUnready(_) => tr.get(Text::NotReady).into(),
+        Unit::ParkedPreparing(_, _, combat_type) => {
+            tr.format(Text::PreparingFor, &[combat_type]).into()
+        }
+        Unit::ParkedReady(_, combat_type) => tr.format(Text::ReadyFor, &[combat_type]).into(),
+        Unit::Patrolling(_, combat_type) => tr.format(Text::PatrollingLane, &[combat_type]).into(),
+        Unit::Returning(_, combat_type) => {
+            tr.format(Text::ReturningFromLane, &[combat_type]).into()
+        }
+        Unit::WaitingToPark => tr.get(Text::Waiting).into(),
+    }
+}
+
+/// The label of the button that prepares a unit for the lane.
+fn prepare_label(combat_type: CombatType) -> &'static str {
+    match combat_type {
+        CombatType::A => "... ◀ A",
+        CombatType::B => "... ◆ B",
+        CombatType::C => "... ▲ C",
+        CombatType::D => "... ● D",
+    }
+}
+
+/// The colour of a status text that names a lane: the lane's own colour while the unit is
+/// preparing for it or ready to take off.
+fn status_color(theme: &Theme, unit: &Unit) -> Option<Color32> {
+    match unit {
+        Unit::ParkedPreparing(_, _, combat_type) | Unit::ParkedReady(_, combat_type) => {
+            Some(theme.combat_type(*combat_type))
+        }
+        _ => None,
+    }
+}
+
+/// One box per parking space, showing the unit in it. Clicking an empty one moves the selected
+/// unit into it if the unit is in storage or waiting to park.
+fn parking_grid(
+    ui: &mut egui::Ui,
+    tr: Strings,
+    theme: &Theme,
+    units: &[UnitBundle],
+    parking_spaces: &TokenPool<ParkingSpace>,
+    selected_unit: Option<UnitId>,
+    commands: &mut Vec<GameCommand>,
+) {
+    let selected_action = units
+        .iter()
+        .find(|bundle| Some(bundle.id) == selected_unit)
+        .and_then(|bundle| match bundle.unit {
+            Unit::InStorage => Some(UnitAction::UnStore),
+            Unit::WaitingToPark => Some(UnitAction::Park),
+            _ => None,
+        });
+
+    let mut requested = None;
+    ui.horizontal_wrapped(|ui| {
+        for slot in 0..parking_spaces.slot_count() {
+            let occupant = units.iter().find(|bundle| {
+                bundle
+                    .unit
+                    .parking_space()
+                    .is_some_and(|parking_space| parking_space.slot() == slot)
+            });
+
+            ui.group(|ui| {
+                ui.set_width(PARKING_SLOT_WIDTH);
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr.format(Text::ParkingSlot, &[&(slot + 1)]));
+                        if slot >= parking_spaces.capacity() {
+                            ui.colored_label(theme.critical, tr.get(Text::WreckedSlot))
+                                .on_hover_text(tr.get(Text::WreckedSlotTooltip));
+                        }
+                    });
+
+                    match occupant {
+                        Some(UnitBundle { id, unit, .. }) => {
+                            ui.add(egui::Label::new(id.to_string()).strong());
+                            let status = unit_status_text(tr, unit);
+                            match status_color(theme, unit) {
+                                Some(color) => ui.colored_label(color, &*status),
+                                None => ui.label(&*status),
+                            };
+                            if let Unit::UnStoring(timer, _)
+                            | Unit::Parking(timer, _)
+                            | Unit::ParkedPreparing(timer, _, _) = unit
+                            {
+                                progress_bar(ui, timer.percent(), PARKING_SLOT_WIDTH, None);
+                            }
+                        }
+                        None => {
+                            let button = egui::Button::new(tr.get(Text::EmptySlot))
+                                .enabled(selected_action.is_some() && parking_spaces.is_free(slot));
+                            if ui
+                                .add(button)
+                                .on_hover_text(tr.get(Text::EmptySlotTooltip))
+                                .clicked()
+                            {
+                                requested = selected_action.map(|action| (action, slot));
+                            }
+                        }
+                    }
+                });
+            });
+        }
+    });
+
+    if let (Some((action, slot)), Some(unit)) = (requested, selected_unit) {
+        commands.push(GameCommand::PerformInSlot { unit, action, slot });
+    }
+}
+
+/// A unit's complete row in the base panel, with the buttons for whatever state it is in.
+/// In a narrow panel, or with large text, the buttons go on a second line below the status.
+/// Right-clicking the row, or in the touch layout tapping its actions button, opens a menu with
+/// every action available right now.
+fn unit_row(
+    ui: &mut egui::Ui,
+    bundle: &UnitBundle,
+    rows: &RowContext<'_>,
+    selected_unit: &mut Option<UnitId>,
+    commands: &mut Vec<GameCommand>,
+) {
+    let RowContext {
+        tr,
+        theme,
+        timings,
+        parking_spaces,
+        text_scale,
+        touch_layout,
+        ..
+    } = *rows;
+    let UnitBundle { id, unit, .. } = bundle;
+    let status = unit_status_text(tr, unit);
+    let color = status_color(theme, unit);
+
+    let mut requested = None;
+    let mut controls = |ui: &mut egui::Ui| match unit {
+        Unit::InStorage => {
+            let button = egui::Button::new(tr.get(Text::BringOutOfStorage))
+                .enabled(parking_spaces.can_take());
+            if ui
+                .add(button)
+                .on_hover_ui(|ui| {
+                    ui.label(&*parking_hint(
+                        tr,
+                        parking_spaces,
+                        Text::UnStoreTooltip,
+                        timings.un_store_seconds,
+                    ));
+                })
+                .clicked()
+            {
+                requested = Some(UnitAction::UnStore);
+            }
+        }
+        Unit::Storing(timer)
+        | Unit::UnStoring(timer, _)
+        | Unit::Parking(timer, _)
+        | Unit::ParkedPreparing(timer, _, _)
+        | Unit::Patrolling(timer, _)
+        | Unit::Returning(timer, _) => timer_bar(ui, tr, timer),
+        Unit::ParkedUnready(_) => {
+            for combat_type in CombatType::iter() {
+                if ui
+                    .add(
+                        egui::Button::new(prepare_label(combat_type))
+                            .text_color(theme.combat_type(combat_type)),
+                    )
+                    .on_hover_ui(|ui| {
+                        ui.label(timed_hint(
+                            tr,
+                            Text::PrepareTooltip,
+                            timings.prepare_seconds,
+                        ));
+                    })
+                    .clicked()
+                {
+                    requested = Some(UnitAction::Prepare(combat_type));
+                }
+            }
+            if ui
+                .button(tr.get(Text::MoveIntoStorage))
+                .on_hover_ui(|ui| {
+                    ui.label(timed_hint(tr, Text::StoreTooltip, timings.store_seconds));
+                })
+                .clicked()
+            {
+                requested = Some(UnitAction::Store);
+            }
+        }
+        Unit::ParkedReady(_, _) => {
+            if ui
+                .button(tr.get(Text::TakeOff))
+                .on_hover_ui(|ui| {
+                    ui.label(timed_hint(tr, Text::TakeOffTooltip, timings.patrol_seconds));
+                })
+                .clicked()
+            {
+                requested = Some(UnitAction::TakeOff);
+            }
+        }
+        Unit::WaitingToPark => {
+            if ui
+                .button(tr.get(Text::MoveIntoStorage))
+                .on_hover_ui(|ui| {
+                    ui.label(timed_hint(tr, Text::StoreTooltip, timings.store_seconds));
+                })
+                .clicked()
+            {
+                requested = Some(UnitAction::Store);
+            }
+
+            let button = egui::Button::new(tr.get(Text::Park)).enabled(parking_spaces.can_take());
+            if ui
+                .add(button)
+                .on_hover_ui(|ui| {
+                    ui.label(&*parking_hint(
+                        tr,
+                        parking_spaces,
+                        Text::ParkTooltip,
+                        timings.park_seconds,
+                    ));
+                })
+                .clicked()
+            {
+                requested = Some(UnitAction::Park);
+            }
+        }
+    };
+
+    // Fingers can't right-click, so the touch layout offers the menu on a button of its own.
+    let mut actions_button = None;
+    let mut add_actions_button = |ui: &mut egui::Ui| {
+        if touch_layout {
+            actions_button = Some(
+                ui.button(tr.get(Text::UnitActions))
+                    .on_hover_text(tr.get(Text::UnitActionsTooltip)),
+            );
+        }
+    };
+
+    let unit_id = *id;
+    let row = if ui.available_width() < COMPACT_ROW_WIDTH * text_scale {
+        let status = ui
+            .horizontal(|ui| unit_status(ui, rows, bundle, selected_unit, &status, color))
+            .response;
+        let buttons = ui
+            .horizontal_wrapped(|ui| {
+                ui.add_space(COMPACT_ROW_INDENT);
+                controls(ui);
+                add_actions_button(ui);
+            })
+            .response;
+        status.rect.union(buttons.rect)
+    } else {
+        ui.horizontal(|ui| {
+            unit_status(ui, rows, bundle, selected_unit, &status, color);
+            controls(ui);
+            add_actions_button(ui);
+        })
+        .response
+        .rect
+    };
+
+    let row = ui.interact(
+        row,
+        ui.make_persistent_id(("unit_row", unit_id)),
+        egui::Sense::click(),
+    );
+    let menu_id = row.id.with("actions");
+    if row.secondary_clicked()
+        || actions_button
+            .as_ref()
+            .is_some_and(|button| button.clicked())
+    {
+        ui.memory().open_popup(menu_id);
+    }
+    egui::popup_below_widget(ui, menu_id, actions_button.as_ref().unwrap_or(&row), |ui| {
+        for &action in UnitAction::all()
+            .iter()
+            .filter(|&&action| bundle.can_perform(action, parking_spaces))
+        {
+            if ui.button(action_label(tr, action)).clicked() {
+                requested = Some(action);
+                ui.memory().close_popup();
+            }
+        }
+    });
+
+    if let Some(action) = requested {
+        commands.push(GameCommand::Perform {
+            unit: bundle.id,
+            action,
+        });
+    }
+}
+
+/// Replacing the visuals has egui redo its whole style, so the GUI only does it when the theme
+/// changed. Tests count how often that is.
+fn set_visuals(egui_ctx: &CtxRef, visuals: egui::Visuals) {
+    #[cfg(test)]
+    tests::VISUALS_SET.with(|count| count.set(count.get() + 1));
+    egui_ctx.set_visuals(visuals);
+}
+
+/// What an action is called in the unit rows' menus.
+fn action_label(tr: Strings, action: UnitAction) -> Cow<'static, str> {
+    match action {
+        UnitAction::UnStore => tr.get(Text::BringOutOfStorage).into(),
+        UnitAction::Prepare(combat_type) => tr.format(Text::PrepareForLane, &[&combat_type]).into(),
+        UnitAction::TakeOff => tr.get(Text::TakeOff).into(),
+        UnitAction::Store => tr.get(Text::MoveIntoStorage).into(),
+        UnitAction::Park => tr.get(Text::Park).into(),
+        UnitAction::Recall => tr.get(Text::Recall).into(),
+        UnitAction::CancelPreparation => tr.get(Text::CancelPreparation).into(),
+    }
+}
+
+/// How long a busy unit still has to go.
+fn timer_bar(ui: &mut egui::Ui, tr: Strings, timer: &Timer) {
+    let text = tr.format(
+        Text::SecondsToGo,
+        &[&format!("{:.1}", timer.remaining_seconds())],
+    );
+    progress_bar(ui, timer.percent(), TIMER_BAR_WIDTH, Some(&text));
+}
+
+/// A bar filled up to `fraction`, with the text over it if there is one. egui has no progress
+/// bar of its own yet.
+fn progress_bar(ui: &mut egui::Ui, fraction: f32, width: f32, text: Option<&str>) {
+    let size = Vec2::new(width, ui.spacing().interact_size.y);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+
+    let visuals = ui.visuals();
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let mut filled = rect;
+    filled.set_width(rect.width() * fraction.clamp(0.0, 1.0));
+    painter.rect_filled(filled, 2.0, visuals.selection.bg_fill);
+    if let Some(text) = text {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            text,
+            TextStyle::Button,
+            visuals.text_color(),
+        );
+    }
+}
+
+/// Binds the first key pressed to the action being rebound. Escape cancels instead.
+fn capture_binding(egui_ctx: &CtxRef, settings: &mut Settings, gui_state: &mut GuiState) {
+    let action = match gui_state.rebinding {
+        Some(action) => action,
+        None => return,
+    };
+
+    if let Some(key) = pressed_keys(egui_ctx.input()).into_iter().next() {
+        if key != KeyCode::Escape {
+            settings.key_bindings.bind(action, key);
+        }
+        gui_state.rebinding = None;
+    }
+}
+
+/// The pause key pauses and resumes, + and - step through the game speeds.
+fn speed_keys(
+    egui_ctx: &CtxRef,
+    actions: &ActionState,
+    game_state: &GameState,
+) -> Option<GuiAction> {
+    let input = egui_ctx.input();
+    if actions.just_pressed(PAUSE) {
+        return match game_state {
+            GameState::Running => Some(GuiAction::Pause),
+            GameState::Paused => Some(GuiAction::Resume),
+            GameState::MainMenu | GameState::GameOver | GameState::Victory => None,
+        };
+    }
+
+    input.events.iter().rev().find_map(|event| match event {
+        egui::Event::Text(text) if text == "+" => Some(GuiAction::SpeedUp),
+        egui::Event::Text(text) if text == "-" => Some(GuiAction::SlowDown),
+        _ => None,
+    })
+}
+
+/// Turns this frame's keyboard shortcuts into commands for the selected unit.
+fn handle_key_commands(actions: &ActionState, gui_state: &mut GuiState) {
+    for command in key_commands(actions) {
+        match command {
+            KeyCommand::Select(id) => gui_state.selected_unit = Some(id),
+            KeyCommand::Perform(action) => {
+                if let Some(unit) = gui_state.selected_unit {
+                    gui_state
+                        .commands
+                        .push(GameCommand::Perform { unit, action });
+                }
+            }
+            KeyCommand::Scramble(combat_type) => {
+                gui_state.commands.push(GameCommand::Scramble(combat_type))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    thread_local! {
+        /// How often the visuals were replaced on this test's thread.
+        pub(super) static VISUALS_SET: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[test]
+    fn the_visuals_are_set_once_per_theme_change() {
+        let config = GameConfig::built_in();
+        let enemy_spawner =
+            EnemySpawner::with_seed(Difficulty::Normal, GameMode::Endless, 0, 1, &config);
+        let view = GameView {
+            units: &[],
+            enemies: &[],
+            lane_positions: &LanePositions::default(),
+            enemy_spawner: &enemy_spawner,
+            config: &config,
+            parking_spaces: &TokenPool::new(config.units.parking_spaces),
+            game_state: &GameState::Running,
+            play_time: &PlayTime::default(),
+            time: &Time::default(),
+            high_score: &HighScore::default(),
+            leaderboard: &Leaderboard::default(),
+            online_status: None,
+            defeat: None,
+            achievements: &Achievements::default(),
+            awaiting_initials: false,
+            saved_run_seconds: None,
+            save_slots: &SaveSlots::default(),
+            watching_replay: false,
+            can_save_replay: false,
+            saved_replay_seconds: None,
+            challenge_seed: None,
+            daily: None,
+            daily_results: &DailyResults::default(),
+            scenarios: &[],
+            scenario: None,
+            sandbox: false,
+            cheats: &CheatState::default(),
+            stats: &GameStats::default(),
+            score: &Score::default(),
+            timeline: &Timeline::default(),
+            battle_log: &BattleLog::default(),
+            toasts: &Toasts::default(),
+            tutorial: None,
+        };
+        let mut egui_ctx = CtxRef::default();
+        let mut threat_alerts = ThreatAlerts::default();
+        let mut settings = Settings::default();
+        let mut gui_state = GuiState::default();
+        let mut frames = |settings: &mut Settings, count: usize| {
+            for _ in 0..count {
+                egui_ctx.begin_frame(egui::RawInput::default());
+                gui(
+                    &egui_ctx,
+                    &view,
+                    &mut threat_alerts,
+                    settings,
+                    &mut gui_state,
+                );
+                let _ = egui_ctx.end_frame();
+            }
+            VISUALS_SET.with(Cell::get)
+        };
+
+        assert_eq!(frames(&mut settings, 10), 1);
+        settings.color_scheme = ColorScheme::Light;
+        assert_eq!(frames(&mut settings, 10), 2);
+        assert_eq!(frames(&mut settings, 10), 2);
+    }
+
+    #[test]
+    fn health_colors_change_at_every_quarter() {
+        let orange = Color32::from_rgb(255, 140, 0);
+        let expected = [
+            (1.0, Color32::GREEN),
+            (0.75, Color32::GREEN),
+            (0.74, Color32::YELLOW),
+            (0.5, Color32::YELLOW),
+            (0.49, orange),
+            (0.25, orange),
+            (0.24, Color32::RED),
+            (0.0, Color32::RED),
+        ];
+        for &(fraction, color) in expected.iter() {
+            assert_eq!(traffic_light(fraction), color, "at {}", fraction);
+        }
+    }
+
+    #[test]
+    fn any_pressure_lights_a_segment() {
+        let expected = [
+            (-0.5, 0),
+            (0.0, 0),
+            (0.01, 1),
+            (0.2, 1),
+            (0.21, 2),
+            (0.5, 3),
+            (0.99, 5),
+            (1.0, 5),
+            (2.0, 5),
+        ];
+        for &(pressure, lit) in expected.iter() {
+            assert_eq!(pressure_segments(pressure), lit, "at {}", pressure);
+        }
+    }
+}
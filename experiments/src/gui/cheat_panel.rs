@@ -0,0 +1,109 @@
+//! The sandbox's cheats, in a small window of their own. Every button hands back a cheat for the
+//! game to carry out like any other order.
+
+use eframe::{
+    egui,
+    egui::{Align2, CtxRef, Vec2},
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    cheats::{Cheat, CheatState},
+    game::{CombatType, UnitId},
+    gui::strings::{Strings, Text},
+};
+
+/// What is picked in the window, kept between frames.
+pub struct CheatPanelState {
+    combat_type: CombatType,
+    /// How much of its lane a spawned enemy has left, in percent.
+    percent_left: u8,
+    spawn_interval_seconds: u32,
+}
+
+impl Default for CheatPanelState {
+    fn default() -> Self {
+        Self {
+            combat_type: CombatType::A,
+            percent_left: 50,
+            spawn_interval_seconds: 10,
+        }
+    }
+}
+
+/// A window in the bottom left corner. Returns the cheats asked for this frame.
+pub fn show(
+    ctx: &CtxRef,
+    tr: Strings,
+    cheats: &CheatState,
+    selected_unit: Option<UnitId>,
+    state: &mut CheatPanelState,
+) -> Vec<Cheat> {
+    let mut requested = Vec::new();
+
+    egui::Window::new(tr.get(Text::Cheats))
+        .anchor(Align2::LEFT_BOTTOM, Vec2::new(16.0, -16.0))
+        .collapsible(true)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for combat_type in CombatType::iter() {
+                    if ui
+                        .selectable_label(state.combat_type == combat_type, combat_type.to_string())
+                        .clicked()
+                    {
+                        state.combat_type = combat_type;
+                    }
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut state.percent_left, 0..=100)
+                    .text(tr.get(Text::CheatDistanceLeft)),
+            );
+            if ui.button(tr.get(Text::CheatSpawnEnemy)).clicked() {
+                requested.push(Cheat::SpawnEnemy {
+                    combat_type: state.combat_type,
+                    percent_left: state.percent_left,
+                });
+            }
+            ui.separator();
+
+            if ui.button(tr.get(Text::CheatHealAll)).clicked() {
+                requested.push(Cheat::HealAll);
+            }
+            ui.horizontal(|ui| {
+                ui.set_enabled(selected_unit.is_some());
+                let label = match selected_unit {
+                    Some(unit) => tr.format(Text::CheatFinishNow, &[&unit]),
+                    None => tr.get(Text::CheatFinishNowNoUnit).to_string(),
+                };
+                if ui.button(label).clicked() {
+                    if let Some(unit) = selected_unit {
+                        requested.push(Cheat::FinishNow(unit));
+                    }
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Slider::new(&mut state.spawn_interval_seconds, 1..=40)
+                        .text(tr.get(Text::CheatSpawnInterval)),
+                );
+                if ui.button(tr.get(Text::CheatSet)).clicked() {
+                    requested.push(Cheat::SetSpawnInterval {
+                        seconds: state.spawn_interval_seconds,
+                    });
+                }
+            });
+            let mut invincible = cheats.invincible;
+            if ui
+                .checkbox(&mut invincible, tr.get(Text::CheatInvincible))
+                .changed()
+            {
+                requested.push(Cheat::SetInvincible(invincible));
+            }
+        });
+
+    requested
+}
@@ -0,0 +1,73 @@
+//! Yes/no questions for actions that can't be undone.
+
+use eframe::{
+    egui,
+    egui::{Align2, CtxRef, Vec2},
+};
+
+use crate::gui::strings::{Strings, Text};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConfirmResponse {
+    Pending,
+    Confirmed,
+    Cancelled,
+}
+
+/// A centred window with a confirm and a cancel button. It keeps no state of its own, so the
+/// caller decides how long to keep showing it.
+pub struct ConfirmDialog<'a> {
+    title: &'a str,
+    message: String,
+    /// Falls back to a generic "Confirm" when not set.
+    confirm_label: Option<&'a str>,
+}
+
+impl<'a> ConfirmDialog<'a> {
+    pub fn new(title: &'a str, message: impl Into<String>) -> Self {
+        Self {
+            title,
+            message: message.into(),
+            confirm_label: None,
+        }
+    }
+
+    pub fn confirm_label(mut self, confirm_label: &'a str) -> Self {
+        self.confirm_label = Some(confirm_label);
+        self
+    }
+
+    pub fn show(self, ctx: &CtxRef, tr: Strings) -> ConfirmResponse {
+        let mut response = ConfirmResponse::Pending;
+        let Self {
+            title,
+            message,
+            confirm_label,
+        } = self;
+
+        egui::Window::new(title)
+            .anchor(Align2::CENTER_CENTER, Vec2::new(0.0, 0.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(message);
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(confirm_label.unwrap_or_else(|| tr.get(Text::Confirm)))
+                        .clicked()
+                    {
+                        response = ConfirmResponse::Confirmed;
+                    }
+                    if ui
+                        .button(tr.get(Text::Cancel))
+                        .on_hover_text(tr.get(Text::CancelTooltip))
+                        .clicked()
+                    {
+                        response = ConfirmResponse::Cancelled;
+                    }
+                });
+            });
+
+        response
+    }
+}
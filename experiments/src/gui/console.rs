@@ -0,0 +1,214 @@
+//! The developer console: a line to type commands into, with what they had to say above it. Only
+//! in the sandbox and in debug builds, opened and closed with the backtick key. It is a tool for
+//! development, so it speaks English whatever the language setting.
+
+use eframe::{
+    egui,
+    egui::{Align, Align2, CtxRef, Vec2},
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    commands::GameCommand,
+    console::{completions, parse, ConsoleCommand, COMMANDS},
+    game::{CombatType, EnemySpawner, GameState},
+    gui::GuiAction,
+    stats::GameStats,
+};
+
+/// How many lines of output are kept.
+const SCROLLBACK_LINES: usize = 200;
+const SCROLLBACK_HEIGHT: f32 = 160.0;
+const CONSOLE_WIDTH: f32 = 480.0;
+
+#[derive(Default)]
+pub struct ConsoleState {
+    open: bool,
+    input: String,
+    scrollback: Vec<String>,
+    /// The scrollback grew since it was last drawn.
+    scroll_to_end: bool,
+}
+
+impl ConsoleState {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Opens or closes the console if its key was pressed this frame.
+    pub fn toggle_on_key(&mut self, ctx: &CtxRef) {
+        let pressed = ctx
+            .input()
+            .events
+            .iter()
+            .any(|event| matches!(event, egui::Event::Text(text) if text == "`"));
+        if pressed {
+            self.open = !self.open;
+        }
+    }
+
+    fn print(&mut self, line: impl Into<String>) {
+        self.scrollback.push(line.into());
+        let excess = self.scrollback.len().saturating_sub(SCROLLBACK_LINES);
+        self.scrollback.drain(..excess);
+        self.scroll_to_end = true;
+    }
+}
+
+/// What the console needs to know about the run to answer.
+pub struct RunInfo<'a> {
+    pub sandbox: bool,
+    pub game_state: GameState,
+    pub enemy_spawner: &'a EnemySpawner,
+    pub stats: &'a GameStats,
+}
+
+/// A window across the top of the screen, while the console is open. Orders for the run go
+/// into `commands`; anything for the GUI to handle is returned.
+pub fn show(
+    ctx: &CtxRef,
+    state: &mut ConsoleState,
+    run: &RunInfo<'_>,
+    commands: &mut Vec<GameCommand>,
+) -> Option<GuiAction> {
+    if !state.open {
+        return None;
+    }
+
+    let mut action = None;
+    egui::Window::new("Console")
+        .anchor(Align2::CENTER_TOP, Vec2::new(0.0, 8.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::ScrollArea::from_max_height(SCROLLBACK_HEIGHT).show(ui, |ui| {
+                for line in &state.scrollback {
+                    ui.monospace(line);
+                }
+                if state.scroll_to_end {
+                    ui.scroll_to_cursor(Align::BOTTOM);
+                    state.scroll_to_end = false;
+                }
+            });
+
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut state.input)
+                    .text_style(egui::TextStyle::Monospace)
+                    .desired_width(CONSOLE_WIDTH),
+            );
+            // The key that closes the console isn't meant to be typed into it.
+            state.input.retain(|c| c != '`');
+
+            let input = ctx.input();
+            if input.key_pressed(egui::Key::Tab) {
+                complete(state);
+            }
+            if field.lost_focus() && input.key_pressed(egui::Key::Enter) {
+                let line = std::mem::take(&mut state.input);
+                action = run_line(state, &line, run, commands);
+            }
+            // The console keeps the keyboard for as long as it is open.
+            field.request_focus();
+        });
+
+    action
+}
+
+/// Completes the command name typed so far, as far as it is the same for every command it
+/// could be. Lists them if there are several.
+fn complete(state: &mut ConsoleState) {
+    if state.input.trim_start().contains(char::is_whitespace) {
+        return;
+    }
+
+    let candidates = completions(&state.input);
+    match candidates.as_slice() {
+        [] => {}
+        [only] => state.input = format!("{} ", only),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.len(), |common, name| {
+                first
+                    .bytes()
+                    .zip(name.bytes())
+                    .take(common)
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            });
+            state.input = first[..common].to_string();
+            state.print(candidates.join("  "));
+        }
+    }
+}
+
+fn run_line(
+    state: &mut ConsoleState,
+    line: &str,
+    run: &RunInfo<'_>,
+    commands: &mut Vec<GameCommand>,
+) -> Option<GuiAction> {
+    state.print(format!("> {}", line));
+    let command = match parse(line) {
+        Ok(command) => command,
+        Err(error) => {
+            state.print(format!("error: {}", error));
+            return None;
+        }
+    };
+
+    match command {
+        ConsoleCommand::Game(command) => {
+            if matches!(command, GameCommand::Cheat(_)) && !run.sandbox {
+                state.print("cheats only work in the sandbox: start with --sandbox or ?sandbox=1");
+            } else if run.game_state.is_interactive() {
+                commands.push(command);
+                state.print("done");
+            } else {
+                state.print("only while a run is going");
+            }
+            None
+        }
+        ConsoleCommand::Speed(speed) => {
+            state.print(format!("speed {}", speed.label()));
+            Some(GuiAction::SetSpeed(speed))
+        }
+        ConsoleCommand::ShowSeed => {
+            state.print(format!("seed {}", run.enemy_spawner.seed()));
+            None
+        }
+        ConsoleCommand::ShowStats => {
+            print_stats(state, run.stats);
+            None
+        }
+        ConsoleCommand::Help => {
+            for info in COMMANDS {
+                state.print(format!("{:<48} {}", info.usage, info.description));
+            }
+            None
+        }
+    }
+}
+
+fn print_stats(state: &mut ConsoleState, stats: &GameStats) {
+    let by_type: Vec<_> = CombatType::iter()
+        .map(|combat_type| format!("{} {}", combat_type, stats.enemies_destroyed(combat_type)))
+        .collect();
+    state.print(format!(
+        "enemies destroyed: {} ({})",
+        stats.total_enemies_destroyed(),
+        by_type.join(", ")
+    ));
+    state.print(format!(
+        "sorties: {}, units lost: {}, repairs: {}",
+        stats.sorties, stats.units_lost, stats.repairs_completed
+    ));
+    state.print(format!(
+        "bosses leaked: {}, most parked: {}, parking used: {:.0}%",
+        stats.bosses_leaked,
+        stats.most_parked,
+        stats.parking_utilization()
+    ));
+}
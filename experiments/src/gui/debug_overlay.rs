@@ -0,0 +1,102 @@
+//! Numbers for development and bug reports: how fast frames are drawn, what is on the field and
+//! what the spawner is up to.
+
+use eframe::{
+    egui,
+    egui::{Align2, CtxRef, Vec2},
+};
+
+use crate::{
+    game::{census, UnitTimings},
+    gui::{
+        strings::{Strings, Text},
+        GameView,
+    },
+};
+
+/// A small window in the top right corner. Everything it shows is only worked out while it is
+/// open.
+pub fn show(ctx: &CtxRef, tr: Strings, view: &GameView<'_>, timings: &UnitTimings) {
+    let GameView {
+        time,
+        units,
+        enemies,
+        enemy_spawner,
+        parking_spaces,
+        ..
+    } = *view;
+    let census = census(units, enemies);
+
+    egui::Window::new(tr.get(Text::DebugOverlay))
+        .anchor(Align2::RIGHT_TOP, Vec2::new(-16.0, 48.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("debug_overlay").show(ui, |ui| {
+                ui.label(tr.get(Text::FrameTime));
+                ui.label(format!("{:.1} ms", time.frame_seconds() * 1000.0));
+                ui.end_row();
+
+                let diagnostics = time.diagnostics();
+                ui.label(tr.get(Text::AverageFps));
+                ui.label(format!("{:.0}", diagnostics.average_fps()));
+                ui.end_row();
+
+                ui.label(tr.get(Text::FrameTimeRange));
+                ui.label(format!(
+                    "{:.1} – {:.1} ms",
+                    diagnostics.min_frame_seconds().unwrap_or_default() * 1000.0,
+                    diagnostics.max_frame_seconds().unwrap_or_default() * 1000.0
+                ));
+                ui.end_row();
+
+                ui.label(tr.get(Text::FrameCount));
+                ui.label(diagnostics.frame_count().to_string());
+                ui.end_row();
+
+                ui.label(tr.get(Text::UnitCensus));
+                ui.label(tr.format(
+                    Text::UnitCensusValue,
+                    &[
+                        &census.stored,
+                        &census.moving,
+                        &census.parked,
+                        &census.airborne,
+                        &census.waiting,
+                    ],
+                ));
+                ui.end_row();
+
+                ui.label(tr.get(Text::EnemyCensus));
+                ui.label(tr.format(Text::EnemyCensusValue, &[&census.enemies, &census.bosses]));
+                ui.end_row();
+
+                ui.label(tr.get(Text::MeanSpawnInterval));
+                ui.label(format!(
+                    "{:.2}s",
+                    enemy_spawner.mean_seconds_between_enemies()
+                ));
+                ui.end_row();
+
+                ui.label(tr.get(Text::RepairTime));
+                ui.label(format!("{:.0}s", timings.seconds_to_fully_repair));
+                ui.end_row();
+
+                ui.label(tr.get(Text::PrepareTime));
+                ui.label(format!("{:.0}s", timings.prepare_seconds));
+                ui.end_row();
+
+                ui.label(tr.get(Text::ParkingSlotsUsed));
+                ui.label(format!(
+                    "{} / {}",
+                    parking_spaces.slots_used(),
+                    parking_spaces.capacity()
+                ));
+                ui.end_row();
+
+                ui.label(tr.get(Text::Seed));
+                ui.label(enemy_spawner.seed().to_string());
+                ui.end_row();
+            });
+        });
+}
@@ -0,0 +1,62 @@
+//! Small icons for units and enemies. They are optional: whatever isn't registered is drawn with
+//! the plain text glyphs instead.
+
+use eframe::{
+    egui,
+    egui::{epaint::Mesh, Color32, Painter, Pos2, Rect, TextureId},
+    epi,
+};
+use engine::{DecodedTextures, EguiTextures, TextureAsset};
+
+use crate::game::CombatType;
+
+const UNIT: &str = "plum";
+/// Indexed by combat type.
+const ENEMIES: [&str; 4] = ["sausage_a", "sausage_b", "sausage_c", "sausage_d"];
+
+pub const ASSETS: [TextureAsset; 5] = [
+    (UNIT, include_bytes!("../../assets/plum.png")),
+    (ENEMIES[0], include_bytes!("../../assets/sausage_a.png")),
+    (ENEMIES[1], include_bytes!("../../assets/sausage_b.png")),
+    (ENEMIES[2], include_bytes!("../../assets/sausage_c.png")),
+    (ENEMIES[3], include_bytes!("../../assets/sausage_d.png")),
+];
+
+#[derive(Default)]
+pub struct Sprites {
+    textures: EguiTextures,
+}
+
+impl Sprites {
+    pub fn load(tex_allocator: &mut dyn epi::TextureAllocator) -> Self {
+        Self {
+            textures: EguiTextures::load(tex_allocator, &ASSETS),
+        }
+    }
+
+    /// Registers the `ASSETS` decoded before.
+    pub fn register(
+        tex_allocator: &mut dyn epi::TextureAllocator,
+        decoded: DecodedTextures,
+    ) -> Self {
+        Self {
+            textures: EguiTextures::register(tex_allocator, decoded),
+        }
+    }
+
+    pub fn unit(&self) -> Option<TextureId> {
+        self.textures.get(UNIT)
+    }
+
+    pub fn enemy(&self, combat_type: CombatType) -> Option<TextureId> {
+        self.textures.get(ENEMIES[combat_type as usize])
+    }
+}
+
+/// Stretches the whole texture over `rect`.
+pub fn paint(painter: &Painter, texture_id: TextureId, rect: Rect) {
+    let uv = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1.0, 1.0));
+    let mut mesh = Mesh::with_texture(texture_id);
+    mesh.add_rect_with_uv(rect, uv, Color32::WHITE);
+    painter.add(egui::Shape::Mesh(mesh));
+}
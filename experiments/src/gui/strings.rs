@@ -0,0 +1,1675 @@
+//! Every text the GUI shows, in every language it speaks. English is the reference: it has an
+//! entry for each key, and anything another language is missing is shown in English instead.
+//!
+//! Texts with `{}` placeholders are filled in with [`Strings::format`].
+
+use std::fmt::Display;
+
+use strum::EnumIter;
+
+use crate::settings::Language;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter)]
+pub enum Text {
+    File,
+    Menu,
+    MenuTooltip,
+    Settings,
+    SettingsTooltip,
+    Restart,
+    RestartTooltip,
+    Quit,
+    QuitTooltip,
+    Resume,
+    ResumeTooltip,
+    Confirm,
+    Cancel,
+    CancelTooltip,
+    Paused,
+    QuitQuestion,
+    RestartQuestion,
+    RunWillBeLost,
+    Best,
+    Fps,
+    AllocationsPerFrame,
+    Survived,
+    NextContact,
+    Pressure,
+    PressureTooltip,
+
+    Start,
+    StartTooltip,
+    Difficulty,
+    DifficultyTooltip,
+    Easy,
+    Normal,
+    Hard,
+    Tutorial,
+    TutorialTooltip,
+    TutorialStep,
+    SkipTutorial,
+    SkipTutorialTooltip,
+
+    Language,
+    GameSpeedTooltip,
+    PauseTooltip,
+    UiScale,
+    UiScaleTooltip,
+    Fullscreen,
+    FullscreenTooltip,
+    PauseOnFocusLoss,
+    PauseOnFocusLossTooltip,
+    SoundVolume,
+    SoundVolumeTooltip,
+    Mute,
+    MuteTooltip,
+    MusicVolume,
+    MusicVolumeTooltip,
+    MuteMusic,
+    MuteMusicTooltip,
+    ColorScheme,
+    ColorSchemeTooltip,
+    DarkPurple,
+    Light,
+    HighContrast,
+    LargeUi,
+    LargeUiTooltip,
+    ColorblindMode,
+    ColorblindModeTooltip,
+    AutoPark,
+    AutoParkTooltip,
+    ShowFps,
+    ShowFpsTooltip,
+    ToastUnitDestroyed,
+    ToastBossLeaked,
+    DebugOverlay,
+    DebugOverlayTooltip,
+    FrameTime,
+    AverageFps,
+    FrameTimeRange,
+    FrameCount,
+    UnitCensus,
+    UnitCensusValue,
+    EnemyCensus,
+    EnemyCensusValue,
+    MeanSpawnInterval,
+    RepairTime,
+    PrepareTime,
+    ParkingSlotsUsed,
+    Seed,
+    Cheats,
+    CheatDistanceLeft,
+    CheatSpawnEnemy,
+    CheatHealAll,
+    CheatFinishNow,
+    CheatFinishNowNoUnit,
+    CheatSpawnInterval,
+    CheatSet,
+    CheatInvincible,
+
+    YourBase,
+    Controls,
+    ListUnits,
+    ByState,
+    ByStateTooltip,
+    SingleList,
+    SingleListTooltip,
+    StoredUnits,
+    StoredUnitsDescription,
+    ParkingArea,
+    ParkingAreaDescription,
+    WaitingToReturn,
+    WaitingToReturnDescription,
+    ParkingSlot,
+    EmptySlot,
+    EmptySlotTooltip,
+    WreckedSlot,
+    WreckedSlotTooltip,
+    SelectUnitTooltip,
+    Health,
+    SecondsToGo,
+
+    InStorage,
+    MovingIntoStorage,
+    ComingOutOfStorage,
+    Parking,
+    NotReady,
+    PreparingFor,
+    ReadyFor,
+    PatrollingLane,
+    ReturningFromLane,
+    Waiting,
+
+    BringOutOfStorage,
+    UnStoreTooltip,
+    PrepareTooltip,
+    MoveIntoStorage,
+    StoreTooltip,
+    TakeOff,
+    TakeOffTooltip,
+    Park,
+    ParkTooltip,
+    NoFreeParkingTooltip,
+    PrepareForLane,
+    Recall,
+    CancelPreparation,
+
+    SurvivedSoFar,
+    Briefing,
+    BriefingBody,
+    Battlezone,
+    BattlezoneHelp,
+    Threats,
+    Lane,
+    Inbound,
+    NextHitIn,
+    Airborne,
+    Prepared,
+    Scramble,
+    ScrambleButton,
+    ScrambleTooltip,
+    NothingToScrambleTooltip,
+    UncoveredLaneTooltip,
+    LaneTooltip,
+    LaneBase,
+    LaneSpawn,
+    Boss,
+    Breaching,
+    DismissAlertTooltip,
+    BattleLog,
+    HoldLogScroll,
+    HoldLogScrollTooltip,
+
+    Stats,
+    SortiesFlown,
+    EnemiesDestroyed,
+    InLane,
+    UnitsLost,
+    RepairsCompleted,
+    ParkingUtilisation,
+
+    Hit,
+    BaseHit,
+    SurvivedFinal,
+    NewPersonalBest,
+    PlayAgain,
+    PlayAgainTooltip,
+    RetrySameSeed,
+    RetrySameSeedTooltip,
+    NewGamePlus,
+    NewGamePlusTooltip,
+    NewGamePlusLevel,
+
+    Version,
+    RunSeed,
+    CopySeed,
+    CopySeedTooltip,
+    SaveScreenshot,
+    SaveScreenshotTooltip,
+    ScreenshotUnsupportedTooltip,
+    ToastScreenshotSaved,
+    ToastScreenshotFailed,
+    PressAKey,
+    RebindTooltip,
+    ResetControls,
+    ResetControlsTooltip,
+    ContinueRun,
+    ContinueRunTooltip,
+    ChallengeSeed,
+    TouchLayout,
+    TouchLayoutTooltip,
+    UnitActions,
+    UnitActionsTooltip,
+    MostParked,
+    Score,
+    FinalScore,
+    NewBestScore,
+    CheatsUsed,
+    BestScore,
+    HighScores,
+    HighScoresTooltip,
+    NoHighScores,
+    DailyChallenge,
+    DailyScoredTooltip,
+    DailyPracticeTooltip,
+    DailyResults,
+    DailyResultsTooltip,
+    NoDailyResults,
+    DailyUnfinished,
+    DailyLabel,
+    DailyPracticeLabel,
+    DailyRecorded,
+    DailyPractice,
+    DailySpeedTooltip,
+    OnlineSubmitting,
+    OnlineSubmitted,
+    OnlineFailed,
+    OnlineLeaderboard,
+    OnlineLeaderboardTooltip,
+    OnlineInitials,
+    OnlineInitialsTooltip,
+    Scenarios,
+    ScenariosTooltip,
+    PlayScenario,
+    PlayScenarioTooltip,
+    ScenarioSurviveGoal,
+    ScenarioInterceptAllGoal,
+    ScenarioTimeLeft,
+    ScenarioEnemiesLeft,
+    ScenarioWon,
+    ScenarioFailed,
+    ScenarioPlayAgainTooltip,
+    DefeatBreached,
+    DefeatBossBreached,
+    DefeatNoUnits,
+    DefeatPatrolling,
+    DefeatReady,
+    DefeatPreparing,
+    DefeatParked,
+    DefeatParking,
+    DefeatAirborne,
+    DefeatWaitingToPark,
+    DefeatInStorage,
+    Timeline,
+    TimelineScore,
+    TimelineEnemies,
+    TimelineAirborne,
+    TimelineParking,
+    TimelineLeak,
+    TimelineUnitLost,
+    TimelineMinute,
+    TimelineHover,
+    MadeLeaderboard,
+    EnterInitials,
+    EnterInitialsTooltip,
+    RankColumn,
+    InitialsColumn,
+    PointsColumn,
+    TimeColumn,
+    SeedColumn,
+    DateColumn,
+    SecondsValue,
+    SavedGames,
+    SavedGamesTooltip,
+    SaveName,
+    NameColumn,
+    SaveGame,
+    SaveGameTooltip,
+    LoadGameTooltip,
+    LoadQuestion,
+    DeleteSave,
+    DeleteSaveTooltip,
+    OverwriteQuestion,
+    OverwriteMessage,
+    DeleteQuestion,
+    DeleteMessage,
+    Load,
+    ToastGameSaved,
+    ToastLoadFailed,
+    SaveReplay,
+    SaveReplayTooltip,
+    WatchReplay,
+    WatchReplayTooltip,
+    WatchingReplay,
+    ToastReplaySaved,
+    ToastReplayDiverged,
+    Mode,
+    Endless,
+    EndlessTooltip,
+    DefenseContract,
+    DefenseContractTooltip,
+    ContractTimeLeft,
+    ContractFulfilled,
+    ContractFulfilledHeading,
+    MedalEarned,
+    Bronze,
+    Silver,
+    Gold,
+    BossesLeaked,
+    Achievements,
+    AchievementsTooltip,
+    AchievementsUnlocked,
+    ToastAchievementUnlocked,
+    FirstInterception,
+    FirstInterceptionDescription,
+    TenInOneRun,
+    TenInOneRunDescription,
+    BossHunter,
+    BossHunterDescription,
+    CleanThreeMinutes,
+    CleanThreeMinutesDescription,
+    TenMinutes,
+    TenMinutesDescription,
+    LeanContract,
+    LeanContractDescription,
+    GoldContract,
+    GoldContractDescription,
+}
+
+/// Looks up texts in the player's language.
+#[derive(Debug, Copy, Clone)]
+pub struct Strings {
+    language: Language,
+}
+
+impl Strings {
+    pub fn new(language: Language) -> Self {
+        Self { language }
+    }
+
+    pub fn get(self, text: Text) -> &'static str {
+        let translations = match self.language {
+            Language::English => return english(text),
+            Language::German => GERMAN,
+        };
+
+        translations
+            .iter()
+            .find(|(key, _)| *key == text)
+            .map_or_else(|| english(text), |(_, translation)| *translation)
+    }
+
+    /// Fills the text's `{}` placeholders with `args`, in order.
+    pub fn format(self, text: Text, args: &[&dyn Display]) -> String {
+        interpolate(self.get(text), args)
+    }
+
+    /// A whole number with its thousands grouped the way the language does, like 12,450.
+    pub fn number(self, value: u64) -> String {
+        let separator = match self.language {
+            Language::English => ',',
+            Language::German => '.',
+        };
+
+        let digits = value.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+
+        grouped
+    }
+}
+
+/// A much smaller `format!` for templates that are only known at runtime.
+fn interpolate(template: &str, args: &[&dyn Display]) -> String {
+    debug_assert_eq!(
+        template.matches("{}").count(),
+        args.len(),
+        "wrong number of arguments for {:?}",
+        template
+    );
+
+    let mut args = args.iter();
+    let mut pieces = template.split("{}");
+    let mut result = pieces.next().unwrap_or_default().to_string();
+    for piece in pieces {
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(piece);
+    }
+
+    result
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::File => "File",
+        Text::Menu => "Menu",
+        Text::MenuTooltip => "Pause the game and open the menu. (Esc)",
+        Text::Settings => "Settings",
+        Text::SettingsTooltip => "Change how the game looks and plays.",
+        Text::Restart => "Restart",
+        Text::RestartTooltip => "Abandon this run and go back to the main menu.",
+        Text::Quit => "Quit",
+        Text::QuitTooltip => "Close the game. Your current run will be lost.",
+        Text::Resume => "Resume",
+        Text::ResumeTooltip => "Carry on where you left off. (Esc)",
+        Text::Confirm => "Confirm",
+        Text::Cancel => "Cancel",
+        Text::CancelTooltip => "Never mind, keep this run going.",
+        Text::Paused => "Paused",
+        Text::QuitQuestion => "Quit?",
+        Text::RestartQuestion => "Restart?",
+        Text::RunWillBeLost => "Your run of {}s will be lost.",
+        Text::Best => "Best: {}s",
+        Text::Fps => "{} FPS",
+        Text::AllocationsPerFrame => "{} allocations/frame",
+        Text::Survived => "Survived {}s",
+        Text::NextContact => "next contact ~{}s",
+        Text::Pressure => "pressure",
+        Text::PressureTooltip => "How fast enemies are coming. It only ever goes up.",
+
+        Text::Start => "Start",
+        Text::StartTooltip => "Start a new run. Enemies only show up once you press this.",
+        Text::Difficulty => "Difficulty",
+        Text::DifficultyTooltip => {
+            "How fast enemies come and how quickly they speed up, and how long repairs and \
+             preparing units take."
+        }
+        Text::Easy => "Easy",
+        Text::Normal => "Normal",
+        Text::Hard => "Hard",
+        Text::Tutorial => "Tutorial",
+        Text::TutorialTooltip => {
+            "Start a run with a step-by-step introduction. Enemies only come once it is over."
+        }
+        Text::TutorialStep => "Step {} of {}",
+        Text::SkipTutorial => "Skip tutorial",
+        Text::SkipTutorialTooltip => {
+            "Let the enemies come right away. The tutorial won't start by itself again."
+        }
+
+        Text::Language => "Language",
+        Text::GameSpeedTooltip => {
+            "Slow the game down to learn the ropes or speed it up for a challenge. Space pauses, \
+            + and - change the speed."
+        }
+        Text::PauseTooltip => "Pause the game. Space does the same.",
+        Text::UiScale => "UI scale",
+        Text::UiScaleTooltip => "Make everything on screen bigger or smaller.",
+        Text::Fullscreen => "Fullscreen",
+        Text::PauseOnFocusLoss => "Pause when switching away",
+        Text::PauseOnFocusLossTooltip => {
+            "Pause the run when you switch to another tab. Only works in the browser for now."
+        }
+        Text::FullscreenTooltip => {
+            "Fill the whole screen with the game. Only available in the browser for now."
+        }
+        Text::SoundVolume => "Sound volume",
+        Text::SoundVolumeTooltip => "How loud the sound effects are.",
+        Text::Mute => "Mute",
+        Text::MuteTooltip => "Turn all sounds off without losing the volume setting.",
+        Text::MusicVolume => "Music volume",
+        Text::MusicVolumeTooltip => "How loud the background music is.",
+        Text::MuteMusic => "Mute music",
+        Text::MuteMusicTooltip => "Turn the music off but keep the sound effects.",
+        Text::ColorScheme => "Theme",
+        Text::ColorSchemeTooltip => "The colours of panels, text and lanes.",
+        Text::DarkPurple => "Dark purple",
+        Text::Light => "Light",
+        Text::HighContrast => "High contrast",
+        Text::LargeUi => "Large UI",
+        Text::LargeUiTooltip => "Bigger text and lanes, for playing on a screen across the room.",
+        Text::ColorblindMode => "Colorblind mode",
+        Text::ColorblindModeTooltip => {
+            "Use colours that stay distinguishable with red-green colour blindness."
+        }
+        Text::AutoPark => "Auto-park",
+        Text::AutoParkTooltip => "Park units as soon as they are back and a parking space is free.",
+        Text::ShowFps => "Show FPS",
+        Text::ShowFpsTooltip => "Show how many frames per second are drawn.",
+        Text::ToastUnitDestroyed => "Unit {} was destroyed!",
+        Text::ToastBossLeaked => "A type {} boss wrecked a parking space! {} left.",
+        Text::DebugOverlay => "Debug info",
+        Text::DebugOverlayTooltip => {
+            "Show frame times, what is on the field and the random seed. Handy for bug reports."
+        }
+        Text::FrameTime => "Frame time",
+        Text::AverageFps => "Average FPS",
+        Text::FrameTimeRange => "Fastest / slowest frame",
+        Text::FrameCount => "Frames drawn",
+        Text::UnitCensus => "Units",
+        Text::UnitCensusValue => "{} stored, {} moving, {} parked, {} airborne, {} waiting",
+        Text::EnemyCensus => "Enemies",
+        Text::EnemyCensusValue => "{} ({} bosses)",
+        Text::MeanSpawnInterval => "Mean spawn interval",
+        Text::RepairTime => "Full repair",
+        Text::PrepareTime => "Preparation",
+        Text::ParkingSlotsUsed => "Parking spaces used",
+        Text::Seed => "Seed",
+        Text::Cheats => "Cheats",
+        Text::CheatDistanceLeft => "% of the lane left",
+        Text::CheatSpawnEnemy => "Spawn enemy",
+        Text::CheatHealAll => "Heal all units",
+        Text::CheatFinishNow => "Finish unit {} now",
+        Text::CheatFinishNowNoUnit => "Finish selected unit now",
+        Text::CheatSpawnInterval => "s between enemies",
+        Text::CheatSet => "Set",
+        Text::CheatInvincible => "Invincible",
+
+        Text::YourBase => "Your Base",
+        Text::Controls => "Controls",
+        Text::ListUnits => "List units",
+        Text::ByState => "by state",
+        Text::ByStateTooltip => "Group units by where they are in the base.",
+        Text::SingleList => "in one list",
+        Text::SingleListTooltip => "List every unit once, in order, wherever it is.",
+        Text::StoredUnits => "Stored Units ({})",
+        Text::StoredUnitsDescription => "Repair damaged units here.",
+        Text::ParkingArea => "Parking Area ({} units)",
+        Text::ParkingAreaDescription => {
+            "Prepare your units for battle in one of the lanes and send them off to fight here!"
+        }
+        Text::WaitingToReturn => "Waiting to Return ({})",
+        Text::WaitingToReturnDescription => {
+            "Units here are just standing around when they could be fighting or getting \
+            repaired! Move them on as quickly as you can!"
+        }
+        Text::ParkingSlot => "P{}",
+        Text::EmptySlot => "(empty)",
+        Text::EmptySlotTooltip => {
+            "Select a unit that is in storage or waiting to park, then click here to move it into \
+            this space."
+        }
+        Text::WreckedSlot => "wrecked",
+        Text::WreckedSlotTooltip => {
+            "A boss wrecked this space. It is gone once the unit in it leaves."
+        }
+        Text::SelectUnitTooltip => {
+            "Select this unit to give it orders with the keyboard. Right-click its row for \
+            everything it can do right now."
+        }
+        Text::Health => "Health: {}",
+        Text::SecondsToGo => "{}s to go",
+
+        Text::InStorage => "In storage.",
+        Text::MovingIntoStorage => "Moving into storage.",
+        Text::ComingOutOfStorage => "Coming out of storage.",
+        Text::Parking => "Parking.",
+        Text::NotReady => "Not ready. Prepare for...",
+        Text::PreparingFor => "Preparing for combat type {}.",
+        Text::ReadyFor => "Ready for combat type {}.",
+        Text::PatrollingLane => "Patrolling lane {}.",
+        Text::ReturningFromLane => "Returning from lane {}.",
+        Text::Waiting => "Waiting.",
+
+        Text::BringOutOfStorage => "Bring out of storage",
+        Text::UnStoreTooltip => {
+            "Move this unit to a free parking space so it can be prepared for battle. Takes {} \
+            seconds."
+        }
+        Text::PrepareTooltip => {
+            "Prepare this unit for a lane. A unit can only fight enemies in the lane it was \
+            prepared for. Takes {} seconds."
+        }
+        Text::MoveIntoStorage => "Move into storage",
+        Text::StoreTooltip => {
+            "Move this unit into storage, where it slowly gets repaired. Takes {} seconds and \
+            frees up its parking space."
+        }
+        Text::TakeOff => "Take off!",
+        Text::TakeOffTooltip => {
+            "Send this unit out on a {} second patrol down its lane. It can fend off a single \
+            enemy, then it returns to base."
+        }
+        Text::Park => "Park",
+        Text::ParkTooltip => {
+            "Park this unit so it can be prepared for its next sortie. Takes {} seconds."
+        }
+        Text::NoFreeParkingTooltip => {
+            "No free parking space. Move a parked unit into storage or send it off first."
+        }
+        Text::PrepareForLane => "Prepare for lane {}",
+        Text::Recall => "Recall",
+        Text::CancelPreparation => "Cancel preparation",
+
+        Text::SurvivedSoFar => "You have survived for {} seconds so far!",
+        Text::Briefing => "Briefing",
+        Text::BriefingBody => {
+            "Ze evil people from ze Meatropolis wiz zeir Queen on zat island in ze sea are \
+            invading our great country of Fruitopia! Zey vant to cut down our precious orchards \
+            to make ze trees into zeir wretched sawdust sausages!\n\
+            Ze Kaiser has ordered YOU to run ze main defense operation agenst ze Meatropolitans. \
+            Zey are stronk and REALLY vant zose orchards. Zey vill come faster and faster.\n\
+            Hold zem off for as long as you can and ve vill propose you for ze Eiserne Pflaume \
+            medal!"
+        }
+        Text::Battlezone => "The Battlezone",
+        Text::BattlezoneHelp => {
+            "Enemies (◀ ◆ ▲ ●) approach from the right on different lanes. Prepare your units \
+            for the right type of lane and send them off to fight. Each unit (▶) can fend off a \
+            single enemy before it returns to base (▷). Your units will wear out with use. \
+            Remember to repair them! Bosses that get through will wreck one of your parking \
+            spaces."
+        }
+        Text::Threats => "Threats",
+        Text::Lane => "Lane",
+        Text::Inbound => "Inbound",
+        Text::NextHitIn => "Next hit in",
+        Text::Airborne => "Airborne",
+        Text::Prepared => "Prepared",
+        Text::Scramble => "Scramble",
+        Text::ScrambleButton => "Scramble: {}, ~{}s to airborne",
+        Text::ScrambleTooltip => {
+            "Sends the quickest unit against this lane: one already prepared for it, then a parked \
+            one, then one from storage. It goes through every step up to take-off by itself. \
+            Giving it another order cancels the rest."
+        }
+        Text::NothingToScrambleTooltip => {
+            "No unit can be sent: none is parked or prepared for this lane, and there is no free \
+            parking space to take one out of storage."
+        }
+        Text::UncoveredLaneTooltip => {
+            "More enemies are coming down this lane than you have units airborne or prepared for \
+            it."
+        }
+        Text::LaneTooltip => {
+            "Enemies of this type approach from the right and attack the base on the left. Only \
+            units prepared for this lane can intercept them."
+        }
+        Text::LaneBase => "BASE",
+        Text::LaneSpawn => "SPAWN",
+        Text::Boss => "BOSS",
+        Text::Breaching => "{} Type {} breaching in {}s!",
+        Text::DismissAlertTooltip => {
+            "Hide this warning. It comes back if the enemy gets any closer."
+        }
+        Text::BattleLog => "Battle Log",
+        Text::HoldLogScroll => "Hold scroll position",
+        Text::HoldLogScrollTooltip => {
+            "Keep the battle log where it is instead of following new entries."
+        }
+
+        Text::Stats => "Stats",
+        Text::SortiesFlown => "Sorties flown",
+        Text::EnemiesDestroyed => "Enemies destroyed",
+        Text::InLane => "    in lane {} {}",
+        Text::UnitsLost => "Units lost",
+        Text::RepairsCompleted => "Repairs completed",
+        Text::ParkingUtilisation => "Parking utilisation",
+
+        Text::Hit => "Hit!",
+        Text::BaseHit => "Your base was hit! You are dead !!!!",
+        Text::SurvivedFinal => {
+            "You survived for {} seconds though, which is great! Now take a screenshot and brag \
+            to your friends about your m4d sk1llz :-D"
+        }
+        Text::NewPersonalBest => "That's a new personal best!",
+        Text::PlayAgain => "Thanks man! This was totally fun!! Let me try this again...",
+        Text::PlayAgainTooltip => "Start a new run straight away.",
+        Text::RetrySameSeed => "Retry seed {}",
+        Text::RetrySameSeedTooltip => "Start a new run with the same enemies as this one.",
+        Text::NewGamePlus => "New Game+ {}",
+        Text::NewGamePlusTooltip => {
+            "Take on the next contract straight away. The enemies come faster from the start, and \
+            faster again with every contract won in a row."
+        }
+        Text::NewGamePlusLevel => "NG+{}",
+
+        Text::Version => "Version {} ({})",
+        Text::RunSeed => "Seed {}",
+        Text::CopySeed => "Copy seed",
+        Text::CopySeedTooltip => {
+            "Copies the seed to the clipboard. Runs with the same seed send the same enemies."
+        }
+        Text::SaveScreenshot => "Save screenshot",
+        Text::SaveScreenshotTooltip => "Downloads a picture of the game as it looks right now.",
+        Text::ScreenshotUnsupportedTooltip => {
+            "Screenshots only work in the browser. Use your system's screenshot tool instead."
+        }
+        Text::ToastScreenshotSaved => "Screenshot saved as {}",
+        Text::ToastScreenshotFailed => "Couldn't save a screenshot: {}",
+        Text::PressAKey => "Press a key…",
+        Text::RebindTooltip => "Click, then press the new key. Escape keeps the old one.",
+        Text::ResetControls => "Reset controls",
+        Text::ResetControlsTooltip => "Go back to the keys the game came with.",
+        Text::ContinueRun => "Continue previous run ({}s survived)",
+        Text::ContinueRunTooltip => {
+            "Pick up the run that was still going when the game was closed, paused. Starting a \
+            new run forgets it."
+        }
+        Text::ChallengeSeed => "Challenge seed {} ({})",
+        Text::TouchLayout => "Touch layout",
+        Text::TouchLayoutTooltip => {
+            "Bigger buttons and lanes, and a button for each unit's menu instead of right-clicking. \
+            On by default on touch screens."
+        }
+        Text::UnitActions => "Actions…",
+        Text::UnitActionsTooltip => "Everything this unit can do right now.",
+        Text::MostParked => "Most units parked at once",
+        Text::Score => "Score: {} (x{})",
+        Text::FinalScore => "Score: {}",
+        Text::NewBestScore => "New best score!",
+        Text::CheatsUsed => "Cheats were used, so this run doesn't count for the high scores.",
+        Text::BestScore => "Best score: {}",
+        Text::HighScores => "High scores",
+        Text::HighScoresTooltip => "The ten best runs played here.",
+        Text::NoHighScores => "No runs yet. Yours could be the first!",
+        Text::DailyChallenge => "Daily challenge",
+        Text::DailyScoredTooltip => {
+            "Today's challenge ({}): the same enemies for everyone, endless on Normal, at 1× at \
+             most and without cheats. Only your first attempt of the day counts, even if you give \
+             it up."
+        }
+        Text::DailyPracticeTooltip => {
+            "You had your attempt at today's challenge ({}) already. Playing it again is practice \
+             and doesn't count."
+        }
+        Text::DailyResults => "Daily results",
+        Text::DailyResultsTooltip => {
+            "How your attempts at the daily challenges of the last days went."
+        }
+        Text::NoDailyResults => "No daily challenges played yet.",
+        Text::DailyUnfinished => "not finished",
+        Text::DailyLabel => "Daily {}",
+        Text::DailyPracticeLabel => "Daily {} (practice)",
+        Text::DailyRecorded => "Your result in the daily challenge of {} is on record.",
+        Text::DailyPractice => "Practice, so this run doesn't count for the daily challenge.",
+        Text::DailySpeedTooltip => "The daily challenge runs at 1× at most.",
+        Text::OnlineSubmitting => "Sending the run to the online leaderboard…",
+        Text::OnlineSubmitted => "The run is on the online leaderboard.",
+        Text::OnlineFailed => "Couldn't send the run to the online leaderboard: {}",
+        Text::OnlineLeaderboard => "Send runs to the online leaderboard",
+        Text::OnlineLeaderboardTooltip => {
+            "Once a run that counts is over, send your initials, points, time survived, seed, \
+             difficulty and the game's version to the online leaderboard."
+        }
+        Text::OnlineInitials => "Initials",
+        Text::OnlineInitialsTooltip => "What the online leaderboard shows you as.",
+        Text::Scenarios => "Scenarios",
+        Text::ScenariosTooltip => {
+            "Set pieces with their own units and enemies, and a goal to reach. They don't count \
+             for the high score."
+        }
+        Text::PlayScenario => "Play",
+        Text::PlayScenarioTooltip => "Start this scenario.",
+        Text::ScenarioSurviveGoal => "Goal: hold out for {}s.",
+        Text::ScenarioInterceptAllGoal => {
+            "Goal: stop every enemy. A boss that breaks through fails the scenario."
+        }
+        Text::ScenarioTimeLeft => "{}s left to hold out",
+        Text::ScenarioEnemiesLeft => "{} enemies left",
+        Text::ScenarioWon => "Scenario won",
+        Text::ScenarioFailed => "Scenario failed",
+        Text::ScenarioPlayAgainTooltip => "Play the same scenario again from the start.",
+        Text::DefeatBreached => "Type {} broke through at {}s.",
+        Text::DefeatBossBreached => "A type {} boss broke through at {}s.",
+        Text::DefeatNoUnits => "You had no units left to stop it.",
+        Text::DefeatPatrolling => {
+            "Your nearest unit for lane {}, unit {}, was patrolling it already, but too late to \
+             meet the enemy."
+        }
+        Text::DefeatReady => {
+            "Your nearest unit for lane {}, unit {}, was parked and ready for it, but never took \
+             off."
+        }
+        Text::DefeatPreparing => {
+            "Your nearest unit for lane {}, unit {}, was still preparing, {}s from ready."
+        }
+        Text::DefeatParked => {
+            "Your nearest unit for lane {}, unit {}, was parked but not prepared for it, {}s from \
+             ready."
+        }
+        Text::DefeatParking => {
+            "Your nearest unit for lane {}, unit {}, was on its way into a parking space, {}s \
+             from ready."
+        }
+        Text::DefeatAirborne => {
+            "Your nearest unit for lane {}, unit {}, was in the air elsewhere, {}s from ready."
+        }
+        Text::DefeatWaitingToPark => {
+            "Your nearest unit for lane {}, unit {}, was waiting for a parking space, {}s from \
+             ready."
+        }
+        Text::DefeatInStorage => {
+            "Your nearest unit for lane {}, unit {}, was in storage, {}s from ready."
+        }
+        Text::Timeline => "Timeline",
+        Text::TimelineScore => "Score",
+        Text::TimelineEnemies => "Enemies",
+        Text::TimelineAirborne => "Units in the air",
+        Text::TimelineParking => "Parking spaces in use",
+        Text::TimelineLeak => "Enemy through",
+        Text::TimelineUnitLost => "Unit lost",
+        Text::TimelineMinute => "{}m",
+        Text::TimelineHover => {
+            "{}s: {} points, {} enemies, {} units in the air, {} of {} parking spaces in use"
+        }
+        Text::MadeLeaderboard => "You made the top ten! Enter your initials:",
+        Text::EnterInitials => "Enter",
+        Text::EnterInitialsTooltip => "Put this run in the high score table.",
+        Text::RankColumn => "#",
+        Text::InitialsColumn => "Name",
+        Text::PointsColumn => "Points",
+        Text::TimeColumn => "Time",
+        Text::SeedColumn => "Seed",
+        Text::DateColumn => "Date",
+        Text::SecondsValue => "{}s",
+        Text::SavedGames => "Saved games",
+        Text::SavedGamesTooltip => {
+            "Keep this run in one of {} slots to load it again later, or pick up a run you \
+            saved."
+        }
+        Text::SaveName => "Name:",
+        Text::NameColumn => "Name",
+        Text::SaveGame => "Save",
+        Text::SaveGameTooltip => "Keep this run exactly as it is in this slot.",
+        Text::LoadGameTooltip => "Pick up the run in this slot, paused, where it was saved.",
+        Text::LoadQuestion => "Load the saved game?",
+        Text::DeleteSave => "Delete",
+        Text::DeleteSaveTooltip => "Throw away the run saved in this slot.",
+        Text::OverwriteQuestion => "Save over this game?",
+        Text::OverwriteMessage => "\"{}\", saved {}, will be replaced by this run.",
+        Text::DeleteQuestion => "Delete the saved game?",
+        Text::DeleteMessage => "\"{}\", saved {}, will be gone for good.",
+        Text::Load => "Load",
+        Text::ToastGameSaved => "Game saved at {}s",
+        Text::ToastLoadFailed => "Couldn't load the saved game: {}",
+        Text::SaveReplay => "Save replay",
+        Text::SaveReplayTooltip => {
+            "Keep this run to watch it again from the start. Replaces the replay saved before."
+        }
+        Text::WatchReplay => "Watch replay ({}s run)",
+        Text::WatchReplayTooltip => "Watch the run you saved a replay of, from the start.",
+        Text::WatchingReplay => "Replay",
+        Text::ToastReplaySaved => "Replay saved",
+        Text::ToastReplayDiverged => {
+            "The replay went differently from the recorded run at {}s"
+        }
+        Text::Mode => "Mode",
+        Text::Endless => "Endless",
+        Text::EndlessTooltip => "Hold out as long as you can. Enemies come faster and faster.",
+        Text::DefenseContract => "Defense contract",
+        Text::DefenseContractTooltip => {
+            "Hold out for {} minutes against enemies on a fixed schedule. The fewer units you \
+            lose and bosses you let through, the better the medal."
+        }
+        Text::ContractTimeLeft => "({}s to go)",
+        Text::ContractFulfilled => "Contract fulfilled!",
+        Text::ContractFulfilledHeading => "The base held out until the end of the contract!",
+        Text::MedalEarned => "Medal: {}",
+        Text::Bronze => "Bronze",
+        Text::Silver => "Silver",
+        Text::Gold => "Gold",
+        Text::BossesLeaked => "Bosses that broke through",
+        Text::Achievements => "Achievements",
+        Text::AchievementsTooltip => "Goals reached so far, and hints for the others.",
+        Text::AchievementsUnlocked => "{} of {} unlocked",
+        Text::ToastAchievementUnlocked => "Achievement unlocked: {}",
+        Text::FirstInterception => "First contact",
+        Text::FirstInterceptionDescription => "Intercept an enemy.",
+        Text::TenInOneRun => "Busy skies",
+        Text::TenInOneRunDescription => "Intercept 10 enemies in one run.",
+        Text::BossHunter => "Boss hunter",
+        Text::BossHunterDescription => "Intercept a boss.",
+        Text::CleanThreeMinutes => "Not a scratch",
+        Text::CleanThreeMinutesDescription => "Survive 3 minutes without losing a unit.",
+        Text::TenMinutes => "Long haul",
+        Text::TenMinutesDescription => "Survive 10 minutes in one run.",
+        Text::LeanContract => "Skeleton crew",
+        Text::LeanContractDescription => {
+            "Fulfil a defense contract with no more than 4 units ever taken out of storage. \
+            Runs picked up from a save don't count."
+        }
+        Text::GoldContract => "Flawless",
+        Text::GoldContractDescription => "Fulfil a defense contract with a gold medal.",
+    }
+}
+
+const GERMAN: &[(Text, &str)] = &[
+    (Text::File, "Datei"),
+    (Text::Menu, "Menü"),
+    (
+        Text::MenuTooltip,
+        "Das Spiel anhalten und das Menü öffnen. (Esc)",
+    ),
+    (Text::Settings, "Einstellungen"),
+    (
+        Text::SettingsTooltip,
+        "Ändern, wie das Spiel aussieht und sich spielt.",
+    ),
+    (Text::Restart, "Neustart"),
+    (
+        Text::RestartTooltip,
+        "Diese Runde aufgeben und zurück zum Hauptmenü.",
+    ),
+    (Text::Quit, "Beenden"),
+    (
+        Text::QuitTooltip,
+        "Das Spiel schließen. Die laufende Runde geht verloren.",
+    ),
+    (Text::Resume, "Weiter"),
+    (
+        Text::ResumeTooltip,
+        "Da weitermachen, wo du aufgehört hast. (Esc)",
+    ),
+    (Text::Confirm, "Bestätigen"),
+    (Text::Cancel, "Abbrechen"),
+    (Text::CancelTooltip, "Doch nicht, die Runde geht weiter."),
+    (Text::Paused, "Pause"),
+    (Text::QuitQuestion, "Beenden?"),
+    (Text::RestartQuestion, "Neustart?"),
+    (Text::RunWillBeLost, "Deine Runde von {} s geht verloren."),
+    (Text::Best, "Bestzeit: {} s"),
+    (Text::Fps, "{} FPS"),
+    (Text::AllocationsPerFrame, "{} Allokationen/Frame"),
+    (Text::Survived, "Überlebt: {} s"),
+    (Text::NextContact, "nächster Kontakt in ~{} s"),
+    (Text::Pressure, "Druck"),
+    (
+        Text::PressureTooltip,
+        "Wie schnell Feinde kommen. Es wird nur mehr.",
+    ),
+    (Text::Start, "Start"),
+    (
+        Text::StartTooltip,
+        "Eine neue Runde beginnen. Feinde kommen erst, wenn du hier drückst.",
+    ),
+    (Text::Difficulty, "Schwierigkeit"),
+    (
+        Text::DifficultyTooltip,
+        "Wie schnell Feinde kommen und wie sehr sie dabei zulegen, und wie lange Reparaturen \
+         und das Vorbereiten von Einheiten dauern.",
+    ),
+    (Text::Easy, "Leicht"),
+    (Text::Normal, "Normal"),
+    (Text::Hard, "Schwer"),
+    (Text::Tutorial, "Einführung"),
+    (
+        Text::TutorialTooltip,
+        "Eine Runde mit Schritt-für-Schritt-Einführung beginnen. Feinde kommen erst, wenn sie \
+        vorbei ist.",
+    ),
+    (Text::TutorialStep, "Schritt {} von {}"),
+    (Text::SkipTutorial, "Einführung überspringen"),
+    (
+        Text::SkipTutorialTooltip,
+        "Die Feinde sofort kommen lassen. Die Einführung startet nicht mehr von selbst.",
+    ),
+    (Text::Language, "Sprache"),
+    (
+        Text::GameSpeedTooltip,
+        "Das Spiel zum Lernen verlangsamen oder als Herausforderung beschleunigen. Leertaste \
+        pausiert, + und - ändern das Tempo.",
+    ),
+    (
+        Text::PauseTooltip,
+        "Das Spiel pausieren. Die Leertaste tut das auch.",
+    ),
+    (Text::UiScale, "Oberflächengröße"),
+    (
+        Text::UiScaleTooltip,
+        "Alles auf dem Bildschirm größer oder kleiner machen.",
+    ),
+    (Text::Fullscreen, "Vollbild"),
+    (Text::PauseOnFocusLoss, "Beim Wegwechseln pausieren"),
+    (
+        Text::PauseOnFocusLossTooltip,
+        "Die Runde pausieren, wenn du zu einem anderen Tab wechselst. Geht bisher nur im Browser.",
+    ),
+    (
+        Text::FullscreenTooltip,
+        "Das Spiel füllt den ganzen Bildschirm. Vorerst nur im Browser möglich.",
+    ),
+    (Text::SoundVolume, "Lautstärke"),
+    (Text::SoundVolumeTooltip, "Wie laut die Soundeffekte sind."),
+    (Text::Mute, "Stumm"),
+    (
+        Text::MuteTooltip,
+        "Alle Töne ausschalten, ohne die Lautstärke zu verlieren.",
+    ),
+    (Text::MusicVolume, "Musiklautstärke"),
+    (
+        Text::MusicVolumeTooltip,
+        "Wie laut die Hintergrundmusik ist.",
+    ),
+    (Text::MuteMusic, "Musik stumm"),
+    (
+        Text::MuteMusicTooltip,
+        "Die Musik ausschalten, die Soundeffekte aber behalten.",
+    ),
+    (Text::ColorScheme, "Design"),
+    (
+        Text::ColorSchemeTooltip,
+        "Die Farben von Fenstern, Text und Bahnen.",
+    ),
+    (Text::DarkPurple, "Dunkles Lila"),
+    (Text::Light, "Hell"),
+    (Text::HighContrast, "Hoher Kontrast"),
+    (Text::LargeUi, "Große Oberfläche"),
+    (
+        Text::LargeUiTooltip,
+        "Größere Schrift und Bahnen, zum Spielen an einem Bildschirm am anderen Ende des Raums.",
+    ),
+    (Text::ColorblindMode, "Farbenblind-Modus"),
+    (
+        Text::ColorblindModeTooltip,
+        "Farben verwenden, die auch bei Rot-Grün-Schwäche unterscheidbar bleiben.",
+    ),
+    (Text::AutoPark, "Automatisch parken"),
+    (
+        Text::AutoParkTooltip,
+        "Einheiten parken, sobald sie zurück sind und ein Parkplatz frei ist.",
+    ),
+    (Text::ShowFps, "FPS anzeigen"),
+    (
+        Text::ShowFpsTooltip,
+        "Anzeigen, wie viele Bilder pro Sekunde gezeichnet werden.",
+    ),
+    (Text::ToastUnitDestroyed, "Einheit {} wurde zerstört!"),
+    (
+        Text::ToastBossLeaked,
+        "Ein Typ-{}-Boss hat einen Parkplatz zerstört! Noch {} übrig.",
+    ),
+    (Text::DebugOverlay, "Debug-Infos"),
+    (
+        Text::DebugOverlayTooltip,
+        "Bildzeiten, das Geschehen auf dem Feld und den Zufalls-Seed anzeigen. Praktisch für Fehlerberichte.",
+    ),
+    (Text::FrameTime, "Bildzeit"),
+    (Text::AverageFps, "FPS im Schnitt"),
+    (Text::FrameTimeRange, "Schnellstes / langsamstes Bild"),
+    (Text::FrameCount, "Gezeichnete Bilder"),
+    (Text::UnitCensus, "Einheiten"),
+    (
+        Text::UnitCensusValue,
+        "{} eingelagert, {} unterwegs, {} geparkt, {} in der Luft, {} wartend",
+    ),
+    (Text::EnemyCensus, "Gegner"),
+    (Text::EnemyCensusValue, "{} ({} Bosse)"),
+    (Text::MeanSpawnInterval, "Mittlerer Abstand"),
+    (Text::RepairTime, "Volle Reparatur"),
+    (Text::PrepareTime, "Vorbereitung"),
+    (Text::ParkingSlotsUsed, "Belegte Parkplätze"),
+    (Text::Seed, "Seed"),
+    (Text::Cheats, "Cheats"),
+    (Text::CheatDistanceLeft, "% der Bahn übrig"),
+    (Text::CheatSpawnEnemy, "Feind erscheinen lassen"),
+    (Text::CheatHealAll, "Alle Einheiten heilen"),
+    (Text::CheatFinishNow, "Einheit {} sofort fertig"),
+    (Text::CheatFinishNowNoUnit, "Gewählte Einheit sofort fertig"),
+    (Text::CheatSpawnInterval, "s zwischen Feinden"),
+    (Text::CheatSet, "Setzen"),
+    (Text::CheatInvincible, "Unverwundbar"),
+    (Text::YourBase, "Deine Basis"),
+    (Text::Controls, "Steuerung"),
+    (Text::ListUnits, "Einheiten auflisten"),
+    (Text::ByState, "nach Zustand"),
+    (
+        Text::ByStateTooltip,
+        "Einheiten danach gruppieren, wo sie in der Basis sind.",
+    ),
+    (Text::SingleList, "in einer Liste"),
+    (
+        Text::SingleListTooltip,
+        "Jede Einheit einmal der Reihe nach auflisten, egal wo sie ist.",
+    ),
+    (Text::StoredUnits, "Eingelagerte Einheiten ({})"),
+    (
+        Text::StoredUnitsDescription,
+        "Hier werden beschädigte Einheiten repariert.",
+    ),
+    (Text::ParkingArea, "Parkplatz ({} Einheiten)"),
+    (
+        Text::ParkingAreaDescription,
+        "Bereite deine Einheiten hier auf den Kampf in einer der Bahnen vor und schick sie los!",
+    ),
+    (Text::ParkingSlot, "P{}"),
+    (Text::EmptySlot, "(frei)"),
+    (
+        Text::EmptySlotTooltip,
+        "Wähle eine eingelagerte oder wartende Einheit aus und klicke hier, um sie auf diesen \
+        Platz zu bringen.",
+    ),
+    (Text::WreckedSlot, "zerstört"),
+    (
+        Text::WreckedSlotTooltip,
+        "Ein Boss hat diesen Platz zerstört. Er verschwindet, sobald die Einheit darauf wegfährt.",
+    ),
+    (Text::WaitingToReturn, "Warten auf Rückkehr ({})"),
+    (
+        Text::WaitingToReturnDescription,
+        "Einheiten hier stehen nur herum, obwohl sie kämpfen oder repariert werden könnten! \
+        Bring sie so schnell wie möglich weiter!",
+    ),
+    (
+        Text::SelectUnitTooltip,
+        "Diese Einheit auswählen, um ihr mit der Tastatur Befehle zu geben. Rechtsklick auf ihre \
+        Zeile für alles, was sie gerade tun kann.",
+    ),
+    (Text::Health, "Zustand: {}"),
+    (Text::SecondsToGo, "noch {} s"),
+    (Text::InStorage, "Eingelagert."),
+    (Text::MovingIntoStorage, "Wird eingelagert."),
+    (Text::ComingOutOfStorage, "Wird ausgelagert."),
+    (Text::Parking, "Parkt."),
+    (Text::NotReady, "Nicht bereit. Vorbereiten für..."),
+    (Text::PreparingFor, "Wird für Kampftyp {} vorbereitet."),
+    (Text::ReadyFor, "Bereit für Kampftyp {}."),
+    (Text::PatrollingLane, "Patrouilliert Bahn {}."),
+    (Text::ReturningFromLane, "Kehrt von Bahn {} zurück."),
+    (Text::Waiting, "Wartet."),
+    (Text::BringOutOfStorage, "Auslagern"),
+    (
+        Text::UnStoreTooltip,
+        "Diese Einheit auf einen freien Parkplatz bringen, damit sie für den Kampf vorbereitet \
+        werden kann. Dauert {} Sekunden.",
+    ),
+    (
+        Text::PrepareTooltip,
+        "Diese Einheit für eine Bahn vorbereiten. Eine Einheit kann nur Feinde in der Bahn \
+        bekämpfen, für die sie vorbereitet wurde. Dauert {} Sekunden.",
+    ),
+    (Text::MoveIntoStorage, "Einlagern"),
+    (
+        Text::StoreTooltip,
+        "Diese Einheit einlagern, wo sie langsam repariert wird. Dauert {} Sekunden und macht \
+        ihren Parkplatz frei.",
+    ),
+    (Text::TakeOff, "Abheben!"),
+    (
+        Text::TakeOffTooltip,
+        "Diese Einheit für {} Sekunden auf Patrouille durch ihre Bahn schicken. Sie kann einen \
+        einzigen Feind abwehren und kehrt dann zur Basis zurück.",
+    ),
+    (Text::Park, "Parken"),
+    (
+        Text::ParkTooltip,
+        "Diese Einheit parken, damit sie für ihren nächsten Einsatz vorbereitet werden kann. \
+        Dauert {} Sekunden.",
+    ),
+    (
+        Text::NoFreeParkingTooltip,
+        "Kein Parkplatz frei. Lagere zuerst eine geparkte Einheit ein oder schick sie los.",
+    ),
+    (Text::PrepareForLane, "Für Bahn {} vorbereiten"),
+    (Text::Recall, "Zurückrufen"),
+    (Text::CancelPreparation, "Vorbereitung abbrechen"),
+    (Text::SurvivedSoFar, "Du hast bisher {} Sekunden überlebt!"),
+    (Text::Briefing, "Lagebesprechung"),
+    (
+        Text::BriefingBody,
+        "Die fiesen Leute aus der Meatropolis mit ihrer Königin auf dieser Insel im Meer fallen \
+        in unser großartiges Land Fruitopia ein! Sie wollen unsere kostbaren Obstgärten abholzen \
+        und die Bäume zu ihren elenden Sägemehlwürstchen verarbeiten!\n\
+        Der Kaiser hat DIR befohlen, die Hauptverteidigung gegen die Meatropolitaner zu leiten. \
+        Sie sind stark und wollen diese Obstgärten WIRKLICH haben. Sie werden immer schneller \
+        kommen.\n\
+        Halte sie so lange auf, wie du kannst, und wir schlagen dich für die Eiserne Pflaume \
+        vor!",
+    ),
+    (Text::Battlezone, "Das Kampfgebiet"),
+    (
+        Text::BattlezoneHelp,
+        "Feinde (◀ ◆ ▲ ●) kommen von rechts auf verschiedenen Bahnen. Bereite deine Einheiten \
+        für die richtige Bahn vor und schick sie in den Kampf. Jede Einheit (▶) kann einen \
+        einzigen Feind abwehren, bevor sie zur Basis zurückkehrt (▷). Deine Einheiten nutzen \
+        sich ab. Denk daran, sie zu reparieren! Bosse, die durchkommen, zerstören einen deiner \
+        Parkplätze.",
+    ),
+    (Text::Threats, "Bedrohungen"),
+    (Text::Lane, "Bahn"),
+    (Text::Inbound, "Im Anflug"),
+    (Text::NextHitIn, "Nächster Treffer in"),
+    (Text::Airborne, "In der Luft"),
+    (Text::Prepared, "Vorbereitet"),
+    (Text::Scramble, "Alarmstart"),
+    (Text::ScrambleButton, "Alarmstart: {}, ~{}s bis in der Luft"),
+    (
+        Text::ScrambleTooltip,
+        "Schickt die schnellste Einheit gegen diese Bahn: zuerst eine, die schon dafür vorbereitet \
+        ist, dann eine geparkte, dann eine aus dem Lager. Sie erledigt jeden Schritt bis zum Start \
+        von selbst. Ein anderer Befehl bricht den Rest ab.",
+    ),
+    (
+        Text::NothingToScrambleTooltip,
+        "Keine Einheit kann geschickt werden: Keine ist geparkt oder für diese Bahn vorbereitet, \
+        und es ist kein Parkplatz frei, um eine aus dem Lager zu holen.",
+    ),
+    (
+        Text::UncoveredLaneTooltip,
+        "Auf dieser Bahn kommen mehr Feinde, als du Einheiten in der Luft oder dafür vorbereitet \
+        hast.",
+    ),
+    (
+        Text::LaneTooltip,
+        "Feinde dieses Typs kommen von rechts und greifen die Basis links an. Nur Einheiten, die \
+        für diese Bahn vorbereitet sind, können sie abfangen.",
+    ),
+    (Text::LaneBase, "BASIS"),
+    (Text::LaneSpawn, "START"),
+    (Text::Boss, "BOSS"),
+    (Text::Breaching, "{} Typ {} bricht durch in {} s!"),
+    (
+        Text::DismissAlertTooltip,
+        "Diese Warnung ausblenden. Sie kommt wieder, wenn der Feind näher kommt.",
+    ),
+    (Text::BattleLog, "Kampfprotokoll"),
+    (Text::HoldLogScroll, "Position halten"),
+    (
+        Text::HoldLogScrollTooltip,
+        "Das Kampfprotokoll nicht zu neuen Einträgen springen lassen.",
+    ),
+    (Text::Stats, "Statistik"),
+    (Text::SortiesFlown, "Einsätze geflogen"),
+    (Text::EnemiesDestroyed, "Feinde zerstört"),
+    (Text::InLane, "    auf Bahn {} {}"),
+    (Text::UnitsLost, "Einheiten verloren"),
+    (Text::RepairsCompleted, "Reparaturen abgeschlossen"),
+    (Text::ParkingUtilisation, "Parkplatzauslastung"),
+    (Text::Hit, "Treffer!"),
+    (
+        Text::BaseHit,
+        "Deine Basis wurde getroffen! Du bist tot !!!!",
+    ),
+    (
+        Text::SurvivedFinal,
+        "Immerhin hast du {} Sekunden überlebt, das ist großartig! Jetzt mach einen Screenshot \
+        und prahl bei deinen Freunden mit deinen m4d sk1llz :-D",
+    ),
+    (
+        Text::NewPersonalBest,
+        "Das ist eine neue persönliche Bestzeit!",
+    ),
+    (
+        Text::PlayAgain,
+        "Danke! Das hat echt Spaß gemacht!! Gleich nochmal...",
+    ),
+    (
+        Text::PlayAgainTooltip,
+        "Sofort eine neue Runde beginnen.",
+    ),
+    (Text::RetrySameSeed, "Seed {} nochmal"),
+    (
+        Text::RetrySameSeedTooltip,
+        "Eine neue Runde mit denselben Gegnern wie in dieser beginnen.",
+    ),
+    (Text::NewGamePlus, "Neues Spiel+ {}"),
+    (
+        Text::NewGamePlusTooltip,
+        "Gleich den nächsten Auftrag annehmen. Die Gegner kommen von Anfang an schneller, und \
+        mit jedem weiteren erfüllten Auftrag in Folge noch schneller.",
+    ),
+    (Text::NewGamePlusLevel, "NG+{}"),
+    (Text::Version, "Version {} ({})"),
+    (Text::RunSeed, "Seed {}"),
+    (Text::CopySeed, "Seed kopieren"),
+    (
+        Text::CopySeedTooltip,
+        "Kopiert den Seed in die Zwischenablage. Runden mit demselben Seed schicken dieselben \
+        Feinde.",
+    ),
+    (Text::SaveScreenshot, "Screenshot speichern"),
+    (
+        Text::SaveScreenshotTooltip,
+        "Lädt ein Bild des Spiels herunter, so wie es gerade aussieht.",
+    ),
+    (
+        Text::ScreenshotUnsupportedTooltip,
+        "Screenshots gehen nur im Browser. Nimm stattdessen das Screenshot-Werkzeug deines \
+        Systems.",
+    ),
+    (Text::ToastScreenshotSaved, "Screenshot gespeichert als {}"),
+    (
+        Text::ToastScreenshotFailed,
+        "Screenshot konnte nicht gespeichert werden: {}",
+    ),
+    (Text::PressAKey, "Taste drücken…"),
+    (
+        Text::RebindTooltip,
+        "Klicken, dann die neue Taste drücken. Escape behält die alte.",
+    ),
+    (Text::ResetControls, "Steuerung zurücksetzen"),
+    (
+        Text::ResetControlsTooltip,
+        "Zurück zu den Tasten, mit denen das Spiel kam.",
+    ),
+    (
+        Text::ContinueRun,
+        "Vorherige Runde fortsetzen ({} s überlebt)",
+    ),
+    (
+        Text::ContinueRunTooltip,
+        "Die Runde, die beim Schließen des Spiels noch lief, pausiert fortsetzen. Eine neue Runde \
+        zu beginnen verwirft sie.",
+    ),
+    (Text::ChallengeSeed, "Herausforderung mit Seed {} ({})"),
+    (Text::TouchLayout, "Touch-Bedienung"),
+    (
+        Text::TouchLayoutTooltip,
+        "Größere Knöpfe und Bahnen und ein Knopf für das Menü jeder Einheit statt Rechtsklick. Auf \
+        Touchscreens standardmäßig an.",
+    ),
+    (Text::UnitActions, "Aktionen…"),
+    (
+        Text::UnitActionsTooltip,
+        "Alles, was diese Einheit gerade tun kann.",
+    ),
+    (Text::MostParked, "Höchstens gleichzeitig geparkt"),
+    (Text::Score, "Punkte: {} (x{})"),
+    (Text::FinalScore, "Punkte: {}"),
+    (Text::NewBestScore, "Neuer Punkterekord!"),
+    (
+        Text::CheatsUsed,
+        "Es wurde geschummelt, daher zählt diese Runde nicht für die Bestenliste.",
+    ),
+    (Text::BestScore, "Meiste Punkte: {}"),
+    (Text::HighScores, "Bestenliste"),
+    (
+        Text::HighScoresTooltip,
+        "Die zehn besten Runden, die hier gespielt wurden.",
+    ),
+    (
+        Text::NoHighScores,
+        "Noch keine Runden. Deine könnte die erste sein!",
+    ),
+    (Text::DailyChallenge, "Tägliche Herausforderung"),
+    (
+        Text::DailyScoredTooltip,
+        "Die Herausforderung von heute ({}): dieselben Gegner für alle, endlos auf Normal, \
+        höchstens 1× und ohne Schummeln. Nur dein erster Versuch des Tages zählt, auch wenn du \
+        ihn aufgibst.",
+    ),
+    (
+        Text::DailyPracticeTooltip,
+        "Du hattest deinen Versuch an der Herausforderung von heute ({}) schon. Nochmal zu \
+        spielen ist Übung und zählt nicht.",
+    ),
+    (Text::DailyResults, "Tägliche Ergebnisse"),
+    (
+        Text::DailyResultsTooltip,
+        "Wie deine Versuche an den täglichen Herausforderungen der letzten Tage liefen.",
+    ),
+    (
+        Text::NoDailyResults,
+        "Noch keine tägliche Herausforderung gespielt.",
+    ),
+    (Text::DailyUnfinished, "nicht beendet"),
+    (Text::DailyLabel, "Täglich {}"),
+    (Text::DailyPracticeLabel, "Täglich {} (Übung)"),
+    (
+        Text::DailyRecorded,
+        "Dein Ergebnis in der Herausforderung vom {} ist festgehalten.",
+    ),
+    (
+        Text::DailyPractice,
+        "Übung, daher zählt diese Runde nicht für die tägliche Herausforderung.",
+    ),
+    (
+        Text::DailySpeedTooltip,
+        "Die tägliche Herausforderung läuft höchstens mit 1×.",
+    ),
+    (
+        Text::OnlineSubmitting,
+        "Die Runde wird an die Online-Bestenliste geschickt…",
+    ),
+    (
+        Text::OnlineSubmitted,
+        "Die Runde steht in der Online-Bestenliste.",
+    ),
+    (
+        Text::OnlineFailed,
+        "Die Runde konnte nicht an die Online-Bestenliste geschickt werden: {}",
+    ),
+    (
+        Text::OnlineLeaderboard,
+        "Runden an die Online-Bestenliste schicken",
+    ),
+    (
+        Text::OnlineLeaderboardTooltip,
+        "Nach jeder Runde, die zählt, deine Initialen, Punkte, Überlebenszeit, Seed, \
+        Schwierigkeit und die Version des Spiels an die Online-Bestenliste schicken.",
+    ),
+    (Text::OnlineInitials, "Initialen"),
+    (
+        Text::OnlineInitialsTooltip,
+        "Wie dich die Online-Bestenliste zeigt.",
+    ),
+    (Text::Scenarios, "Szenarien"),
+    (
+        Text::ScenariosTooltip,
+        "Vorgegebene Lagen mit eigenen Einheiten und Gegnern und einem Ziel. Sie zählen nicht \
+        für den Rekord.",
+    ),
+    (Text::PlayScenario, "Spielen"),
+    (Text::PlayScenarioTooltip, "Dieses Szenario starten."),
+    (Text::ScenarioSurviveGoal, "Ziel: {}s durchhalten."),
+    (
+        Text::ScenarioInterceptAllGoal,
+        "Ziel: jeden Gegner aufhalten. Ein Boss, der durchkommt, lässt das Szenario scheitern.",
+    ),
+    (Text::ScenarioTimeLeft, "noch {}s durchhalten"),
+    (Text::ScenarioEnemiesLeft, "noch {} Gegner"),
+    (Text::ScenarioWon, "Szenario gewonnen"),
+    (Text::ScenarioFailed, "Szenario gescheitert"),
+    (
+        Text::ScenarioPlayAgainTooltip,
+        "Dasselbe Szenario noch einmal von vorn spielen.",
+    ),
+    (Text::DefeatBreached, "Typ {} ist bei {}s durchgebrochen."),
+    (
+        Text::DefeatBossBreached,
+        "Ein Boss vom Typ {} ist bei {}s durchgebrochen.",
+    ),
+    (
+        Text::DefeatNoUnits,
+        "Du hattest keine Einheiten mehr, um ihn aufzuhalten.",
+    ),
+    (
+        Text::DefeatPatrolling,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, patrouillierte dort schon, aber zu spät, \
+        um den Gegner abzufangen.",
+    ),
+    (
+        Text::DefeatReady,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, stand bereit, ist aber nie gestartet.",
+    ),
+    (
+        Text::DefeatPreparing,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, wurde noch vorbereitet, {}s bis bereit.",
+    ),
+    (
+        Text::DefeatParked,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, war geparkt, aber nicht dafür \
+        vorbereitet, {}s bis bereit.",
+    ),
+    (
+        Text::DefeatParking,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, war auf dem Weg zu einem Parkplatz, {}s \
+        bis bereit.",
+    ),
+    (
+        Text::DefeatAirborne,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, war anderswo in der Luft, {}s bis bereit.",
+    ),
+    (
+        Text::DefeatWaitingToPark,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, wartete auf einen Parkplatz, {}s bis \
+        bereit.",
+    ),
+    (
+        Text::DefeatInStorage,
+        "Deine nächste Einheit für Bahn {}, Einheit {}, war im Lager, {}s bis bereit.",
+    ),
+    (Text::Timeline, "Verlauf"),
+    (Text::TimelineScore, "Punkte"),
+    (Text::TimelineEnemies, "Gegner"),
+    (Text::TimelineAirborne, "Einheiten in der Luft"),
+    (Text::TimelineParking, "Belegte Parkplätze"),
+    (Text::TimelineLeak, "Gegner durchgekommen"),
+    (Text::TimelineUnitLost, "Einheit verloren"),
+    (Text::TimelineMinute, "{} min"),
+    (
+        Text::TimelineHover,
+        "{}s: {} Punkte, {} Gegner, {} Einheiten in der Luft, {} von {} Parkplätzen belegt",
+    ),
+    (
+        Text::MadeLeaderboard,
+        "Du bist unter den besten zehn! Gib deine Initialen ein:",
+    ),
+    (Text::EnterInitials, "Eintragen"),
+    (
+        Text::EnterInitialsTooltip,
+        "Diese Runde in die Bestenliste eintragen.",
+    ),
+    (Text::RankColumn, "#"),
+    (Text::InitialsColumn, "Name"),
+    (Text::PointsColumn, "Punkte"),
+    (Text::TimeColumn, "Zeit"),
+    (Text::SeedColumn, "Seed"),
+    (Text::DateColumn, "Datum"),
+    (Text::SecondsValue, "{} s"),
+    (Text::SavedGames, "Gespeicherte Spiele"),
+    (
+        Text::SavedGamesTooltip,
+        "Diese Runde in einem von {} Plätzen behalten, um sie später wieder zu laden, oder \
+        eine gespeicherte Runde fortsetzen.",
+    ),
+    (Text::SaveName, "Name:"),
+    (Text::NameColumn, "Name"),
+    (Text::SaveGame, "Speichern"),
+    (
+        Text::SaveGameTooltip,
+        "Diese Runde genau so in diesem Platz behalten.",
+    ),
+    (
+        Text::LoadGameTooltip,
+        "Die Runde aus diesem Platz pausiert dort fortsetzen, wo sie gespeichert wurde.",
+    ),
+    (Text::LoadQuestion, "Gespeichertes Spiel laden?"),
+    (Text::DeleteSave, "Löschen"),
+    (
+        Text::DeleteSaveTooltip,
+        "Die in diesem Platz gespeicherte Runde wegwerfen.",
+    ),
+    (Text::OverwriteQuestion, "Dieses Spiel überschreiben?"),
+    (
+        Text::OverwriteMessage,
+        "„{}“, gespeichert {}, wird durch diese Runde ersetzt.",
+    ),
+    (Text::DeleteQuestion, "Gespeichertes Spiel löschen?"),
+    (
+        Text::DeleteMessage,
+        "„{}“, gespeichert {}, ist dann für immer weg.",
+    ),
+    (Text::Load, "Laden"),
+    (Text::ToastGameSaved, "Spiel bei {} s gespeichert"),
+    (
+        Text::ToastLoadFailed,
+        "Das gespeicherte Spiel konnte nicht geladen werden: {}",
+    ),
+    (Text::SaveReplay, "Wiederholung speichern"),
+    (
+        Text::SaveReplayTooltip,
+        "Diese Runde behalten, um sie von Anfang an noch einmal anzusehen. Ersetzt die zuvor \
+        gespeicherte Wiederholung.",
+    ),
+    (Text::WatchReplay, "Wiederholung ansehen ({} s Runde)"),
+    (
+        Text::WatchReplayTooltip,
+        "Die Runde, deren Wiederholung du gespeichert hast, von Anfang an ansehen.",
+    ),
+    (Text::WatchingReplay, "Wiederholung"),
+    (Text::ToastReplaySaved, "Wiederholung gespeichert"),
+    (
+        Text::ToastReplayDiverged,
+        "Die Wiederholung weicht bei {} s von der aufgezeichneten Runde ab",
+    ),
+    (Text::Mode, "Modus"),
+    (Text::Endless, "Endlos"),
+    (
+        Text::EndlessTooltip,
+        "So lange durchhalten, wie du kannst. Feinde kommen immer schneller.",
+    ),
+    (Text::DefenseContract, "Verteidigungsauftrag"),
+    (
+        Text::DefenseContractTooltip,
+        "{} Minuten gegen Feinde nach festem Zeitplan durchhalten. Je weniger Einheiten du \
+        verlierst und Bosse du durchlässt, desto besser die Medaille.",
+    ),
+    (Text::ContractTimeLeft, "(noch {} s)"),
+    (Text::ContractFulfilled, "Auftrag erfüllt!"),
+    (
+        Text::ContractFulfilledHeading,
+        "Die Basis hat bis zum Ende des Auftrags durchgehalten!",
+    ),
+    (Text::MedalEarned, "Medaille: {}"),
+    (Text::Bronze, "Bronze"),
+    (Text::Silver, "Silber"),
+    (Text::Gold, "Gold"),
+    (Text::BossesLeaked, "Durchgebrochene Bosse"),
+    (Text::Achievements, "Erfolge"),
+    (
+        Text::AchievementsTooltip,
+        "Bisher erreichte Ziele und Hinweise zu den übrigen.",
+    ),
+    (Text::AchievementsUnlocked, "{} von {} freigeschaltet"),
+    (Text::ToastAchievementUnlocked, "Erfolg freigeschaltet: {}"),
+    (Text::FirstInterception, "Erster Kontakt"),
+    (Text::FirstInterceptionDescription, "Einen Feind abfangen."),
+    (Text::TenInOneRun, "Viel los am Himmel"),
+    (
+        Text::TenInOneRunDescription,
+        "10 Feinde in einer Runde abfangen.",
+    ),
+    (Text::BossHunter, "Bossjäger"),
+    (Text::BossHunterDescription, "Einen Boss abfangen."),
+    (Text::CleanThreeMinutes, "Kein Kratzer"),
+    (
+        Text::CleanThreeMinutesDescription,
+        "3 Minuten überleben, ohne eine Einheit zu verlieren.",
+    ),
+    (Text::TenMinutes, "Langstrecke"),
+    (
+        Text::TenMinutesDescription,
+        "10 Minuten in einer Runde überleben.",
+    ),
+    (Text::LeanContract, "Rumpfmannschaft"),
+    (
+        Text::LeanContractDescription,
+        "Einen Verteidigungsauftrag erfüllen, ohne mehr als 4 Einheiten aus dem Lager zu \
+        holen. Aus einem Spielstand fortgesetzte Runden zählen nicht.",
+    ),
+    (Text::GoldContract, "Makellos"),
+    (
+        Text::GoldContractDescription,
+        "Einen Verteidigungsauftrag mit Goldmedaille erfüllen.",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn german_has_every_text_once() {
+        for text in Text::iter() {
+            let entries = GERMAN.iter().filter(|(key, _)| *key == text).count();
+            assert_eq!(entries, 1, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn translations_have_the_same_placeholders() {
+        for &(text, german) in GERMAN {
+            assert_eq!(
+                german.matches("{}").count(),
+                english(text).matches("{}").count(),
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_order() {
+        assert_eq!(interpolate("{} of {}", &[&2, &"five"]), "2 of five");
+        assert_eq!(interpolate("{}%", &[&40]), "40%");
+        assert_eq!(interpolate("no placeholders", &[]), "no placeholders");
+    }
+
+    #[test]
+    fn numbers_are_grouped_by_language() {
+        assert_eq!(Strings::new(Language::English).number(12450), "12,450");
+        assert_eq!(Strings::new(Language::German).number(1234567), "1.234.567");
+        assert_eq!(Strings::new(Language::German).number(999), "999");
+    }
+
+    #[test]
+    fn no_tooltip_is_empty() {
+        let tooltips = Text::iter().filter(|text| format!("{:?}", text).ends_with("Tooltip"));
+        for text in tooltips {
+            for language in Language::iter() {
+                let tooltip = Strings::new(language).get(text);
+                assert!(!tooltip.trim().is_empty(), "{:?} in {}", text, language);
+            }
+        }
+    }
+}
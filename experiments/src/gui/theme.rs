@@ -0,0 +1,207 @@
+//! Every colour the GUI uses for a gameplay role, so switching palettes is a one-liner.
+
+use eframe::egui::{Color32, Style, Visuals};
+
+use crate::{game::CombatType, interpolation::lerp_color, settings::ColorScheme};
+
+pub struct Theme {
+    pub scheme: ColorScheme,
+    pub colorblind_mode: bool,
+    pub background: Color32,
+    pub enemy: Color32,
+    pub patrolling: Color32,
+    pub returning: Color32,
+    pub highlight: Color32,
+    pub warning: Color32,
+    pub critical: Color32,
+    pub lane: Color32,
+    pub lane_furniture: Color32,
+}
+
+impl Theme {
+    pub fn new(scheme: ColorScheme, colorblind_mode: bool) -> Self {
+        let mut theme = match scheme {
+            ColorScheme::DarkPurple => Self::dark_purple(),
+            ColorScheme::Light => Self::light(),
+            ColorScheme::HighContrast => Self::high_contrast(),
+        };
+
+        theme.colorblind_mode = colorblind_mode;
+        if colorblind_mode {
+            theme.with_colorblind_roles()
+        } else {
+            theme
+        }
+    }
+
+    /// The original dark purple look with red/green/amber markers.
+    pub fn dark_purple() -> Self {
+        Self {
+            scheme: ColorScheme::DarkPurple,
+            colorblind_mode: false,
+            background: Color32::from_rgb(77, 53, 77).linear_multiply(0.25),
+            enemy: Color32::RED,
+            patrolling: Color32::GREEN,
+            returning: Color32::GOLD,
+            highlight: Color32::GOLD,
+            warning: Color32::from_rgb(255, 191, 0),
+            critical: Color32::RED,
+            lane: Color32::from_gray(24),
+            lane_furniture: Color32::from_gray(110),
+        }
+    }
+
+    /// Light panels with a purple tint, and darker markers so they still stand out.
+    pub fn light() -> Self {
+        Self {
+            scheme: ColorScheme::Light,
+            colorblind_mode: false,
+            background: Color32::from_rgb(236, 226, 236),
+            enemy: Color32::from_rgb(200, 0, 0),
+            patrolling: Color32::from_rgb(0, 140, 0),
+            returning: Color32::from_rgb(170, 120, 0),
+            highlight: Color32::from_rgb(170, 120, 0),
+            warning: Color32::from_rgb(200, 120, 0),
+            critical: Color32::from_rgb(200, 0, 0),
+            lane: Color32::from_gray(225),
+            lane_furniture: Color32::from_gray(120),
+        }
+    }
+
+    /// Pure black and white with saturated markers.
+    pub fn high_contrast() -> Self {
+        Self {
+            scheme: ColorScheme::HighContrast,
+            colorblind_mode: false,
+            background: Color32::BLACK,
+            enemy: Color32::from_rgb(255, 60, 60),
+            patrolling: Color32::from_rgb(0, 255, 0),
+            returning: Color32::from_rgb(255, 255, 0),
+            highlight: Color32::from_rgb(255, 255, 0),
+            warning: Color32::from_rgb(255, 200, 0),
+            critical: Color32::from_rgb(255, 60, 60),
+            lane: Color32::BLACK,
+            lane_furniture: Color32::WHITE,
+        }
+    }
+
+    /// Swaps the marker colours for Okabe-Ito based ones that stay apart in luminance for
+    /// red-green colour blindness.
+    fn with_colorblind_roles(self) -> Self {
+        Self {
+            enemy: Color32::from_rgb(230, 159, 0),
+            patrolling: Color32::from_rgb(0, 114, 178),
+            returning: Color32::from_rgb(86, 180, 233),
+            highlight: Color32::from_rgb(240, 228, 66),
+            warning: Color32::from_rgb(240, 228, 66),
+            critical: Color32::from_rgb(213, 94, 0),
+            ..self
+        }
+    }
+
+    /// The colour the type is shown in everywhere, from the prepare buttons to the lanes. The
+    /// colourblind palette is based on Okabe-Ito.
+    pub fn combat_type(&self, combat_type: CombatType) -> Color32 {
+        match (combat_type, self.colorblind_mode) {
+            (CombatType::A, false) => Color32::from_rgb(66, 135, 245),
+            (CombatType::B, false) => Color32::from_rgb(245, 166, 35),
+            (CombatType::C, false) => Color32::from_rgb(126, 211, 33),
+            (CombatType::D, false) => Color32::from_rgb(189, 16, 224),
+            (CombatType::A, true) => Color32::from_rgb(0, 114, 178),
+            (CombatType::B, true) => Color32::from_rgb(230, 159, 0),
+            (CombatType::C, true) => Color32::from_rgb(0, 158, 115),
+            (CombatType::D, true) => Color32::from_rgb(204, 121, 167),
+        }
+    }
+
+    /// The panel colour for how far the nearest enemy is down its lane, from 0 for none at all
+    /// to 1 for about to hit the base. It creeps towards red the closer that enemy gets, and
+    /// goes a dim grey once the base has been hit. Comes in a few steps only, so the visuals
+    /// don't have to be replaced every frame.
+    pub fn ambient_background(&self, nearest_threat: f32, game_over: bool) -> Color32 {
+        const STEPS: f32 = 16.0;
+        const MAX_TINT: f32 = 0.35;
+
+        // Backgrounds may be see-through, so everything is mixed premultiplied, at the
+        // background's own opacity.
+        let background = self.background;
+        let opacity = background.a() as f32 / 255.0;
+        if game_over {
+            let sum = background.r() as u32 + background.g() as u32 + background.b() as u32;
+            let grey = ((sum / 3) as f32 * 0.6) as u8;
+            return Color32::from_rgba_premultiplied(grey, grey, grey, background.a());
+        }
+
+        let tint = (nearest_threat.clamp(0.0, 1.0) * STEPS).round() / STEPS * MAX_TINT;
+        let danger = Color32::from_rgb(150, 0, 0).linear_multiply(opacity);
+        lerp_color(background, danger, tint)
+    }
+
+    /// The egui visuals for the scheme, with `background` behind the panels.
+    pub fn visuals(&self, background: Color32) -> Visuals {
+        let mut visuals = match self.scheme {
+            ColorScheme::DarkPurple | ColorScheme::HighContrast => Visuals::dark(),
+            ColorScheme::Light => Visuals::light(),
+        };
+
+        visuals.extreme_bg_color = background;
+        visuals.widgets.noninteractive.bg_fill = background;
+
+        if self.scheme == ColorScheme::HighContrast {
+            visuals.override_text_color = Some(Color32::WHITE);
+            visuals.widgets.noninteractive.bg_stroke.color = Color32::WHITE;
+        }
+
+        visuals
+    }
+}
+
+/// The default scheme's look, installed before the first frame so the game doesn't start out in
+/// egui's own colours. The GUI replaces the visuals whenever the scheme or background changes.
+pub fn install_style(style: &mut Style) {
+    let theme = Theme::new(ColorScheme::default(), false);
+    style.visuals = theme.visuals(theme.background);
+}
+
+/// The lane's glyph and name, as used in tables.
+pub fn lane_name(combat_type: CombatType) -> &'static str {
+    match combat_type {
+        CombatType::A => "◀ A",
+        CombatType::B => "◆ B",
+        CombatType::C => "▲ C",
+        CombatType::D => "● D",
+    }
+}
+
+pub const PATROLLING_GLYPH: &str = "▶";
+pub const RETURNING_GLYPH: &str = "▷";
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn combat_types_have_distinct_colors_in_both_palettes() {
+        for colorblind_mode in [false, true] {
+            let theme = Theme::new(ColorScheme::default(), colorblind_mode);
+            let mut colors: Vec<_> = CombatType::iter()
+                .map(|combat_type| theme.combat_type(combat_type))
+                .collect();
+            colors.sort_by_key(|color| color.to_array());
+            colors.dedup();
+
+            assert_eq!(colors.len(), CombatType::iter().count());
+        }
+    }
+
+    #[test]
+    fn combat_types_have_distinct_glyphs() {
+        let mut glyphs: Vec<_> = CombatType::iter().map(CombatType::glyph).collect();
+        glyphs.sort_unstable();
+        glyphs.dedup();
+
+        assert_eq!(glyphs.len(), CombatType::iter().count());
+    }
+}
@@ -1,15 +1,84 @@
-use std::ops::Sub;
+use std::ops::{Add, Sub};
 
 use derive_more::{Add, AddAssign, Sub, SubAssign};
+use engine::diagnostics::FrameDiagnostics;
+#[cfg(target_arch = "wasm32")]
 use js_sys::Date;
+use serde::{Deserialize, Serialize};
 
+/// Milliseconds since the Unix epoch.
 #[derive(Clone, Debug, Copy, Default)]
 pub struct Instant(f64);
 
 impl Instant {
+    pub(crate) fn from_millis_since_epoch(millis: f64) -> Self {
+        Self(millis)
+    }
+
+    pub(crate) fn millis_since_epoch(self) -> f64 {
+        self.0
+    }
+
+    /// Seconds from `earlier` to this instant, negative if `earlier` is later after all.
+    pub(crate) fn seconds_since(self, earlier: Self) -> f64 {
+        (self.0 - earlier.0) / 1000.0
+    }
+
+    /// The day, as year-month-day in UTC.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn utc_date(self) -> String {
+        // Days to civil date, after Howard Hinnant's `civil_from_days`.
+        let days = (self.0 / 86_400_000.0).floor() as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// The day and the time of day to the minute, in UTC.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn utc_date_time(self) -> String {
+        let minute_of_day = (self.0 / 60_000.0).floor().rem_euclid(24.0 * 60.0) as i64;
+        format!(
+            "{} {:02}:{:02}",
+            self.utc_date(),
+            minute_of_day / 60,
+            minute_of_day % 60
+        )
+    }
+
+    #[cfg(target_arch = "wasm32")]
     pub fn now() -> Self {
         Self(Date::now())
     }
+
+    /// `Date` only exists in the browser, so native builds ask the system clock instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn now() -> Self {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self(since_epoch.as_secs_f64() * 1000.0)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        Instant(self.0 + rhs.0)
+    }
 }
 
 impl Sub for Instant {
@@ -20,7 +89,20 @@ impl Sub for Instant {
     }
 }
 
-#[derive(Clone, Debug, Copy, Add, AddAssign, Default, Sub, SubAssign, PartialEq, PartialOrd)]
+#[derive(
+    Clone,
+    Debug,
+    Copy,
+    Add,
+    AddAssign,
+    Default,
+    Sub,
+    SubAssign,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+)]
 pub struct Duration(f64);
 
 impl Duration {
@@ -39,13 +121,124 @@ impl Duration {
     pub(crate) fn from_secs_f64(secs: f64) -> Self {
         Self(secs * 1000.0)
     }
+
+    /// The exact bits of the duration, for storing it where a float might not come back bit
+    /// for bit, like JSON.
+    pub(crate) fn to_bits(self) -> u64 {
+        self.0.to_bits()
+    }
+
+    pub(crate) fn from_bits(bits: u64) -> Self {
+        Self(f64::from_bits(bits))
+    }
+}
+
+/// The speeds the game can be set to run at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameSpeed {
+    Half,
+    Normal,
+    Double,
+    Quadruple,
+}
+
+impl GameSpeed {
+    pub const ALL: [Self; 4] = [Self::Half, Self::Normal, Self::Double, Self::Quadruple];
+
+    pub fn scale(self) -> f64 {
+        match self {
+            Self::Half => 0.5,
+            Self::Normal => 1.0,
+            Self::Double => 2.0,
+            Self::Quadruple => 4.0,
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Half => "0.5×",
+            Self::Normal => "1×",
+            Self::Double => "2×",
+            Self::Quadruple => "4×",
+        }
+    }
+
+    /// The speed that runs at this time scale, if any.
+    pub fn from_scale(scale: f64) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|speed| speed.scale() == scale)
+    }
+
+    /// The next speed up, or this one if it is the fastest.
+    pub fn faster(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&speed| speed == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1).min(Self::ALL.len() - 1)]
+    }
+
+    /// The next speed down, or this one if it is the slowest.
+    pub fn slower(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&speed| speed == self)
+            .unwrap_or(0);
+        Self::ALL[index.saturating_sub(1)]
+    }
+}
+
+/// Milliseconds on a clock that only ever goes forward, for timing frames. Where it starts is
+/// arbitrary, so only the time between two readings means anything. Unlike `Instant`, setting
+/// the system clock doesn't move it.
+#[derive(Clone, Debug, Copy, Default)]
+struct FrameInstant(f64);
+
+impl FrameInstant {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now() -> Self {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        let start = *START.get_or_init(std::time::Instant::now);
+        Self(start.elapsed().as_secs_f64() * 1000.0)
+    }
+
+    /// `Date` follows the system clock, so frame times are kept from going negative in `Time`.
+    #[cfg(target_arch = "wasm32")]
+    fn now() -> Self {
+        Self(Date::now())
+    }
+}
+
+impl Add<Duration> for FrameInstant {
+    type Output = FrameInstant;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        FrameInstant(self.0 + rhs.0)
+    }
+}
+
+impl Sub for FrameInstant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Duration(self.0 - rhs.0)
+    }
 }
 
 #[derive(Debug)]
 pub struct Time {
-    start: Instant,
-    current_update: Instant,
+    #[cfg(feature = "backend-eframe")]
+    start: FrameInstant,
+    current_update: FrameInstant,
     delta_since_previous: Duration,
+    /// How much faster than real time the game runs.
+    scale: f64,
+    /// How long the last frame took in real time.
+    frame_duration: Duration,
+    diagnostics: FrameDiagnostics,
 }
 
 impl Time {
@@ -58,18 +251,80 @@ impl Time {
     }
 
     pub fn new() -> Self {
-        let now = Instant::now();
+        let now = FrameInstant::now();
         Self {
+            #[cfg(feature = "backend-eframe")]
             start: now,
             current_update: now,
             delta_since_previous: Duration::default(),
+            scale: 1.0,
+            frame_duration: Duration::default(),
+            diagnostics: FrameDiagnostics::default(),
         }
     }
 
+    /// Wall-clock seconds since the game started, ignoring the game speed. For animations that
+    /// should look the same however fast the game runs.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn real_seconds(&self) -> f64 {
+        (self.current_update - self.start).as_secs_f64()
+    }
+
+    /// Real seconds the last frame took.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn frame_seconds(&self) -> f64 {
+        self.frame_duration.as_secs_f64()
+    }
+
+    /// Real frame times over the last couple of seconds.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn diagnostics(&self) -> &FrameDiagnostics {
+        &self.diagnostics
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+
+    #[cfg(feature = "backend-eframe")]
     pub fn tick(&mut self) {
-        let now = Instant::now();
-        self.delta_since_previous = now - self.current_update;
-        self.current_update = now;
+        let now = FrameInstant::now();
+        // Should the clock go back after all, the frame took no time rather than running every
+        // timer backwards.
+        let elapsed = now - self.current_update;
+        if elapsed < Duration::default() {
+            self.current_update = now;
+            self.advance(Duration::default());
+        } else {
+            self.advance(elapsed);
+        }
+    }
+
+    /// Starts the next frame from now, dropping the time since the last one instead of playing
+    /// it out, e.g. after the browser stopped sending frames for a hidden tab.
+    #[cfg(feature = "backend-eframe")]
+    pub fn skip(&mut self) {
+        self.current_update = FrameInstant::now();
+        self.delta_since_previous = Duration::default();
+    }
+
+    /// Plays the next frame out over `delta` of game time, whatever the clock and the game speed
+    /// say, e.g. to repeat a recorded frame exactly. The clock isn't touched.
+    pub(crate) fn set_delta(&mut self, delta: Duration) {
+        self.delta_since_previous = delta;
+    }
+
+    /// Moves on by `frame_duration` of real time without looking at the clock, for running
+    /// the game without a window.
+    pub fn advance(&mut self, frame_duration: Duration) {
+        self.frame_duration = frame_duration;
+        self.delta_since_previous = self.frame_duration.mul_f64(self.scale);
+        self.current_update = self.current_update + frame_duration;
+        self.diagnostics.record(frame_duration.as_secs_f64());
     }
 }
 
@@ -79,7 +334,50 @@ impl Default for Time {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Splits the game time that passes into steps of the same length, so a run plays out the same
+/// however often the screen is drawn.
+#[derive(Debug, Default)]
+pub struct FixedTimestep {
+    /// Game time that passed without a step played out for it yet.
+    accumulated: Duration,
+}
+
+impl FixedTimestep {
+    /// Game seconds per step, for 20 steps per second.
+    pub const STEP_SECONDS: f64 = 1.0 / 20.0;
+    /// The most steps owed at once. A slow frame drops the game time beyond that rather than
+    /// making the game race to catch up afterwards.
+    const MAX_STEPS_OWED: f64 = 16.0;
+
+    /// Adds game time that passed, to be played out in steps.
+    pub fn accumulate(&mut self, delta: Duration) {
+        let max_owed = Duration::from_secs_f64(Self::STEP_SECONDS * Self::MAX_STEPS_OWED);
+        self.accumulated = if self.accumulated + delta > max_owed {
+            max_owed
+        } else {
+            self.accumulated + delta
+        };
+    }
+
+    /// The time step of the next step, once enough game time has passed for it.
+    pub fn next_step(&mut self) -> Option<Duration> {
+        let step = Duration::from_secs_f64(Self::STEP_SECONDS);
+        if self.accumulated < step {
+            return None;
+        }
+
+        self.accumulated -= step;
+        Some(step)
+    }
+
+    /// How far into the next step the game time is, from 0 to 1, for drawing the run part of
+    /// the way there.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulated.as_secs_f64() / Self::STEP_SECONDS).clamp(0.0, 1.0) as f32
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timer {
     duration: Duration,
     elapsed: Duration,
@@ -99,6 +397,16 @@ impl Timer {
         self.elapsed = Duration::default();
     }
 
+    pub(crate) fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    /// Runs the timer out. Like one that ran out on its own, it counts as finished once it
+    /// ticks again.
+    pub(crate) fn finish_now(&mut self) {
+        self.elapsed = self.duration;
+    }
+
     pub(crate) fn set_duration(&mut self, duration: Duration) {
         self.duration = duration;
     }
@@ -135,3 +443,33 @@ impl Timer {
         (self.duration() - self.elapsed()).as_secs_f32()
     }
 }
+
+/// FNV-1a, a hash that unlike the standard library's is the same in every version, for what
+/// has to match across runs and devices.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_times_never_go_negative() {
+        let mut time = Time::new();
+        // As if the clock had gone back a minute since the last frame.
+        time.current_update = time.current_update + Duration::from_secs_f64(60.0);
+        time.tick();
+        assert_eq!(time.delta(), Duration::default());
+
+        // The frame after that is timed from where the clock went back to.
+        time.tick();
+        assert!(time.delta() >= Duration::default());
+        assert!(time.delta() < Duration::from_secs_f64(1.0));
+    }
+}
@@ -0,0 +1,370 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::Difficulty,
+    helpers::Instant,
+    storage::{self, Storage},
+};
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScore {
+    best_survival_seconds: f64,
+    best_points: u64,
+    #[serde(skip)]
+    new_record: bool,
+    #[serde(skip)]
+    new_best_points: bool,
+}
+
+impl HighScore {
+    const STORAGE_KEY: &'static str = "high_score";
+
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage::load(storage, Self::STORAGE_KEY)
+    }
+
+    /// Compares a finished run against the best ones so far and persists it if it beat them,
+    /// by time or by points.
+    pub fn submit(&mut self, survival_seconds: f64, points: u64, storage: &mut dyn Storage) {
+        self.new_record = survival_seconds > self.best_survival_seconds;
+        self.new_best_points = points > self.best_points;
+
+        if self.new_record {
+            self.best_survival_seconds = survival_seconds;
+        }
+        if self.new_best_points {
+            self.best_points = points;
+        }
+        if self.new_record || self.new_best_points {
+            storage::save(storage, Self::STORAGE_KEY, self);
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn best_survival_seconds(&self) -> f64 {
+        self.best_survival_seconds
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn best_points(&self) -> u64 {
+        self.best_points
+    }
+
+    /// Whether the most recently submitted run set a new record.
+    #[cfg(feature = "backend-eframe")]
+    pub fn is_new_record(&self) -> bool {
+        self.new_record
+    }
+
+    /// Whether the most recently submitted run scored more points than any before.
+    #[cfg(feature = "backend-eframe")]
+    pub fn is_new_best_points(&self) -> bool {
+        self.new_best_points
+    }
+}
+
+/// The best runs played on this device, best first.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub initials: String,
+    pub points: u64,
+    pub survived_seconds: f64,
+    /// Milliseconds since the Unix epoch.
+    pub played_at: f64,
+    pub difficulty: Difficulty,
+    /// Entries from before New Game+ are of ordinary runs.
+    #[serde(default)]
+    pub new_game_plus: u32,
+    pub seed: u64,
+}
+
+impl Leaderboard {
+    const STORAGE_KEY: &'static str = "leaderboard";
+    pub const MAX_ENTRIES: usize = 10;
+
+    pub fn load(storage: &dyn Storage) -> Self {
+        let mut leaderboard: Self = storage::load(storage, Self::STORAGE_KEY);
+        // Whatever was stored, it is only ever shown sorted and trimmed.
+        leaderboard.entries.sort_by(LeaderboardEntry::rank);
+        leaderboard.entries.truncate(Self::MAX_ENTRIES);
+        leaderboard
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.entries
+    }
+
+    /// Whether a run that ended now with these results would make the table.
+    pub fn qualifies(&self, points: u64, survived_seconds: f64) -> bool {
+        let candidate = LeaderboardEntry {
+            initials: String::new(),
+            points,
+            survived_seconds,
+            played_at: f64::INFINITY,
+            difficulty: Difficulty::default(),
+            new_game_plus: 0,
+            seed: 0,
+        };
+
+        self.entries.len() < Self::MAX_ENTRIES
+            || self
+                .entries
+                .last()
+                .is_none_or(|last| LeaderboardEntry::rank(&candidate, last) == Ordering::Less)
+    }
+
+    /// Puts the entry in its place and persists the table, dropping whatever falls off the
+    /// bottom.
+    pub fn insert(&mut self, entry: LeaderboardEntry, storage: &mut dyn Storage) {
+        let position = self
+            .entries
+            .iter()
+            .position(|other| LeaderboardEntry::rank(&entry, other) == Ordering::Less)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(position, entry);
+        self.entries.truncate(Self::MAX_ENTRIES);
+        storage::save(storage, Self::STORAGE_KEY, self);
+    }
+
+    /// Puts the initials on the entry played last, e.g. once the player typed them in for a
+    /// run that went on the table without them.
+    #[cfg(feature = "backend-eframe")]
+    pub fn name_most_recent(&mut self, initials: &str, storage: &mut dyn Storage) {
+        let entry = match self.most_recent() {
+            Some(index) => &mut self.entries[index],
+            None => return,
+        };
+        entry.initials = LeaderboardEntry::clean_initials(initials);
+        storage::save(storage, Self::STORAGE_KEY, self);
+    }
+
+    /// The entry played last, which the table highlights.
+    #[cfg(feature = "backend-eframe")]
+    pub fn most_recent(&self) -> Option<usize> {
+        (0..self.entries.len()).max_by(|&a, &b| {
+            self.entries[a]
+                .played_at
+                .partial_cmp(&self.entries[b].played_at)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+impl LeaderboardEntry {
+    pub fn new(
+        initials: &str,
+        points: u64,
+        survived_seconds: f64,
+        difficulty: Difficulty,
+        new_game_plus: u32,
+        seed: u64,
+    ) -> Self {
+        Self {
+            initials: Self::clean_initials(initials),
+            points,
+            survived_seconds,
+            played_at: Instant::now().millis_since_epoch(),
+            difficulty,
+            new_game_plus,
+            seed,
+        }
+    }
+
+    /// Up to three letters or digits, upper case, like on an arcade cabinet.
+    pub fn clean_initials(initials: &str) -> String {
+        initials
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_uppercase)
+            .take(3)
+            .collect()
+    }
+
+    /// Better entries first: more points, then longer survival, then whoever got there first,
+    /// so that no two entries tie.
+    fn rank(a: &Self, b: &Self) -> Ordering {
+        b.points
+            .cmp(&a.points)
+            .then_with(|| {
+                b.survived_seconds
+                    .partial_cmp(&a.survived_seconds)
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| {
+                a.played_at
+                    .partial_cmp(&b.played_at)
+                    .unwrap_or(Ordering::Equal)
+            })
+    }
+
+    /// The day the run was played, as year-month-day in UTC.
+    #[cfg(feature = "backend-eframe")]
+    pub fn date(&self) -> String {
+        Instant::from_millis_since_epoch(self.played_at).utc_date()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn a_better_run_is_kept() {
+        let mut storage = MemoryStorage::default();
+        let mut high_score = HighScore::load(&storage);
+        high_score.submit(120.0, 900, &mut storage);
+        assert!(high_score.new_record);
+        assert!(high_score.new_best_points);
+
+        let loaded = HighScore::load(&storage);
+        assert_eq!(loaded.best_survival_seconds, 120.0);
+        assert_eq!(loaded.best_points, 900);
+        assert!(!loaded.new_record);
+    }
+
+    #[test]
+    fn a_worse_run_changes_nothing() {
+        let mut storage = MemoryStorage::default();
+        let mut high_score = HighScore::load(&storage);
+        high_score.submit(120.0, 900, &mut storage);
+        let saved = storage.0.clone();
+
+        high_score.submit(60.0, 500, &mut storage);
+        assert!(!high_score.new_record);
+        assert!(!high_score.new_best_points);
+        assert_eq!(storage.0, saved);
+    }
+
+    #[test]
+    fn records_by_time_and_by_points_are_kept_apart() {
+        let mut storage = MemoryStorage::default();
+        let mut high_score = HighScore::load(&storage);
+        high_score.submit(120.0, 900, &mut storage);
+        high_score.submit(60.0, 1000, &mut storage);
+        assert!(!high_score.new_record);
+        assert!(high_score.new_best_points);
+
+        let loaded = HighScore::load(&storage);
+        assert_eq!(loaded.best_survival_seconds, 120.0);
+        assert_eq!(loaded.best_points, 1000);
+    }
+
+    #[test]
+    fn corrupt_or_missing_storage_starts_fresh() {
+        let mut storage = MemoryStorage::default();
+        assert_eq!(HighScore::load(&storage).best_survival_seconds, 0.0);
+
+        storage.set(HighScore::STORAGE_KEY, "{not json".to_string());
+        let high_score = HighScore::load(&storage);
+        assert_eq!(high_score.best_survival_seconds, 0.0);
+        assert_eq!(high_score.best_points, 0);
+    }
+
+    /// An entry played `played_at` milliseconds after the epoch.
+    fn entry(points: u64, survived_seconds: f64, played_at: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            played_at,
+            ..LeaderboardEntry::new("abc", points, survived_seconds, Difficulty::Normal, 0, 7)
+        }
+    }
+
+    fn ranking(leaderboard: &Leaderboard) -> Vec<(u64, f64, f64)> {
+        leaderboard
+            .entries
+            .iter()
+            .map(|entry| (entry.points, entry.survived_seconds, entry.played_at))
+            .collect()
+    }
+
+    #[test]
+    fn entries_are_ranked_by_points_then_time_then_age() {
+        let mut storage = MemoryStorage::default();
+        let mut leaderboard = Leaderboard::default();
+        for entry in [
+            entry(500, 60.0, 1.0),
+            entry(900, 30.0, 2.0),
+            entry(500, 90.0, 3.0),
+            entry(500, 60.0, 4.0),
+        ] {
+            leaderboard.insert(entry, &mut storage);
+        }
+
+        assert_eq!(
+            ranking(&leaderboard),
+            [
+                (900, 30.0, 2.0),
+                (500, 90.0, 3.0),
+                (500, 60.0, 1.0),
+                (500, 60.0, 4.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_full_table_only_takes_runs_better_than_its_last() {
+        let mut storage = MemoryStorage::default();
+        let mut leaderboard = Leaderboard::default();
+        for points in 1..=Leaderboard::MAX_ENTRIES as u64 {
+            assert!(leaderboard.qualifies(0, 0.0));
+            leaderboard.insert(entry(points * 100, 60.0, points as f64), &mut storage);
+        }
+
+        // A tie with the last entry doesn't push out who got there first.
+        assert!(!leaderboard.qualifies(100, 60.0));
+        assert!(leaderboard.qualifies(100, 61.0));
+
+        leaderboard.insert(entry(150, 10.0, 20.0), &mut storage);
+        assert_eq!(leaderboard.entries.len(), Leaderboard::MAX_ENTRIES);
+        assert_eq!(leaderboard.entries.last().unwrap().points, 150);
+    }
+
+    #[test]
+    fn the_table_comes_back_as_it_was_saved() {
+        let mut storage = MemoryStorage::default();
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.insert(entry(300, 45.5, 1.0), &mut storage);
+        leaderboard.insert(entry(700, 80.25, 2.0), &mut storage);
+
+        assert_eq!(Leaderboard::load(&storage), leaderboard);
+    }
+
+    #[test]
+    fn a_stored_table_is_sorted_and_trimmed_on_load() {
+        let mut storage = MemoryStorage::default();
+        let entries: Vec<_> = (0..Leaderboard::MAX_ENTRIES as u64 + 2)
+            .map(|points| entry(points, 0.0, points as f64))
+            .collect();
+        storage::save(
+            &mut storage,
+            Leaderboard::STORAGE_KEY,
+            &Leaderboard { entries },
+        );
+
+        let leaderboard = Leaderboard::load(&storage);
+        assert_eq!(leaderboard.entries.len(), Leaderboard::MAX_ENTRIES);
+        assert_eq!(
+            leaderboard.entries[0].points,
+            Leaderboard::MAX_ENTRIES as u64 + 1
+        );
+        assert_eq!(leaderboard.entries.last().unwrap().points, 2);
+    }
+
+    #[test]
+    fn initials_are_up_to_three_capital_letters_or_digits() {
+        assert_eq!(LeaderboardEntry::clean_initials("a.k-g"), "AKG");
+        assert_eq!(LeaderboardEntry::clean_initials("r2d2"), "R2D");
+        assert_eq!(LeaderboardEntry::clean_initials(" x "), "X");
+        assert_eq!(LeaderboardEntry::clean_initials(""), "");
+    }
+}
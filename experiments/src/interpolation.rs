@@ -0,0 +1,121 @@
+//! Where the markers on the lanes were before the last simulation step. The run only moves on
+//! in fixed steps, several frames apart on a fast screen, so the GUI draws the markers part of
+//! the way from there to where they are now to keep them moving smoothly.
+
+#[cfg(feature = "backend-eframe")]
+use eframe::egui::Color32;
+
+use crate::game::{Enemy, EnemyId, Unit, UnitBundle, UnitId};
+
+/// Which way along its lane a unit is flying. Turning back starts a new trip, which isn't
+/// blended with the old one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Leg {
+    Out,
+    Back,
+}
+
+#[derive(Debug, Default)]
+pub struct LanePositions {
+    /// How much of its lane each enemy had left.
+    enemies: Vec<(EnemyId, f32)>,
+    /// How far along its trip each airborne unit was.
+    units: Vec<(UnitId, Leg, f32)>,
+    /// How far the time drawn is from the positions remembered to the current ones, from 0
+    /// to 1.
+    alpha: f32,
+}
+
+impl LanePositions {
+    /// Remembers where everything is, before the next step moves it on. The vectors are
+    /// refilled rather than replaced, so this doesn't allocate once they are big enough.
+    pub fn remember(&mut self, units: &[UnitBundle], enemies: &[Enemy]) {
+        self.enemies.clear();
+        self.enemies.extend(
+            enemies
+                .iter()
+                .map(|enemy| (enemy.id, enemy.progress.percent_left())),
+        );
+        self.units.clear();
+        self.units.extend(units.iter().filter_map(|bundle| {
+            let (leg, percent) = trip(&bundle.unit)?;
+            Some((bundle.id, leg, percent))
+        }));
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+
+    /// How much of its lane the enemy has left, as drawn.
+    #[cfg(feature = "backend-eframe")]
+    pub fn enemy_percent_left(&self, enemy: &Enemy) -> f32 {
+        let current = enemy.progress.percent_left();
+        match self.enemies.iter().find(|&&(id, _)| id == enemy.id) {
+            Some(&(_, previous)) => lerp(previous, current, self.alpha),
+            None => current,
+        }
+    }
+
+    /// How far along its trip the unit is, as drawn, if it is airborne.
+    #[cfg(feature = "backend-eframe")]
+    pub fn unit_percent(&self, bundle: &UnitBundle) -> Option<f32> {
+        let (leg, current) = trip(&bundle.unit)?;
+        let previous = self
+            .units
+            .iter()
+            .find(|&&(id, previous_leg, _)| id == bundle.id && previous_leg == leg)
+            .map(|&(_, _, previous)| previous);
+        Some(match previous {
+            Some(previous) => lerp(previous, current, self.alpha),
+            None => current,
+        })
+    }
+}
+
+fn trip(unit: &Unit) -> Option<(Leg, f32)> {
+    match unit {
+        Unit::Patrolling(progress, _) => Some((Leg::Out, progress.percent())),
+        Unit::Returning(progress, _) => Some((Leg::Back, progress.percent())),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "backend-eframe")]
+fn lerp(from: f32, to: f32, alpha: f32) -> f32 {
+    from + (to - from) * alpha
+}
+
+/// The colour `alpha` of the way from `from` to `to`. Mixed channel by channel as stored, so
+/// premultiplied colours stay premultiplied, and at `from`'s opacity.
+#[cfg(feature = "backend-eframe")]
+pub fn lerp_color(from: Color32, to: Color32, alpha: f32) -> Color32 {
+    let mix = |from: u8, to: u8| lerp(f32::from(from), f32::from(to), alpha).round() as u8;
+    Color32::from_rgba_premultiplied(
+        mix(from.r(), to.r()),
+        mix(from.g(), to.g()),
+        mix(from.b(), to.b()),
+        from.a(),
+    )
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colours_blend_from_end_to_end() {
+        let from = Color32::from_rgba_premultiplied(20, 40, 60, 200);
+        let to = Color32::from_rgba_premultiplied(120, 0, 61, 255);
+
+        assert_eq!(lerp_color(from, to, 0.0), from);
+        assert_eq!(
+            lerp_color(from, to, 1.0),
+            Color32::from_rgba_premultiplied(120, 0, 61, 200)
+        );
+        assert_eq!(
+            lerp_color(from, to, 0.5),
+            Color32::from_rgba_premultiplied(70, 20, 61, 200)
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! What has to hold for the units and the parking spaces after every frame, whatever orders were
+//! given. Simulated runs check it with `--check`, mostly with random orders, so that a state the
+//! rules should never get into shows up with the seed that leads there.
+
+use crate::game::{ParkingSpace, TokenPool, UnitBundle, UnitId};
+
+/// Checks a run frame after frame. Some of what has to hold depends on how things stood the frame
+/// before.
+#[derive(Debug, Default)]
+pub struct InvariantChecker {
+    /// The parking space each unit held at the last check.
+    held: Vec<(UnitId, usize)>,
+    /// How many parking spaces there were at the last check.
+    capacity: usize,
+}
+
+impl InvariantChecker {
+    /// Says what doesn't hold, if anything.
+    pub fn check(
+        &mut self,
+        units: &[UnitBundle],
+        parking_spaces: &TokenPool<ParkingSpace>,
+    ) -> Result<(), String> {
+        let result = self.find_violation(units, parking_spaces);
+        self.held = units
+            .iter()
+            .filter_map(|bundle| Some((bundle.id, bundle.unit.parking_space()?.slot())))
+            .collect();
+        self.capacity = parking_spaces.capacity();
+        result
+    }
+
+    fn find_violation(
+        &self,
+        units: &[UnitBundle],
+        parking_spaces: &TokenPool<ParkingSpace>,
+    ) -> Result<(), String> {
+        let mut held = Vec::new();
+        for bundle in units {
+            if !bundle.health.is_valid() {
+                return Err(format!("unit {} is at {} health", bundle.id, bundle.health));
+            }
+            // A timer that ran out moves the unit on in the same frame.
+            if bundle.unit.timer().is_some_and(|timer| timer.finished()) {
+                return Err(format!("unit {} is stuck past its timer", bundle.id));
+            }
+
+            let slot = match bundle.unit.parking_space() {
+                Some(parking_space) => parking_space.slot(),
+                None => continue,
+            };
+            if held.contains(&slot) {
+                return Err(format!("parking space {} is held twice", slot + 1));
+            }
+            held.push(slot);
+            // A boss wrecking a space can leave the unit in it beyond capacity, but no unit may
+            // take a space that was wrecked already.
+            let wrecked_before = slot >= self.capacity;
+            if slot >= parking_spaces.capacity()
+                && wrecked_before
+                && !self.held.contains(&(bundle.id, slot))
+            {
+                return Err(format!(
+                    "unit {} took parking space {}, but there are only {}",
+                    bundle.id,
+                    slot + 1,
+                    parking_spaces.capacity()
+                ));
+            }
+        }
+
+        if held.len() != parking_spaces.slots_used() {
+            return Err(format!(
+                "{} parking spaces are in use, but the units hold {}",
+                parking_spaces.slots_used(),
+                held.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prelude::*, sample::select};
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{
+        commands::{apply_commands, GameCommand},
+        events::GameEvent,
+        game::{
+            fixtures::{enemy, unit, TIMINGS},
+            ticker, CombatType, Enemy, GameState, PlayTime, StepContext, Unit, UnitAction,
+        },
+        game_mode::GameMode,
+        helpers::{Duration, Time},
+    };
+
+    /// What happens to the game between two checks.
+    #[derive(Debug, Clone)]
+    enum Step {
+        Command(GameCommand),
+        /// A frame of this many milliseconds.
+        Tick(u64),
+    }
+
+    fn lane() -> impl Strategy<Value = CombatType> {
+        select(CombatType::iter().collect::<Vec<_>>())
+    }
+
+    /// Orders of any kind, also for a unit that doesn't exist and a parking space that doesn't.
+    fn command() -> impl Strategy<Value = GameCommand> {
+        let unit = (1..=3u32).prop_map(UnitId);
+        let action = select(UnitAction::all());
+        prop_oneof![
+            (unit.clone(), action.clone())
+                .prop_map(|(unit, action)| GameCommand::Perform { unit, action }),
+            (unit, action, 0..3usize).prop_map(|(unit, action, slot)| {
+                GameCommand::PerformInSlot { unit, action, slot }
+            }),
+            lane().prop_map(GameCommand::Scramble),
+        ]
+    }
+
+    fn steps() -> impl Strategy<Value = Vec<Step>> {
+        prop::collection::vec(
+            prop_oneof![
+                command().prop_map(Step::Command),
+                // From a short frame to one that runs several timers out at once.
+                (0..12_000u64).prop_map(Step::Tick),
+            ],
+            0..60,
+        )
+    }
+
+    /// Enemies already on their way, as lane, seconds to cross it and whether it is a boss.
+    fn enemies() -> impl Strategy<Value = Vec<(CombatType, f64, bool)>> {
+        prop::collection::vec((lane(), 1.0..60.0f64, any::<bool>()), 0..4)
+    }
+
+    /// Two units and one parking space, the smallest game in which units compete for one.
+    struct Game {
+        units: Vec<UnitBundle>,
+        enemies: Vec<Enemy>,
+        parking_spaces: TokenPool<ParkingSpace>,
+        time: Time,
+        play_time: PlayTime,
+        events: Vec<GameEvent>,
+    }
+
+    impl Game {
+        /// `bosses` says whether enemies meant to be bosses are.
+        fn new(enemies: &[(CombatType, f64, bool)], bosses: bool) -> Self {
+            Self {
+                units: vec![unit(1, Unit::InStorage), unit(2, Unit::InStorage)],
+                enemies: enemies
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &(lane, run_seconds, is_boss))| Enemy {
+                        is_boss: bosses && is_boss,
+                        ..enemy(index as u32 + 1, lane, run_seconds, 0.0)
+                    })
+                    .collect(),
+                parking_spaces: TokenPool::new(1),
+                time: Time::new(),
+                play_time: PlayTime::default(),
+                events: Vec::new(),
+            }
+        }
+
+        fn take(&mut self, step: &Step) {
+            match step {
+                Step::Command(command) => apply_commands(
+                    [*command],
+                    &mut self.units,
+                    &TIMINGS,
+                    &mut self.parking_spaces,
+                    &mut self.events,
+                ),
+                Step::Tick(millis) => {
+                    self.time
+                        .advance(Duration::from_secs_f64(*millis as f64 / 1000.0));
+                    let context = StepContext {
+                        time: &self.time,
+                        timings: &TIMINGS,
+                        mode: GameMode::default(),
+                        // Enemies reaching the base don't end the run, so the steps after
+                        // still count.
+                        invincible: true,
+                    };
+                    ticker(
+                        &context,
+                        &mut self.units,
+                        &mut self.enemies,
+                        &mut self.parking_spaces,
+                        &mut GameState::Running,
+                        &mut self.play_time,
+                        &mut self.events,
+                    );
+                }
+            }
+        }
+    }
+
+    proptest! {
+        /// Whatever the orders and however long the frames, the invariants hold after every
+        /// step, and no more parking spaces are in use than there are.
+        #[test]
+        fn any_orders_keep_the_invariants(enemies in enemies(), steps in steps()) {
+            let mut game = Game::new(&enemies, false);
+            let mut checker = InvariantChecker::default();
+            for step in &steps {
+                game.take(step);
+                let checked = checker.check(&game.units, &game.parking_spaces);
+                prop_assert!(checked.is_ok(), "{} after {:?}", checked.unwrap_err(), step);
+                prop_assert!(game.parking_spaces.slots_used() <= game.parking_spaces.capacity());
+            }
+        }
+
+        /// Bosses wreck parking spaces, which can leave a unit in one that is gone. That is
+        /// allowed, but nothing else is.
+        #[test]
+        fn bosses_getting_through_keep_the_invariants(
+            enemies in enemies(),
+            steps in steps(),
+        ) {
+            let mut game = Game::new(&enemies, true);
+            let mut checker = InvariantChecker::default();
+            for step in &steps {
+                game.take(step);
+                let checked = checker.check(&game.units, &game.parking_spaces);
+                prop_assert!(checked.is_ok(), "{} after {:?}", checked.unwrap_err(), step);
+            }
+        }
+    }
+
+    #[test]
+    fn a_space_held_twice_is_caught() {
+        let mut parking_spaces = TokenPool::new(1);
+        let token = parking_spaces.try_take().unwrap();
+        let units = [
+            unit(1, Unit::ParkedUnready(token.clone())),
+            unit(2, Unit::ParkedUnready(token)),
+        ];
+        assert_eq!(
+            InvariantChecker::default().check(&units, &parking_spaces),
+            Err("parking space 1 is held twice".to_string())
+        );
+    }
+}
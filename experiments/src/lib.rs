@@ -1,64 +1,1490 @@
-// use engine::eframe::{egui::CtxRef, epi, epi::Frame};
+use std::{
+    cell::{Cell, RefCell},
+    path::PathBuf,
+    rc::Rc,
+};
 
+#[cfg(feature = "backend-eframe")]
 use eframe::{egui::CtxRef, epi, epi::Frame};
+#[cfg(feature = "backend-eframe")]
+use engine::{focus::FocusWatcher, DecodedTextures, ExitRequest};
+#[cfg(not(target_arch = "wasm32"))]
+use rand::SeedableRng;
+#[cfg(not(target_arch = "wasm32"))]
+use rand_chacha::ChaCha12Rng;
 
+#[cfg(feature = "backend-eframe")]
+pub use crate::gui::install_style;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::invariants::InvariantChecker;
 use crate::{
+    achievements::Achievements,
+    alerts::ThreatAlerts,
+    audio::{SoundEvent, SoundPlayer, SoundQueue},
+    balance::GameConfig,
+    battle_log::{BattleLog, Severity},
+    cheats::{apply_cheat, Cheat, CheatState},
+    commands::{apply_commands, GameCommand},
+    crash_report::CrashContext,
+    daily::{DailyOutcome, DailyResults, DailyRun},
+    defeat::DefeatReport,
+    events::GameEvent,
     game::{
-        gui, init_stuff, repair_tick, spawn_enemies, ticker, units_meet_enemies, Enemy,
-        EnemySpawner, GameState, ParkingSpace, PlayTime, TokenPool, Unit, UnitBundle,
+        auto_park, census, init_stuff, repair_tick, spawn_enemies, ticker, units_meet_enemies,
+        Enemy, EnemySpawner, GameState, ParkingSpace, PlayTime, StepContext, TokenPool, UnitBundle,
     },
-    helpers::Time,
+    game_mode::GameMode,
+    helpers::{Duration, FixedTimestep, GameSpeed, Time},
+    high_score::{HighScore, Leaderboard, LeaderboardEntry},
+    interpolation::LanePositions,
+    new_game_plus::CarryOver,
+    online_leaderboard::{OnlineLeaderboard, Submission},
+    page_link::{PageLink, RunSummary},
+    replay::{Replay, ReplayPlayer},
+    save_state::{LiveRun, SaveSlot, SavedRun},
+    scenario::{Scenario, ScenarioRun},
+    score::Score,
+    settings::Settings,
+    stats::GameStats,
+    storage::GameStorage,
+    timeline::{Timeline, TimelineSample},
+    toasts::{ToastMessage, Toasts},
+    tutorial::{Tutorial, TutorialRecord},
+};
+#[cfg(feature = "backend-eframe")]
+use crate::{
+    audio::MusicState,
+    gui::{gui, GameView, GuiAction, GuiState},
+    page_link::RunStatus,
+    save_state::{RestoredRun, SaveSlots},
+};
+pub use crate::{crash_report::install_panic_hook, start_config::StartConfig};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::{
+    simulation::{Outcome, Policy, SimulatedRun, SimulationConfig, Totals},
+    storage::FileStorage,
 };
 
+mod achievements;
+mod alerts;
+#[cfg(feature = "count-allocations")]
+mod alloc_count;
+mod audio;
+mod balance;
+mod battle_log;
+mod cheats;
+mod commands;
+#[cfg(feature = "backend-eframe")]
+mod console;
+mod controls;
+mod crash_report;
+mod daily;
+mod defeat;
+mod events;
 mod game;
+mod game_mode;
+#[cfg(feature = "backend-eframe")]
+mod gui;
 mod helpers;
+mod high_score;
+mod interpolation;
+#[cfg(not(target_arch = "wasm32"))]
+mod invariants;
+mod new_game_plus;
+mod online_leaderboard;
+mod page_link;
+mod replay;
+mod save_state;
+mod scenario;
+mod score;
+mod settings;
+#[cfg(not(target_arch = "wasm32"))]
+mod simulation;
+mod start_config;
+mod stats;
+mod storage;
+mod timeline;
+mod toasts;
 mod todo;
+mod tutorial;
+#[cfg(all(feature = "backend-eframe", target_arch = "wasm32"))]
 mod wasm_startup;
 
-#[cfg(not(target_arch = "wasm32"))]
-fn main() {
-    engine::run(MyGame, "Fruitopian Defender");
-}
-
 #[derive(Default)]
 pub struct MyGame {
+    /// The balance runs start with.
+    config: GameConfig,
+    /// A balance file to read afresh for every run instead of using the built-in balance, to
+    /// try numbers out.
+    balance_path: Option<PathBuf>,
     enemy_spawner: EnemySpawner,
     play_time: PlayTime,
     parking_spaces: TokenPool<ParkingSpace>,
     game_state: GameState,
     time: Time,
+    /// Turns the game time that passes into the fixed steps the run moves on in.
+    timestep: FixedTimestep,
+    /// Where the markers on the lanes were before the last step, to draw them in between.
+    lane_positions: LanePositions,
     units: Vec<UnitBundle>,
     enemies: Vec<Enemy>,
+    #[cfg(feature = "backend-eframe")]
+    gui_state: GuiState,
+    storage: GameStorage,
+    high_score: HighScore,
+    leaderboard: Leaderboard,
+    /// Where runs go if the player sends them online, and how the last one got on.
+    online_leaderboard: OnlineLeaderboard,
+    /// The run that just ended made the leaderboard, and the player hasn't entered their
+    /// initials yet.
+    awaiting_initials: bool,
+    settings: Settings,
+    stats: GameStats,
+    score: Score,
+    /// How the run went over time, for the chart once it is over.
+    timeline: Timeline,
+    battle_log: BattleLog,
+    threat_alerts: ThreatAlerts,
+    toasts: Toasts,
+    /// What ended the run, once an enemy got through to the base and lost it.
+    defeat: Option<DefeatReport>,
+    /// The tutorial, while it is running.
+    tutorial: Option<Tutorial>,
+    tutorial_record: TutorialRecord,
+    achievements: Achievements,
+    sounds: SoundQueue,
+    sound_player: SoundPlayer,
+    #[cfg(feature = "backend-eframe")]
+    exit: ExitRequest,
+    #[cfg(feature = "backend-eframe")]
+    focus: FocusWatcher,
+    /// Set by the page when the tab was hidden, which stops frames altogether. The next frame
+    /// takes it.
+    externally_paused: Rc<Cell<bool>>,
+    /// Requests from the page hosting the game, and the status it reads back.
+    page_link: Rc<RefCell<PageLink>>,
+    /// The seed every run uses, if the page hosting the game asked for one.
+    seed: Option<u64>,
+    /// What the page hosting the game or the command line asked for, kept to apply again after
+    /// a reset, which loads the saved settings.
+    start_config: StartConfig,
+    /// Cheats are on offer, for balancing and testing.
+    sandbox: bool,
+    /// What cheats did to the current run.
+    cheats: CheatState,
+    /// What the next run starts with, handed on from the contract won before it.
+    carry_over: CarryOver,
+    /// The daily challenge the current run is, if it is one.
+    daily: Option<DailyRun>,
+    daily_results: DailyResults,
+    /// The scenarios on offer on the main menu.
+    scenarios: Vec<Scenario>,
+    /// The scenario the current run plays, if it plays one.
+    scenario: Option<ScenarioRun>,
+    /// A run that was still going when the game was last closed, offered on the main menu.
+    saved_run: Option<SavedRun>,
+    /// What is in the slots the player saves runs into themselves.
+    #[cfg(feature = "backend-eframe")]
+    save_slots: SaveSlots,
+    /// The run being recorded, or the one that just ended. Runs in the tutorial or picked up
+    /// from a save aren't recorded, as they can't be watched from the start.
+    replay: Option<Replay>,
+    /// The replay being watched, which gives the orders instead of the player.
+    playback: Option<ReplayPlayer>,
+    /// Seconds survived in the run of the replay the player saved, if there is one.
+    #[cfg(feature = "backend-eframe")]
+    saved_replay_seconds: Option<f64>,
+    /// Parking spaces in use when `ParkingChanged` was last sent.
+    parking_used: usize,
+    /// Real seconds since the game started when the run was last saved.
+    #[cfg(feature = "backend-eframe")]
+    last_autosave: f64,
+    /// The canvas the web build draws on, for screenshots.
+    canvas_id: String,
+    crash_context: CrashContext,
 }
 
-impl epi::App for MyGame {
-    fn update(&mut self, ctx: &CtxRef, frame: &mut Frame<'_>) {
-        ctx.request_repaint();
-        // web_sys::console::log_1(&format!("GameState: {:#?}", self.game_state).into());
-        if self.game_state == GameState::Running {
-            ticker(
+impl MyGame {
+    pub fn new() -> Self {
+        let mut game = Self::with_storage(GameStorage::default(), GameConfig::built_in());
+        game.scenarios = scenario::built_in();
+        game
+    }
+
+    fn with_storage(storage: GameStorage, config: GameConfig) -> Self {
+        let mut game = Self {
+            parking_spaces: TokenPool::new(config.units.parking_spaces),
+            config,
+            high_score: HighScore::load(&storage),
+            leaderboard: Leaderboard::load(&storage),
+            settings: Settings::load(&storage),
+            tutorial_record: TutorialRecord::load(&storage),
+            achievements: Achievements::load(&storage),
+            daily_results: DailyResults::load(&storage),
+            saved_run: SavedRun::load(&storage, SaveSlot::Autosave),
+            #[cfg(feature = "backend-eframe")]
+            saved_replay_seconds: Replay::load(&storage)
+                .as_ref()
+                .map(Replay::survived_seconds),
+            storage,
+            ..Self::default()
+        };
+        #[cfg(feature = "backend-eframe")]
+        {
+            game.save_slots = SaveSlots::load(&mut game.storage);
+        }
+        init_stuff(&mut game.units, &game.config);
+        game
+    }
+
+    /// Applies what the page hosting the game asked for. Settings given there hold for this
+    /// session, without overwriting what the player saved.
+    pub fn apply_start_config(&mut self, config: StartConfig) {
+        self.seed = config.seed;
+        self.start_config = config.clone();
+        self.apply_start_settings();
+        if let Some(sandbox) = config.sandbox {
+            self.sandbox = sandbox;
+            log::info!("Sandbox {}", if sandbox { "on" } else { "off" });
+        }
+        if let Some(path) = config.balance {
+            self.balance_path = Some(path);
+            self.reload_balance();
+        }
+        if let Some(url) = config.leaderboard_url {
+            self.online_leaderboard = OnlineLeaderboard::new(&url);
+        }
+    }
+
+    /// Overrides the player's settings and the speed with what the start config asked for.
+    fn apply_start_settings(&mut self) {
+        let config = &self.start_config;
+        if let Some(difficulty) = config.difficulty {
+            self.settings.difficulty = difficulty;
+        }
+        if let Some(language) = config.language {
+            self.settings.language = language;
+        }
+        if let Some(muted) = config.muted {
+            self.settings.muted = muted;
+        }
+        if let Some(speed) = config.speed.and_then(GameSpeed::from_scale) {
+            self.time.set_scale(speed.scale());
+        }
+    }
+
+    /// Sets how fast the game runs, no faster than a daily challenge allows.
+    pub fn set_speed(&mut self, speed: GameSpeed) {
+        let speed = match self.daily {
+            Some(_) if speed.scale() > daily::MAX_SPEED.scale() => daily::MAX_SPEED,
+            _ => speed,
+        };
+        self.time.set_scale(speed.scale());
+    }
+
+    /// Runs the game at the next speed up, if it isn't at the fastest already.
+    pub fn speed_up(&mut self) {
+        self.set_speed(self.speed().faster());
+    }
+
+    /// Runs the game at the next speed down, if it isn't at the slowest already.
+    pub fn slow_down(&mut self) {
+        self.set_speed(self.speed().slower());
+    }
+
+    /// How fast the game runs. A time scale that isn't one of the speeds counts as normal.
+    fn speed(&self) -> GameSpeed {
+        GameSpeed::from_scale(self.time.scale()).unwrap_or(GameSpeed::Normal)
+    }
+
+    /// Reads the balance file given at the start again, so changes to it hold from the next run
+    /// on, and sets up the units and parking spaces waiting on the main menu with it. A file
+    /// that can't be used leaves the balance as it was.
+    fn reload_balance(&mut self) {
+        let path = match &self.balance_path {
+            Some(path) => path,
+            None => return,
+        };
+        match GameConfig::from_file(path) {
+            Ok(config) => {
+                if config != self.config {
+                    log::info!("Balance read from {}", path.display());
+                }
+                self.config = config;
+            }
+            Err(error) => log::error!("{}; keeping the balance as it was", error),
+        }
+
+        self.units.clear();
+        init_stuff(&mut self.units, &self.config);
+        self.parking_spaces = TokenPool::new(self.config.units.parking_spaces);
+    }
+
+    /// Leaves the main menu and starts the run, with or without the tutorial.
+    pub fn start(&mut self, with_tutorial: bool) {
+        self.start_with_seed(with_tutorial, self.seed);
+    }
+
+    /// Like `start`, but the enemies come from `seed` if there is one.
+    fn start_with_seed(&mut self, with_tutorial: bool, seed: Option<u64>) {
+        if self.game_state != GameState::MainMenu {
+            return;
+        }
+
+        self.saved_run = None;
+        SavedRun::clear(&mut self.storage, SaveSlot::Autosave);
+        self.reload_balance();
+        if let Some(scenario) = &self.scenario {
+            let (units, parking_spaces) = scenario.scenario().setup();
+            self.units = units;
+            self.parking_spaces = parking_spaces;
+        }
+
+        // The tutorial holds enemies back, which would make a contract too easy. Scenarios end
+        // by their own goals.
+        let (mode, new_game_plus) = if with_tutorial || self.scenario.is_some() {
+            (GameMode::Endless, 0)
+        } else if self.daily.is_some() {
+            (daily::MODE, 0)
+        } else {
+            (self.settings.game_mode, self.carry_over.level)
+        };
+        let difficulty = match (&self.daily, &self.scenario) {
+            (Some(_), _) => daily::DIFFICULTY,
+            (None, Some(scenario)) => scenario.scenario().difficulty,
+            (None, None) => self.settings.difficulty,
+        };
+        self.enemy_spawner = match seed {
+            Some(seed) => {
+                EnemySpawner::with_seed(difficulty, mode, new_game_plus, seed, &self.config)
+            }
+            None => EnemySpawner::new(difficulty, mode, new_game_plus, &self.config),
+        };
+        self.crash_context.set_seed(self.enemy_spawner.seed());
+        self.score = Score::new(difficulty);
+        self.replay = if with_tutorial || self.scenario.is_some() {
+            None
+        } else {
+            Some(Replay::new(
+                difficulty,
+                mode,
+                new_game_plus,
+                self.enemy_spawner.seed(),
+            ))
+        };
+        log::info!(
+            "Run started on {}{} with seed {}{}",
+            difficulty,
+            if new_game_plus > 0 {
+                format!(", New Game+ {}", new_game_plus)
+            } else {
+                String::new()
+            },
+            self.enemy_spawner.seed(),
+            match (&self.daily, &self.scenario) {
+                (Some(daily), _) if daily.scored => {
+                    format!(", the daily challenge of {}", daily.date)
+                }
+                (Some(daily), _) => format!(", practising the daily challenge of {}", daily.date),
+                (None, Some(scenario)) => {
+                    format!(", in the scenario {:?}", scenario.scenario().name)
+                }
+                (None, None) if with_tutorial => ", in the tutorial".to_string(),
+                (None, None) => String::new(),
+            }
+        );
+        self.tutorial = if with_tutorial {
+            Some(Tutorial::default())
+        } else {
+            None
+        };
+        self.achievements.start_run(mode, true);
+        self.game_state = GameState::Running;
+    }
+
+    /// Tells the game which canvas the web build draws it on, so screenshots show the right one.
+    pub fn set_canvas_id(&mut self, canvas_id: &str) {
+        self.canvas_id = canvas_id.to_string();
+    }
+
+    /// Gets the sounds ready now rather than on first use.
+    pub fn preload_audio(&mut self) {
+        self.sound_player.preload();
+    }
+
+    /// Tells the page about the run that just ended, if it asked to be told.
+    fn report_game_over(&self) {
+        // Let go of the link first; the callback may call back into it.
+        let on_game_over = self.page_link.borrow().on_game_over();
+        if let Some(on_game_over) = on_game_over {
+            on_game_over(&RunSummary::new(
+                self.play_time.seconds(),
+                &self.stats,
+                &self.score,
+                self.enemy_spawner.seed(),
+                self.enemy_spawner.difficulty(),
+            ));
+        }
+    }
+
+    fn capture_run(&self) -> SavedRun {
+        SavedRun::capture(&LiveRun {
+            play_time: &self.play_time,
+            enemy_spawner: &self.enemy_spawner,
+            parking_spaces: &self.parking_spaces,
+            units: &self.units,
+            enemies: &self.enemies,
+            stats: &self.stats,
+            score: &self.score,
+            time_scale: self.time.scale(),
+            cheats: self.cheats,
+            daily: self.daily.as_ref(),
+        })
+    }
+
+    /// Warns, once per replay, that it doesn't show the recorded run any more, e.g. because the
+    /// game changed since it was recorded.
+    fn replay_diverged(&mut self) {
+        let news = self.playback.as_mut().is_some_and(ReplayPlayer::diverge);
+        if news {
+            let seconds = self.play_time.seconds();
+            log::warn!(
+                "The replay went differently from the recorded run at {:.1}s",
+                seconds
+            );
+            self.toasts
+                .push(ToastMessage::ReplayDiverged { seconds }, Severity::Warning);
+        }
+    }
+
+    /// Records the frame about to be played out, and every few seconds the state of the run.
+    fn record_frame(&mut self, auto_park: bool) {
+        let checkpoint_due = match &self.replay {
+            Some(replay) => replay.checkpoint_due(self.play_time.seconds()),
+            None => return,
+        };
+        let fingerprint = if checkpoint_due {
+            Some(self.capture_run().fingerprint())
+        } else {
+            None
+        };
+
+        if let Some(replay) = &mut self.replay {
+            if let Some(fingerprint) = fingerprint {
+                replay.record_checkpoint(fingerprint);
+            }
+            replay.record_frame(self.time.delta(), auto_park);
+        }
+    }
+
+    /// Seconds survived in the current run.
+    pub fn play_seconds(&self) -> f64 {
+        self.play_time.seconds()
+    }
+
+    /// Whether the run has ended, lost or won.
+    pub fn is_game_over(&self) -> bool {
+        self.game_state.is_over()
+    }
+
+    /// Pauses a running run or resumes a paused one. A run that isn't going is left alone.
+    pub fn set_paused(&mut self, paused: bool) {
+        match (paused, self.game_state) {
+            (true, GameState::Running) => self.game_state = GameState::Paused,
+            (false, GameState::Paused) => self.game_state = GameState::Running,
+            _ => {}
+        }
+    }
+
+    /// Plays an endless run with `seed` as fast as it goes, with the simulation's policy giving
+    /// the orders, until it is over, it has gone on for the simulation's time limit or, if it is
+    /// checked, something breaks. Nothing is read from or saved to the player's storage.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn simulate_run(simulation: &SimulationConfig, seed: u64) -> SimulatedRun {
+        let mut config = GameConfig::built_in();
+        if let Some(units) = simulation.units {
+            config.units.starting_units = units;
+        }
+        if let Some(parking_spaces) = simulation.parking_spaces {
+            config.units.parking_spaces = parking_spaces;
+        }
+        let policy = simulation.policy;
+        let mut game = Self::with_storage(GameStorage::new(FileStorage::discarding()), config);
+        game.settings.difficulty = simulation.difficulty;
+        game.settings.game_mode = GameMode::Endless;
+        game.settings.auto_park = policy.parks_itself();
+        game.start_with_seed(false, Some(seed));
+        if simulation.invincible {
+            game.cheats = CheatState {
+                used: true,
+                invincible: true,
+            };
+        }
+
+        // Apart from the spawner's own, so a policy can't change which enemies come.
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let mut checker = InvariantChecker::default();
+        let played = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            while game.game_state.is_interactive()
+                && game.play_time.seconds() < simulation.max_seconds
+            {
+                let orders = policy.orders(&game.units, &game.enemies, &mut rng);
+                game.carry_out(orders);
+                engine::Headless::step(&mut game, policy.frame_time(&mut rng));
+                if simulation.check {
+                    checker
+                        .check(&game.units, &game.parking_spaces)
+                        .map_err(|violation| {
+                            format!("{} at {:.2}s", violation, game.play_time.seconds())
+                        })?;
+                }
+            }
+            Ok(())
+        }));
+        let violation = match played {
+            Ok(Ok(())) => None,
+            Ok(Err(violation)) => Some(violation),
+            Err(panic) => Some(engine::RunError::from_panic(panic).to_string()),
+        };
+
+        let survived_seconds = game.play_time.seconds();
+        SimulatedRun {
+            seed,
+            difficulty: simulation.difficulty,
+            policy,
+            outcome: match game.game_state {
+                _ if violation.is_some() => Outcome::Broken,
+                GameState::GameOver => Outcome::Lost,
+                GameState::Victory => Outcome::Won,
+                _ => Outcome::Stopped,
+            },
+            survived_seconds,
+            points: game.score.points(survived_seconds),
+            kills: game.stats.total_enemies_destroyed(),
+            leaks: game.stats.bosses_leaked,
+            units_lost: game.stats.units_lost,
+            violation,
+        }
+    }
+
+    fn finish_tutorial(&mut self) {
+        self.tutorial = None;
+        self.tutorial_record.complete(&mut self.storage);
+    }
+
+    /// Carries out orders for the units, whoever gave them, while the run is going.
+    fn carry_out(&mut self, commands: Vec<GameCommand>) {
+        if !self.game_state.is_interactive() {
+            return;
+        }
+
+        let mut events = Vec::new();
+        for command in &commands {
+            if let GameCommand::Cheat(cheat) = *command {
+                self.cheat(cheat, &mut events);
+            }
+        }
+        apply_commands(
+            commands,
+            &mut self.units,
+            &self.config.unit_timings(self.enemy_spawner.difficulty()),
+            &mut self.parking_spaces,
+            &mut events,
+        );
+        self.report_parking(&mut events);
+        self.handle_events(&events);
+    }
+
+    /// Carries out a cheat, in the sandbox or in a replay of a run that was played there.
+    fn cheat(&mut self, cheat: Cheat, events: &mut Vec<GameEvent>) {
+        if self.daily.is_some() {
+            log::warn!("Ignoring {:?} in the daily challenge", cheat);
+            return;
+        }
+        if !self.sandbox && self.playback.is_none() {
+            log::warn!("Ignoring {:?} outside the sandbox", cheat);
+            return;
+        }
+
+        log::info!("{:.1}s: cheat {:?}", self.play_time.seconds(), cheat);
+        apply_cheat(
+            cheat,
+            &mut self.cheats,
+            &mut self.units,
+            &mut self.enemies,
+            &mut self.enemy_spawner,
+            events,
+        );
+    }
+
+    /// Passes on what happened this frame to everything that keeps track of it.
+    fn handle_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            log::debug!("{:.1}s: {:?}", self.play_time.seconds(), event);
+            self.stats.record(event);
+            self.score.record(event);
+            self.timeline.record(self.play_time.seconds(), event);
+            self.battle_log.record(self.play_time.seconds(), event);
+            self.toasts.record(event);
+
+            if let Some(tutorial) = &mut self.tutorial {
+                tutorial.advance(event);
+            }
+            // The tutorial holds enemies back, scenarios are made to be won, a replay's run was
+            // had already, and cheats make anything easy.
+            if self.tutorial.is_none()
+                && self.scenario.is_none()
+                && self.playback.is_none()
+                && !self.cheats.used
+            {
+                let unlocked = self.achievements.record(
+                    event,
+                    &self.stats,
+                    self.play_time.seconds(),
+                    &mut self.storage,
+                );
+                for achievement in unlocked {
+                    log::info!("Achievement unlocked: {:?}", achievement);
+                    self.toasts.push(
+                        ToastMessage::AchievementUnlocked { achievement },
+                        Severity::Info,
+                    );
+                }
+            }
+            if let Some(sound) = SoundEvent::for_event(event) {
+                self.sounds.push(sound);
+            }
+            // The first enemy through is the one that ended the run, if it did.
+            if let GameEvent::BaseHit { report } = event {
+                if self.game_state == GameState::GameOver && self.defeat.is_none() {
+                    self.defeat = Some(report.clone());
+                }
+            }
+            if let GameEvent::GameOver { survived_seconds }
+            | GameEvent::Victory { survived_seconds } = *event
+            {
+                self.finish_run(survived_seconds);
+            }
+        }
+
+        if self.tutorial.as_ref().is_some_and(Tutorial::is_finished) {
+            self.finish_tutorial();
+        }
+    }
+
+    /// Plays out as many steps of the run as the game time since the last frame makes up for,
+    /// each over the same fixed time step. However often frames come, the run goes the same.
+    fn step_simulation(&mut self, events: &mut Vec<GameEvent>) {
+        if !self.game_state.is_interactive() {
+            return;
+        }
+
+        self.timestep.accumulate(self.time.delta());
+        while self.game_state.is_interactive() {
+            let step = match self.timestep.next_step() {
+                Some(step) => step,
+                None => break,
+            };
+            self.lane_positions.remember(&self.units, &self.enemies);
+            self.time.set_delta(step);
+            self.simulate(events);
+        }
+        self.lane_positions.set_alpha(self.timestep.alpha());
+    }
+
+    /// Plays out as many of the replay's frames as the game time since the last frame makes up
+    /// for, so the game speed works like in a run.
+    fn play_back(&mut self) {
+        // Keeps a long frame from stalling the game while playback catches up.
+        const MAX_FRAMES_PER_UPDATE: usize = 64;
+
+        if !self.game_state.is_interactive() {
+            return;
+        }
+        let delta = self.time.delta();
+        if let Some(player) = &mut self.playback {
+            player.owe(delta);
+        }
+
+        for _ in 0..MAX_FRAMES_PER_UPDATE {
+            if !self.play_back_frame() {
+                break;
+            }
+        }
+        let alpha = self.playback.as_ref().map_or(1.0, ReplayPlayer::alpha);
+        self.lane_positions.set_alpha(alpha);
+    }
+
+    /// Plays out the replay's next frame and the orders given after it, if enough game time is
+    /// owed for it. Returns whether it did.
+    fn play_back_frame(&mut self) -> bool {
+        let player = match &self.playback {
+            Some(player) if self.game_state.is_interactive() => player,
+            _ => return false,
+        };
+        let delta = match player.next_delta() {
+            Some(delta) => delta,
+            None => return false,
+        };
+        if let Some(recorded) = player.checkpoint() {
+            if self.capture_run().fingerprint() != recorded {
+                self.replay_diverged();
+            }
+        }
+
+        self.time.set_delta(delta);
+        self.lane_positions.remember(&self.units, &self.enemies);
+        let mut events = Vec::new();
+        self.simulate(&mut events);
+        self.handle_events(&events);
+        if let Some(player) = &mut self.playback {
+            let commands = player.finish_frame();
+            self.carry_out(commands);
+        }
+        true
+    }
+
+    /// Plays out one step of the run, if it is running. Everything but the GUI.
+    fn simulate(&mut self, events: &mut Vec<GameEvent>) {
+        if !self.game_state.is_interactive() {
+            return;
+        }
+
+        let parks_itself = match &self.playback {
+            Some(player) => player.auto_park(),
+            None => self.settings.auto_park,
+        };
+        self.record_frame(parks_itself);
+
+        let timings = self.config.unit_timings(self.enemy_spawner.difficulty());
+        let context = StepContext {
+            time: &self.time,
+            timings: &timings,
+            mode: self.enemy_spawner.mode(),
+            invincible: self.cheats.invincible,
+        };
+        ticker(
+            &context,
+            self.units.as_mut_slice(),
+            &mut self.enemies,
+            &mut self.parking_spaces,
+            &mut self.game_state,
+            &mut self.play_time,
+            events,
+        );
+
+        units_meet_enemies(
+            &mut self.units,
+            &mut self.enemies,
+            self.config.units.damage_per_hit,
+            events,
+        );
+        if let Some(scenario) = &mut self.scenario {
+            scenario.spawn_due(
+                &self.play_time,
+                &mut self.enemy_spawner,
+                &mut self.enemies,
+                events,
+            );
+        } else if self.tutorial.is_none() {
+            spawn_enemies(
+                &mut self.enemy_spawner,
                 &self.time,
+                &self.play_time,
+                &mut self.enemies,
+                events,
+            );
+        }
+        repair_tick(&self.time, &timings, self.units.as_mut_slice(), events);
+
+        for alert in self.threat_alerts.update(&self.units, &self.enemies) {
+            events.push(GameEvent::EnemyBreaching {
+                combat_type: alert.combat_type,
+                threshold: alert.threshold,
+                eta_seconds: alert.eta_seconds,
+            });
+        }
+
+        if parks_itself {
+            auto_park(
                 self.units.as_mut_slice(),
-                self.enemies.as_mut_slice(),
-                &mut self.game_state,
-                &mut self.play_time,
+                &timings,
+                &mut self.parking_spaces,
+                events,
             );
+        }
+
+        self.stats.record_parking(&self.time, &self.parking_spaces);
+        self.report_parking(events);
+        self.sample_timeline();
+
+        if let Some(scenario) = &mut self.scenario {
+            scenario.record(events);
+            if self.game_state == GameState::Running {
+                if let Some(outcome) = scenario.outcome(&self.play_time, &self.enemies) {
+                    self.game_state = outcome;
+                }
+            }
+        }
+
+        let survived_seconds = self.play_time.seconds();
+        match self.game_state {
+            GameState::GameOver => events.push(GameEvent::GameOver { survived_seconds }),
+            GameState::Victory => events.push(GameEvent::Victory { survived_seconds }),
+            _ => {}
+        }
+    }
+
+    /// Adds how things stand to the timeline, if a sample is due.
+    fn sample_timeline(&mut self) {
+        let seconds = self.play_time.seconds();
+        let (units, enemies) = (&self.units, &self.enemies);
+        let (parking_spaces, score) = (&self.parking_spaces, &self.score);
+        self.timeline.sample(seconds, || {
+            let census = census(units, enemies);
+            TimelineSample {
+                at_seconds: seconds,
+                points: score.points(seconds),
+                enemies: census.enemies as u32,
+                airborne: census.airborne as u32,
+                parking_used: parking_spaces.slots_used() as u32,
+                parking_capacity: parking_spaces.capacity() as u32,
+            }
+        });
+    }
+
+    /// Sends `ParkingChanged` if units came into or left the parking spaces since last time.
+    fn report_parking(&mut self, events: &mut Vec<GameEvent>) {
+        let used = self.parking_spaces.slots_used();
+        if used != self.parking_used {
+            self.parking_used = used;
+            events.push(GameEvent::ParkingChanged { used });
+        }
+    }
+
+    /// Settles the run once it is over: records it and tells the page hosting the game.
+    fn finish_run(&mut self, survived_seconds: f64) {
+        let points = self.score.points(survived_seconds);
+        // A replay's run was settled when it was recorded.
+        if let Some(player) = &self.playback {
+            log::info!("Replay over after {:.0}s", survived_seconds);
+            if !player.matches_result(points) {
+                self.replay_diverged();
+            }
+            return;
+        }
+
+        if self.game_state == GameState::Victory && self.scenario.is_some() {
+            log::info!("Scenario won after {:.0}s", survived_seconds);
+        } else if self.game_state == GameState::Victory {
+            log::info!("Contract fulfilled after {:.0}s", survived_seconds);
+        } else {
+            log::info!("Game over after {:.0}s", survived_seconds);
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.finish(survived_seconds, points);
+        }
+        if self.cheats.used {
+            log::info!("Cheats were used, so the run doesn't count for the high score");
+        } else if self.daily.as_ref().is_some_and(|daily| !daily.scored) {
+            log::info!("Practice at the daily challenge, so the run doesn't count");
+        } else if self.scenario.is_some() {
+            log::info!("Scenarios are set up beforehand, so the run doesn't count");
+        } else {
+            self.put_on_record(survived_seconds, points);
+            self.submit_online(survived_seconds, points);
+        }
+        SavedRun::clear(&mut self.storage, SaveSlot::Autosave);
+        self.report_game_over();
+    }
+
+    /// Submits the run that just ended for the high score and, if it qualifies, puts it on the
+    /// leaderboard. Kept straight away, before the run-over window even shows, so a crash or
+    /// closing the game now loses nothing. The initials follow once they are typed in.
+    fn put_on_record(&mut self, survived_seconds: f64, points: u64) {
+        if let Some(daily) = &self.daily {
+            let outcome = DailyOutcome {
+                survived_seconds,
+                points,
+            };
+            if !self
+                .daily_results
+                .record(&daily.date, outcome, &mut self.storage)
+            {
+                log::warn!("The daily challenge of {} was settled already", daily.date);
+            }
+        }
+        self.high_score
+            .submit(survived_seconds, points, &mut self.storage);
+        self.awaiting_initials = self.leaderboard.qualifies(points, survived_seconds);
+        if self.awaiting_initials {
+            let entry = LeaderboardEntry::new(
+                "",
+                points,
+                survived_seconds,
+                self.enemy_spawner.difficulty(),
+                self.enemy_spawner.new_game_plus(),
+                self.enemy_spawner.seed(),
+            );
+            self.leaderboard.insert(entry, &mut self.storage);
+        }
+    }
+
+    /// Sends the run that just ended to the online leaderboard, if the player turned that on.
+    /// Tutorial runs hold enemies back, so they aren't sent.
+    fn submit_online(&mut self, survived_seconds: f64, points: u64) {
+        if !self.settings.online_leaderboard || self.tutorial.is_some() {
+            return;
+        }
+
+        self.online_leaderboard.submit(Submission {
+            initials: LeaderboardEntry::clean_initials(&self.settings.online_initials),
+            points,
+            survived_seconds,
+            seed: self.enemy_spawner.seed(),
+            difficulty: self.enemy_spawner.difficulty(),
+            version: env!("CARGO_PKG_VERSION"),
+        });
+    }
 
-            units_meet_enemies(&mut self.units, &mut self.enemies);
-            spawn_enemies(&mut self.enemy_spawner, &self.time, &mut self.enemies);
-            repair_tick(&self.time, self.units.as_mut_slice());
+    /// Throws away the current run and its save and goes back to the main menu. Only the
+    /// storage, the GUI's own state, like loaded sprites, the sound player, what links the game
+    /// to the page and its canvas, the seed the page asked for, the balance and the online
+    /// leaderboard are kept. Watching a replay leaves the save alone, as it belongs to another
+    /// run.
+    pub fn reset(&mut self) {
+        log::info!("Run thrown away, back to the main menu");
+        let mut storage = std::mem::take(&mut self.storage);
+        if self.playback.is_none() {
+            SavedRun::clear(&mut storage, SaveSlot::Autosave);
+        }
+        #[cfg(feature = "backend-eframe")]
+        let gui_state = std::mem::take(&mut self.gui_state);
+        let sound_player = std::mem::take(&mut self.sound_player);
+        let externally_paused = Rc::clone(&self.externally_paused);
+        let page_link = Rc::clone(&self.page_link);
+        let seed = self.seed;
+        let start_config = std::mem::take(&mut self.start_config);
+        let sandbox = self.sandbox;
+        let balance_path = self.balance_path.take();
+        let scenarios = std::mem::take(&mut self.scenarios);
+        let mut online_leaderboard = std::mem::take(&mut self.online_leaderboard);
+        online_leaderboard.clear();
+        let canvas_id = std::mem::take(&mut self.canvas_id);
+        *self = Self::with_storage(storage, std::mem::take(&mut self.config));
+        self.externally_paused = externally_paused;
+        self.page_link = page_link;
+        self.canvas_id = canvas_id;
+        self.seed = seed;
+        self.start_config = start_config;
+        self.sandbox = sandbox;
+        self.balance_path = balance_path;
+        self.scenarios = scenarios;
+        self.online_leaderboard = online_leaderboard;
+        #[cfg(feature = "backend-eframe")]
+        {
+            self.gui_state = gui_state;
+        }
+        self.sound_player = sound_player;
+        self.apply_start_settings();
+    }
+}
+
+/// What only the front end does: everything the player or the page hosting the game asks for
+/// outside the run itself.
+#[cfg(feature = "backend-eframe")]
+impl MyGame {
+    /// Starts today's daily challenge: the attempt that counts if there was none today yet,
+    /// practice otherwise.
+    fn start_daily(&mut self) {
+        if self.game_state != GameState::MainMenu {
+            return;
+        }
+
+        let daily = self.daily_results.begin(&daily::today(), &mut self.storage);
+        let seed = daily.seed();
+        self.daily = Some(daily);
+        if self.time.scale() > daily::MAX_SPEED.scale() {
+            self.time.set_scale(daily::MAX_SPEED.scale());
+        }
+        self.start_with_seed(false, Some(seed));
+    }
+
+    /// Starts the scenario at `index` among those on offer.
+    fn start_scenario(&mut self, index: usize) {
+        if self.game_state != GameState::MainMenu {
+            return;
+        }
+
+        let scenario = match self.scenarios.get(index) {
+            Some(scenario) => scenario.clone(),
+            None => return,
+        };
+        self.scenario = Some(ScenarioRun::new(index, scenario));
+        self.start_with_seed(false, self.seed);
+    }
+
+    /// The flag for the page to set when the game couldn't run for a while. The run is paused
+    /// and the time in between dropped.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn externally_paused(&self) -> Rc<Cell<bool>> {
+        Rc::clone(&self.externally_paused)
+    }
+
+    /// Hands over the sprites decoded ahead of time, e.g. while the page showed a loading bar,
+    /// so starting the GUI only has to register them.
+    pub fn use_decoded_sprites(&mut self, decoded: DecodedTextures) {
+        self.gui_state.set_decoded_sprites(decoded);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn page_link(&self) -> Rc<RefCell<PageLink>> {
+        Rc::clone(&self.page_link)
+    }
+
+    /// Pauses, resumes or mutes the game as the page asked since the last frame. Muting holds
+    /// for this session, like the start config.
+    fn apply_page_requests(&mut self) {
+        let (paused, muted) = {
+            let mut page_link = self.page_link.borrow_mut();
+            (page_link.take_pause(), page_link.take_muted())
+        };
+        if let Some(paused) = paused {
+            self.set_paused(paused);
+        }
+        if let Some(muted) = muted {
+            self.settings.muted = muted;
+            self.start_config.muted = Some(muted);
+        }
+    }
+
+    fn publish_status(&self) {
+        let mut page_link = self.page_link.borrow_mut();
+        page_link.publish(RunStatus {
+            survival_seconds: self.play_time.seconds(),
+            score: self.score.points(self.play_time.seconds()),
+            state: self.game_state,
+        });
+        page_link.publish_key_bindings(&self.settings.key_bindings);
+    }
+
+    /// Picks up the run that was saved last time where it left off, paused.
+    fn continue_saved_run(&mut self) {
+        if self.game_state != GameState::MainMenu {
+            return;
+        }
+
+        let saved_run = match self.saved_run.take() {
+            Some(saved_run) => saved_run,
+            None => return,
+        };
+        match saved_run.restore(&self.config) {
+            Ok(restored) => self.resume(restored),
+            Err(error) => log::warn!("The saved run couldn't be continued: {}", error),
+        }
+    }
+
+    /// Swaps the current run, if there is one, for the one the player saved in the slot,
+    /// paused. Says in a toast why if the save can't be picked up again.
+    fn load_game(&mut self, slot: usize) {
+        let restored = match self
+            .save_slots
+            .load_run(&self.storage, slot)
+            .and_then(|run| run.restore(&self.config))
+        {
+            Ok(restored) => restored,
+            Err(error) => {
+                log::warn!(
+                    "The game saved in slot {} couldn't be loaded: {}",
+                    slot + 1,
+                    error
+                );
+                self.toasts.push(
+                    ToastMessage::LoadFailed {
+                        reason: error.to_string(),
+                    },
+                    Severity::Warning,
+                );
+                return;
+            }
+        };
+
+        if self.game_state != GameState::MainMenu {
+            self.reset();
+        }
+        self.resume(restored);
+    }
+
+    /// Takes over a restored run, paused.
+    fn resume(&mut self, restored: RestoredRun) {
+        self.play_time = restored.play_time;
+        self.enemy_spawner = restored.enemy_spawner;
+        self.crash_context.set_seed(self.enemy_spawner.seed());
+        self.parking_spaces = restored.parking_spaces;
+        self.units = restored.units;
+        self.enemies = restored.enemies;
+        self.stats = restored.stats;
+        self.score = restored.score;
+        self.time.set_scale(restored.time_scale);
+        self.cheats = restored.cheats;
+        self.daily = restored.daily;
+        self.tutorial = None;
+        self.replay = None;
+        self.achievements
+            .start_run(self.enemy_spawner.mode(), false);
+        self.game_state = GameState::Paused;
+        log::info!(
+            "Run resumed at {:.0}s with seed {}",
+            self.play_time.seconds(),
+            self.enemy_spawner.seed()
+        );
+    }
+
+    /// Saves the run every few seconds while it is going, outside the tutorial.
+    fn autosave(&mut self) {
+        const AUTOSAVE_SECONDS: f64 = 5.0;
+
+        let now = self.time.real_seconds();
+        if !self.has_own_run() || now < self.last_autosave + AUTOSAVE_SECONDS {
+            return;
+        }
+
+        self.capture_run()
+            .save(&mut self.storage, SaveSlot::Autosave);
+        self.last_autosave = now;
+    }
+
+    /// Whether one of the player's own runs is going, rather than the tutorial, a scenario or a
+    /// replay.
+    fn has_own_run(&self) -> bool {
+        let in_run = matches!(self.game_state, GameState::Running | GameState::Paused);
+        in_run && self.tutorial.is_none() && self.scenario.is_none() && self.playback.is_none()
+    }
+
+    /// Keeps what closing the game would lose: the settings, and the run if one is going, as
+    /// the autosave that Continue on the main menu picks up.
+    fn save_on_exit(&mut self) {
+        self.settings.save(&mut self.storage);
+        if self.has_own_run() {
+            self.capture_run()
+                .save(&mut self.storage, SaveSlot::Autosave);
+            log::info!("Run saved on exit at {:.0}s", self.play_time.seconds());
+        }
+    }
+
+    /// Keeps the run in the slot under `name`, until the player saves over it or deletes it.
+    /// Without a name, it goes by how long the run has lasted. Runs outside the tutorial and
+    /// replays only, like the autosave, and no daily challenges, which could otherwise be tried
+    /// again from the save.
+    fn save_game(&mut self, slot: usize, name: &str) {
+        if !self.has_own_run() || self.daily.is_some() {
+            return;
+        }
+
+        let seconds = self.play_time.seconds();
+        let name = match name.trim() {
+            "" => format!("{:.0}s", seconds),
+            name => name.to_string(),
+        };
+        let run = self.capture_run();
+        self.save_slots.save(&mut self.storage, slot, &name, run);
+        log::info!(
+            "Run saved at {:.0}s in slot {} as {:?}",
+            seconds,
+            slot + 1,
+            name
+        );
+        self.toasts
+            .push(ToastMessage::GameSaved { seconds }, Severity::Info);
+    }
+
+    /// Keeps the run that just ended to be watched again, replacing the replay saved before.
+    fn save_replay(&mut self) {
+        let replay = match &self.replay {
+            Some(replay) if replay.is_finished() => replay,
+            _ => return,
+        };
+
+        replay.save(&mut self.storage);
+        self.saved_replay_seconds = Some(replay.survived_seconds());
+        log::info!("Replay of the run saved");
+        self.toasts.push(ToastMessage::ReplaySaved, Severity::Info);
+    }
+
+    /// Swaps the current run, if there is one, for the saved replay, played back from the start.
+    fn watch_replay(&mut self) {
+        let replay = match Replay::load(&self.storage) {
+            Some(replay) => replay,
+            None => return,
+        };
+
+        if self.game_state != GameState::MainMenu {
+            self.reset();
+        }
+        self.enemy_spawner = EnemySpawner::with_seed(
+            replay.difficulty(),
+            replay.mode(),
+            replay.new_game_plus(),
+            replay.seed(),
+            &self.config,
+        );
+        self.crash_context.set_seed(replay.seed());
+        self.score = Score::new(replay.difficulty());
+        self.tutorial = None;
+        log::info!(
+            "Watching the replay of a {:.0}s run on {} with seed {}",
+            replay.survived_seconds(),
+            replay.difficulty(),
+            replay.seed()
+        );
+        self.playback = Some(ReplayPlayer::new(replay));
+        self.game_state = GameState::Running;
+    }
+
+    /// Carries out orders for the units, e.g. from the GUI, skipping those that aren't possible
+    /// any more. Only while the run is going, and not while a replay gives the orders.
+    pub(crate) fn apply_commands(&mut self, commands: Vec<GameCommand>) {
+        if self.playback.is_some() || !self.game_state.is_interactive() {
+            return;
+        }
+
+        if let Some(replay) = &mut self.replay {
+            replay.record_commands(&commands);
+        }
+        self.carry_out(commands);
+    }
+
+    /// Puts the player's initials on the leaderboard entry of the run that just ended.
+    fn enter_leaderboard(&mut self, initials: &str) {
+        if !self.awaiting_initials || !self.game_state.is_over() {
+            return;
+        }
+
+        self.leaderboard
+            .name_most_recent(initials, &mut self.storage);
+        log::info!(
+            "{} entered the leaderboard",
+            LeaderboardEntry::clean_initials(initials)
+        );
+        self.awaiting_initials = false;
+    }
+
+    /// Offers a picture of the game as a download and says in a toast how that went.
+    fn save_screenshot(&mut self) {
+        match engine::screenshot::save(&self.canvas_id, "fruitopian_defender") {
+            Ok(file_name) => self
+                .toasts
+                .push(ToastMessage::ScreenshotSaved { file_name }, Severity::Info),
+            Err(error) => self.toasts.push(
+                ToastMessage::ScreenshotFailed {
+                    reason: error.to_string(),
+                },
+                Severity::Warning,
+            ),
+        }
+    }
+
+    /// Like `reset`, but the next run starts with what `carry_over` hands on.
+    fn reset_carrying_over(&mut self, carry_over: CarryOver) {
+        self.reset();
+        self.carry_over = carry_over;
+    }
+}
+
+#[cfg(feature = "backend-eframe")]
+impl epi::App for MyGame {
+    fn update(&mut self, ctx: &CtxRef, frame: &mut Frame<'_>) {
+        #[cfg(feature = "count-allocations")]
+        alloc_count::end_frame();
+
+        // epi only hands out the texture allocator while drawing a frame.
+        self.gui_state.load_sprites(frame.tex_allocator());
+
+        if let Some(ui_scale) = self.settings.ui_scale {
+            engine::ui::set_ui_scale(ctx, ui_scale);
         }
 
-        gui(
+        // Coming back to a lost run because the game played on in a background tab is no fun.
+        let was_hidden = self.externally_paused.replace(false);
+        let lost_focus = self.focus.lost_focus() && self.settings.pause_on_focus_loss;
+        if (was_hidden || lost_focus) && self.game_state == GameState::Running {
+            self.game_state = GameState::Paused;
+        }
+        self.apply_page_requests();
+
+        log::trace!("GameState: {:?}", self.game_state);
+        let mut events = Vec::new();
+        if self.playback.is_some() {
+            self.play_back();
+        } else {
+            self.step_simulation(&mut events);
+        }
+        // Before the GUI, so a run that just ended is on record by the time it is shown as over.
+        self.handle_events(&events);
+        self.autosave();
+        self.online_leaderboard.update();
+
+        self.toasts.update(self.time.real_seconds());
+        let previous_settings = self.settings.clone();
+
+        let view = GameView {
+            units: &self.units,
+            enemies: &self.enemies,
+            lane_positions: &self.lane_positions,
+            enemy_spawner: &self.enemy_spawner,
+            config: &self.config,
+            parking_spaces: &self.parking_spaces,
+            game_state: &self.game_state,
+            play_time: &self.play_time,
+            time: &self.time,
+            high_score: &self.high_score,
+            leaderboard: &self.leaderboard,
+            online_status: self.online_leaderboard.status(),
+            defeat: self.defeat.as_ref(),
+            achievements: &self.achievements,
+            awaiting_initials: self.awaiting_initials,
+            saved_run_seconds: self.saved_run.as_ref().map(SavedRun::play_seconds),
+            save_slots: &self.save_slots,
+            watching_replay: self.playback.is_some(),
+            can_save_replay: self.replay.as_ref().is_some_and(Replay::is_finished),
+            saved_replay_seconds: self.saved_replay_seconds,
+            challenge_seed: self.seed,
+            daily: self.daily.as_ref(),
+            daily_results: &self.daily_results,
+            scenarios: &self.scenarios,
+            scenario: self.scenario.as_ref(),
+            sandbox: self.sandbox,
+            cheats: &self.cheats,
+            stats: &self.stats,
+            score: &self.score,
+            timeline: &self.timeline,
+            battle_log: &self.battle_log,
+            toasts: &self.toasts,
+            tutorial: self.tutorial.as_ref(),
+        };
+        let action = gui(
             ctx,
-            self.units.as_mut_slice(),
-            self.enemies.as_mut_slice(),
-            &mut self.parking_spaces,
-            &self.game_state,
-            &self.play_time,
+            &view,
+            &mut self.threat_alerts,
+            &mut self.settings,
+            &mut self.gui_state,
         );
 
-        self.time.tick();
+        let commands = self.gui_state.take_commands();
+        self.apply_commands(commands);
+        self.sounds.play_all(&mut self.sound_player, &self.settings);
+
+        if self.settings != previous_settings {
+            self.start_config
+                .forget_changed(&previous_settings, &self.settings);
+            self.settings.save(&mut self.storage);
+        }
+
+        match action {
+            GuiAction::None => {}
+            GuiAction::Start => {
+                self.sound_player.unlock();
+                self.start(!self.tutorial_record.is_completed());
+            }
+            GuiAction::ContinueRun => {
+                self.sound_player.unlock();
+                self.continue_saved_run();
+            }
+            GuiAction::StartDaily => {
+                if self.game_state.is_over() {
+                    self.reset();
+                }
+                self.sound_player.unlock();
+                self.start_daily();
+            }
+            GuiAction::StartScenario(index) => {
+                if self.game_state.is_over() {
+                    self.reset();
+                }
+                self.sound_player.unlock();
+                self.start_scenario(index);
+            }
+            GuiAction::StartTutorial => {
+                self.sound_player.unlock();
+                self.start(true);
+            }
+            GuiAction::SkipTutorial => self.finish_tutorial(),
+            GuiAction::Pause => self.set_paused(true),
+            GuiAction::Resume => self.set_paused(false),
+            GuiAction::SetSpeed(speed) => self.set_speed(speed),
+            GuiAction::SpeedUp => self.speed_up(),
+            GuiAction::SlowDown => self.slow_down(),
+            GuiAction::Restart => self.reset(),
+            GuiAction::PlayAgain => {
+                self.reset();
+                self.start(false);
+            }
+            GuiAction::RetrySameSeed => {
+                let seed = self.enemy_spawner.seed();
+                // The same enemies, so at the same New Game+ level too.
+                let carry_over = CarryOver {
+                    level: self.enemy_spawner.new_game_plus(),
+                };
+                self.reset_carrying_over(carry_over);
+                self.start_with_seed(false, Some(seed));
+            }
+            GuiAction::NewGamePlus => {
+                let carry_over = CarryOver::extract(
+                    self.game_state,
+                    &self.enemy_spawner,
+                    &self.cheats,
+                    self.playback.is_some(),
+                );
+                if let Some(carry_over) = carry_over {
+                    log::info!("On to New Game+ {}", carry_over.level);
+                    self.reset_carrying_over(carry_over);
+                    self.start(false);
+                }
+            }
+            GuiAction::SaveGame(slot) => {
+                let name = self.gui_state.take_save_name();
+                self.save_game(slot, &name);
+            }
+            GuiAction::LoadGame(slot) => {
+                self.sound_player.unlock();
+                self.load_game(slot);
+            }
+            GuiAction::DeleteSave(slot) => {
+                self.save_slots.delete(&mut self.storage, slot);
+                log::info!("Saved game in slot {} deleted", slot + 1);
+            }
+            GuiAction::SaveReplay => self.save_replay(),
+            GuiAction::WatchReplay => {
+                self.sound_player.unlock();
+                self.watch_replay();
+            }
+            GuiAction::EnterInitials => {
+                let initials = self.gui_state.take_initials();
+                self.enter_leaderboard(&initials);
+            }
+            GuiAction::SaveScreenshot => self.save_screenshot(),
+            GuiAction::Quit => self.exit.request(),
+        }
+        self.exit.apply(frame);
+
+        self.sound_player
+            .update_music(&MusicState::new(self.game_state, &self.settings));
+        self.publish_status();
+        // Let go of the link first; the callback may call back into it.
+        let on_first_frame = self.page_link.borrow_mut().take_on_first_frame();
+        if let Some(on_first_frame) = on_first_frame {
+            on_first_frame();
+        }
+
+        if was_hidden {
+            self.time.skip();
+        } else {
+            self.time.tick();
+        }
+    }
+
+    fn on_exit(&mut self) {
+        self.save_on_exit();
     }
 
     fn name(&self) -> &str {
@@ -66,6 +1492,21 @@ impl epi::App for MyGame {
     }
 }
 
+impl engine::Headless for MyGame {
+    fn step(&mut self, frame_time: std::time::Duration) {
+        self.time
+            .advance(Duration::from_secs_f64(frame_time.as_secs_f64()));
+        let mut events = Vec::new();
+        if self.playback.is_some() {
+            self.play_back();
+        } else {
+            self.step_simulation(&mut events);
+        }
+        self.handle_events(&events);
+        self.online_leaderboard.update();
+    }
+}
+
 // impl Plugin for MyGame {
 //     fn build(&self, app: &mut AppBuilder) {
 //         app.add_startup_system(init_stuff.system())
@@ -83,3 +1524,303 @@ impl epi::App for MyGame {
 //             );
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Difficulty, UnitAction, UnitId};
+
+    const FRAME: std::time::Duration = std::time::Duration::from_millis(16);
+
+    fn test_game() -> MyGame {
+        MyGame::with_storage(
+            GameStorage::new(FileStorage::discarding()),
+            GameConfig::built_in(),
+        )
+    }
+
+    /// Everything a run is made of, as far as it can be compared. Every game rolls a seed of its
+    /// own, which its first draws depend on, and the time of capture always differs, so those
+    /// are left out.
+    fn run_state(game: &MyGame) -> (serde_json::Value, String) {
+        let mut run = serde_json::to_value(game.capture_run()).unwrap();
+        run["enemy_spawner"]["seed"] = serde_json::Value::Null;
+        run["enemy_spawner"]["rng_word_pos"] = serde_json::Value::Null;
+        run["saved_at"] = serde_json::Value::Null;
+        let rest = format!(
+            "{:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {}",
+            game.game_state,
+            game.timeline,
+            game.battle_log,
+            game.threat_alerts,
+            game.toasts,
+            game.defeat,
+            game.carry_over,
+            game.lane_positions,
+            game.parking_used,
+            game.awaiting_initials,
+        );
+        (run, rest)
+    }
+
+    #[test]
+    fn a_reset_game_is_like_a_fresh_one() {
+        let mut game = test_game();
+        game.start_with_seed(false, Some(7));
+        game.carry_out(vec![GameCommand::Perform {
+            unit: UnitId(1),
+            action: UnitAction::UnStore,
+        }]);
+        for _ in 0..600 {
+            engine::Headless::step(&mut game, FRAME);
+        }
+        assert_ne!(run_state(&game), run_state(&test_game()));
+
+        game.reset();
+        assert_eq!(run_state(&game), run_state(&test_game()));
+        assert!(game.replay.is_none());
+        assert!(game.tutorial.is_none());
+    }
+
+    #[test]
+    fn a_thousand_headless_frames_play_a_thousand_frames_of_time() {
+        let mut game = test_game();
+        game.start_with_seed(false, Some(7));
+
+        let game = engine::run_headless(game, 1000).unwrap();
+        assert_eq!(game.game_state, GameState::Running);
+        // Played out in whole steps, so up to one is still owed.
+        let seconds = game.play_time.seconds();
+        let expected = 1000.0 / 60.0;
+        assert!(
+            seconds <= expected && seconds > expected - FixedTimestep::STEP_SECONDS,
+            "{}",
+            seconds
+        );
+    }
+
+    #[test]
+    fn the_frame_rate_does_not_change_how_a_run_goes() {
+        use engine::Headless;
+
+        // Thirty seconds and a sixth, which is whole frames at both rates and part of the way
+        // into a step, so rounding can't tip either over into one more.
+        let play = |frames_per_second: u32, frames: u32| {
+            let mut game = test_game();
+            game.settings.auto_park = true;
+            game.start_with_seed(false, Some(11));
+            let frame = std::time::Duration::from_secs_f64(1.0 / f64::from(frames_per_second));
+            for _ in 0..frames {
+                game.step(frame);
+            }
+            game
+        };
+        let slow = play(30, 905);
+        let fast = play(144, 4344);
+
+        assert!(slow.play_seconds() > 30.0);
+        assert_eq!(
+            slow.capture_run().fingerprint(),
+            fast.capture_run().fingerprint()
+        );
+    }
+
+    /// Keeps nothing, but notes down every write in order.
+    #[derive(Clone, Default)]
+    struct WriteLog(Rc<RefCell<Vec<(String, String)>>>);
+
+    impl crate::storage::Storage for WriteLog {
+        fn get(&self, _key: &str) -> Option<String> {
+            None
+        }
+
+        fn set(&mut self, key: &str, value: String) {
+            self.0.borrow_mut().push((key.to_string(), value));
+        }
+    }
+
+    #[test]
+    fn a_lost_run_is_on_record_before_its_autosave_is_cleared() {
+        use engine::Headless;
+
+        let writes = WriteLog::default();
+        let mut game =
+            MyGame::with_storage(GameStorage::new(writes.clone()), GameConfig::built_in());
+        game.start_with_seed(false, Some(7));
+        // Starting a run throws the autosave away as well; only the end of it is of interest.
+        writes.0.borrow_mut().clear();
+        // Left alone, the units let the first enemy through.
+        for _ in 0..20_000 {
+            if game.is_game_over() {
+                break;
+            }
+            game.step(FRAME);
+        }
+        assert_eq!(game.game_state, GameState::GameOver);
+
+        let writes = writes.0.borrow();
+        let position = |key: &str| writes.iter().position(|(written, _)| written == key);
+        let recorded = position("high_score").expect("the run wasn't put on record");
+        let cleared = position("saved_run").expect("the autosave wasn't cleared");
+        assert!(recorded < cleared, "{:?}", writes);
+        assert_eq!(writes[cleared].1, "null");
+    }
+
+    #[test]
+    fn a_paused_run_stands_still_and_resumes_where_it_stopped() {
+        use engine::Headless;
+
+        let mut paused = test_game();
+        let mut straight = test_game();
+        for game in [&mut paused, &mut straight] {
+            game.start_with_seed(false, Some(7));
+            for _ in 0..200 {
+                game.step(FRAME);
+            }
+        }
+
+        paused.set_paused(true);
+        let stopped = run_state(&paused);
+        for _ in 0..300 {
+            paused.step(FRAME);
+        }
+        assert_eq!(paused.game_state, GameState::Paused);
+        assert_eq!(run_state(&paused), stopped);
+
+        paused.set_paused(false);
+        for game in [&mut paused, &mut straight] {
+            for _ in 0..200 {
+                game.step(FRAME);
+            }
+        }
+        assert_eq!(run_state(&paused), run_state(&straight));
+    }
+
+    #[test]
+    fn a_clean_contract_is_won_with_gold() {
+        use crate::{game_mode::Medal, simulation::Policy};
+
+        // Units that aren't hurt can't be lost, so the run stays clean as long as every enemy
+        // is met in time.
+        let mut config = GameConfig::built_in();
+        config.units.damage_per_hit = 0.0;
+        let mut game = MyGame::with_storage(GameStorage::new(FileStorage::discarding()), config);
+        game.settings.difficulty = Difficulty::Easy;
+        game.settings.game_mode = GameMode::DefenseContract;
+        game.settings.auto_park = true;
+        game.start_with_seed(false, Some(1));
+
+        let contract = GameMode::DefenseContract.contract().unwrap();
+        let mut rng = ChaCha12Rng::seed_from_u64(1);
+        while game.game_state.is_interactive() {
+            let orders = Policy::Commander.orders(&game.units, &game.enemies, &mut rng);
+            game.carry_out(orders);
+            engine::Headless::step(&mut game, FRAME);
+            assert!(game.play_time.seconds() <= contract.duration_seconds + 1.0);
+        }
+
+        assert_eq!(game.game_state, GameState::Victory);
+        assert!(game.stats.total_enemies_destroyed() > 0);
+        assert_eq!(
+            contract.medal(game.stats.units_lost, game.stats.bosses_leaked),
+            Medal::Gold
+        );
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    #[test]
+    fn every_scenario_can_be_won() {
+        use crate::simulation::Policy;
+
+        let scenarios = scenario::built_in();
+        assert_eq!(scenarios.len(), 3);
+        for (index, scenario) in scenarios.iter().enumerate() {
+            let mut game = test_game();
+            game.scenarios = scenarios.clone();
+            game.settings.auto_park = true;
+            game.seed = Some(1);
+            game.start_scenario(index);
+            assert!(game.scenario.is_some(), "{}", scenario.name);
+
+            let mut rng = ChaCha12Rng::seed_from_u64(1);
+            while game.game_state.is_interactive() {
+                let orders = Policy::Commander.orders(&game.units, &game.enemies, &mut rng);
+                game.carry_out(orders);
+                engine::Headless::step(&mut game, FRAME);
+                assert!(game.play_time.seconds() < 600.0, "{}", scenario.name);
+            }
+            assert_eq!(game.game_state, GameState::Victory, "{}", scenario.name);
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    #[test]
+    fn what_happens_in_a_frame_is_reported_once_to_everyone() {
+        use crate::{
+            achievements::AchievementId,
+            game::{fixtures::enemy, CombatType, Health, Unit},
+            helpers::Timer,
+            toasts::ToastMessage,
+        };
+
+        let mut game = test_game();
+        game.start_with_seed(false, Some(7));
+        let unit = game.units[0].id;
+        let mut patrol = Timer::from_seconds(10.0, false);
+        patrol.set_elapsed(Duration::from_secs_f64(6.0));
+        game.units[0].unit = Unit::Patrolling(patrol, CombatType::B);
+        // One more hit and it's gone.
+        game.units[0].health = Health::new(0.2);
+        game.enemies = vec![enemy(1, CombatType::B, 10.0, 5.0)];
+
+        let mut events = Vec::new();
+        game.time
+            .set_delta(Duration::from_secs_f64(FixedTimestep::STEP_SECONDS));
+        game.simulate(&mut events);
+        assert_eq!(
+            events,
+            [
+                GameEvent::EnemyIntercepted {
+                    unit,
+                    combat_type: CombatType::B,
+                    is_boss: false,
+                },
+                GameEvent::UnitDestroyed { unit },
+            ]
+        );
+
+        game.handle_events(&events);
+        assert_eq!(game.stats.total_enemies_destroyed(), 1);
+        assert_eq!(game.stats.units_lost, 1);
+        assert!(game.score.points(0.0) > 0);
+        assert_eq!(game.battle_log.recorded(), 2);
+        assert_eq!(
+            game.sounds.queued(),
+            [SoundEvent::Interception, SoundEvent::UnitDestroyed]
+        );
+        game.toasts.update(0.0);
+        let toasts: Vec<_> = game
+            .toasts
+            .visible()
+            .iter()
+            .map(|toast| &toast.message)
+            .collect();
+        assert_eq!(
+            toasts,
+            [
+                &ToastMessage::AchievementUnlocked {
+                    achievement: AchievementId::FirstInterception
+                },
+                &ToastMessage::UnitDestroyed { unit },
+            ]
+        );
+
+        // Nothing that happened is reported again in the next frame.
+        let mut events = Vec::new();
+        game.simulate(&mut events);
+        assert!(events.iter().all(|event| matches!(
+            event,
+            GameEvent::EnemySpawned { .. } | GameEvent::EnemyBreaching { .. }
+        )));
+    }
+}
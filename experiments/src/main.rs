@@ -0,0 +1,30 @@
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    // `RUST_LOG=debug` shows every event of the run.
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    fruitopian_defender::install_panic_hook();
+    let (config, warnings) = fruitopian_defender::StartConfig::from_args(std::env::args().skip(1));
+    for warning in warnings {
+        log::warn!("{}", warning);
+    }
+    let mut game = fruitopian_defender::MyGame::new();
+    game.apply_start_config(config);
+    let result = engine::run(
+        game,
+        engine::RunConfig {
+            title: "Fruitopian Defender".to_string(),
+            style: Some(fruitopian_defender::install_style),
+            icon: Some(include_bytes!("../assets/plum.png")),
+            ..Default::default()
+        },
+    );
+
+    if let Err(error) = result {
+        eprintln!("Fruitopian Defender could not start: {}", error);
+        std::process::exit(1);
+    }
+}
+
+// The web build starts from `wasm_startup` in the library instead.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
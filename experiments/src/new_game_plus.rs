@@ -0,0 +1,109 @@
+//! New Game+: a won contract can be followed straight away by the next, at one level higher.
+//! Every level makes the enemies come faster from the start, by `new_game_plus_pace` in the
+//! balance. The level is all there is to hand on, as the units and parking spaces of a run are
+//! the same for every run.
+
+#[cfg(feature = "backend-eframe")]
+use crate::{cheats::CheatState, game::EnemySpawner, game::GameState};
+
+/// What a won contract hands on to the run after it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CarryOver {
+    /// The New Game+ level the next run is at, 0 for an ordinary run.
+    pub level: u32,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl CarryOver {
+    /// What the run that just ended hands on, if it may go on to New Game+: only a contract
+    /// won without cheats, and not a replay of one, does.
+    pub fn extract(
+        game_state: GameState,
+        enemy_spawner: &EnemySpawner,
+        cheats: &CheatState,
+        watching_replay: bool,
+    ) -> Option<Self> {
+        let won = game_state == GameState::Victory && enemy_spawner.mode().contract().is_some();
+        if !won || cheats.used || watching_replay {
+            return None;
+        }
+
+        Some(Self {
+            level: enemy_spawner.new_game_plus() + 1,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+    use crate::{balance::GameConfig, game::Difficulty, game_mode::GameMode};
+
+    fn spawner(mode: GameMode, new_game_plus: u32) -> EnemySpawner {
+        EnemySpawner::with_seed(
+            Difficulty::Normal,
+            mode,
+            new_game_plus,
+            1,
+            &GameConfig::built_in(),
+        )
+    }
+
+    #[test]
+    fn a_won_contract_goes_on_one_level_higher() {
+        let extract = |new_game_plus| {
+            CarryOver::extract(
+                GameState::Victory,
+                &spawner(GameMode::DefenseContract, new_game_plus),
+                &CheatState::default(),
+                false,
+            )
+        };
+        assert_eq!(extract(0), Some(CarryOver { level: 1 }));
+        assert_eq!(extract(2), Some(CarryOver { level: 3 }));
+    }
+
+    #[test]
+    fn nothing_else_goes_on_to_new_game_plus() {
+        let contract = spawner(GameMode::DefenseContract, 0);
+        let clean = CheatState::default();
+        let cheated = CheatState {
+            used: true,
+            ..CheatState::default()
+        };
+        assert_eq!(
+            CarryOver::extract(GameState::GameOver, &contract, &clean, false),
+            None
+        );
+        assert_eq!(
+            CarryOver::extract(GameState::Victory, &contract, &cheated, false),
+            None
+        );
+        assert_eq!(
+            CarryOver::extract(GameState::Victory, &contract, &clean, true),
+            None
+        );
+        assert_eq!(
+            CarryOver::extract(
+                GameState::Victory,
+                &spawner(GameMode::Endless, 0),
+                &clean,
+                false
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn every_level_brings_the_enemies_sooner() {
+        let pace = GameConfig::built_in().enemies.new_game_plus_pace;
+        for mode in [GameMode::Endless, GameMode::DefenseContract] {
+            let base = spawner(mode, 0).mean_seconds_between_enemies();
+            for level in 1..=3 {
+                let expected = base * pace.powi(level as i32);
+                let actual = spawner(mode, level).mean_seconds_between_enemies();
+                assert!((actual - expected).abs() < 1e-9, "{:?} {}", mode, level);
+            }
+        }
+    }
+}
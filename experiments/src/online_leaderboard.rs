@@ -0,0 +1,405 @@
+//! Sending finished runs to an online leaderboard, for players who turn it on in the settings.
+//! Where to send them is up to the page hosting the game or the native build's command line;
+//! the HTTP part is only built with the `online-leaderboard` feature. Sending happens in the
+//! background and nothing in the game waits on it: the run-over screen just says how it went.
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::Serialize;
+
+use crate::game::Difficulty;
+
+/// What the leaderboard is told about a run, posted to the endpoint as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Submission {
+    pub initials: String,
+    pub points: u64,
+    pub survived_seconds: f64,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub version: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionStatus {
+    Submitting,
+    Submitted,
+    /// Why it didn't work, for the player to read.
+    Failed(String),
+}
+
+/// Gets submissions to a leaderboard. `send` returns straight away; how it went comes from
+/// `poll` later, once it is known.
+pub trait LeaderboardClient {
+    fn send(&mut self, submission: &Submission);
+    /// How the submission sent last went, if that is known by now. Each outcome is handed out
+    /// once.
+    fn poll(&mut self) -> Option<Result<(), String>>;
+}
+
+/// The leaderboard runs go to, if there is one, and how the last submission is going.
+#[derive(Default)]
+pub struct OnlineLeaderboard {
+    client: Option<Box<dyn LeaderboardClient>>,
+    status: Option<SubmissionStatus>,
+}
+
+impl OnlineLeaderboard {
+    /// A leaderboard at `endpoint`, a URL. Endpoints like `mock:ok` go nowhere: `mock:ok`
+    /// accepts every run and `mock:<reason>` turns them all down, to try the game's side out
+    /// without a server.
+    pub fn new(endpoint: &str) -> Self {
+        let client: Option<Box<dyn LeaderboardClient>> = match endpoint.strip_prefix("mock:") {
+            Some("ok") => Some(Box::new(MockClient::new(Ok(())))),
+            Some(reason) => Some(Box::new(MockClient::new(Err(reason.to_string())))),
+            None => http_client(endpoint),
+        };
+        client.map_or_else(Self::default, Self::with_client)
+    }
+
+    pub fn with_client(client: Box<dyn LeaderboardClient>) -> Self {
+        Self {
+            client: Some(client),
+            status: None,
+        }
+    }
+
+    /// Starts sending the run, if there is anywhere to send it.
+    pub fn submit(&mut self, submission: Submission) {
+        let client = match &mut self.client {
+            Some(client) => client,
+            None => return,
+        };
+        log::info!("Sending the run to the online leaderboard");
+        client.send(&submission);
+        self.status = Some(SubmissionStatus::Submitting);
+    }
+
+    /// Picks up how the submission went, once a frame.
+    pub fn update(&mut self) {
+        if self.status != Some(SubmissionStatus::Submitting) {
+            return;
+        }
+        let outcome = match self.client.as_mut().and_then(|client| client.poll()) {
+            Some(outcome) => outcome,
+            None => return,
+        };
+        self.status = Some(match outcome {
+            Ok(()) => {
+                log::info!("The run is on the online leaderboard");
+                SubmissionStatus::Submitted
+            }
+            Err(reason) => {
+                log::warn!(
+                    "The run couldn't be sent to the online leaderboard: {}",
+                    reason
+                );
+                SubmissionStatus::Failed(reason)
+            }
+        });
+    }
+
+    /// How sending the run that ended last is going, if it was sent.
+    #[cfg(feature = "backend-eframe")]
+    pub fn status(&self) -> Option<&SubmissionStatus> {
+        self.status.as_ref()
+    }
+
+    /// Forgets the last submission, for a new run. Should it still be on its way, how it goes
+    /// is never shown.
+    pub fn clear(&mut self) {
+        self.status = None;
+    }
+}
+
+/// Keeps what it is sent and answers every submission the same, on the next poll.
+pub struct MockClient {
+    /// Everything sent so far. Shared, so it can still be looked at once the client is handed
+    /// to an `OnlineLeaderboard`.
+    pub sent: Rc<RefCell<Vec<Submission>>>,
+    outcome: Result<(), String>,
+    answered: bool,
+}
+
+impl MockClient {
+    pub fn new(outcome: Result<(), String>) -> Self {
+        Self {
+            sent: Rc::default(),
+            outcome,
+            answered: true,
+        }
+    }
+}
+
+impl LeaderboardClient for MockClient {
+    fn send(&mut self, submission: &Submission) {
+        self.sent.borrow_mut().push(submission.clone());
+        self.answered = false;
+    }
+
+    fn poll(&mut self) -> Option<Result<(), String>> {
+        if self.answered {
+            return None;
+        }
+        self.answered = true;
+        Some(self.outcome.clone())
+    }
+}
+
+#[cfg(feature = "online-leaderboard")]
+fn http_client(endpoint: &str) -> Option<Box<dyn LeaderboardClient>> {
+    Some(Box::new(http::HttpClient::new(endpoint)))
+}
+
+#[cfg(not(feature = "online-leaderboard"))]
+fn http_client(endpoint: &str) -> Option<Box<dyn LeaderboardClient>> {
+    log::warn!(
+        "Not sending runs to {}: this build has no online leaderboard",
+        endpoint
+    );
+    None
+}
+
+/// Posts submissions from a thread of their own, so a slow server holds nothing up.
+#[cfg(all(feature = "online-leaderboard", not(target_arch = "wasm32")))]
+mod http {
+    use std::{
+        sync::mpsc::{self, Receiver, TryRecvError},
+        time::Duration,
+    };
+
+    use super::{LeaderboardClient, Submission};
+
+    /// Long enough for a slow connection, short enough that the run-over screen doesn't say
+    /// "submitting" forever.
+    const TIMEOUT: Duration = Duration::from_secs(15);
+
+    pub struct HttpClient {
+        endpoint: String,
+        outcome: Option<Receiver<Result<(), String>>>,
+    }
+
+    impl HttpClient {
+        pub fn new(endpoint: &str) -> Self {
+            Self {
+                endpoint: endpoint.to_string(),
+                outcome: None,
+            }
+        }
+    }
+
+    impl LeaderboardClient for HttpClient {
+        fn send(&mut self, submission: &Submission) {
+            let (sender, receiver) = mpsc::channel();
+            let endpoint = self.endpoint.clone();
+            let submission = submission.clone();
+            std::thread::spawn(move || {
+                let outcome = reqwest::blocking::Client::new()
+                    .post(&endpoint)
+                    .timeout(TIMEOUT)
+                    .json(&submission)
+                    .send()
+                    .and_then(reqwest::blocking::Response::error_for_status)
+                    .map(|_| ())
+                    .map_err(|error| error.to_string());
+                // The game may have moved on and stopped listening.
+                let _ = sender.send(outcome);
+            });
+            self.outcome = Some(receiver);
+        }
+
+        fn poll(&mut self) -> Option<Result<(), String>> {
+            let outcome = match self.outcome.as_ref()?.try_recv() {
+                Ok(outcome) => outcome,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => Err("the request got lost".to_string()),
+            };
+            self.outcome = None;
+            Some(outcome)
+        }
+    }
+}
+
+/// Posts submissions with the browser's `fetch`, which never blocks.
+#[cfg(all(feature = "online-leaderboard", target_arch = "wasm32"))]
+mod http {
+    use std::{cell::RefCell, rc::Rc};
+
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{Request, RequestInit, Response};
+
+    use super::{LeaderboardClient, Submission};
+
+    pub struct HttpClient {
+        endpoint: String,
+        /// Filled in when `fetch` is done. Each submission gets a fresh one, so an answer to an
+        /// earlier one can't be mistaken for the last.
+        outcome: Rc<RefCell<Option<Result<(), String>>>>,
+    }
+
+    impl HttpClient {
+        pub fn new(endpoint: &str) -> Self {
+            Self {
+                endpoint: endpoint.to_string(),
+                outcome: Rc::default(),
+            }
+        }
+    }
+
+    impl LeaderboardClient for HttpClient {
+        fn send(&mut self, submission: &Submission) {
+            self.outcome = Rc::default();
+            let outcome = Rc::clone(&self.outcome);
+            let request = match request(&self.endpoint, submission) {
+                Ok(request) => request,
+                Err(error) => {
+                    *outcome.borrow_mut() = Some(Err(error));
+                    return;
+                }
+            };
+            let window = match web_sys::window() {
+                Some(window) => window,
+                None => {
+                    *outcome.borrow_mut() = Some(Err("there is no window".to_string()));
+                    return;
+                }
+            };
+            let response = JsFuture::from(window.fetch_with_request(&request));
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = match response.await {
+                    Ok(response) => {
+                        let response: Response = response.unchecked_into();
+                        if response.ok() {
+                            Ok(())
+                        } else {
+                            Err(format!("the server answered {}", response.status()))
+                        }
+                    }
+                    Err(error) => Err(format!("{:?}", error)),
+                };
+                *outcome.borrow_mut() = Some(result);
+            });
+        }
+
+        fn poll(&mut self) -> Option<Result<(), String>> {
+            self.outcome.borrow_mut().take()
+        }
+    }
+
+    fn request(endpoint: &str, submission: &Submission) -> Result<Request, String> {
+        let body = serde_json::to_string(submission).map_err(|error| error.to_string())?;
+        let mut init = RequestInit::new();
+        init.method("POST");
+        init.body(Some(&JsValue::from_str(&body)));
+        let request = Request::new_with_str_and_init(endpoint, &init)
+            .map_err(|error| format!("{:?}", error))?;
+        request
+            .headers()
+            .set("Content-Type", "application/json")
+            .map_err(|error| format!("{:?}", error))?;
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission() -> Submission {
+        Submission {
+            initials: "ABC".to_string(),
+            points: 3150,
+            survived_seconds: 214.5,
+            seed: 7,
+            difficulty: Difficulty::Hard,
+            version: "1.0.0",
+        }
+    }
+
+    /// A leaderboard with a mock client, and what the client was sent.
+    fn leaderboard(
+        outcome: Result<(), String>,
+    ) -> (OnlineLeaderboard, Rc<RefCell<Vec<Submission>>>) {
+        let client = MockClient::new(outcome);
+        let sent = Rc::clone(&client.sent);
+        (OnlineLeaderboard::with_client(Box::new(client)), sent)
+    }
+
+    #[test]
+    fn an_accepted_run_is_submitted() {
+        let (mut leaderboard, sent) = leaderboard(Ok(()));
+        leaderboard.update();
+        assert_eq!(leaderboard.status, None);
+
+        leaderboard.submit(submission());
+        assert_eq!(leaderboard.status, Some(SubmissionStatus::Submitting));
+        assert_eq!(*sent.borrow(), vec![submission()]);
+
+        leaderboard.update();
+        assert_eq!(leaderboard.status, Some(SubmissionStatus::Submitted));
+        leaderboard.update();
+        assert_eq!(leaderboard.status, Some(SubmissionStatus::Submitted));
+    }
+
+    #[test]
+    fn a_refused_run_says_why() {
+        let (mut leaderboard, _) = leaderboard(Err("the server answered 503".to_string()));
+        leaderboard.submit(submission());
+        leaderboard.update();
+        assert_eq!(
+            leaderboard.status,
+            Some(SubmissionStatus::Failed(
+                "the server answered 503".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn a_new_run_forgets_the_last_submission() {
+        let (mut leaderboard, _) = leaderboard(Ok(()));
+        leaderboard.submit(submission());
+        leaderboard.clear();
+        leaderboard.update();
+        assert_eq!(leaderboard.status, None);
+    }
+
+    #[test]
+    fn without_an_endpoint_nothing_is_sent() {
+        let mut leaderboard = OnlineLeaderboard::default();
+        leaderboard.submit(submission());
+        leaderboard.update();
+        assert_eq!(leaderboard.status, None);
+    }
+
+    #[test]
+    fn mock_endpoints_answer_as_they_say() {
+        let mut accepting = OnlineLeaderboard::new("mock:ok");
+        accepting.submit(submission());
+        accepting.update();
+        assert_eq!(accepting.status, Some(SubmissionStatus::Submitted));
+
+        let mut refusing = OnlineLeaderboard::new("mock:closed for the night");
+        refusing.submit(submission());
+        refusing.update();
+        assert_eq!(
+            refusing.status,
+            Some(SubmissionStatus::Failed("closed for the night".to_string()))
+        );
+    }
+
+    #[test]
+    fn the_submission_is_posted_as_json() {
+        assert_eq!(
+            serde_json::to_value(submission()).unwrap(),
+            serde_json::json!({
+                "initials": "ABC",
+                "points": 3150,
+                "survived_seconds": 214.5,
+                "seed": 7,
+                "difficulty": "Hard",
+                "version": "1.0.0",
+            })
+        );
+    }
+}
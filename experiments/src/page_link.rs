@@ -0,0 +1,265 @@
+//! The line between the running game and the page hosting the web build: what the page asked for
+//! since the last frame, and how the run is going for it to read back.
+
+use std::{collections::BTreeMap, rc::Rc};
+
+#[cfg(feature = "backend-eframe")]
+use engine::input::InputMap;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+#[cfg(feature = "backend-eframe")]
+use crate::game::GameState;
+use crate::{
+    game::{CombatType, Difficulty},
+    score::Score,
+    stats::GameStats,
+};
+
+/// What the page has told about every finished run.
+pub type GameOverCallback = Rc<dyn Fn(&RunSummary)>;
+
+/// Shared between the game and the functions the page calls. The game takes the requests at the
+/// start of every frame and publishes its status at the end.
+#[derive(Default)]
+pub struct PageLink {
+    /// `Some(true)` to pause, `Some(false)` to resume.
+    #[cfg(feature = "backend-eframe")]
+    pause: Option<bool>,
+    #[cfg(feature = "backend-eframe")]
+    muted: Option<bool>,
+    #[cfg(feature = "backend-eframe")]
+    status: RunStatus,
+    /// Told about every run that ends, e.g. for the page's own leaderboard.
+    on_game_over: Option<GameOverCallback>,
+    /// Told once the game has drawn itself, e.g. to take down a loading bar.
+    #[cfg(feature = "backend-eframe")]
+    on_first_frame: Option<Box<dyn FnOnce()>>,
+    /// The player's current key bindings, so the page knows which keys are meant for the game.
+    #[cfg(feature = "backend-eframe")]
+    key_bindings: InputMap,
+}
+
+/// How the run is going, as the page sees it.
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RunStatus {
+    /// Seconds survived, which is what the high score counts.
+    pub survival_seconds: f64,
+    /// The points scored so far.
+    pub score: u64,
+    pub state: GameState,
+}
+
+impl PageLink {
+    #[cfg(all(feature = "backend-eframe", target_arch = "wasm32"))]
+    pub fn request_pause(&mut self, paused: bool) {
+        self.pause = Some(paused);
+    }
+
+    #[cfg(all(feature = "backend-eframe", target_arch = "wasm32"))]
+    pub fn request_muted(&mut self, muted: bool) {
+        self.muted = Some(muted);
+    }
+
+    #[cfg(all(feature = "backend-eframe", target_arch = "wasm32"))]
+    pub fn status(&self) -> &RunStatus {
+        &self.status
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn take_pause(&mut self) -> Option<bool> {
+        self.pause.take()
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn take_muted(&mut self) -> Option<bool> {
+        self.muted.take()
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn publish(&mut self, status: RunStatus) {
+        self.status = status;
+    }
+
+    #[cfg(all(feature = "backend-eframe", target_arch = "wasm32"))]
+    pub fn key_bindings(&self) -> &InputMap {
+        &self.key_bindings
+    }
+
+    /// Only copies the bindings when they have changed, which is rarely.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn publish_key_bindings(&mut self, key_bindings: &InputMap) {
+        if self.key_bindings != *key_bindings {
+            self.key_bindings = key_bindings.clone();
+        }
+    }
+
+    #[cfg(all(feature = "backend-eframe", target_arch = "wasm32"))]
+    pub fn set_on_game_over(&mut self, callback: impl Fn(&RunSummary) + 'static) {
+        self.on_game_over = Some(Rc::new(callback));
+    }
+
+    #[cfg(all(feature = "backend-eframe", target_arch = "wasm32"))]
+    pub fn set_on_first_frame(&mut self, callback: impl FnOnce() + 'static) {
+        self.on_first_frame = Some(Box::new(callback));
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn take_on_first_frame(&mut self) -> Option<Box<dyn FnOnce()>> {
+        self.on_first_frame.take()
+    }
+
+    /// The callback to tell about a finished run. Handed out rather than called here, so the
+    /// callback can use the link itself.
+    pub(crate) fn on_game_over(&self) -> Option<GameOverCallback> {
+        self.on_game_over.clone()
+    }
+}
+
+/// How a finished run went, for the page to record.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub survived_seconds: f64,
+    /// The points scored, like the score in `RunStatus`.
+    pub score: u64,
+    /// Enemies intercepted in each lane.
+    pub kills: BTreeMap<&'static str, usize>,
+    /// A string, since JavaScript numbers can't hold every seed.
+    pub seed: String,
+    pub difficulty: Difficulty,
+    /// The version of the game the run was played on.
+    pub version: &'static str,
+}
+
+impl RunSummary {
+    pub fn new(
+        survived_seconds: f64,
+        stats: &GameStats,
+        score: &Score,
+        seed: u64,
+        difficulty: Difficulty,
+    ) -> Self {
+        Self {
+            survived_seconds,
+            score: score.points(survived_seconds),
+            kills: CombatType::iter()
+                .map(|combat_type| (combat_type.into(), stats.enemies_destroyed(combat_type)))
+                .collect(),
+            seed: seed.to_string(),
+            difficulty,
+            version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{events::GameEvent, game::UnitId};
+
+    /// The payload as the page reads it, after the `RunSummary` interface in `wasm_startup.rs`.
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct PagePayload {
+        survived_seconds: f64,
+        score: u64,
+        kills: Kills,
+        seed: String,
+        difficulty: Difficulty,
+        version: String,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Kills {
+        #[serde(rename = "A")]
+        a: usize,
+        #[serde(rename = "B")]
+        b: usize,
+        #[serde(rename = "C")]
+        c: usize,
+        #[serde(rename = "D")]
+        d: usize,
+    }
+
+    /// Reads the payload like the page would, seed and all.
+    fn decode(json: &str) -> Result<(PagePayload, u64), String> {
+        let payload: PagePayload = serde_json::from_str(json).map_err(|error| error.to_string())?;
+        let seed = payload.seed.parse().map_err(|_| payload.seed.clone())?;
+        Ok((payload, seed))
+    }
+
+    fn summary() -> RunSummary {
+        let mut stats = GameStats::default();
+        for combat_type in [CombatType::A, CombatType::C, CombatType::C] {
+            stats.record(&GameEvent::EnemyIntercepted {
+                unit: UnitId(1),
+                combat_type,
+                is_boss: false,
+            });
+        }
+        // Beyond what a JavaScript number holds exactly.
+        RunSummary::new(
+            95.5,
+            &stats,
+            &Score::new(Difficulty::Hard),
+            u64::MAX - 1,
+            Difficulty::Hard,
+        )
+    }
+
+    #[test]
+    fn a_summary_reaches_the_page_as_it_was() {
+        let summary = summary();
+        let json = serde_json::to_string(&summary).unwrap();
+
+        let (payload, seed) = decode(&json).unwrap();
+        assert_eq!(seed, u64::MAX - 1);
+        assert_eq!(
+            payload,
+            PagePayload {
+                survived_seconds: 95.5,
+                score: summary.score,
+                kills: Kills {
+                    a: 1,
+                    b: 0,
+                    c: 2,
+                    d: 0
+                },
+                seed: seed.to_string(),
+                difficulty: Difficulty::Hard,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn a_malformed_or_tampered_payload_is_rejected() {
+        let json = serde_json::to_string(&summary()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert!(decode(&json[..json.len() - 1]).is_err());
+        assert!(decode("[]").is_err());
+
+        let tampered = |change: &dyn Fn(&mut serde_json::Value)| {
+            let mut copy = value.clone();
+            change(&mut copy);
+            decode(&copy.to_string())
+        };
+        assert!(tampered(&|payload| payload["seed"] = "1e19".into()).is_err());
+        assert!(tampered(&|payload| payload["seed"] = 5.into()).is_err());
+        assert!(tampered(&|payload| payload["difficulty"] = "Impossible".into()).is_err());
+        assert!(tampered(&|payload| payload["score"] = (-1).into()).is_err());
+        assert!(tampered(&|payload| payload["kills"]["E"] = 3.into()).is_err());
+        assert!(tampered(&|payload| payload["cheated"] = false.into()).is_err());
+        assert!(tampered(&|payload| {
+            payload.as_object_mut().unwrap().remove("version");
+        })
+        .is_err());
+        // Left as it was, it reads fine.
+        assert!(tampered(&|_| {}).is_ok());
+    }
+}
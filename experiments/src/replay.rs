@@ -0,0 +1,245 @@
+//! Runs recorded as the orders the player gave, to be watched again from the start. The enemies
+//! come from the seed and every frame plays out over the same time step it did, so the same
+//! orders make the same run. The state of the run is hashed every few seconds while recording,
+//! so playback notices when it went differently anyway.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "backend-eframe")]
+use crate::storage::{self, Storage};
+use crate::{commands::GameCommand, game::Difficulty, game_mode::GameMode, helpers::Duration};
+
+#[cfg(feature = "backend-eframe")]
+const STORAGE_KEY: &str = "replay";
+/// Replays from other versions of this layout are thrown away rather than misread.
+const SCHEMA_VERSION: u32 = 1;
+/// Seconds survived between two hashes of the run.
+const CHECKPOINT_SECONDS: f64 = 10.0;
+/// The most game time playback falls behind by before it drops some, so a slow frame doesn't
+/// make it race through the run afterwards.
+const MAX_OWED_SECONDS: f64 = 1.0;
+
+/// A run from its start, kept as what it takes to play it out again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    schema_version: u32,
+    difficulty: Difficulty,
+    /// Replays from before there were modes are of endless runs.
+    #[serde(default)]
+    mode: GameMode,
+    /// Replays from before New Game+ are of ordinary runs.
+    #[serde(default)]
+    new_game_plus: u32,
+    seed: u64,
+    /// The time step of every frame the run was played out over, as the bits of the duration.
+    frames: Vec<u64>,
+    /// Orders, with the number of frames played out before they were carried out.
+    commands: Vec<(usize, GameCommand)>,
+    /// Whether units parked themselves, from the frame it was switched on or off.
+    auto_park: Vec<(usize, bool)>,
+    /// Hashes of the run before the given frame was played out.
+    checkpoints: Vec<(usize, u64)>,
+    /// How the run ended, once it is over.
+    survived_seconds: f64,
+    points: u64,
+}
+
+impl Replay {
+    pub fn new(difficulty: Difficulty, mode: GameMode, new_game_plus: u32, seed: u64) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            difficulty,
+            mode,
+            new_game_plus,
+            seed,
+            frames: Vec::new(),
+            commands: Vec::new(),
+            auto_park: Vec::new(),
+            checkpoints: Vec::new(),
+            survived_seconds: 0.0,
+            points: 0,
+        }
+    }
+
+    /// The replay saved last, if there is one from this version.
+    #[cfg(feature = "backend-eframe")]
+    pub fn load(storage: &dyn Storage) -> Option<Self> {
+        storage::load::<Option<Self>>(storage, STORAGE_KEY)
+            .filter(|replay| replay.schema_version == SCHEMA_VERSION)
+    }
+
+    /// Keeps the replay, replacing the one saved before.
+    #[cfg(feature = "backend-eframe")]
+    pub fn save(&self, storage: &mut dyn Storage) {
+        storage::save(storage, STORAGE_KEY, &Some(self));
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn new_game_plus(&self) -> u32 {
+        self.new_game_plus
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Seconds survived in the recorded run, once it is over.
+    #[cfg(feature = "backend-eframe")]
+    pub fn survived_seconds(&self) -> f64 {
+        self.survived_seconds
+    }
+
+    /// Whether the recorded run is over, which is when it is worth keeping.
+    #[cfg(feature = "backend-eframe")]
+    pub fn is_finished(&self) -> bool {
+        self.survived_seconds > 0.0
+    }
+
+    /// Whether the run is due for its next hash, `play_seconds` into it.
+    pub fn checkpoint_due(&self, play_seconds: f64) -> bool {
+        play_seconds >= (self.checkpoints.len() + 1) as f64 * CHECKPOINT_SECONDS
+    }
+
+    /// Records the hash of the run as it is before the next frame.
+    pub fn record_checkpoint(&mut self, fingerprint: u64) {
+        self.checkpoints.push((self.frames.len(), fingerprint));
+    }
+
+    /// Records the next frame, played out over `delta` of game time.
+    pub fn record_frame(&mut self, delta: Duration, auto_park: bool) {
+        if self.auto_park.last().map(|&(_, on)| on) != Some(auto_park) {
+            self.auto_park.push((self.frames.len(), auto_park));
+        }
+        self.frames.push(delta.to_bits());
+    }
+
+    /// Records orders carried out after the frames recorded so far.
+    #[cfg(feature = "backend-eframe")]
+    pub fn record_commands(&mut self, commands: &[GameCommand]) {
+        let frame = self.frames.len();
+        self.commands
+            .extend(commands.iter().map(|&command| (frame, command)));
+    }
+
+    pub fn finish(&mut self, survived_seconds: f64, points: u64) {
+        self.survived_seconds = survived_seconds;
+        self.points = points;
+    }
+}
+
+/// Plays a replay back frame by frame, as fast as the game speed says.
+pub struct ReplayPlayer {
+    replay: Replay,
+    /// Frames played out so far.
+    frame: usize,
+    /// The next of the replay's commands to carry out.
+    next_command: usize,
+    /// Game time that passed without frames of the replay played out for it yet.
+    owed: Duration,
+    /// The run went differently from the recording at some point.
+    diverged: bool,
+}
+
+impl ReplayPlayer {
+    #[cfg(feature = "backend-eframe")]
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            frame: 0,
+            next_command: 0,
+            owed: Duration::default(),
+            diverged: false,
+        }
+    }
+
+    /// Adds game time that passed, to be played out in the replay's own frames.
+    pub fn owe(&mut self, delta: Duration) {
+        let max_owed = Duration::from_secs_f64(MAX_OWED_SECONDS);
+        self.owed = if self.owed + delta > max_owed {
+            max_owed
+        } else {
+            self.owed + delta
+        };
+    }
+
+    /// The time step of the next frame, once enough game time is owed to play it out.
+    pub fn next_delta(&self) -> Option<Duration> {
+        let delta = Duration::from_bits(*self.replay.frames.get(self.frame)?);
+        if delta > self.owed {
+            return None;
+        }
+
+        Some(delta)
+    }
+
+    /// How far the game time owed is into the next frame, from 0 to 1, for drawing the run part
+    /// of the way there.
+    pub fn alpha(&self) -> f32 {
+        let next = match self.replay.frames.get(self.frame) {
+            Some(&bits) => Duration::from_bits(bits).as_secs_f64(),
+            None => return 1.0,
+        };
+        if next <= 0.0 {
+            return 1.0;
+        }
+
+        (self.owed.as_secs_f64() / next).clamp(0.0, 1.0) as f32
+    }
+
+    /// Whether units parked themselves in the next frame.
+    pub fn auto_park(&self) -> bool {
+        self.replay
+            .auto_park
+            .iter()
+            .take_while(|&&(frame, _)| frame <= self.frame)
+            .last()
+            .is_some_and(|&(_, on)| on)
+    }
+
+    /// The hash the run had before the next frame when it was recorded, if there is one.
+    pub fn checkpoint(&self) -> Option<u64> {
+        self.replay
+            .checkpoints
+            .iter()
+            .find(|&&(frame, _)| frame == self.frame)
+            .map(|&(_, fingerprint)| fingerprint)
+    }
+
+    /// Counts the next frame as played out, and hands back the orders that were carried out
+    /// after it.
+    pub fn finish_frame(&mut self) -> Vec<GameCommand> {
+        if let Some(delta) = self.next_delta() {
+            self.owed -= delta;
+        }
+        self.frame += 1;
+
+        let commands: Vec<_> = self.replay.commands[self.next_command..]
+            .iter()
+            .take_while(|&&(frame, _)| frame <= self.frame)
+            .map(|&(_, command)| command)
+            .collect();
+        self.next_command += commands.len();
+        commands
+    }
+
+    /// Notes that the run went differently from the recording. Returns whether that is news.
+    pub fn diverge(&mut self) -> bool {
+        !std::mem::replace(&mut self.diverged, true)
+    }
+
+    /// Whether the run ended on the points it was recorded with.
+    pub fn matches_result(&self, points: u64) -> bool {
+        points == self.replay.points
+    }
+}
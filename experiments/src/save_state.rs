@@ -0,0 +1,671 @@
+//! The run in progress, saved every few seconds so reloading the page or closing the game by
+//! accident doesn't lose it, and into named slots whenever the player saves it themselves. Only
+//! what decides how the run goes on is kept; the battle log, alerts and toasts start out empty
+//! again.
+
+use std::borrow::Cow;
+#[cfg(feature = "backend-eframe")]
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "backend-eframe")]
+use crate::balance::GameConfig;
+#[cfg(feature = "backend-eframe")]
+use crate::game::Difficulty;
+use crate::{
+    cheats::CheatState,
+    daily::DailyRun,
+    game::{
+        CombatType, Enemy, EnemyId, EnemySpawner, Health, ParkingSpace, PlayTime, SpawnerSnapshot,
+        TokenPool, Unit, UnitAction, UnitBundle, UnitId,
+    },
+    helpers::{fnv1a, Instant, Timer},
+    score::Score,
+    stats::GameStats,
+    storage::{self, Storage},
+};
+
+/// Saves from other versions of this layout are thrown away rather than misread.
+const SCHEMA_VERSION: u32 = 2;
+/// Autosaves older than this aren't offered any more.
+const MAX_AGE_SECONDS: f64 = 60.0 * 60.0;
+/// How many runs the player can keep at once.
+#[cfg(feature = "backend-eframe")]
+pub const NAMED_SLOTS: usize = 5;
+/// Where the only save the player could keep was before there were slots.
+#[cfg(feature = "backend-eframe")]
+const LEGACY_MANUAL_KEY: &str = "manual_save";
+
+/// Where a run is kept. Each holds one run at most, under its own storage key, so one that
+/// can't be read doesn't take the others with it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SaveSlot {
+    /// Saved every few seconds, and only offered for an hour.
+    Autosave,
+    /// Saved when the player asks, and kept until they save over it or delete it. Numbered
+    /// from 0 up to `NAMED_SLOTS`.
+    #[cfg(feature = "backend-eframe")]
+    Named(usize),
+}
+
+impl SaveSlot {
+    fn storage_key(self) -> Cow<'static, str> {
+        match self {
+            Self::Autosave => Cow::Borrowed("saved_run"),
+            #[cfg(feature = "backend-eframe")]
+            Self::Named(index) => Cow::Owned(format!("save_slot_{}", index)),
+        }
+    }
+}
+
+/// A run as it was when it was saved.
+#[derive(Serialize, Deserialize)]
+pub struct SavedRun {
+    schema_version: u32,
+    /// What the player called the save. Autosaves have no name.
+    #[serde(default)]
+    name: String,
+    /// Milliseconds since the Unix epoch.
+    saved_at: f64,
+    play_time: PlayTime,
+    enemy_spawner: SpawnerSnapshot,
+    parking_capacity: usize,
+    /// Parking slots including those beyond capacity that were still held, e.g. after a boss
+    /// wrecked a space with a unit in it.
+    parking_slots: usize,
+    units: Vec<SavedUnit>,
+    enemies: Vec<SavedEnemy>,
+    stats: GameStats,
+    /// Saves from before there were points start over at none.
+    #[serde(default)]
+    score: Score,
+    time_scale: f64,
+    /// Saves from before there was a sandbox never used cheats.
+    #[serde(default)]
+    cheats: CheatState,
+    /// Saves from before the daily challenge weren't of one.
+    #[serde(default)]
+    daily: Option<DailyRun>,
+}
+
+/// A unit, with the parking space it holds as a slot number.
+#[derive(Serialize, Deserialize)]
+struct SavedUnit {
+    id: UnitId,
+    state: SavedUnitState,
+    health: Health,
+    orders: Vec<UnitAction>,
+}
+
+/// `Unit` with slot numbers for tokens.
+#[derive(Serialize, Deserialize)]
+enum SavedUnitState {
+    InStorage,
+    UnStoring(Timer, usize),
+    ParkedUnready(usize),
+    ParkedPreparing(Timer, usize, CombatType),
+    ParkedReady(usize, CombatType),
+    Patrolling(Timer, CombatType),
+    Returning(Timer, CombatType),
+    WaitingToPark,
+    Storing(Timer),
+    Parking(Timer, usize),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedEnemy {
+    id: EnemyId,
+    progress: Timer,
+    combat_type: CombatType,
+    is_boss: bool,
+}
+
+/// Why a save can't be picked up again.
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreError {
+    /// More units hold parking spaces than there were spaces.
+    TooManyParked { parked: usize, slots: usize },
+    /// A unit holds a parking space that didn't exist.
+    NoSuchSlot { slot: usize, slots: usize },
+    /// Two units hold the same parking space.
+    SlotShared { slot: usize },
+    /// Nothing that makes sense as a save of this version is stored there.
+    Unreadable,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl Display for RestoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyParked { parked, slots } => write!(
+                f,
+                "{} units hold a parking space, but there are only {}",
+                parked, slots
+            ),
+            Self::NoSuchSlot { slot, slots } => write!(
+                f,
+                "a unit holds parking space {}, but there are only {}",
+                slot + 1,
+                slots
+            ),
+            Self::SlotShared { slot } => {
+                write!(f, "two units hold parking space {}", slot + 1)
+            }
+            Self::Unreadable => write!(f, "the save is damaged or from another version"),
+        }
+    }
+}
+
+/// What the game takes over from a save.
+#[cfg(feature = "backend-eframe")]
+pub struct RestoredRun {
+    pub play_time: PlayTime,
+    pub enemy_spawner: EnemySpawner,
+    pub parking_spaces: TokenPool<ParkingSpace>,
+    pub units: Vec<UnitBundle>,
+    pub enemies: Vec<Enemy>,
+    pub stats: GameStats,
+    pub score: Score,
+    pub time_scale: f64,
+    pub cheats: CheatState,
+    pub daily: Option<DailyRun>,
+}
+
+/// The run as the game has it, to be saved.
+pub struct LiveRun<'a> {
+    pub play_time: &'a PlayTime,
+    pub enemy_spawner: &'a EnemySpawner,
+    pub parking_spaces: &'a TokenPool<ParkingSpace>,
+    pub units: &'a [UnitBundle],
+    pub enemies: &'a [Enemy],
+    pub stats: &'a GameStats,
+    pub score: &'a Score,
+    pub time_scale: f64,
+    pub cheats: CheatState,
+    pub daily: Option<&'a DailyRun>,
+}
+
+impl SavedRun {
+    pub fn capture(run: &LiveRun<'_>) -> Self {
+        let LiveRun {
+            play_time,
+            enemy_spawner,
+            parking_spaces,
+            units,
+            enemies,
+            stats,
+            score,
+            time_scale,
+            cheats,
+            daily,
+        } = *run;
+        Self {
+            schema_version: SCHEMA_VERSION,
+            name: String::new(),
+            saved_at: Instant::now().millis_since_epoch(),
+            play_time: play_time.clone(),
+            enemy_spawner: enemy_spawner.snapshot(),
+            parking_capacity: parking_spaces.capacity(),
+            parking_slots: parking_spaces.slot_count(),
+            units: units.iter().map(SavedUnit::capture).collect(),
+            enemies: enemies
+                .iter()
+                .map(|enemy| SavedEnemy {
+                    id: enemy.id,
+                    progress: enemy.progress.clone(),
+                    combat_type: enemy.combat_type,
+                    is_boss: enemy.is_boss,
+                })
+                .collect(),
+            stats: stats.clone(),
+            score: score.clone(),
+            time_scale,
+            cheats,
+            daily: daily.cloned(),
+        }
+    }
+
+    /// The run saved in `slot`, if there is one from this version. Autosaves must be recent
+    /// enough, too.
+    pub fn load(storage: &dyn Storage, slot: SaveSlot) -> Option<Self> {
+        let saved: Self = storage::load::<Option<Self>>(storage, &slot.storage_key())?;
+        let age_seconds =
+            Instant::now().seconds_since(Instant::from_millis_since_epoch(saved.saved_at));
+        let expired = slot == SaveSlot::Autosave && !(0.0..MAX_AGE_SECONDS).contains(&age_seconds);
+        if saved.schema_version != SCHEMA_VERSION || expired {
+            return None;
+        }
+
+        Some(saved)
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn save(&self, storage: &mut dyn Storage, slot: SaveSlot) {
+        storage::save(storage, &slot.storage_key(), &Some(self));
+    }
+
+    /// Forgets the saved run, once it is over or the player chose to start a new one.
+    pub fn clear(storage: &mut dyn Storage, slot: SaveSlot) {
+        storage::save(storage, &slot.storage_key(), &None::<Self>);
+    }
+
+    /// Seconds survived when the run was saved.
+    #[cfg(feature = "backend-eframe")]
+    pub fn play_seconds(&self) -> f64 {
+        self.play_time.seconds()
+    }
+
+    /// A hash of everything that decides how the run goes on, to tell cheaply whether two runs
+    /// got to the same state. When the run was saved and how fast it was running don't count,
+    /// and neither do the cheats, whose effects show in the rest.
+    pub fn fingerprint(&self) -> u64 {
+        let state = (
+            &self.play_time,
+            &self.enemy_spawner,
+            self.parking_capacity,
+            self.parking_slots,
+            &self.units,
+            &self.enemies,
+            &self.stats,
+            &self.score,
+        );
+        fnv1a(serde_json::to_string(&state).unwrap_or_default().as_bytes())
+    }
+
+    /// Rebuilds the run, with every parking space taken by the unit that held it. Fails for
+    /// saves that don't add up, e.g. two units in one space. The enemies to come follow
+    /// `config`, the balance the game has now.
+    #[cfg(feature = "backend-eframe")]
+    pub fn restore(self, config: &GameConfig) -> Result<RestoredRun, RestoreError> {
+        let parked = self
+            .units
+            .iter()
+            .filter(|unit| unit.state.parking_slot().is_some())
+            .count();
+        if parked > self.parking_slots {
+            return Err(RestoreError::TooManyParked {
+                parked,
+                slots: self.parking_slots,
+            });
+        }
+
+        let mut parking_spaces = TokenPool::new(self.parking_capacity);
+        let slots = self.parking_slots;
+        let units = self
+            .units
+            .into_iter()
+            .map(|unit| unit.restore(&mut parking_spaces, slots))
+            .collect::<Result<Vec<_>, _>>()?;
+        let enemies = self
+            .enemies
+            .into_iter()
+            .map(|enemy| Enemy {
+                id: enemy.id,
+                progress: enemy.progress,
+                combat_type: enemy.combat_type,
+                is_boss: enemy.is_boss,
+            })
+            .collect();
+
+        Ok(RestoredRun {
+            play_time: self.play_time,
+            enemy_spawner: EnemySpawner::from_snapshot(self.enemy_spawner, config),
+            parking_spaces,
+            units,
+            enemies,
+            stats: self.stats,
+            score: self.score,
+            time_scale: self.time_scale,
+            cheats: self.cheats,
+            daily: self.daily,
+        })
+    }
+}
+
+/// What the save dialog shows of a run in a slot, without reading the whole run.
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotSummary {
+    pub name: String,
+    /// Milliseconds since the Unix epoch.
+    saved_at: f64,
+    pub play_seconds: f64,
+    pub difficulty: Difficulty,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl SlotSummary {
+    fn of(run: &SavedRun) -> Self {
+        Self {
+            name: run.name.clone(),
+            saved_at: run.saved_at,
+            play_seconds: run.play_seconds(),
+            difficulty: run.enemy_spawner.difficulty(),
+        }
+    }
+
+    /// When the run was saved, to the minute in UTC.
+    pub fn saved_at(&self) -> String {
+        Instant::from_millis_since_epoch(self.saved_at).utc_date_time()
+    }
+}
+
+/// The runs the player saved themselves. Each is kept in a slot of its own; an index of what
+/// is in them is kept alongside, so the save dialog doesn't have to read every run. The index
+/// is rebuilt from the slots when it is missing or damaged.
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSlots {
+    /// One entry per slot, `None` for empty ones.
+    slots: Vec<Option<SlotSummary>>,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl Default for SaveSlots {
+    fn default() -> Self {
+        Self {
+            slots: vec![None; NAMED_SLOTS],
+        }
+    }
+}
+
+#[cfg(feature = "backend-eframe")]
+impl SaveSlots {
+    const INDEX_KEY: &'static str = "save_slots";
+
+    pub fn load(storage: &mut dyn Storage) -> Self {
+        let slots: Vec<Option<SlotSummary>> = storage::load(storage, Self::INDEX_KEY);
+        if slots.len() == NAMED_SLOTS {
+            return Self { slots };
+        }
+
+        let mut rebuilt = Self {
+            slots: (0..NAMED_SLOTS)
+                .map(|index| SavedRun::load(storage, SaveSlot::Named(index)))
+                .map(|run| run.as_ref().map(SlotSummary::of))
+                .collect(),
+        };
+        rebuilt.adopt_legacy_save(storage);
+        storage::save(storage, Self::INDEX_KEY, &rebuilt.slots);
+        rebuilt
+    }
+
+    /// Moves the one save from before there were slots into the first free slot.
+    fn adopt_legacy_save(&mut self, storage: &mut dyn Storage) {
+        let legacy: Option<SavedRun> = storage::load(storage, LEGACY_MANUAL_KEY);
+        let (mut run, index) = match (legacy, self.first_free()) {
+            (Some(run), Some(index)) if run.schema_version == SCHEMA_VERSION => (run, index),
+            _ => return,
+        };
+
+        run.name = format!("{:.0}s", run.play_seconds());
+        run.save(storage, SaveSlot::Named(index));
+        self.slots[index] = Some(SlotSummary::of(&run));
+        storage::save(storage, LEGACY_MANUAL_KEY, &None::<SavedRun>);
+    }
+
+    pub fn slots(&self) -> &[Option<SlotSummary>] {
+        &self.slots
+    }
+
+    pub fn get(&self, index: usize) -> Option<&SlotSummary> {
+        self.slots.get(index)?.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    fn first_free(&self) -> Option<usize> {
+        self.slots.iter().position(Option::is_none)
+    }
+
+    /// Keeps `run` in the slot under `name`, replacing what was there.
+    pub fn save(&mut self, storage: &mut dyn Storage, index: usize, name: &str, mut run: SavedRun) {
+        if index >= NAMED_SLOTS {
+            return;
+        }
+
+        run.name = name.to_string();
+        run.save(storage, SaveSlot::Named(index));
+        self.slots[index] = Some(SlotSummary::of(&run));
+        storage::save(storage, Self::INDEX_KEY, &self.slots);
+    }
+
+    /// The run in the slot. Fails if the slot is empty or can't be read, which leaves the
+    /// others alone.
+    pub fn load_run(&self, storage: &dyn Storage, index: usize) -> Result<SavedRun, RestoreError> {
+        if self.get(index).is_none() {
+            return Err(RestoreError::Unreadable);
+        }
+
+        SavedRun::load(storage, SaveSlot::Named(index)).ok_or(RestoreError::Unreadable)
+    }
+
+    pub fn delete(&mut self, storage: &mut dyn Storage, index: usize) {
+        if index >= NAMED_SLOTS {
+            return;
+        }
+
+        SavedRun::clear(storage, SaveSlot::Named(index));
+        self.slots[index] = None;
+        storage::save(storage, Self::INDEX_KEY, &self.slots);
+    }
+}
+
+impl SavedUnit {
+    fn capture(bundle: &UnitBundle) -> Self {
+        let state = match &bundle.unit {
+            Unit::InStorage => SavedUnitState::InStorage,
+            Unit::UnStoring(timer, token) => SavedUnitState::UnStoring(timer.clone(), token.slot()),
+            Unit::ParkedUnready(token) => SavedUnitState::ParkedUnready(token.slot()),
+            Unit::ParkedPreparing(timer, token, combat_type) => {
+                SavedUnitState::ParkedPreparing(timer.clone(), token.slot(), *combat_type)
+            }
+            Unit::ParkedReady(token, combat_type) => {
+                SavedUnitState::ParkedReady(token.slot(), *combat_type)
+            }
+            Unit::Patrolling(timer, combat_type) => {
+                SavedUnitState::Patrolling(timer.clone(), *combat_type)
+            }
+            Unit::Returning(timer, combat_type) => {
+                SavedUnitState::Returning(timer.clone(), *combat_type)
+            }
+            Unit::WaitingToPark => SavedUnitState::WaitingToPark,
+            Unit::Storing(timer) => SavedUnitState::Storing(timer.clone()),
+            Unit::Parking(timer, token) => SavedUnitState::Parking(timer.clone(), token.slot()),
+        };
+
+        Self {
+            id: bundle.id,
+            state,
+            health: bundle.health,
+            orders: bundle.orders.iter().copied().collect(),
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    fn restore(
+        self,
+        parking_spaces: &mut TokenPool<ParkingSpace>,
+        slots: usize,
+    ) -> Result<UnitBundle, RestoreError> {
+        let mut take = |slot: usize| {
+            if slot >= slots {
+                return Err(RestoreError::NoSuchSlot { slot, slots });
+            }
+            parking_spaces
+                .restore_slot(slot)
+                .ok_or(RestoreError::SlotShared { slot })
+        };
+
+        let unit = match self.state {
+            SavedUnitState::InStorage => Unit::InStorage,
+            SavedUnitState::UnStoring(timer, slot) => Unit::UnStoring(timer, take(slot)?),
+            SavedUnitState::ParkedUnready(slot) => Unit::ParkedUnready(take(slot)?),
+            SavedUnitState::ParkedPreparing(timer, slot, combat_type) => {
+                Unit::ParkedPreparing(timer, take(slot)?, combat_type)
+            }
+            SavedUnitState::ParkedReady(slot, combat_type) => {
+                Unit::ParkedReady(take(slot)?, combat_type)
+            }
+            SavedUnitState::Patrolling(timer, combat_type) => Unit::Patrolling(timer, combat_type),
+            SavedUnitState::Returning(timer, combat_type) => Unit::Returning(timer, combat_type),
+            SavedUnitState::WaitingToPark => Unit::WaitingToPark,
+            SavedUnitState::Storing(timer) => Unit::Storing(timer),
+            SavedUnitState::Parking(timer, slot) => Unit::Parking(timer, take(slot)?),
+        };
+
+        Ok(UnitBundle {
+            id: self.id,
+            unit,
+            health: self.health,
+            orders: self.orders.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(feature = "backend-eframe")]
+impl SavedUnitState {
+    /// The parking space the unit holds, if any.
+    fn parking_slot(&self) -> Option<usize> {
+        match *self {
+            Self::UnStoring(_, slot)
+            | Self::ParkedUnready(slot)
+            | Self::ParkedPreparing(_, slot, _)
+            | Self::ParkedReady(slot, _)
+            | Self::Parking(_, slot) => Some(slot),
+            Self::InStorage
+            | Self::Patrolling(..)
+            | Self::Returning(..)
+            | Self::WaitingToPark
+            | Self::Storing(_) => None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+    use crate::{game_mode::GameMode, storage::MemoryStorage};
+
+    /// A fresh run on `difficulty`, which is what tells the runs in these tests apart.
+    fn run(difficulty: Difficulty) -> SavedRun {
+        let config = GameConfig::built_in();
+        SavedRun::capture(&LiveRun {
+            play_time: &PlayTime::default(),
+            enemy_spawner: &EnemySpawner::with_seed(difficulty, GameMode::default(), 0, 1, &config),
+            parking_spaces: &TokenPool::new(2),
+            units: &[],
+            enemies: &[],
+            stats: &GameStats::default(),
+            score: &Score::default(),
+            time_scale: 1.0,
+            cheats: CheatState::default(),
+            daily: None,
+        })
+    }
+
+    #[test]
+    fn saved_runs_are_listed_in_their_slots() {
+        let mut storage = MemoryStorage::default();
+        let mut slots = SaveSlots::load(&mut storage);
+        assert!(slots.is_empty());
+        assert_eq!(slots.slots().len(), NAMED_SLOTS);
+
+        slots.save(&mut storage, 2, "before the boss", run(Difficulty::Hard));
+        let summary = slots.get(2).unwrap();
+        assert_eq!(summary.name, "before the boss");
+        assert_eq!(summary.difficulty, Difficulty::Hard);
+        assert!(slots.get(0).is_none());
+
+        // The index is kept, so the dialog lists the same without reading the runs. JSON may
+        // round the time of saving off by a bit, so that is left out.
+        let listed = |slots: &SaveSlots| {
+            slots
+                .slots()
+                .iter()
+                .map(|slot| {
+                    slot.as_ref()
+                        .map(|summary| (summary.name.clone(), summary.difficulty))
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(listed(&SaveSlots::load(&mut storage)), listed(&slots));
+        let loaded = slots.load_run(&storage, 2).unwrap();
+        assert_eq!(loaded.name, "before the boss");
+        assert_eq!(loaded.enemy_spawner.difficulty(), Difficulty::Hard);
+    }
+
+    #[test]
+    fn slots_can_be_saved_over_and_deleted() {
+        let mut storage = MemoryStorage::default();
+        let mut slots = SaveSlots::load(&mut storage);
+        slots.save(&mut storage, 0, "first", run(Difficulty::Easy));
+        slots.save(&mut storage, 0, "second", run(Difficulty::Hard));
+        assert_eq!(slots.get(0).unwrap().name, "second");
+        assert_eq!(
+            slots
+                .load_run(&storage, 0)
+                .unwrap()
+                .enemy_spawner
+                .difficulty(),
+            Difficulty::Hard
+        );
+
+        slots.delete(&mut storage, 0);
+        assert!(slots.is_empty());
+        assert_eq!(
+            slots.load_run(&storage, 0).err(),
+            Some(RestoreError::Unreadable)
+        );
+        assert!(SaveSlots::load(&mut storage).is_empty());
+
+        // Slots that don't exist are left alone.
+        slots.save(&mut storage, NAMED_SLOTS, "nowhere", run(Difficulty::Easy));
+        slots.delete(&mut storage, NAMED_SLOTS);
+        assert!(slots.is_empty());
+    }
+
+    #[test]
+    fn a_damaged_slot_leaves_the_others_alone() {
+        let mut storage = MemoryStorage::default();
+        let mut slots = SaveSlots::load(&mut storage);
+        slots.save(&mut storage, 0, "damaged", run(Difficulty::Easy));
+        slots.save(&mut storage, 1, "intact", run(Difficulty::Normal));
+        storage.0.insert(
+            SaveSlot::Named(0).storage_key().into_owned(),
+            "{ not a run".to_string(),
+        );
+
+        assert_eq!(
+            slots.load_run(&storage, 0).err(),
+            Some(RestoreError::Unreadable)
+        );
+        assert_eq!(slots.load_run(&storage, 1).unwrap().name, "intact");
+
+        // Without an index, it is rebuilt from the slots that can still be read.
+        storage.0.remove(SaveSlots::INDEX_KEY);
+        let rebuilt = SaveSlots::load(&mut storage);
+        assert!(rebuilt.get(0).is_none());
+        assert_eq!(rebuilt.get(1).unwrap().name, "intact");
+    }
+
+    #[test]
+    fn the_save_from_before_slots_moves_into_the_first_free_one() {
+        let mut storage = MemoryStorage::default();
+        storage::save(
+            &mut storage,
+            LEGACY_MANUAL_KEY,
+            &Some(run(Difficulty::Hard)),
+        );
+
+        let slots = SaveSlots::load(&mut storage);
+        assert_eq!(slots.get(0).unwrap().difficulty, Difficulty::Hard);
+        assert!(slots.load_run(&storage, 0).is_ok());
+        let legacy: Option<SavedRun> = storage::load(&storage, LEGACY_MANUAL_KEY);
+        assert!(legacy.is_none());
+    }
+}
@@ -0,0 +1,425 @@
+//! Scenarios: set pieces played instead of the procedural game, each with its own units, parking
+//! spaces and enemies. They are RON files in `assets/scenarios`, built into the game. The enemies
+//! come when the file's script says, messages show from set times on, and the scenario is won by
+//! reaching its goal. As in any run, an enemy reaching the base loses it.
+
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    events::GameEvent,
+    game::{
+        CombatType, Difficulty, Enemy, EnemySpawner, GameState, Health, ParkingSpace, PlayTime,
+        TokenPool, Unit, UnitBundle, UnitId,
+    },
+};
+
+/// The scenarios built into the game, by file name, in the order the menu lists them.
+const BUILT_IN: &[(&str, &str)] = &[
+    (
+        "tutorial.ron",
+        include_str!("../assets/scenarios/tutorial.ron"),
+    ),
+    (
+        "heavy_armor_drill.ron",
+        include_str!("../assets/scenarios/heavy_armor_drill.ron"),
+    ),
+    (
+        "swarm_defense.ron",
+        include_str!("../assets/scenarios/swarm_defense.ron"),
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    pub description: String,
+    /// How fast the enemies run and how long repairs and preparing take. Normal if left out.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub units: Vec<UnitGroup>,
+    pub parking_spaces: usize,
+    /// In the order they come once the scenario is read.
+    pub spawns: Vec<ScriptedEnemy>,
+    #[serde(default)]
+    pub messages: Vec<ScriptedMessage>,
+    pub goal: Goal,
+}
+
+/// Units that start out alike. They are numbered in the order the groups are given.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnitGroup {
+    pub count: u32,
+    /// From just above 0 to 1, fully repaired, which is what it is if left out.
+    #[serde(default = "full_health")]
+    pub health: f64,
+    /// Units ready for a lane start out parked and ready to take off; the others start in
+    /// storage.
+    #[serde(default)]
+    pub ready_for: Option<CombatType>,
+}
+
+fn full_health() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptedEnemy {
+    pub at_seconds: f64,
+    pub lane: CombatType,
+    #[serde(default)]
+    pub boss: bool,
+}
+
+/// Shown from `at_seconds` on, until the next message is due.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptedMessage {
+    pub at_seconds: f64,
+    pub text: String,
+}
+
+/// What it takes to win.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+pub enum Goal {
+    /// Hold out this many seconds.
+    SurviveSeconds(f64),
+    /// Stop every enemy of the script. A boss that breaks through fails the scenario.
+    InterceptAll,
+}
+
+/// Why a scenario can't be played. Syntax errors come with the line and column the parser got
+/// to; the others name the entry at fault, like `spawns[3]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioError {
+    pub file: String,
+    pub message: String,
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+/// The scenarios built into the game. One that can't be read, which is a bug, is logged and
+/// left out.
+pub fn built_in() -> Vec<Scenario> {
+    BUILT_IN
+        .iter()
+        .filter_map(|(file, text)| match Scenario::from_ron(text, file) {
+            Ok(scenario) => Some(scenario),
+            Err(error) => {
+                log::error!("{}", error);
+                None
+            }
+        })
+        .collect()
+}
+
+impl Scenario {
+    /// Reads a scenario from the text of a RON file. `file` names it in errors.
+    pub fn from_ron(text: &str, file: &str) -> Result<Self, ScenarioError> {
+        let error = |message: String| ScenarioError {
+            file: file.to_string(),
+            message,
+        };
+        let mut scenario: Self =
+            ron::de::from_str(text).map_err(|parse_error| error(parse_error.to_string()))?;
+        scenario.validate().map_err(error)?;
+
+        // Stable, so enemies given for the same time come in the order they are written.
+        scenario
+            .spawns
+            .sort_by(|a, b| a.at_seconds.partial_cmp(&b.at_seconds).unwrap());
+        scenario
+            .messages
+            .sort_by(|a, b| a.at_seconds.partial_cmp(&b.at_seconds).unwrap());
+        Ok(scenario)
+    }
+
+    /// What has to hold for the scenario to be playable, and winnable at all.
+    fn validate(&self) -> Result<(), String> {
+        let time = |entry: String, seconds: f64| {
+            if seconds.is_finite() && seconds >= 0.0 {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{}: at_seconds must be 0 or more, not {}",
+                    entry, seconds
+                ))
+            }
+        };
+
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        if self.parking_spaces == 0 {
+            return Err("parking_spaces must be at least 1".to_string());
+        }
+        if self.units.iter().map(|group| group.count).sum::<u32>() == 0 {
+            return Err("units must hold at least one unit".to_string());
+        }
+        for (index, group) in self.units.iter().enumerate() {
+            if group.count == 0 {
+                return Err(format!("units[{}]: count must be at least 1", index));
+            }
+            if !(group.health > 0.0 && group.health <= 1.0) {
+                return Err(format!(
+                    "units[{}]: health must be more than 0 and at most 1, not {}",
+                    index, group.health
+                ));
+            }
+        }
+        let ready: u32 = self
+            .units
+            .iter()
+            .filter(|group| group.ready_for.is_some())
+            .map(|group| group.count)
+            .sum();
+        if ready as usize > self.parking_spaces {
+            return Err(format!(
+                "{} units start out parked, but there are only {} parking spaces",
+                ready, self.parking_spaces
+            ));
+        }
+
+        for (index, spawn) in self.spawns.iter().enumerate() {
+            time(format!("spawns[{}]", index), spawn.at_seconds)?;
+        }
+        for (index, message) in self.messages.iter().enumerate() {
+            time(format!("messages[{}]", index), message.at_seconds)?;
+            if message.text.trim().is_empty() {
+                return Err(format!("messages[{}]: text must not be empty", index));
+            }
+        }
+
+        match self.goal {
+            Goal::SurviveSeconds(seconds) => {
+                if !(seconds.is_finite() && seconds > 0.0) {
+                    return Err(format!(
+                        "goal: SurviveSeconds must be more than 0, not {}",
+                        seconds
+                    ));
+                }
+                if let Some((index, spawn)) = self
+                    .spawns
+                    .iter()
+                    .enumerate()
+                    .find(|(_, spawn)| spawn.at_seconds >= seconds)
+                {
+                    return Err(format!(
+                        "spawns[{}] comes at {}s, but the scenario is won at {}s",
+                        index, spawn.at_seconds, seconds
+                    ));
+                }
+            }
+            Goal::InterceptAll => {
+                if self.spawns.is_empty() {
+                    return Err("goal: InterceptAll needs spawns to intercept".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The units and parking spaces the scenario starts with.
+    pub fn setup(&self) -> (Vec<UnitBundle>, TokenPool<ParkingSpace>) {
+        let mut parking_spaces = TokenPool::new(self.parking_spaces);
+        let mut units = Vec::new();
+        for group in &self.units {
+            for _ in 0..group.count {
+                // Validation made sure there are parking spaces for every unit that is ready.
+                let unit = match (group.ready_for, parking_spaces.try_take()) {
+                    (Some(combat_type), Some(parking_space)) => {
+                        Unit::ParkedReady(parking_space, combat_type)
+                    }
+                    _ => Unit::InStorage,
+                };
+                units.push(UnitBundle {
+                    id: UnitId(units.len() as u32 + 1),
+                    unit,
+                    health: Health::new(group.health),
+                    orders: VecDeque::new(),
+                });
+            }
+        }
+        (units, parking_spaces)
+    }
+}
+
+/// A scenario being played, and how far its script has got.
+#[derive(Debug, Clone)]
+pub struct ScenarioRun {
+    /// Where it is among the scenarios on offer, to play it again.
+    #[cfg(feature = "backend-eframe")]
+    index: usize,
+    scenario: Scenario,
+    /// How many of the script's enemies were sent.
+    spawned: usize,
+    boss_leaked: bool,
+}
+
+impl ScenarioRun {
+    #[cfg(feature = "backend-eframe")]
+    pub fn new(index: usize, scenario: Scenario) -> Self {
+        Self {
+            index,
+            scenario,
+            spawned: 0,
+            boss_leaked: false,
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    /// Sends the enemies of the script that are due by now, in place of the spawner's own.
+    pub fn spawn_due(
+        &mut self,
+        play_time: &PlayTime,
+        enemy_spawner: &mut EnemySpawner,
+        enemies: &mut Vec<Enemy>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        while let Some(spawn) = self.scenario.spawns.get(self.spawned) {
+            if spawn.at_seconds > play_time.seconds() {
+                break;
+            }
+            enemy_spawner.send_scripted(spawn.lane, spawn.boss, enemies, events);
+            self.spawned += 1;
+        }
+    }
+
+    /// Takes note of what happened that has a say in how the scenario ends.
+    pub fn record(&mut self, events: &[GameEvent]) {
+        if events
+            .iter()
+            .any(|event| matches!(event, GameEvent::BossLeaked { .. }))
+        {
+            self.boss_leaked = true;
+        }
+    }
+
+    /// How the scenario ended, once it is decided by its goal.
+    pub fn outcome(&self, play_time: &PlayTime, enemies: &[Enemy]) -> Option<GameState> {
+        match self.scenario.goal {
+            Goal::SurviveSeconds(seconds) => {
+                (play_time.seconds() >= seconds).then_some(GameState::Victory)
+            }
+            Goal::InterceptAll if self.boss_leaked => Some(GameState::GameOver),
+            Goal::InterceptAll => (self.enemies_left(enemies) == 0).then_some(GameState::Victory),
+        }
+    }
+
+    /// The latest message that is due, if any is.
+    #[cfg(feature = "backend-eframe")]
+    pub fn message(&self, play_time: &PlayTime) -> Option<&str> {
+        self.scenario
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.at_seconds <= play_time.seconds())
+            .map(|message| message.text.as_str())
+    }
+
+    /// Enemies of the script still to come or still on their way.
+    pub fn enemies_left(&self, enemies: &[Enemy]) -> usize {
+        self.scenario.spawns.len() - self.spawned + enemies.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRILL: &str = r#"Scenario(
+        name: "Drill",
+        description: "Two enemies.",
+        units: [UnitGroup(count: 1, ready_for: Some(A)), UnitGroup(count: 1)],
+        parking_spaces: 1,
+        spawns: [
+            ScriptedEnemy(at_seconds: 20.0, lane: B),
+            ScriptedEnemy(at_seconds: 5.0, lane: A, boss: true),
+        ],
+        goal: InterceptAll,
+    )"#;
+
+    #[test]
+    fn every_shipped_scenario_loads() {
+        for (file, text) in BUILT_IN {
+            if let Err(error) = Scenario::from_ron(text, file) {
+                panic!("{}", error);
+            }
+        }
+        assert_eq!(built_in().len(), BUILT_IN.len());
+    }
+
+    #[test]
+    fn the_script_is_read_in_order_of_time() {
+        let scenario = Scenario::from_ron(DRILL, "drill.ron").unwrap();
+        assert_eq!(scenario.difficulty, Difficulty::Normal);
+        let lanes: Vec<_> = scenario.spawns.iter().map(|spawn| spawn.lane).collect();
+        assert_eq!(lanes, [CombatType::A, CombatType::B]);
+
+        let (units, parking_spaces) = scenario.setup();
+        assert!(matches!(units[0].unit, Unit::ParkedReady(_, CombatType::A)));
+        assert!(matches!(units[1].unit, Unit::InStorage));
+        assert_eq!(parking_spaces.slots_used(), 1);
+    }
+
+    #[test]
+    fn syntax_errors_say_where() {
+        let text = DRILL.replace("parking_spaces: 1,", "parking_spaces: one,");
+        let error = Scenario::from_ron(&text, "drill.ron").unwrap_err();
+        assert_eq!(error.file, "drill.ron");
+        // The line and column of `one`.
+        assert!(error.message.starts_with("5:25:"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_lanes_are_named() {
+        let text = DRILL.replace("lane: B", "lane: E");
+        let error = Scenario::from_ron(&text, "drill.ron").unwrap_err();
+        assert!(error.to_string().starts_with("drill.ron: "), "{}", error);
+        assert!(error.message.contains("`E`"), "{}", error);
+    }
+
+    #[test]
+    fn scenarios_that_cant_be_played_are_refused() {
+        let refused = |from: &str, to: &str| {
+            Scenario::from_ron(&DRILL.replace(from, to), "drill.ron")
+                .unwrap_err()
+                .message
+        };
+        assert_eq!(
+            refused("parking_spaces: 1", "parking_spaces: 0"),
+            "parking_spaces must be at least 1"
+        );
+        assert_eq!(
+            refused(
+                "UnitGroup(count: 1)",
+                "UnitGroup(count: 1, ready_for: Some(B))"
+            ),
+            "2 units start out parked, but there are only 1 parking spaces"
+        );
+        assert!(refused("at_seconds: 20.0", "at_seconds: -1.0").starts_with("spawns[0]"));
+        assert_eq!(
+            refused("InterceptAll", "SurviveSeconds(10.0)"),
+            "spawns[0] comes at 20s, but the scenario is won at 10s"
+        );
+    }
+}
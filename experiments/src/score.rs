@@ -0,0 +1,163 @@
+//! Points for a run, beyond how long it lasted. Every enemy intercepted is worth more the faster
+//! it came and the longer the streak of interceptions without a leak or a lost unit, and surviving
+//! earns a little on its own.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{balance::GameConfig, events::GameEvent, game::Difficulty};
+
+/// What intercepting an ordinary enemy on Normal is worth, before the multiplier.
+const ENEMY_POINTS: f64 = 100.0;
+/// Bosses are worth this many ordinary enemies.
+const BOSS_FACTOR: u64 = 5;
+/// Interceptions in a row it takes to raise the multiplier by one.
+const INTERCEPTIONS_PER_STEP: u32 = 5;
+const MAX_MULTIPLIER: u32 = 8;
+/// Points for every second survived. These aren't multiplied.
+const POINTS_PER_SECOND: f64 = 10.0;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    difficulty: Difficulty,
+    /// Points from interceptions. The ones for surviving are added on top.
+    interception_points: u64,
+    /// Interceptions since the last leak or lost unit.
+    streak: u32,
+}
+
+impl Score {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::EnemyIntercepted { is_boss, .. } => {
+                let points = enemy_points(self.difficulty, *is_boss);
+                self.interception_points += points * u64::from(self.multiplier());
+                self.streak += 1;
+            }
+            GameEvent::BossLeaked { .. }
+            | GameEvent::BaseHit { .. }
+            | GameEvent::UnitDestroyed { .. } => self.streak = 0,
+            _ => {}
+        }
+    }
+
+    /// What the next interception's points are multiplied by.
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / INTERCEPTIONS_PER_STEP).min(MAX_MULTIPLIER)
+    }
+
+    /// All points of a run that has lasted `survived_seconds` so far.
+    pub fn points(&self, survived_seconds: f64) -> u64 {
+        self.interception_points + (survived_seconds.max(0.0) * POINTS_PER_SECOND) as u64
+    }
+}
+
+/// What intercepting an enemy is worth before the multiplier. Enemies cross their lane faster on
+/// harder difficulties, so they are worth more there. How much faster is taken from the default
+/// balance, so a balance being tried out doesn't change what points are worth.
+fn enemy_points(difficulty: Difficulty, is_boss: bool) -> u64 {
+    let config = GameConfig::default();
+    let speed = config.normal.enemy_run_seconds / config.difficulty(difficulty).enemy_run_seconds;
+    let points = (ENEMY_POINTS * speed).round() as u64;
+
+    if is_boss {
+        points * BOSS_FACTOR
+    } else {
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CombatType, UnitId};
+
+    fn intercepted(is_boss: bool) -> GameEvent {
+        GameEvent::EnemyIntercepted {
+            unit: UnitId(1),
+            combat_type: CombatType::A,
+            is_boss,
+        }
+    }
+
+    fn intercept(score: &mut Score, times: u32) {
+        for _ in 0..times {
+            score.record(&intercepted(false));
+        }
+    }
+
+    #[test]
+    fn enemies_are_worth_more_the_faster_they_come() {
+        assert_eq!(enemy_points(Difficulty::Easy, false), 75);
+        assert_eq!(enemy_points(Difficulty::Normal, false), 100);
+        assert_eq!(enemy_points(Difficulty::Hard, false), 125);
+        assert_eq!(enemy_points(Difficulty::Normal, true), 500);
+    }
+
+    #[test]
+    fn surviving_earns_points_on_its_own() {
+        let score = Score::new(Difficulty::Normal);
+        assert_eq!(score.points(0.0), 0);
+        assert_eq!(score.points(12.59), 125);
+        assert_eq!(score.points(-1.0), 0);
+    }
+
+    #[test]
+    fn every_few_interceptions_in_a_row_raise_the_multiplier() {
+        let mut score = Score::new(Difficulty::Normal);
+        intercept(&mut score, INTERCEPTIONS_PER_STEP - 1);
+        assert_eq!(score.multiplier(), 1);
+        intercept(&mut score, 1);
+        assert_eq!(score.multiplier(), 2);
+
+        // The interception that raises the multiplier is still paid at the old one.
+        assert_eq!(score.points(0.0), u64::from(INTERCEPTIONS_PER_STEP) * 100);
+        score.record(&intercepted(true));
+        assert_eq!(
+            score.points(0.0),
+            u64::from(INTERCEPTIONS_PER_STEP) * 100 + 2 * 500
+        );
+    }
+
+    #[test]
+    fn the_multiplier_stops_at_its_maximum() {
+        let mut score = Score::new(Difficulty::Normal);
+        intercept(&mut score, INTERCEPTIONS_PER_STEP * MAX_MULTIPLIER * 2);
+        assert_eq!(score.multiplier(), MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn leaks_and_lost_units_end_the_streak_but_keep_the_points() {
+        let resets = [
+            GameEvent::BossLeaked {
+                combat_type: CombatType::B,
+                parking_spaces_left: 3,
+            },
+            GameEvent::UnitDestroyed { unit: UnitId(2) },
+        ];
+        for reset in &resets {
+            let mut score = Score::new(Difficulty::Normal);
+            intercept(&mut score, INTERCEPTIONS_PER_STEP);
+            let points = score.points(0.0);
+
+            score.record(reset);
+            assert_eq!(score.multiplier(), 1);
+            assert_eq!(score.points(0.0), points);
+        }
+    }
+
+    #[test]
+    fn other_events_leave_the_streak_alone() {
+        let mut score = Score::new(Difficulty::Normal);
+        intercept(&mut score, INTERCEPTIONS_PER_STEP);
+        score.record(&GameEvent::UnitRepaired { unit: UnitId(1) });
+        score.record(&GameEvent::ParkingChanged { used: 0 });
+        assert_eq!(score.multiplier(), 2);
+    }
+}
@@ -0,0 +1,229 @@
+use std::ops::RangeInclusive;
+
+use engine::input::InputMap;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+use crate::{
+    controls,
+    game::Difficulty,
+    game_mode::GameMode,
+    storage::{self, Storage},
+};
+
+/// The language the GUI is shown in. Each one is named in itself so players can find theirs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize, Default)]
+pub enum Language {
+    #[default]
+    English,
+    #[strum(serialize = "Deutsch")]
+    German,
+}
+
+/// The overall look of the GUI.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter, Serialize, Deserialize, Default)]
+pub enum ColorScheme {
+    #[default]
+    DarkPurple,
+    Light,
+    HighContrast,
+}
+
+/// The player's preferences. Fields missing from what was saved by an older version fall back
+/// to their defaults and unknown ones are ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub game_mode: GameMode,
+    pub color_scheme: ColorScheme,
+    pub colorblind_mode: bool,
+    /// Bigger text and lanes, for playing from across the room.
+    pub large_ui: bool,
+    /// Park units as soon as they are back and a parking space is free.
+    pub auto_park: bool,
+    /// Pause the run when the player switches to another tab or window.
+    pub pause_on_focus_loss: bool,
+    /// Points per pixel, or `None` to keep whatever the display suggests.
+    pub ui_scale: Option<f32>,
+    /// Bigger buttons and lanes for fingers, and a button for the unit menus, or `None` to use
+    /// them on touch screens only.
+    pub touch_layout: Option<bool>,
+    /// Whether the device has a touch screen. Looked up afresh every start.
+    #[serde(skip)]
+    pub touch_screen: bool,
+    pub show_fps: bool,
+    /// Frame times, entity counts and spawner internals, for development and bug reports.
+    pub show_debug_overlay: bool,
+    pub language: Language,
+    /// Loudness of the sound effects, from 0 to 1.
+    pub sound_volume: f32,
+    pub muted: bool,
+    pub music_volume: f32,
+    pub music_muted: bool,
+    /// Which key triggers which of the `controls::ACTIONS`.
+    pub key_bindings: InputMap,
+    /// Send runs that count to the online leaderboard, if the game has one.
+    pub online_leaderboard: bool,
+    /// What the online leaderboard shows the player as.
+    pub online_initials: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::default(),
+            game_mode: GameMode::default(),
+            color_scheme: ColorScheme::default(),
+            colorblind_mode: false,
+            large_ui: false,
+            auto_park: false,
+            pause_on_focus_loss: true,
+            ui_scale: None,
+            touch_layout: None,
+            touch_screen: false,
+            show_fps: false,
+            show_debug_overlay: false,
+            language: Language::default(),
+            sound_volume: 0.8,
+            muted: false,
+            music_volume: 0.5,
+            music_muted: false,
+            key_bindings: controls::default_bindings(),
+            online_leaderboard: false,
+            online_initials: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    const STORAGE_KEY: &'static str = "settings";
+    pub const UI_SCALES: RangeInclusive<f32> = engine::ui::UI_SCALES;
+    pub const VOLUMES: RangeInclusive<f32> = 0.0..=1.0;
+
+    /// How much bigger than normal text and lanes are drawn.
+    #[cfg(feature = "backend-eframe")]
+    pub fn text_scale(&self) -> f32 {
+        if self.large_ui {
+            1.5
+        } else {
+            1.0
+        }
+    }
+
+    /// Whether to lay out the GUI for fingers rather than a mouse.
+    #[cfg(feature = "backend-eframe")]
+    pub fn uses_touch_layout(&self) -> bool {
+        self.touch_layout.unwrap_or(self.touch_screen)
+    }
+
+    pub fn load(storage: &dyn Storage) -> Self {
+        let mut settings: Self = storage::load(storage, Self::STORAGE_KEY);
+        settings.touch_screen = engine::touch::has_touch_screen();
+
+        // Hand-edited or corrupted values shouldn't make the game unplayable.
+        if !Self::VOLUMES.contains(&settings.sound_volume) {
+            settings.sound_volume = Self::default().sound_volume;
+        }
+        if !Self::VOLUMES.contains(&settings.music_volume) {
+            settings.music_volume = Self::default().music_volume;
+        }
+        settings.key_bindings = settings
+            .key_bindings
+            .with_defaults(&controls::default_bindings());
+        if let Some(ui_scale) = settings.ui_scale {
+            if !Self::UI_SCALES.contains(&ui_scale) {
+                settings.ui_scale = None;
+            }
+        }
+
+        settings
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn save(&self, storage: &mut dyn Storage) {
+        storage::save(storage, Self::STORAGE_KEY, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::input::KeyCode;
+
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn load_json(json: &str) -> Settings {
+        let mut storage = MemoryStorage::default();
+        storage.set(Settings::STORAGE_KEY, json.to_string());
+        Settings::load(&storage)
+    }
+
+    #[test]
+    fn saved_settings_load_as_they_were() {
+        let mut storage = MemoryStorage::default();
+        let settings = Settings {
+            difficulty: Difficulty::Hard,
+            color_scheme: ColorScheme::HighContrast,
+            ui_scale: Some(1.5),
+            language: Language::German,
+            sound_volume: 0.3,
+            online_initials: "ABC".to_string(),
+            ..Settings::default()
+        };
+        storage::save(&mut storage, Settings::STORAGE_KEY, &settings);
+
+        let loaded = Settings::load(&storage);
+        assert_eq!(
+            loaded,
+            Settings {
+                touch_screen: loaded.touch_screen,
+                ..settings
+            }
+        );
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_their_defaults() {
+        let settings = load_json(r#"{ "colorblind_mode": true }"#);
+        assert!(settings.colorblind_mode);
+        assert_eq!(settings.difficulty, Difficulty::default());
+        assert_eq!(settings.sound_volume, Settings::default().sound_volume);
+        assert_eq!(settings.key_bindings, controls::default_bindings());
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let settings = load_json(r#"{ "large_ui": true, "from_the_future": [1, 2, 3] }"#);
+        assert!(settings.large_ui);
+    }
+
+    #[test]
+    fn values_out_of_range_are_replaced() {
+        let settings =
+            load_json(r#"{ "sound_volume": 7.0, "music_volume": -1.0, "ui_scale": 40.0 }"#);
+        assert_eq!(settings.sound_volume, Settings::default().sound_volume);
+        assert_eq!(settings.music_volume, Settings::default().music_volume);
+        assert_eq!(settings.ui_scale, None);
+    }
+
+    #[test]
+    fn corrupt_settings_start_fresh() {
+        let settings = load_json("{ \"difficulty\": ");
+        assert_eq!(
+            settings,
+            Settings {
+                touch_screen: settings.touch_screen,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn actions_added_since_are_bound_if_their_key_is_free() {
+        let settings = load_json(r#"{ "key_bindings": { "pause": "P" } }"#);
+        assert_eq!(settings.key_bindings.key("pause"), Some(KeyCode::P));
+        assert_eq!(settings.key_bindings.key("park"), None);
+        assert_eq!(settings.key_bindings.key("take_off"), Some(KeyCode::T));
+    }
+}
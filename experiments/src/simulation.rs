@@ -0,0 +1,385 @@
+//! Runs the computer plays by itself as fast as it can, without a window, for balancing. The
+//! `simulate` binary plays one run for each of a list of seeds and writes a line of CSV for each,
+//! so the numbers of hundreds of runs can be compared before and after a change to the balance.
+//! The runs follow the same fixed steps as a played one and nothing is saved, so the same seeds
+//! give the same lines every time.
+
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+use rand::Rng;
+use rand_chacha::ChaCha12Rng;
+use strum::IntoEnumIterator;
+
+use crate::{
+    commands::GameCommand,
+    game::{CombatType, Difficulty, Enemy, Unit, UnitAction, UnitBundle, UnitId},
+    helpers::FixedTimestep,
+    start_config::parse_difficulty,
+};
+
+/// Who gives the orders in a simulated run.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Policy {
+    /// Nobody. The units stay where they are, so the first enemy ends the run.
+    Idle,
+    /// Nobody either, but units coming back park by themselves.
+    AutoPark,
+    /// Sends a unit up against every enemy that no unit is on its way to yet, and parks the
+    /// units coming back.
+    Commander,
+    /// Gives random orders, many of them impossible, after frames of random length, to shake
+    /// out states the rules shouldn't allow. Mostly for `--check`.
+    Random,
+}
+
+impl Policy {
+    const ALL: [Self; 4] = [Self::Idle, Self::AutoPark, Self::Commander, Self::Random];
+
+    /// What the command line calls it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::AutoPark => "auto-park",
+            Self::Commander => "commander",
+            Self::Random => "random",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|policy| policy.name() == name)
+    }
+
+    pub(crate) fn parks_itself(self) -> bool {
+        self == Self::AutoPark || self == Self::Commander
+    }
+
+    /// How much time passes in the next frame. Random orders come after frames of random
+    /// length, so they land at any point of a step; the others come before every step.
+    pub(crate) fn frame_time(self, rng: &mut ChaCha12Rng) -> Duration {
+        match self {
+            Self::Random => Duration::from_millis(rng.gen_range(0..600)),
+            _ => Duration::from_secs_f64(FixedTimestep::STEP_SECONDS),
+        }
+    }
+
+    /// The orders to give before the next frame, if any. `rng` only matters to random ones.
+    pub(crate) fn orders(
+        self,
+        units: &[UnitBundle],
+        enemies: &[Enemy],
+        rng: &mut ChaCha12Rng,
+    ) -> Vec<GameCommand> {
+        match self {
+            Self::Idle | Self::AutoPark => Vec::new(),
+            Self::Commander => commander_orders(units, enemies),
+            Self::Random => random_orders(units, rng),
+        }
+    }
+}
+
+fn commander_orders(units: &[UnitBundle], enemies: &[Enemy]) -> Vec<GameCommand> {
+    let mut orders = Vec::new();
+    for lane in CombatType::iter() {
+        let enemies = enemies
+            .iter()
+            .filter(|enemy| enemy.combat_type == lane)
+            .count();
+        let on_their_way = units
+            .iter()
+            .filter(|bundle| heading_for(bundle) == Some(lane))
+            .count();
+        // Each scramble picks another unit, as the one before has its orders by then.
+        for _ in on_their_way..enemies {
+            orders.push(GameCommand::Scramble(lane));
+        }
+    }
+    orders
+}
+
+/// Up to two orders of any kind, also for units that are gone and parking spaces that don't
+/// exist.
+fn random_orders(units: &[UnitBundle], rng: &mut ChaCha12Rng) -> Vec<GameCommand> {
+    let actions = UnitAction::all();
+    let lanes: Vec<CombatType> = CombatType::iter().collect();
+    (0..rng.gen_range(0..3))
+        .map(|_| {
+            let unit = UnitId(rng.gen_range(0..=units.len() as u32 + 1));
+            let action = actions[rng.gen_range(0..actions.len())];
+            match rng.gen_range(0..3) {
+                0 => GameCommand::Perform { unit, action },
+                1 => GameCommand::PerformInSlot {
+                    unit,
+                    action,
+                    slot: rng.gen_range(0..=units.len()),
+                },
+                _ => GameCommand::Scramble(lanes[rng.gen_range(0..lanes.len())]),
+            }
+        })
+        .collect()
+}
+
+/// The lane the unit is up in, or is going up in once it has carried out its orders.
+fn heading_for(bundle: &UnitBundle) -> Option<CombatType> {
+    if let Unit::Patrolling(_, combat_type) = bundle.unit {
+        return Some(combat_type);
+    }
+    if !bundle.orders.contains(&UnitAction::TakeOff) {
+        return None;
+    }
+
+    bundle
+        .orders
+        .iter()
+        .find_map(|order| match order {
+            UnitAction::Prepare(combat_type) => Some(*combat_type),
+            _ => None,
+        })
+        .or(match bundle.unit {
+            Unit::ParkedPreparing(_, _, combat_type) | Unit::ParkedReady(_, combat_type) => {
+                Some(combat_type)
+            }
+            _ => None,
+        })
+}
+
+/// What the `simulate` binary plays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConfig {
+    /// One run each, in this order.
+    pub seeds: Vec<u64>,
+    pub difficulty: Difficulty,
+    pub policy: Policy,
+    /// Runs still going after this many seconds are stopped there.
+    pub max_seconds: f64,
+    /// Units and parking spaces to start with instead of the balance's, e.g. to try the rules
+    /// out on a small game.
+    pub units: Option<u32>,
+    pub parking_spaces: Option<usize>,
+    /// Checks the invariants after every frame, and stops a run at the first that breaks.
+    pub check: bool,
+    /// Enemies reaching the base don't end the run, as with the sandbox's cheat, so it goes on
+    /// to the time limit.
+    pub invincible: bool,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seeds: (1..=100).collect(),
+            difficulty: Difficulty::Normal,
+            policy: Policy::Commander,
+            max_seconds: 20.0 * 60.0,
+            units: None,
+            parking_spaces: None,
+            check: false,
+            invincible: false,
+        }
+    }
+}
+
+impl SimulationConfig {
+    pub const USAGE: &'static str = "usage: simulate [--seeds FIRST-LAST | --seed SEED...] \
+                                     [--difficulty easy|normal|hard] \
+                                     [--policy idle|auto-park|commander|random] \
+                                     [--max-seconds SECONDS] [--units COUNT] \
+                                     [--parking-spaces COUNT] [--check] [--invincible]";
+
+    /// Reads the command line of the `simulate` binary, without the program name. Unlike the
+    /// game's own, anything it doesn't understand is an error: numbers from a run set up other
+    /// than asked would be worse than none.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut config = Self::default();
+        let mut seeds = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--seed" => {
+                    let value = value()?;
+                    seeds.push(parse_seed(&value)?);
+                }
+                "--seeds" => {
+                    let value = value()?;
+                    let (first, last) = value
+                        .find('-')
+                        .map(|index| (&value[..index], &value[index + 1..]))
+                        .ok_or_else(|| format!("seeds must be like 1-500, not {:?}", value))?;
+                    seeds.extend(parse_seed(first)?..=parse_seed(last)?);
+                }
+                "--difficulty" => {
+                    let value = value()?;
+                    config.difficulty = parse_difficulty(&value)
+                        .ok_or_else(|| format!("unknown difficulty: {:?}", value))?;
+                }
+                "--policy" => {
+                    let value = value()?;
+                    config.policy = Policy::from_name(&value)
+                        .ok_or_else(|| format!("unknown policy: {:?}", value))?;
+                }
+                "--max-seconds" => {
+                    let value = value()?;
+                    config.max_seconds = value
+                        .parse()
+                        .ok()
+                        .filter(|seconds: &f64| *seconds > 0.0)
+                        .ok_or_else(|| format!("invalid --max-seconds: {:?}", value))?;
+                }
+                "--units" => {
+                    let value = value()?;
+                    config.units = Some(parse_count(&value)?);
+                }
+                "--parking-spaces" => {
+                    let value = value()?;
+                    config.parking_spaces = Some(parse_count(&value)?);
+                }
+                "--check" => config.check = true,
+                "--invincible" => config.invincible = true,
+                _ => return Err(format!("unknown argument: {:?}", arg)),
+            }
+        }
+
+        if !seeds.is_empty() {
+            config.seeds = seeds;
+        }
+        Ok(config)
+    }
+}
+
+fn parse_seed(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid seed: {:?}", value))
+}
+
+fn parse_count<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid count: {:?}", value))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Lost,
+    /// Only in modes that can be won.
+    Won,
+    /// Still going at the time limit.
+    Stopped,
+    /// An invariant didn't hold or the game panicked.
+    Broken,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Lost => "lost",
+            Self::Won => "won",
+            Self::Stopped => "stopped",
+            Self::Broken => "broken",
+        }
+    }
+}
+
+/// How one simulated run went. Displayed, it is a line of CSV under `SimulatedRun::CSV_HEADER`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedRun {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub policy: Policy,
+    pub outcome: Outcome,
+    pub survived_seconds: f64,
+    pub points: u64,
+    pub kills: usize,
+    /// Bosses that broke through. The enemy that got to the base is in the outcome.
+    pub leaks: usize,
+    pub units_lost: usize,
+    /// What broke, in a broken run. Not in the CSV line.
+    pub violation: Option<String>,
+}
+
+impl SimulatedRun {
+    pub const CSV_HEADER: &'static str =
+        "seed,difficulty,policy,outcome,survived_seconds,points,kills,leaks,units_lost";
+}
+
+impl Display for SimulatedRun {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{},{},{},{},{:.2},{},{},{},{}",
+            self.seed,
+            self.difficulty,
+            self.policy.name(),
+            self.outcome.name(),
+            self.survived_seconds,
+            self.points,
+            self.kills,
+            self.leaks,
+            self.units_lost
+        )
+    }
+}
+
+/// What a batch of simulated runs adds up to, to tell at a glance whether a change to the
+/// balance made the game easier or harder.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Totals {
+    runs: usize,
+    lost: usize,
+    broken: usize,
+    survived_seconds: Vec<f64>,
+    kills: usize,
+    leaks: usize,
+    units_lost: usize,
+}
+
+impl Totals {
+    pub fn add(&mut self, run: &SimulatedRun) {
+        self.runs += 1;
+        match run.outcome {
+            Outcome::Lost => self.lost += 1,
+            Outcome::Broken => self.broken += 1,
+            Outcome::Won | Outcome::Stopped => {}
+        }
+        self.survived_seconds.push(run.survived_seconds);
+        self.kills += run.kills;
+        self.leaks += run.leaks;
+        self.units_lost += run.units_lost;
+    }
+
+    /// Runs that broke an invariant or panicked.
+    pub fn broken(&self) -> usize {
+        self.broken
+    }
+}
+
+impl Display for Totals {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.runs == 0 {
+            return write!(f, "no runs");
+        }
+
+        let runs = self.runs as f64;
+        let mut survived = self.survived_seconds.clone();
+        survived.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        write!(
+            f,
+            "{} runs, {} lost, {} broken: survived {:.1}s on average, {:.1}s median; per run \
+             {:.2} kills, {:.2} leaks, {:.2} units lost",
+            self.runs,
+            self.lost,
+            self.broken,
+            survived.iter().sum::<f64>() / runs,
+            survived[survived.len() / 2],
+            self.kills as f64 / runs,
+            self.leaks as f64 / runs,
+            self.units_lost as f64 / runs
+        )
+    }
+}
@@ -0,0 +1,283 @@
+//! What the page hosting the web build can set up before the game starts, e.g. for a daily
+//! challenge with a fixed seed or an embed that starts muted. Links can do the same through
+//! their query string, like `?seed=12345&difficulty=hard&speed=2&mute=1&log=debug`, and the
+//! native build through its command line, like `--sandbox --balance balance.ron`.
+
+use std::path::PathBuf;
+
+use log::LevelFilter;
+use serde::Deserialize;
+
+#[cfg(feature = "backend-eframe")]
+use crate::settings::Settings;
+use crate::{game::Difficulty, helpers::GameSpeed, settings::Language};
+
+/// Anything left out keeps its default or what the player chose before.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StartConfig {
+    /// Every run uses this seed instead of a random one.
+    pub seed: Option<u64>,
+    pub difficulty: Option<Difficulty>,
+    pub language: Option<Language>,
+    pub muted: Option<bool>,
+    /// The time scale the first run starts at. Only the speeds the game offers work.
+    pub speed: Option<f64>,
+    /// The least important messages written to the browser console. Warnings by default.
+    pub log_level: Option<LevelFilter>,
+    /// Offers cheats for balancing and testing. Runs that use them don't count for the high
+    /// score or the leaderboard.
+    pub sandbox: Option<bool>,
+    /// A balance file to use instead of the built-in one, read again for every run. Only the
+    /// native build has files to read.
+    #[serde(skip)]
+    pub balance: Option<PathBuf>,
+    /// Where runs go when the player sends them to the online leaderboard. Links can't set
+    /// this, so a shared one can't send someone's runs elsewhere.
+    pub leaderboard_url: Option<String>,
+}
+
+impl StartConfig {
+    /// Reads the parameters a link can set from a query string like `?seed=12345&mute=1`. Other
+    /// parameters are ignored. Values that don't make sense are left out, with a warning each.
+    pub fn from_query(query: &str) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut warnings = Vec::new();
+
+        let query = query.strip_prefix('?').unwrap_or(query);
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = match pair.find('=') {
+                Some(index) => (&pair[..index], &pair[index + 1..]),
+                None => (pair, ""),
+            };
+            let parsed = match name {
+                "seed" => value.parse().map(|seed| config.seed = Some(seed)).ok(),
+                "difficulty" => parse_difficulty(value).map(|difficulty| {
+                    config.difficulty = Some(difficulty);
+                }),
+                "speed" => value
+                    .parse()
+                    .ok()
+                    .and_then(GameSpeed::from_scale)
+                    .map(|speed| config.speed = Some(speed.scale())),
+                "mute" => parse_flag(value).map(|muted| config.muted = Some(muted)),
+                "log" => value
+                    .parse()
+                    .map(|level| config.log_level = Some(level))
+                    .ok(),
+                "sandbox" => parse_flag(value).map(|sandbox| config.sandbox = Some(sandbox)),
+                _ => Some(()),
+            };
+            if parsed.is_none() {
+                warnings.push(format!("ignoring invalid {}: {:?}", name, value));
+            }
+        }
+
+        (config, warnings)
+    }
+
+    /// Reads the flags the native build takes on its command line, without the program name.
+    /// Anything else is ignored, with a warning each.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut warnings = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--sandbox" => config.sandbox = Some(true),
+                "--balance" => match args.next() {
+                    Some(path) => config.balance = Some(PathBuf::from(path)),
+                    None => warnings.push("ignoring --balance without a path".to_string()),
+                },
+                "--leaderboard-url" => match args.next() {
+                    Some(url) => config.leaderboard_url = Some(url),
+                    None => warnings.push("ignoring --leaderboard-url without a URL".to_string()),
+                },
+                _ => {
+                    if let Some(path) = arg.strip_prefix("--balance=") {
+                        config.balance = Some(PathBuf::from(path));
+                    } else if let Some(url) = arg.strip_prefix("--leaderboard-url=") {
+                        config.leaderboard_url = Some(url.to_string());
+                    } else {
+                        warnings.push(format!("ignoring unknown argument: {:?}", arg));
+                    }
+                }
+            }
+        }
+
+        (config, warnings)
+    }
+
+    /// Fills in whatever this leaves out from `fallback`.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            seed: self.seed.or(fallback.seed),
+            difficulty: self.difficulty.or(fallback.difficulty),
+            language: self.language.or(fallback.language),
+            muted: self.muted.or(fallback.muted),
+            speed: self.speed.or(fallback.speed),
+            log_level: self.log_level.or(fallback.log_level),
+            sandbox: self.sandbox.or(fallback.sandbox),
+            balance: self.balance.or(fallback.balance),
+            leaderboard_url: self.leaderboard_url.or(fallback.leaderboard_url),
+        }
+    }
+
+    /// Stops overriding the settings the player changed from `before` to `after`, so their own
+    /// choice holds after a reset too.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn forget_changed(&mut self, before: &Settings, after: &Settings) {
+        if after.difficulty != before.difficulty {
+            self.difficulty = None;
+        }
+        if after.language != before.language {
+            self.language = None;
+        }
+        if after.muted != before.muted {
+            self.muted = None;
+        }
+    }
+}
+
+pub(crate) fn parse_difficulty(value: &str) -> Option<Difficulty> {
+    match value.to_ascii_lowercase().as_str() {
+        "easy" => Some(Difficulty::Easy),
+        "normal" => Some(Difficulty::Normal),
+        "hard" => Some(Difficulty::Hard),
+        _ => None,
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value {
+        "1" | "true" | "" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_json(json: &str) -> Result<StartConfig, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn an_empty_object_changes_nothing() {
+        assert_eq!(from_json("{}").unwrap(), StartConfig::default());
+    }
+
+    #[test]
+    fn every_field_a_page_can_set_is_read() {
+        let config = from_json(
+            r#"{ "seed": 12345, "difficulty": "Hard", "language": "German", "muted": true,
+                 "speed": 2, "log_level": "debug", "sandbox": false,
+                 "leaderboard_url": "https://example.com/runs" }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            StartConfig {
+                seed: Some(12345),
+                difficulty: Some(Difficulty::Hard),
+                language: Some(Language::German),
+                muted: Some(true),
+                speed: Some(2.0),
+                log_level: Some(LevelFilter::Debug),
+                sandbox: Some(false),
+                balance: None,
+                leaderboard_url: Some("https://example.com/runs".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_fields_are_errors() {
+        for json in [
+            r#"{ "seed": -1 }"#,
+            r#"{ "difficulty": "Impossible" }"#,
+            r#"{ "muted": "yes" }"#,
+            r#"{ "sead": 12345 }"#,
+            // Only the native build reads balance files, from its command line.
+            r#"{ "balance": "balance.ron" }"#,
+        ] {
+            assert!(from_json(json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn a_link_sets_what_its_query_says() {
+        let (config, warnings) =
+            StartConfig::from_query("?seed=12345&difficulty=HARD&speed=2&mute&log=debug");
+
+        assert_eq!(
+            config,
+            StartConfig {
+                seed: Some(12345),
+                difficulty: Some(Difficulty::Hard),
+                muted: Some(true),
+                speed: Some(2.0),
+                log_level: Some(LevelFilter::Debug),
+                ..StartConfig::default()
+            }
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn a_link_cannot_set_everything() {
+        let (config, warnings) = StartConfig::from_query(
+            "leaderboard_url=https://example.com&utm_source=x&&language=de",
+        );
+
+        assert_eq!(config, StartConfig::default());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn malformed_values_are_left_out_with_a_warning() {
+        let (config, warnings) = StartConfig::from_query(
+            "seed=abc&difficulty=brutal&speed=1.7&mute=maybe&sandbox=0&log=loud",
+        );
+
+        assert_eq!(
+            config,
+            StartConfig {
+                sandbox: Some(false),
+                ..StartConfig::default()
+            }
+        );
+        assert_eq!(
+            warnings,
+            [
+                "ignoring invalid seed: \"abc\"",
+                "ignoring invalid difficulty: \"brutal\"",
+                "ignoring invalid speed: \"1.7\"",
+                "ignoring invalid mute: \"maybe\"",
+                "ignoring invalid log: \"loud\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_first_config_wins_field_by_field() {
+        let explicit = StartConfig {
+            seed: Some(1),
+            ..StartConfig::default()
+        };
+        let from_link = StartConfig {
+            seed: Some(2),
+            muted: Some(true),
+            ..StartConfig::default()
+        };
+
+        let config = explicit.or(from_link);
+        assert_eq!(config.seed, Some(1));
+        assert_eq!(config.muted, Some(true));
+        assert_eq!(config.difficulty, None);
+    }
+}
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::GameEvent,
+    game::{CombatType, ParkingSpace, TokenPool},
+    helpers::Time,
+};
+
+/// Counters for the current run, shown while playing and on the game-over screen.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameStats {
+    /// Sorties flown to the end, including the ones a unit didn't come back from.
+    pub(crate) sorties: usize,
+    enemies_destroyed: [usize; 4],
+    pub(crate) units_lost: usize,
+    /// Units that were repaired back to full health.
+    pub(crate) repairs_completed: usize,
+    /// The most parking spaces in use at once.
+    #[serde(default)]
+    pub(crate) most_parked: usize,
+    /// Bosses that broke through and wrecked a parking space.
+    #[serde(default)]
+    pub(crate) bosses_leaked: usize,
+    parking_seconds_used: f64,
+    parking_seconds_available: f64,
+}
+
+impl GameStats {
+    pub(crate) fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::SortieCompleted { .. } => self.sorties += 1,
+            GameEvent::UnitDestroyed { .. } => {
+                self.sorties += 1;
+                self.units_lost += 1;
+            }
+            GameEvent::EnemyIntercepted { combat_type, .. } => {
+                self.enemies_destroyed[*combat_type as usize] += 1;
+            }
+            GameEvent::UnitRepaired { .. } => self.repairs_completed += 1,
+            GameEvent::BossLeaked { .. } => self.bosses_leaked += 1,
+            GameEvent::ParkingChanged { used } => {
+                self.most_parked = self.most_parked.max(*used);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn enemies_destroyed(&self, combat_type: CombatType) -> usize {
+        self.enemies_destroyed[combat_type as usize]
+    }
+
+    pub(crate) fn total_enemies_destroyed(&self) -> usize {
+        self.enemies_destroyed.iter().sum()
+    }
+
+    /// Adds this frame's parking space use to the running average.
+    pub(crate) fn record_parking(&mut self, time: &Time, parking_spaces: &TokenPool<ParkingSpace>) {
+        let seconds = time.delta_seconds_f64();
+        let capacity = parking_spaces.capacity();
+        let used = parking_spaces.slots_used().min(capacity);
+
+        self.parking_seconds_used += used as f64 * seconds;
+        self.parking_seconds_available += capacity as f64 * seconds;
+    }
+
+    /// How much of the available parking was in use over the whole run, in percent.
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) fn parking_utilization(&self) -> f64 {
+        if self.parking_seconds_available > 0.0 {
+            100.0 * self.parking_seconds_used / self.parking_seconds_available
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::UnitId;
+
+    #[test]
+    fn a_scripted_run_adds_up() {
+        let unit = UnitId(1);
+        let script = [
+            GameEvent::ParkingChanged { used: 1 },
+            GameEvent::EnemyIntercepted {
+                unit,
+                combat_type: CombatType::B,
+                is_boss: false,
+            },
+            GameEvent::SortieCompleted { unit },
+            GameEvent::ParkingChanged { used: 3 },
+            GameEvent::ParkingChanged { used: 2 },
+            GameEvent::EnemyIntercepted {
+                unit,
+                combat_type: CombatType::B,
+                is_boss: true,
+            },
+            GameEvent::EnemyIntercepted {
+                unit,
+                combat_type: CombatType::D,
+                is_boss: false,
+            },
+            GameEvent::UnitDestroyed { unit },
+            GameEvent::UnitRepaired { unit: UnitId(2) },
+            GameEvent::BossLeaked {
+                combat_type: CombatType::A,
+                parking_spaces_left: 2,
+            },
+            GameEvent::UnitParked { unit: UnitId(2) },
+        ];
+
+        let mut stats = GameStats::default();
+        for event in &script {
+            stats.record(event);
+        }
+
+        assert_eq!(stats.sorties, 2);
+        assert_eq!(stats.units_lost, 1);
+        assert_eq!(stats.repairs_completed, 1);
+        assert_eq!(stats.most_parked, 3);
+        assert_eq!(stats.bosses_leaked, 1);
+        assert_eq!(stats.enemies_destroyed(CombatType::A), 0);
+        assert_eq!(stats.enemies_destroyed(CombatType::B), 2);
+        assert_eq!(stats.enemies_destroyed(CombatType::D), 1);
+        assert_eq!(stats.total_enemies_destroyed(), 3);
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    #[test]
+    fn parking_use_is_averaged_over_time() {
+        use crate::helpers::Duration;
+
+        let mut stats = GameStats::default();
+        let mut time = Time::new();
+        let mut parking_spaces = TokenPool::<ParkingSpace>::new(2);
+        assert_eq!(stats.parking_utilization(), 0.0);
+
+        time.set_delta(Duration::from_secs_f64(1.0));
+        let token = parking_spaces.try_take().unwrap();
+        stats.record_parking(&time, &parking_spaces);
+        drop(token);
+        time.set_delta(Duration::from_secs_f64(3.0));
+        stats.record_parking(&time, &parking_spaces);
+
+        // One of two spaces for one second out of four.
+        assert_eq!(stats.parking_utilization(), 12.5);
+    }
+}
@@ -0,0 +1,172 @@
+//! Small key-value persistence that survives restarts: localStorage on the web and a JSON file in
+//! the platform's config directory on native. Values are stored as JSON strings.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+pub trait Storage {
+    fn get(&self, key: &str) -> Option<String>;
+    /// Keeps the value if it can. Failures are logged rather than returned; nothing the game
+    /// does, like closing, waits on them.
+    fn set(&mut self, key: &str, value: String);
+}
+
+/// Reads a value, falling back to its default if it is missing or can't be parsed.
+pub fn load<T: DeserializeOwned + Default>(storage: &dyn Storage, key: &str) -> T {
+    storage
+        .get(key)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save<T: Serialize>(storage: &mut dyn Storage, key: &str, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(json) => storage.set(key, json),
+        Err(error) => log::warn!("Couldn't save {}: {}", key, error),
+    }
+}
+
+/// Keeps everything in memory, for tests.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemoryStorage(pub std::collections::BTreeMap<String, String>);
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub type PlatformStorage = LocalStorage;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type PlatformStorage = FileStorage;
+
+/// The storage the game keeps its records in: the platform's, unless it was handed another,
+/// like one that keeps nothing for simulated runs.
+pub struct GameStorage(Box<dyn Storage>);
+
+impl GameStorage {
+    pub fn new(storage: impl Storage + 'static) -> Self {
+        Self(Box::new(storage))
+    }
+}
+
+impl Default for GameStorage {
+    fn default() -> Self {
+        Self::new(PlatformStorage::default())
+    }
+}
+
+impl Storage for GameStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.0.set(key, value);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    // The page may be hosted next to other games, so keep our keys apart.
+    const KEY_PREFIX: &'static str = "fruitopian_defender.";
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        Self::local_storage()?
+            .get_item(&format!("{}{}", Self::KEY_PREFIX, key))
+            .ok()?
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        let local_storage = match Self::local_storage() {
+            Some(local_storage) => local_storage,
+            None => {
+                log::warn!("Couldn't save {}: no local storage", key);
+                return;
+            }
+        };
+        if let Err(error) = local_storage.set_item(&format!("{}{}", Self::KEY_PREFIX, key), &value)
+        {
+            log::warn!("Couldn't save {}: {:?}", key, error);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    path: Option<std::path::PathBuf>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for FileStorage {
+    fn default() -> Self {
+        Self {
+            path: dirs::config_dir()
+                .map(|dir| dir.join("fruitopian_defender").join("storage.json")),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    /// Reads nothing and keeps nothing, for runs like simulated ones that must leave the
+    /// player's records alone.
+    pub fn discarding() -> Self {
+        Self { path: None }
+    }
+
+    fn read_all(&self) -> std::collections::BTreeMap<String, String> {
+        self.path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.read_all().remove(key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let mut all = self.read_all();
+        all.insert(key.to_string(), value);
+
+        if let Some(dir) = path.parent() {
+            if let Err(error) = std::fs::create_dir_all(dir) {
+                log::warn!("Couldn't create {}: {}", dir.display(), error);
+            }
+        }
+        let written = serde_json::to_string_pretty(&all)
+            .map_err(|error| error.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|error| error.to_string()));
+        if let Err(error) = written {
+            log::warn!("Couldn't save {} to {}: {}", key, path.display(), error);
+        }
+    }
+}
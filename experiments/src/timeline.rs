@@ -0,0 +1,235 @@
+//! How the run went over time, for the chart in the run-over window: how things stood about once
+//! a second, and marks where enemies got through and units were lost. Sampling costs next to
+//! nothing between samples, and the buffer is capped. Once it is full, every other sample is
+//! dropped and the rest of the run is sampled half as often, so a run of any length fits.
+
+use crate::events::GameEvent;
+
+/// Half an hour at one sample a second.
+pub const MAX_SAMPLES: usize = 30 * 60;
+/// Marks beyond these are dropped. A run with this many leaks or losses tells its story anyway.
+pub const MAX_MARKS: usize = 256;
+
+/// How things stood at one moment of the run.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct TimelineSample {
+    pub at_seconds: f64,
+    pub points: u64,
+    pub enemies: u32,
+    pub airborne: u32,
+    pub parking_used: u32,
+    pub parking_capacity: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MarkKind {
+    /// An enemy got through, to the base or, for a boss, to a parking space.
+    Leak,
+    UnitLost,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimelineMark {
+    pub at_seconds: f64,
+    pub kind: MarkKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    samples: Vec<TimelineSample>,
+    marks: Vec<TimelineMark>,
+    /// Seconds between two samples, doubled every time the buffer fills up.
+    interval: f64,
+    next_sample_at: f64,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            samples: Vec::new(),
+            marks: Vec::new(),
+            interval: 1.0,
+            next_sample_at: 0.0,
+        }
+    }
+}
+
+impl Timeline {
+    /// Takes a sample with `take` if one is due at `at_seconds`: the first time it is called in
+    /// each stretch of `interval` seconds. `take` isn't called otherwise.
+    pub fn sample(&mut self, at_seconds: f64, take: impl FnOnce() -> TimelineSample) {
+        if at_seconds < self.next_sample_at {
+            return;
+        }
+        if self.samples.len() >= MAX_SAMPLES {
+            self.thin_out();
+            if at_seconds < self.next_sample_at {
+                return;
+            }
+        }
+
+        self.samples.push(take());
+        self.next_sample_at = ((at_seconds / self.interval).floor() + 1.0) * self.interval;
+    }
+
+    /// Drops every other sample and samples half as often from now on.
+    fn thin_out(&mut self) {
+        let mut index = 0;
+        self.samples.retain(|_| {
+            index += 1;
+            index % 2 == 1
+        });
+        self.interval *= 2.0;
+        self.next_sample_at = (self.next_sample_at / self.interval).ceil() * self.interval;
+    }
+
+    /// Marks the moments enemies got through or units were lost.
+    pub fn record(&mut self, at_seconds: f64, event: &GameEvent) {
+        let kind = match event {
+            GameEvent::BaseHit { .. } | GameEvent::BossLeaked { .. } => MarkKind::Leak,
+            GameEvent::UnitDestroyed { .. } => MarkKind::UnitLost,
+            _ => return,
+        };
+        if self.marks.len() < MAX_MARKS {
+            self.marks.push(TimelineMark { at_seconds, kind });
+        }
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn samples(&self) -> &[TimelineSample] {
+        &self.samples
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn marks(&self) -> &[TimelineMark] {
+        &self.marks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CombatType, UnitId};
+
+    /// A sample that tells when it was taken.
+    fn at(at_seconds: f64) -> TimelineSample {
+        TimelineSample {
+            at_seconds,
+            ..TimelineSample::default()
+        }
+    }
+
+    /// Samples every frame of `seconds`, at 60 frames a second.
+    fn play(timeline: &mut Timeline, from_seconds: f64, seconds: f64) {
+        for frame in 0..(seconds * 60.0) as usize {
+            let now = from_seconds + frame as f64 / 60.0;
+            timeline.sample(now, || at(now));
+        }
+    }
+
+    #[test]
+    fn one_sample_is_taken_a_second() {
+        let mut timeline = Timeline::default();
+        play(&mut timeline, 0.0, 10.0);
+
+        let seconds: Vec<u32> = timeline
+            .samples
+            .iter()
+            .map(|sample| sample.at_seconds.floor() as u32)
+            .collect();
+        assert_eq!(seconds, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn samples_that_arent_due_arent_taken() {
+        let mut timeline = Timeline::default();
+        timeline.sample(0.5, || at(0.5));
+        timeline.sample(0.9, || panic!("not due yet"));
+        // A long frame skips the seconds in between rather than making up for them.
+        timeline.sample(4.2, || at(4.2));
+        timeline.sample(4.9, || panic!("not due yet"));
+        timeline.sample(5.0, || at(5.0));
+        assert_eq!(timeline.samples, [at(0.5), at(4.2), at(5.0)]);
+    }
+
+    #[test]
+    fn a_full_timeline_keeps_every_other_sample_and_samples_less_often() {
+        let mut timeline = Timeline::default();
+        for second in 0..MAX_SAMPLES {
+            timeline.sample(second as f64, || at(second as f64));
+        }
+        assert_eq!(timeline.samples.len(), MAX_SAMPLES);
+
+        timeline.sample(MAX_SAMPLES as f64, || at(MAX_SAMPLES as f64));
+        assert_eq!(timeline.samples.len(), MAX_SAMPLES / 2 + 1);
+        assert_eq!(timeline.interval, 2.0);
+        assert!(timeline
+            .samples
+            .iter()
+            .all(|sample| (sample.at_seconds as usize).is_multiple_of(2)));
+
+        timeline.sample(MAX_SAMPLES as f64 + 1.0, || panic!("not due yet"));
+        timeline.sample(MAX_SAMPLES as f64 + 2.0, || at(MAX_SAMPLES as f64 + 2.0));
+        assert_eq!(timeline.samples.len(), MAX_SAMPLES / 2 + 2);
+    }
+
+    #[test]
+    fn any_run_fits() {
+        let mut timeline = Timeline::default();
+        play(&mut timeline, 0.0, 4.0 * MAX_SAMPLES as f64);
+
+        assert!(timeline.samples.len() <= MAX_SAMPLES);
+        assert!(timeline.samples.len() > MAX_SAMPLES / 2);
+        assert_eq!(timeline.samples[0].at_seconds, 0.0);
+        let last = timeline.samples.last().unwrap().at_seconds;
+        assert!(last > 4.0 * MAX_SAMPLES as f64 - 5.0, "{}", last);
+    }
+
+    #[test]
+    fn leaks_and_losses_are_marked() {
+        let mut timeline = Timeline::default();
+        timeline.record(
+            12.0,
+            &GameEvent::BossLeaked {
+                combat_type: CombatType::A,
+                parking_spaces_left: 2,
+            },
+        );
+        timeline.record(20.0, &GameEvent::UnitDestroyed { unit: UnitId(3) });
+        timeline.record(
+            25.0,
+            &GameEvent::EnemyIntercepted {
+                unit: UnitId(1),
+                combat_type: CombatType::B,
+                is_boss: false,
+            },
+        );
+
+        assert_eq!(
+            timeline.marks,
+            [
+                TimelineMark {
+                    at_seconds: 12.0,
+                    kind: MarkKind::Leak,
+                },
+                TimelineMark {
+                    at_seconds: 20.0,
+                    kind: MarkKind::UnitLost,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn marks_are_capped() {
+        let mut timeline = Timeline::default();
+        for second in 0..MAX_MARKS + 10 {
+            timeline.record(second as f64, &GameEvent::UnitDestroyed { unit: UnitId(1) });
+        }
+        assert_eq!(timeline.marks.len(), MAX_MARKS);
+        assert_eq!(
+            timeline.marks.last().unwrap().at_seconds,
+            (MAX_MARKS - 1) as f64
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+
+use crate::{
+    achievements::AchievementId,
+    battle_log::Severity,
+    events::GameEvent,
+    game::{CombatType, UnitId},
+};
+
+/// How long a toast stays on screen, in real seconds.
+#[cfg(feature = "backend-eframe")]
+const LIFETIME: f64 = 4.0;
+/// How long a toast takes to fade out at the end of its lifetime.
+#[cfg(feature = "backend-eframe")]
+const FADE_OUT: f64 = 1.0;
+/// More toasts than this wait until one of the visible ones goes away.
+#[cfg(feature = "backend-eframe")]
+const MAX_VISIBLE: usize = 4;
+
+/// What a toast says. The GUI turns it into text in the player's language.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToastMessage {
+    UnitDestroyed {
+        unit: UnitId,
+    },
+    BossLeaked {
+        combat_type: CombatType,
+        parking_spaces_left: usize,
+    },
+    #[cfg(feature = "backend-eframe")]
+    ScreenshotSaved {
+        file_name: String,
+    },
+    #[cfg(feature = "backend-eframe")]
+    ScreenshotFailed {
+        reason: String,
+    },
+    #[cfg(feature = "backend-eframe")]
+    GameSaved {
+        seconds: f64,
+    },
+    #[cfg(feature = "backend-eframe")]
+    LoadFailed {
+        reason: String,
+    },
+    #[cfg(feature = "backend-eframe")]
+    ReplaySaved,
+    ReplayDiverged {
+        seconds: f64,
+    },
+    AchievementUnlocked {
+        achievement: AchievementId,
+    },
+}
+
+#[cfg(feature = "backend-eframe")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub(crate) message: ToastMessage,
+    pub(crate) severity: Severity,
+    /// Real seconds since the game started when the toast was first shown.
+    pub(crate) shown_at: f64,
+}
+
+#[cfg(feature = "backend-eframe")]
+impl Toast {
+    /// From 1 while the toast is fresh down to 0 when it goes away.
+    pub fn opacity(&self, now: f64) -> f32 {
+        let remaining = self.shown_at + LIFETIME - now;
+        (remaining / FADE_OUT).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// Short notifications for events the player shouldn't have to dig out of the battle log.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    #[cfg(feature = "backend-eframe")]
+    visible: Vec<Toast>,
+    waiting: VecDeque<(ToastMessage, Severity)>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, message: ToastMessage, severity: Severity) {
+        self.waiting.push_back((message, severity));
+    }
+
+    /// Queues a toast for the events that deserve one.
+    pub fn record(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::UnitDestroyed { unit } => {
+                self.push(ToastMessage::UnitDestroyed { unit }, Severity::Critical)
+            }
+            GameEvent::BossLeaked {
+                combat_type,
+                parking_spaces_left,
+            } => self.push(
+                ToastMessage::BossLeaked {
+                    combat_type,
+                    parking_spaces_left,
+                },
+                Severity::Critical,
+            ),
+            _ => {}
+        }
+    }
+
+    /// Dismisses the toasts that have been shown long enough and moves waiting ones up into the
+    /// free places. `now` is in real seconds, so toasts last as long whatever the game speed.
+    #[cfg(feature = "backend-eframe")]
+    pub fn update(&mut self, now: f64) {
+        self.visible.retain(|toast| now < toast.shown_at + LIFETIME);
+
+        while self.visible.len() < MAX_VISIBLE {
+            let (message, severity) = match self.waiting.pop_front() {
+                Some(waiting) => waiting,
+                None => break,
+            };
+            self.visible.push(Toast {
+                message,
+                severity,
+                shown_at: now,
+            });
+        }
+    }
+
+    /// The toasts on screen, oldest first.
+    #[cfg(feature = "backend-eframe")]
+    pub fn visible(&self) -> &[Toast] {
+        &self.visible
+    }
+}
+
+#[cfg(all(test, feature = "backend-eframe"))]
+mod tests {
+    use super::*;
+
+    fn destroyed(unit: u32) -> ToastMessage {
+        ToastMessage::UnitDestroyed { unit: UnitId(unit) }
+    }
+
+    fn shown(toasts: &Toasts) -> Vec<ToastMessage> {
+        toasts
+            .visible()
+            .iter()
+            .map(|toast| toast.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn toasts_wait_for_the_next_update() {
+        let mut toasts = Toasts::default();
+        toasts.record(&GameEvent::UnitDestroyed { unit: UnitId(3) });
+        toasts.record(&GameEvent::ParkingChanged { used: 2 });
+        assert!(toasts.visible().is_empty());
+
+        toasts.update(1.0);
+        assert_eq!(shown(&toasts), [destroyed(3)]);
+        assert_eq!(toasts.visible()[0].severity, Severity::Critical);
+        assert_eq!(toasts.visible()[0].shown_at, 1.0);
+    }
+
+    #[test]
+    fn toasts_fade_out_and_go_away_after_their_lifetime() {
+        let mut toasts = Toasts::default();
+        toasts.push(destroyed(1), Severity::Info);
+        toasts.update(10.0);
+
+        let toast = toasts.visible()[0].clone();
+        assert_eq!(toast.opacity(10.0), 1.0);
+        assert_eq!(toast.opacity(10.0 + LIFETIME - FADE_OUT / 2.0), 0.5);
+        assert_eq!(toast.opacity(10.0 + LIFETIME), 0.0);
+
+        toasts.update(10.0 + LIFETIME - 0.1);
+        assert_eq!(toasts.visible().len(), 1);
+        toasts.update(10.0 + LIFETIME);
+        assert!(toasts.visible().is_empty());
+    }
+
+    #[test]
+    fn overflowing_toasts_take_the_places_that_come_free() {
+        let mut toasts = Toasts::default();
+        for unit in 1..=MAX_VISIBLE as u32 {
+            toasts.push(destroyed(unit), Severity::Info);
+        }
+        toasts.update(0.0);
+        let later = MAX_VISIBLE as u32 + 1;
+        toasts.push(destroyed(later), Severity::Info);
+        toasts.push(destroyed(later + 1), Severity::Info);
+
+        toasts.update(1.0);
+        assert_eq!(toasts.visible().len(), MAX_VISIBLE);
+        assert!(!shown(&toasts).contains(&destroyed(later)));
+
+        // The first ones all go at once, and the waiting ones come up in order.
+        toasts.update(LIFETIME);
+        assert_eq!(shown(&toasts), [destroyed(later), destroyed(later + 1)]);
+        assert!(toasts
+            .visible()
+            .iter()
+            .all(|toast| toast.shown_at == LIFETIME));
+    }
+}
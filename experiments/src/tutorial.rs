@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::GameEvent,
+    game::{CombatType, UnitAction},
+    storage::{self, Storage},
+};
+
+pub struct TutorialStep {
+    #[cfg(feature = "backend-eframe")]
+    pub(crate) instruction: &'static str,
+    /// Whether the event means the player did what the step asked for.
+    completion: fn(&GameEvent) -> bool,
+}
+
+pub const STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        #[cfg(feature = "backend-eframe")]
+        instruction: "Your units start out in storage. Bring one out by clicking \"Bring out of \
+            storage\" in the base panel, or select it with its number key and press U. Wait for \
+            it to reach its parking space.",
+        completion: |event| matches!(event, GameEvent::UnitParked { .. }),
+    },
+    TutorialStep {
+        #[cfg(feature = "backend-eframe")]
+        instruction: "Units have to be prepared for the lane they will fight in. Prepare your \
+            unit for lane A and wait for it to get ready.",
+        completion: |event| {
+            matches!(
+                event,
+                GameEvent::UnitReady {
+                    combat_type: CombatType::A,
+                    ..
+                }
+            )
+        },
+    },
+    TutorialStep {
+        #[cfg(feature = "backend-eframe")]
+        instruction: "Send it off with \"Take off!\" (T).",
+        completion: |event| {
+            matches!(
+                event,
+                GameEvent::UnitOrdered {
+                    action: UnitAction::TakeOff,
+                    ..
+                }
+            )
+        },
+    },
+    TutorialStep {
+        #[cfg(feature = "backend-eframe")]
+        instruction: "Your unit now patrols lane A and will stop the first type A enemy it \
+            meets. Wait for it to come back.",
+        completion: |event| matches!(event, GameEvent::SortieCompleted { .. }),
+    },
+    TutorialStep {
+        #[cfg(feature = "backend-eframe")]
+        instruction: "Returning units wait until you park them again (P) or move them into \
+            storage (S), where they get repaired. Do one of those and the enemies will start \
+            coming.",
+        completion: |event| {
+            matches!(
+                event,
+                GameEvent::UnitOrdered {
+                    action: UnitAction::Park,
+                    ..
+                } | GameEvent::UnitOrdered {
+                    action: UnitAction::Store,
+                    ..
+                }
+            )
+        },
+    },
+];
+
+/// Walks the player through one sortie. Enemies are held back until it is over.
+#[derive(Debug, Default)]
+pub struct Tutorial {
+    step: usize,
+}
+
+impl Tutorial {
+    pub fn current_step(&self) -> Option<&'static TutorialStep> {
+        STEPS.get(self.step)
+    }
+
+    /// The current step counting from 1, for showing as "step 2 of 5".
+    #[cfg(feature = "backend-eframe")]
+    pub fn step_number(&self) -> usize {
+        self.step + 1
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn step_count(&self) -> usize {
+        STEPS.len()
+    }
+
+    /// Moves on to the next step if the event completes the current one.
+    pub fn advance(&mut self, event: &GameEvent) {
+        if let Some(step) = self.current_step() {
+            if (step.completion)(event) {
+                self.step += 1;
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= STEPS.len()
+    }
+}
+
+/// Whether the player has been through the tutorial, so it only starts by itself once.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TutorialRecord {
+    completed: bool,
+}
+
+impl TutorialRecord {
+    const STORAGE_KEY: &'static str = "tutorial";
+
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage::load(storage, Self::STORAGE_KEY)
+    }
+
+    #[cfg(feature = "backend-eframe")]
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    pub fn complete(&mut self, storage: &mut dyn Storage) {
+        self.completed = true;
+        storage::save(storage, Self::STORAGE_KEY, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::UnitId, storage::MemoryStorage};
+
+    const UNIT: UnitId = UnitId(1);
+
+    fn ordered(action: UnitAction) -> GameEvent {
+        GameEvent::UnitOrdered { unit: UNIT, action }
+    }
+
+    #[test]
+    fn one_sortie_walks_through_every_step() {
+        let script = [
+            GameEvent::UnitParked { unit: UNIT },
+            GameEvent::UnitReady {
+                unit: UNIT,
+                combat_type: CombatType::A,
+            },
+            ordered(UnitAction::TakeOff),
+            GameEvent::SortieCompleted { unit: UNIT },
+            ordered(UnitAction::Store),
+        ];
+
+        let mut tutorial = Tutorial::default();
+        for (step, event) in script.iter().enumerate() {
+            assert_eq!(tutorial.step, step);
+            assert!(!tutorial.is_finished());
+            tutorial.advance(event);
+        }
+        assert!(tutorial.is_finished());
+        assert!(tutorial.current_step().is_none());
+    }
+
+    #[test]
+    fn other_events_dont_move_it_on() {
+        let mut tutorial = Tutorial::default();
+        tutorial.advance(&GameEvent::UnitParked { unit: UNIT });
+        tutorial.advance(&GameEvent::UnitReady {
+            unit: UNIT,
+            combat_type: CombatType::B,
+        });
+        tutorial.advance(&ordered(UnitAction::TakeOff));
+        assert_eq!(tutorial.step, 1);
+    }
+
+    #[test]
+    fn completing_it_is_remembered() {
+        let mut storage = MemoryStorage::default();
+        assert!(!TutorialRecord::load(&storage).completed);
+
+        TutorialRecord::default().complete(&mut storage);
+        assert!(TutorialRecord::load(&storage).completed);
+    }
+}
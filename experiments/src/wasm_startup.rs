@@ -3,23 +3,360 @@
 
 // ----------------------------------------------------------------------------
 // When compiling for web:
-use std::panic;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
-#[cfg(target_arch = "wasm32")]
 use eframe::wasm_bindgen::{self, prelude::*};
+use engine::input::KeyCode;
 
-use crate::{game::init_stuff, MyGame};
+use crate::{crash_report, gui::SPRITE_ASSETS, page_link::PageLink, MyGame, StartConfig};
 
 /// This is the entry-point for all the web-assembly.
-/// This is called once from the HTML.
-/// It loads the app, installs some callbacks, then returns.
+/// This is called from the HTML, once for every canvas that should run a game.
+/// It loads the app, installs some callbacks, then resolves to a handle to control it with.
 /// You can add more callbacks like this if you want to call in to your code.
-#[cfg(target_arch = "wasm32")]
+///
+/// `config` is an optional object like `{ seed: 42, difficulty: "Hard", language: "German",
+/// muted: true, speed: 2, log_level: "debug" }`; see `StartConfig`. Anything it leaves out is
+/// taken from the page's query string, like `?seed=42&difficulty=hard`, and then from the
+/// defaults.
+///
+/// `on_progress`, if given, is called as `on_progress(fraction, milestone)` at each
+/// `Milestone`, with `fraction` going from 0.25 to 1, e.g. to fill a loading bar. Loading yields
+/// to the browser in between, so the page gets to draw it.
 #[wasm_bindgen]
-pub fn start(canvas_id: &str) -> Result<(), eframe::wasm_bindgen::JsValue> {
-    // web_sys::console::log_1(&"Start!".into());
-    panic::set_hook(Box::new(console_error_panic_hook::hook));
-    let mut app = MyGame::default();
-    init_stuff(&mut app.units);
-    eframe::start_web(canvas_id, Box::new(app))
+pub async fn start(
+    canvas_id: String,
+    config: JsValue,
+    on_progress: Option<js_sys::Function>,
+) -> Result<GameHandle, JsValue> {
+    crate::install_panic_hook();
+    let config: StartConfig = if config.is_undefined() || config.is_null() {
+        StartConfig::default()
+    } else {
+        serde_wasm_bindgen::from_value(config)
+            .map_err(|error| JsValue::from_str(&format!("invalid start config: {}", error)))?
+    };
+    // What the page passes in explicitly wins over the link it was opened with.
+    let (from_query, warnings) = StartConfig::from_query(&query_string());
+    let config = config.or(from_query);
+
+    // Starting twice leaves the logger from the first time in place.
+    if let Some(level) = config
+        .log_level
+        .unwrap_or(log::LevelFilter::Warn)
+        .to_level()
+    {
+        let _ = console_log::init_with_level(level);
+    }
+    for warning in warnings {
+        log::warn!("{}", warning);
+    }
+    log::info!("Start! {:?}", config);
+    let progress = Progress(on_progress);
+    progress.report(Milestone::WasmInstantiated);
+
+    let mut app = MyGame::new();
+    app.apply_start_config(config);
+    app.set_canvas_id(&canvas_id);
+    pause_when_hidden(app.externally_paused())?;
+    keep_browser_out(&canvas_id, app.page_link())?;
+
+    let mut sprites = engine::DecodedTextures::default();
+    for asset in &SPRITE_ASSETS {
+        sprites.decode(asset);
+        next_tick().await;
+    }
+    app.use_decoded_sprites(sprites);
+    progress.report(Milestone::AssetsDecoded);
+
+    app.preload_audio();
+    progress.report(Milestone::AudioReady);
+    next_tick().await;
+
+    let handle = GameHandle {
+        page_link: app.page_link(),
+    };
+    handle
+        .page_link
+        .borrow_mut()
+        .set_on_first_frame(move || progress.report(Milestone::FirstFrame));
+    engine::run(
+        app,
+        engine::RunConfig {
+            title: "Fruitopian Defender".to_string(),
+            canvas_id,
+            style: Some(crate::install_style),
+            ..Default::default()
+        },
+    )
+    .map_err(|error| JsValue::from_str(&error.to_string()))?;
+
+    Ok(handle)
+}
+
+/// The steps of getting a game going, in order.
+#[derive(Debug, Copy, Clone)]
+enum Milestone {
+    WasmInstantiated,
+    AssetsDecoded,
+    AudioReady,
+    /// The game has drawn its main menu. Nothing is drawn before the assets are ready.
+    FirstFrame,
+}
+
+impl Milestone {
+    fn name(self) -> &'static str {
+        match self {
+            Self::WasmInstantiated => "wasm_instantiated",
+            Self::AssetsDecoded => "assets_decoded",
+            Self::AudioReady => "audio_ready",
+            Self::FirstFrame => "first_frame",
+        }
+    }
+
+    fn fraction(self) -> f64 {
+        match self {
+            Self::WasmInstantiated => 0.25,
+            Self::AssetsDecoded => 0.5,
+            Self::AudioReady => 0.75,
+            Self::FirstFrame => 1.0,
+        }
+    }
+}
+
+/// The page's progress callback, if it passed one.
+struct Progress(Option<js_sys::Function>);
+
+impl Progress {
+    /// Exceptions from the callback are logged; a broken loading bar shouldn't stop the game.
+    fn report(&self, milestone: Milestone) {
+        log::debug!("Loading: {}", milestone.name());
+        if let Some(callback) = &self.0 {
+            let result = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from_f64(milestone.fraction()),
+                &JsValue::from_str(milestone.name()),
+            );
+            if let Err(error) = result {
+                log::warn!("The progress callback failed: {:?}", error);
+            }
+        }
+    }
+}
+
+/// Gives the browser a chance to draw and handle events before carrying on.
+async fn next_tick() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let scheduled = web_sys::window().map_or(false, |window| {
+            window.set_timeout_with_callback(&resolve).is_ok()
+        });
+        // Carry on straight away rather than never.
+        if !scheduled {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// The query string of the page's address, e.g. `?seed=12345`, or nothing if there is none.
+fn query_string() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default()
+}
+
+/// Browsers stop sending frames to hidden tabs, so the game can't pause itself then. This has
+/// the page tell it instead, for the next frame after the tab is back.
+fn pause_when_hidden(externally_paused: Rc<Cell<bool>>) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+
+    let hidden_document = document.clone();
+    let listener = Closure::wrap(Box::new(move || {
+        if hidden_document.hidden() {
+            externally_paused.set(true);
+        }
+    }) as Box<dyn FnMut()>);
+    document
+        .add_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())?;
+    // The listener is needed for as long as the page is open.
+    listener.forget();
+
+    Ok(())
+}
+
+/// Keys the browser would scroll or navigate with, which are never its to have while the game
+/// is on the page.
+const ALWAYS_CONSUMED_KEYS: &[KeyCode] = &[
+    KeyCode::Space,
+    KeyCode::Backspace,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Home,
+    KeyCode::End,
+];
+
+/// Stops the browser from acting on input meant for the game: the context menu on right-clicks,
+/// scrolling with touches that start on the canvas, and scrolling or navigating with keys the
+/// game uses. Keys typed into text fields, and shortcuts with modifiers, are left alone.
+fn keep_browser_out(canvas_id: &str, page_link: Rc<RefCell<PageLink>>) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let canvas = document
+        .get_element_by_id(canvas_id)
+        .ok_or_else(|| JsValue::from_str("no canvas"))?;
+
+    let prevent_default = || {
+        Closure::wrap(Box::new(|event: web_sys::Event| event.prevent_default())
+            as Box<dyn FnMut(web_sys::Event)>)
+    };
+    let context_menu = prevent_default();
+    canvas
+        .add_event_listener_with_callback("contextmenu", context_menu.as_ref().unchecked_ref())?;
+    // Touch events go to where the touch started, so this only catches the canvas' own. Only
+    // listeners that aren't passive may prevent scrolling.
+    let touch_move = prevent_default();
+    canvas.add_event_listener_with_callback_and_add_event_listener_options(
+        "touchmove",
+        touch_move.as_ref().unchecked_ref(),
+        web_sys::AddEventListenerOptions::new().passive(false),
+    )?;
+
+    let key_down = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+        if event.ctrl_key() || event.meta_key() || event.alt_key() || is_typing(&event) {
+            return;
+        }
+        let consumed = KeyCode::from_dom_key(&event.key()).map_or(false, |key| {
+            ALWAYS_CONSUMED_KEYS.contains(&key)
+                || page_link.try_borrow().map_or(false, |page_link| {
+                    page_link.key_bindings().action(key).is_some()
+                })
+        });
+        if consumed {
+            event.prevent_default();
+        }
+    }) as Box<dyn FnMut(web_sys::KeyboardEvent)>);
+    document.add_event_listener_with_callback("keydown", key_down.as_ref().unchecked_ref())?;
+
+    // The listeners are needed for as long as the page is open.
+    context_menu.forget();
+    touch_move.forget();
+    key_down.forget();
+
+    Ok(())
+}
+
+/// Whether the key goes into a text field, like the hidden one egui types into.
+fn is_typing(event: &web_sys::KeyboardEvent) -> bool {
+    event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .map_or(false, |element| {
+            matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA")
+        })
+}
+
+/// One game running on the page, as returned by `start()`. Every game has its own.
+#[wasm_bindgen]
+pub struct GameHandle {
+    page_link: Rc<RefCell<PageLink>>,
+}
+
+impl GameHandle {
+    /// Runs `f` on the link to the game, unless it can't be reached.
+    fn with_page_link<T>(&self, f: impl FnOnce(&mut PageLink) -> T) -> Result<T, JsValue> {
+        // A panic stops every game on the page.
+        if crash_report::has_panicked() {
+            return Err(JsValue::from_str("the game has crashed"));
+        }
+
+        let mut page_link = self
+            .page_link
+            .try_borrow_mut()
+            .map_err(|_| JsValue::from_str("the game is busy"))?;
+        Ok(f(&mut page_link))
+    }
+}
+
+#[wasm_bindgen]
+impl GameHandle {
+    /// Pauses the run from the next frame on, e.g. while the page shows a dialog. Does nothing
+    /// outside a run.
+    pub fn pause(&self) -> Result<(), JsValue> {
+        self.with_page_link(|page_link| page_link.request_pause(true))
+    }
+
+    /// Resumes a paused run from the next frame on, no matter who paused it.
+    pub fn resume(&self) -> Result<(), JsValue> {
+        self.with_page_link(|page_link| page_link.request_pause(false))
+    }
+
+    /// Mutes or unmutes the game for this session, without changing what the player saved.
+    pub fn set_muted(&self, muted: bool) -> Result<(), JsValue> {
+        self.with_page_link(|page_link| page_link.request_muted(muted))
+    }
+
+    /// Calls `callback` with a `RunSummary` whenever a run ends, once per run. Replaces the
+    /// callback set before. Exceptions it throws are logged and otherwise ignored.
+    pub fn set_on_game_over(&self, callback: js_sys::Function) -> Result<(), JsValue> {
+        self.with_page_link(|page_link| {
+            page_link.set_on_game_over(move |summary| {
+                let result = serde_wasm_bindgen::to_value(summary)
+                    .map_err(JsValue::from)
+                    .and_then(|summary| callback.call1(&JsValue::NULL, &summary));
+                if let Err(error) = result {
+                    log::warn!("The game-over callback failed: {:?}", error);
+                }
+            })
+        })
+    }
+
+    /// How the current run is going, as of the last frame.
+    pub fn get_stats(&self) -> Result<GameStats, JsValue> {
+        let status = self.with_page_link(|page_link| page_link.status().clone())?;
+        serde_wasm_bindgen::to_value(&status)
+            .map(JsCast::unchecked_into)
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const GAME_STATS: &'static str = r#"
+/** How the current run is going, as of the last frame. */
+export interface GameStats {
+    /** Seconds survived, which is what the high score counts. */
+    survival_seconds: number;
+    /** The points scored so far. */
+    score: number;
+    state: "MainMenu" | "Running" | "Paused" | "GameOver" | "Victory";
+}
+
+/** How a finished run went, as passed to the `GameHandle.set_on_game_over()` callback. */
+export interface RunSummary {
+    survived_seconds: number;
+    /** The points scored. */
+    score: number;
+    /** Enemies intercepted in each lane. */
+    kills: { A: number; B: number; C: number; D: number };
+    /** A string, since numbers can't hold every seed. */
+    seed: string;
+    difficulty: "Easy" | "Normal" | "Hard";
+    /** The version of the game the run was played on. */
+    version: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "GameStats")]
+    pub type GameStats;
 }